@@ -0,0 +1,140 @@
+// Criterion benchmarks for generated entity and repository methods
+// Run with: cargo bench --features bench
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use pleme_codegen::{BrazilianTaxEntity, PaymentEntity, PaymentMethods, PixPayment, SmartRepository};
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaymentStatus {
+    Pending,
+    Processing,
+    Completed,
+    Failed,
+    Refunded,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PaymentMethods)]
+pub enum PaymentMethod {
+    Pix,
+    Boleto,
+    CreditCard,
+    DebitCard,
+    TransferenciaTed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixKeyType {
+    Cpf,
+    Cnpj,
+    Email,
+    Phone,
+    Random,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PaymentError {
+    #[error("Invalid amount")]
+    InvalidAmount,
+    #[error("Invalid state transition from {from:?} to {to:?}")]
+    InvalidStateTransition { from: PaymentStatus, to: PaymentStatus },
+    #[error("Amount too low: minimum {min}, got {actual}")]
+    AmountTooLow { min: Decimal, actual: Decimal },
+    #[error("Amount too high: maximum {max}, got {actual}")]
+    AmountTooHigh { max: Decimal, actual: Decimal },
+    #[error("Amount has {actual} decimal places, currency allows {max}")]
+    InvalidScale { max: u32, actual: u32 },
+    #[error("Transaction failed: {0}")]
+    TransactionFailed(String),
+    #[error("Invalid PIX key: {reason}")]
+    InvalidPixKey { reason: String },
+    #[error("QR code generation failed: {reason}")]
+    QrCodeGenerationFailed { reason: String },
+    #[error("Invalid refund: {reason}")]
+    InvalidRefund { reason: String },
+}
+
+#[derive(Debug, Clone, PaymentEntity)]
+pub struct Payment {
+    pub id: uuid::Uuid,
+    pub amount: Decimal,
+    pub tax: Decimal,
+    pub status: PaymentStatus,
+    pub method: PaymentMethod,
+    pub failure_reason: Option<String>,
+    pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub failed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, PixPayment)]
+pub struct PixCheckout {
+    pub amount: Decimal,
+    pub pix_key: String,
+    pub pix_key_type: PixKeyType,
+    pub merchant_name: String,
+    pub end_to_end_id: Option<String>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, BrazilianTaxEntity)]
+pub struct Invoice {
+    pub subtotal: Decimal,
+    pub state: String,
+}
+
+#[derive(SmartRepository)]
+pub struct LedgerRepository;
+
+fn bench_entity_methods(c: &mut Criterion) {
+    let payments = Payment::bench_inputs();
+    let checkouts = PixCheckout::bench_inputs();
+    let invoices = Invoice::bench_inputs();
+
+    let mut group = c.benchmark_group("entity_methods");
+    group.bench_function("validate_amount", |b| {
+        b.iter(|| {
+            for payment in &payments {
+                let _ = black_box(payment).validate_amount();
+            }
+        })
+    });
+    group.bench_function("generate_qr_payload", |b| {
+        b.iter(|| {
+            for checkout in &checkouts {
+                black_box(black_box(checkout).generate_qr_payload());
+            }
+        })
+    });
+    // calculate_total_tax is the live counterpart of BrazilianPaymentEntity::calculate_brazilian_taxes
+    group.bench_function("calculate_total_tax", |b| {
+        b.iter(|| {
+            for invoice in &invoices {
+                black_box(black_box(invoice).calculate_total_tax(invoice.subtotal, &invoice.state, false));
+            }
+        })
+    });
+    group.finish();
+}
+
+fn bench_clone_vs_borrow(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    // Large entity where a deep clone dominates the call
+    let entity: Vec<String> = (0..10_000).map(|i| format!("ledger-entry-{}", i)).collect();
+
+    let mut group = c.benchmark_group("clone_vs_borrow");
+    group.bench_function("create_with_observability", |b| {
+        b.iter(|| runtime.block_on(LedgerRepository.create_with_observability(black_box(&entity), None)).unwrap())
+    });
+    group.bench_function("create_with_observability_ref", |b| {
+        b.iter(|| {
+            runtime.block_on(LedgerRepository.create_with_observability_ref(black_box(&entity), None)).unwrap();
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_entity_methods, bench_clone_vs_borrow);
+criterion_main!(benches);
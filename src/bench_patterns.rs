@@ -0,0 +1,92 @@
+//! Benchmark Input Patterns
+//!
+//! Generates `bench_inputs()` for the pure entity derives when the crate is
+//! built with the `bench` feature, so criterion groups can exercise the
+//! generated methods against representative instances
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::DeriveInput;
+
+/// Amounts used across the generated instances (small, typical and large ticket)
+const BENCH_AMOUNTS: &[&str] = &["10.00", "150.00", "2500.00"];
+
+/// Generate `bench_inputs()`; empty unless the `bench` feature is enabled
+pub fn generate_bench_inputs(input: &DeriveInput) -> TokenStream {
+    if !cfg!(feature = "bench") {
+        return quote! {};
+    }
+
+    let struct_name = &input.ident;
+    let instances = BENCH_AMOUNTS.iter().map(|amount| {
+        let body = match &input.data {
+            syn::Data::Struct(syn::DataStruct { fields: syn::Fields::Named(fields), .. }) => {
+                let assigns = fields.named.iter().map(|field| {
+                    let ident = field.ident.as_ref().unwrap();
+                    let value = bench_value(&ident.to_string(), &field.ty, amount);
+                    quote! { #ident: #value, }
+                });
+                quote! { Self { #(#assigns)* } }
+            }
+            syn::Data::Struct(syn::DataStruct { fields: syn::Fields::Unit, .. }) => quote! { Self },
+            _ => quote! { Default::default() },
+        };
+        quote! { #body, }
+    });
+
+    quote! {
+        impl #struct_name {
+            /// Representative instances for the criterion benchmark groups
+            pub fn bench_inputs() -> Vec<Self> {
+                vec![#(#instances)*]
+            }
+        }
+    }
+}
+
+/// Last path segment of a type, e.g. `Decimal` for `rust_decimal::Decimal`
+fn last_segment(ty: &syn::Type) -> Option<&syn::PathSegment> {
+    match ty {
+        syn::Type::Path(type_path) => type_path.path.segments.last(),
+        _ => None,
+    }
+}
+
+/// Representative value for a field, chosen by type and then by name
+fn bench_value(name: &str, ty: &syn::Type, amount: &str) -> TokenStream {
+    let Some(segment) = last_segment(ty) else {
+        return quote! { Default::default() };
+    };
+
+    match segment.ident.to_string().as_str() {
+        "Option" => quote! { None },
+        "Decimal" => quote! { #amount.parse::<rust_decimal::Decimal>().unwrap() },
+        "Uuid" => quote! { uuid::Uuid::new_v4() },
+        "DateTime" if name.contains("expires") => quote! { chrono::Utc::now() + chrono::Duration::minutes(30) },
+        "DateTime" => quote! { chrono::Utc::now() },
+        "String" => {
+            let value = if name.contains("pix_key") || name.contains("email") {
+                "pagamentos@loja.com.br"
+            } else if name.contains("cpf") {
+                "123.456.789-09"
+            } else if name.contains("cnpj") {
+                "11.222.333/0001-81"
+            } else if name.contains("state") || name == "uf" {
+                "SP"
+            } else if name.contains("name") {
+                "Loja Exemplo"
+            } else {
+                "bench"
+            };
+            quote! { #value.to_string() }
+        }
+        "bool" => quote! { false },
+        "i8" | "i16" | "i32" | "i64" | "u8" | "u16" | "u32" | "u64" | "usize" => quote! { 1 },
+        "f32" | "f64" => quote! { 1.0 },
+        // Enums the payment derives already depend on
+        "PaymentStatus" => quote! { PaymentStatus::Pending },
+        "PaymentMethod" => quote! { PaymentMethod::Pix },
+        "PixKeyType" => quote! { PixKeyType::Email },
+        _ => quote! { Default::default() },
+    }
+}
@@ -13,6 +13,8 @@ pub fn derive_brazilian_tax_entity(input: TokenStream) -> TokenStream {
     
     eprintln!("[pleme-codegen] BrazilianTaxEntity pattern applied to {} - saving ~30 lines", struct_name);
     
+    let bench_impl = crate::bench_patterns::generate_bench_inputs(&input);
+    
     let expanded = quote! {
         impl #struct_name {
             /// Calculate ICMS tax by Brazilian state
@@ -115,18 +117,79 @@ pub fn derive_brazilian_tax_entity(input: TokenStream) -> TokenStream {
                 format!("NFE-{}-{}", timestamp.format("%Y%m%d%H%M%S"), random)
             }
         }
+        
+        #bench_impl
     };
     
     TokenStream::from(expanded)
 }
 
+/// Freight zones priced by `freight_for_cep`, with their default base rates in BRL
+const FREIGHT_ZONES: &[(&str, &str)] = &[
+    ("local", "12.50"),
+    ("regional", "18.90"),
+    ("national", "29.90"),
+];
+
+/// Base rate per freight zone from `#[shipping(zone_rates = "local:12.50, national:35")]`,
+/// falling back to `FREIGHT_ZONES` for zones that are not listed
+fn freight_zone_rates(attrs: &[syn::Attribute]) -> syn::Result<Vec<rust_decimal::Decimal>> {
+    let mut rates: Vec<rust_decimal::Decimal> = FREIGHT_ZONES.iter()
+        .map(|(_, rate)| rate.parse().unwrap())
+        .collect();
+    
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("shipping")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("zone_rates") {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                for entry in value.value().split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+                    let (zone, rate) = entry.split_once(':').ok_or_else(|| syn::Error::new_spanned(
+                        &value,
+                        format!("expected `zone:rate`, got `{}`", entry),
+                    ))?;
+                    let index = FREIGHT_ZONES.iter().position(|(name, _)| *name == zone.trim()).ok_or_else(|| {
+                        syn::Error::new_spanned(
+                            &value,
+                            format!("unknown freight zone `{}`; expected `local`, `regional` or `national`", zone.trim()),
+                        )
+                    })?;
+                    rates[index] = match rate.trim().parse::<rust_decimal::Decimal>() {
+                        Ok(rate) if rate >= rust_decimal::Decimal::ZERO => rate,
+                        _ => return Err(syn::Error::new_spanned(
+                            &value,
+                            format!("freight rate for `{}` must be a non-negative decimal, got `{}`", zone.trim(), rate.trim()),
+                        )),
+                    };
+                }
+            } else if meta.input.peek(syn::Token![=]) {
+                let _: syn::Expr = meta.value()?.parse()?;
+            }
+            Ok(())
+        })?;
+    }
+    
+    Ok(rates)
+}
+
 /// ShippingEntity - Generate shipping calculations with Brazilian zones (saves ~25 lines)
 pub fn derive_shipping_entity(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let struct_name = &input.ident;
+    let shipping_error = quote::format_ident!("{}ShippingError", struct_name);
     
     eprintln!("[pleme-codegen] ShippingEntity pattern applied to {} - saving ~25 lines", struct_name);
     
+    let zone_rates = match freight_zone_rates(&input.attrs) {
+        Ok(rates) => rates,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let zone_names = FREIGHT_ZONES.iter().map(|(name, _)| *name);
+    let zone_rates = zone_rates.iter().map(|rate| {
+        let mantissa = rate.mantissa() as i64;
+        let scale = rate.scale();
+        quote! { rust_decimal::Decimal::new(#mantissa, #scale) }
+    });
+    
     let expanded = quote! {
         impl #struct_name {
             /// Calculate shipping cost with Brazilian regional zones
@@ -235,6 +298,68 @@ pub fn derive_shipping_entity(input: TokenStream) -> TokenStream {
                 (base_days as f64 * factor).ceil() as u32
             }
             
+            /// Correios-style freight between two CEPs: the zone's base rate covers the
+            /// first kg, and every further started kg adds half of it
+            pub fn freight_for_cep(&self, origin_cep: &str, dest_cep: &str, weight_kg: rust_decimal::Decimal) -> Result<rust_decimal::Decimal, #shipping_error> {
+                use rust_decimal::Decimal;
+                
+                let zone = Self::freight_zone(origin_cep, dest_cep)?;
+                if weight_kg <= Decimal::ZERO {
+                    return Err(#shipping_error::InvalidWeight(weight_kg));
+                }
+                
+                let base_rate = match zone {
+                    #(#zone_names => #zone_rates,)*
+                    _ => unreachable!("freight_zone returns a known zone"),
+                };
+                let extra_kg = (weight_kg.ceil() - Decimal::ONE).max(Decimal::ZERO);
+                let weight_factor = Decimal::ONE + extra_kg * Decimal::new(5, 1);
+                let freight = (base_rate * weight_factor).round_dp(2);
+                
+                tracing::info!(
+                    entity = %stringify!(#struct_name),
+                    zone = %zone,
+                    weight_kg = %weight_kg,
+                    freight = %freight,
+                    "Freight calculated"
+                );
+                
+                Ok(freight)
+            }
+            
+            /// Freight zone between two CEPs: `local` for the same leading digit (CEP region),
+            /// `regional` within the same macro-region, `national` otherwise
+            pub fn freight_zone(origin_cep: &str, dest_cep: &str) -> Result<&'static str, #shipping_error> {
+                let origin = Self::cep_region(origin_cep).ok_or_else(|| #shipping_error::InvalidCep(origin_cep.to_string()))?;
+                let dest = Self::cep_region(dest_cep).ok_or_else(|| #shipping_error::InvalidCep(dest_cep.to_string()))?;
+                
+                // Leading CEP digit -> macro-region: 0-3 Sudeste, 4-6 Nordeste/Norte, 7 Centro-Oeste, 8-9 Sul
+                let macro_region = |region: u32| match region {
+                    0..=3 => 0,
+                    4..=6 => 1,
+                    7 => 2,
+                    _ => 3,
+                };
+                
+                Ok(if origin == dest {
+                    "local"
+                } else if macro_region(origin) == macro_region(dest) {
+                    "regional"
+                } else {
+                    "national"
+                })
+            }
+            
+            /// Leading digit of a CEP accepted by `BrazilianEntity::validate_cep`
+            /// (8 digits, not all zeros)
+            fn cep_region(cep: &str) -> Option<u32> {
+                let digits: String = cep.chars().filter(|c| c.is_ascii_digit()).collect();
+                if digits.len() != 8 || digits.chars().all(|c| c == '0') {
+                    return None;
+                }
+                digits.chars().next().and_then(|c| c.to_digit(10))
+            }
+            
             /// Get recommended carrier for route
             pub fn recommend_carrier(&self, origin: &str, dest: &str, weight_kg: f64) -> &'static str {
                 if origin == dest {
@@ -252,7 +377,283 @@ pub fn derive_shipping_entity(input: TokenStream) -> TokenStream {
                 }
             }
         }
+        
+        /// Errors from the generated freight calculation
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub enum #shipping_error {
+            /// Origin or destination CEP failed validation
+            InvalidCep(String),
+            /// Weight must be positive
+            InvalidWeight(rust_decimal::Decimal),
+        }
+        
+        impl std::fmt::Display for #shipping_error {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    Self::InvalidCep(cep) => write!(f, "invalid CEP `{}`", cep),
+                    Self::InvalidWeight(weight) => write!(f, "invalid shipment weight {} kg", weight),
+                }
+            }
+        }
+        
+        impl std::error::Error for #shipping_error {}
     };
     
     TokenStream::from(expanded)
-}
\ No newline at end of file
+}
+
+/// Options parsed from a BrazilianEntity's `#[brazilian(...)]`
+#[derive(Default)]
+pub struct BrazilianConfig {
+    /// Accept the 2026 alphanumeric CNPJ format
+    pub cnpj_alphanumeric: bool,
+    /// Verify the SP-style RG check digit on top of the shape check
+    pub rg_checkdigit: bool,
+    /// Generate `lookup_cep`
+    pub cep_lookup: bool,
+}
+
+impl BrazilianConfig {
+    /// Parse `#[brazilian(...)]` attributes, leaving keys handled elsewhere untouched
+    pub fn from_attrs(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut config = BrazilianConfig::default();
+        
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("brazilian")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("cnpj_alphanumeric") {
+                    config.cnpj_alphanumeric = true;
+                } else if meta.path.is_ident("rg_checkdigit") {
+                    config.rg_checkdigit = true;
+                } else if meta.path.is_ident("cep_lookup") {
+                    config.cep_lookup = true;
+                } else if meta.input.peek(syn::Token![=]) {
+                    let _: syn::Expr = meta.value()?.parse()?;
+                }
+                Ok(())
+            })?;
+        }
+        
+        Ok(config)
+    }
+}
+
+/// Generate `lookup_cep` for `#[brazilian(cep_lookup)]`
+///
+/// The generated code calls `reqwest` from the deriving crate, which must depend on it
+/// itself; a proc-macro crate's dependencies are not visible to its users.
+pub fn generate_cep_lookup(input: &DeriveInput, config: &BrazilianConfig) -> proc_macro2::TokenStream {
+    if !config.cep_lookup {
+        return quote! {};
+    }
+    
+    let struct_name = &input.ident;
+    let address_type = quote::format_ident!("{}CepAddress", struct_name);
+    let error_type = quote::format_ident!("{}CepLookupError", struct_name);
+    quote! {
+        impl #struct_name {
+            /// ViaCEP-compatible endpoint used when `CEP_LOOKUP_URL` is unset
+            pub const DEFAULT_CEP_LOOKUP_URL: &'static str = "https://viacep.com.br/ws";
+            
+            /// Upper bound on a `lookup_cep` request, connection included
+            pub const CEP_LOOKUP_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+            
+            /// Resolve a CEP into an address via `{CEP_LOOKUP_URL}/{cep}/json/`, giving up
+            /// after `CEP_LOOKUP_TIMEOUT`
+            pub async fn lookup_cep(cep: &str) -> Result<#address_type, #error_type> {
+                static CLIENT: std::sync::OnceLock<reqwest::Client> = std::sync::OnceLock::new();
+                let client = match CLIENT.get() {
+                    Some(client) => client,
+                    None => {
+                        let client = reqwest::Client::builder()
+                            .timeout(Self::CEP_LOOKUP_TIMEOUT)
+                            .build()
+                            .map_err(|e| #error_type::LookupFailed(e.to_string()))?;
+                        CLIENT.get_or_init(|| client)
+                    }
+                };
+                Self::lookup_cep_with(client, cep).await
+            }
+            
+            /// `lookup_cep` through the caller's client, which decides timeouts and TLS
+            pub async fn lookup_cep_with(client: &reqwest::Client, cep: &str) -> Result<#address_type, #error_type> {
+                if !Self::validate_cep(cep) {
+                    return Err(#error_type::InvalidCep(cep.to_string()));
+                }
+                let digits: String = cep.chars().filter(|c| c.is_ascii_digit()).collect();
+                
+                let base_url = std::env::var("CEP_LOOKUP_URL")
+                    .unwrap_or_else(|_| Self::DEFAULT_CEP_LOOKUP_URL.to_string());
+                let url = format!("{}/{}/json/", base_url.trim_end_matches('/'), digits);
+                
+                let start = std::time::Instant::now();
+                let response = client.get(&url)
+                    .send()
+                    .await
+                    .map_err(|e| #error_type::LookupFailed(e.to_string()))?;
+                if response.status() != reqwest::StatusCode::OK {
+                    tracing::debug!(
+                        entity = %stringify!(#struct_name),
+                        status = %response.status(),
+                        "CEP lookup returned no address"
+                    );
+                    return Err(#error_type::CepNotFound(digits));
+                }
+                let body: serde_json::Value = response
+                    .json()
+                    .await
+                    .map_err(|e| #error_type::LookupFailed(e.to_string()))?;
+                
+                // ViaCEP answers unknown CEPs with 200 and `{"erro": true}`
+                let field = |key: &str| body.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+                if body.get("erro").is_some() || field("cep").is_empty() {
+                    return Err(#error_type::CepNotFound(digits));
+                }
+                
+                tracing::info!(
+                    entity = %stringify!(#struct_name),
+                    duration_ms = %start.elapsed().as_millis(),
+                    "CEP resolved"
+                );
+                
+                Ok(#address_type {
+                    street: field("logradouro"),
+                    neighborhood: field("bairro"),
+                    city: field("localidade"),
+                    state: field("uf"),
+                    cep: Self::format_cep(&digits),
+                })
+            }
+        }
+        
+        /// Address resolved by `lookup_cep`
+        #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+        pub struct #address_type {
+            pub street: String,
+            pub neighborhood: String,
+            pub city: String,
+            /// Two-letter UF
+            pub state: String,
+            /// Formatted `00000-000`
+            pub cep: String,
+        }
+        
+        /// Errors from the generated BrazilianEntity integrations
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub enum #error_type {
+            /// Failed `validate_cep`; no request was made
+            InvalidCep(String),
+            /// Non-200 or empty response for a well-formed CEP
+            CepNotFound(String),
+            /// Transport, timeout or decoding failure
+            LookupFailed(String),
+        }
+        
+        impl std::fmt::Display for #error_type {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    Self::InvalidCep(cep) => write!(f, "invalid CEP `{}`", cep),
+                    Self::CepNotFound(cep) => write!(f, "CEP {} not found", cep),
+                    Self::LookupFailed(reason) => write!(f, "CEP lookup failed: {}", reason),
+                }
+            }
+        }
+        
+        impl std::error::Error for #error_type {}
+    }
+}
+
+/// Document kinds accepted on fields as `#[brazilian(cpf)]`, with the validator each one calls
+const FIELD_KINDS: &[(&str, &str, &str)] = &[
+    ("cpf", "validate_cpf", "CPF"),
+    ("cnpj", "validate_cnpj", "CNPJ"),
+    ("cep", "validate_cep", "CEP"),
+    ("phone", "validate_brazilian_phone", "phone number"),
+];
+
+/// Fields annotated `#[brazilian(cpf | cnpj | cep | phone)]`, paired with their kind
+pub fn brazilian_fields(input: &DeriveInput) -> syn::Result<Vec<(&syn::Field, &'static str)>> {
+    let syn::Data::Struct(syn::DataStruct { fields: syn::Fields::Named(fields), .. }) = &input.data else {
+        return Ok(Vec::new());
+    };
+    
+    let mut annotated = Vec::new();
+    for field in &fields.named {
+        for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("brazilian")) {
+            attr.parse_nested_meta(|meta| {
+                match FIELD_KINDS.iter().find(|(kind, _, _)| meta.path.is_ident(kind)) {
+                    Some((kind, _, _)) => {
+                        annotated.push((field, *kind));
+                        Ok(())
+                    }
+                    None => Err(meta.error("expected one of `cpf`, `cnpj`, `cep`, `phone`")),
+                }
+            })?;
+        }
+    }
+    
+    Ok(annotated)
+}
+
+/// Generate `validate_<field>_field` per annotated field and the aggregating `validate_brazilian_fields`
+pub fn generate_field_validators(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let annotated = brazilian_fields(input)?;
+    if annotated.is_empty() {
+        return Ok(quote! {});
+    }
+    
+    let struct_name = &input.ident;
+    let mut methods = Vec::new();
+    let mut calls = Vec::new();
+    
+    for (field, kind) in annotated {
+        let ident = field.ident.as_ref().unwrap();
+        let (_, validator, label) = FIELD_KINDS.iter().find(|(k, _, _)| *k == kind).unwrap();
+        let validator = quote::format_ident!("{}", validator);
+        let method = quote::format_ident!("validate_{}_field", ident);
+        let message = format!("{}: invalid {}", ident, label);
+        let doc = format!("Validate `{}` as a {}", ident, label);
+        
+        let is_option = matches!(&field.ty, syn::Type::Path(p) if p.path.segments.last().is_some_and(|s| s.ident == "Option"));
+        let check = if is_option {
+            quote! {
+                match &self.#ident {
+                    Some(value) if !Self::#validator(value) => Err(#message.to_string()),
+                    _ => Ok(()),
+                }
+            }
+        } else {
+            quote! {
+                if Self::#validator(&self.#ident) {
+                    Ok(())
+                } else {
+                    Err(#message.to_string())
+                }
+            }
+        };
+        
+        methods.push(quote! {
+            #[doc = #doc]
+            pub fn #method(&self) -> Result<(), String> {
+                #check
+            }
+        });
+        calls.push(quote! {
+            if let Err(error) = self.#method() {
+                errors.push(error);
+            }
+        });
+    }
+    
+    Ok(quote! {
+        impl #struct_name {
+            #(#methods)*
+            
+            /// Run every `#[brazilian(...)]` field validator, collecting all failures
+            pub fn validate_brazilian_fields(&self) -> Vec<String> {
+                let mut errors = Vec::new();
+                #(#calls)*
+                errors
+            }
+        }
+    })
+}
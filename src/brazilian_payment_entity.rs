@@ -6,8 +6,8 @@
 
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
-use quote::{quote, format_ident};
-use syn::{parse_macro_input, DeriveInput, Data, Fields, Field, Attribute, Meta, NestedMeta, Lit};
+use quote::quote;
+use syn::{parse_macro_input, Attribute, DeriveInput};
 
 /// Brazilian payment configuration
 #[derive(Default)]
@@ -20,10 +20,31 @@ struct BrazilianConfig {
     tax_rate_icms: f64,
     tax_rate_pis: f64,
     tax_rate_cofins: f64,
+    /// Anexo table for `regime = "simples"`; `None` keeps the lucro presumido taxes only
+    simples_anexo: Option<&'static SimplesAnexo>,
 }
 
+/// A Simples Nacional anexo: `(revenue ceiling, nominal rate in hundredths of a percent,
+/// deduction)` per bracket, in BRL over the trailing twelve months (LC 155/2016)
+type SimplesAnexo = [(i64, i64, i64); 6];
+
+const SIMPLES_ANEXOS: &[(&str, SimplesAnexo)] = &[
+    ("I", [(180_000, 400, 0), (360_000, 730, 5_940), (720_000, 950, 13_860),
+           (1_800_000, 1070, 22_500), (3_600_000, 1430, 87_300), (4_800_000, 1900, 378_000)]),
+    ("II", [(180_000, 450, 0), (360_000, 780, 5_940), (720_000, 1000, 13_860),
+            (1_800_000, 1120, 22_500), (3_600_000, 1470, 85_500), (4_800_000, 3000, 720_000)]),
+    ("III", [(180_000, 600, 0), (360_000, 1120, 9_360), (720_000, 1350, 17_640),
+             (1_800_000, 1600, 35_640), (3_600_000, 2100, 125_640), (4_800_000, 3300, 648_000)]),
+    ("IV", [(180_000, 450, 0), (360_000, 900, 8_100), (720_000, 1020, 12_420),
+            (1_800_000, 1400, 39_780), (3_600_000, 2200, 183_780), (4_800_000, 3300, 828_000)]),
+    ("V", [(180_000, 1550, 0), (360_000, 1800, 4_500), (720_000, 1950, 9_900),
+           (1_800_000, 2050, 17_100), (3_600_000, 2300, 62_100), (4_800_000, 3050, 540_000)]),
+];
+
 impl BrazilianConfig {
-    fn from_attrs(attrs: &[Attribute]) -> Self {
+    fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut regime: Option<syn::LitStr> = None;
+        let mut anexo: Option<syn::LitStr> = None;
         let mut config = BrazilianConfig {
             pix_support: true,
             boleto_support: true,
@@ -36,79 +57,195 @@ impl BrazilianConfig {
         };
         
         for attr in attrs {
-            if attr.path.is_ident("brazilian_payment") {
-                if let Ok(Meta::List(meta_list)) = attr.parse_meta() {
-                    for nested_meta in meta_list.nested {
-                        match nested_meta {
-                            NestedMeta::Meta(Meta::NameValue(name_value)) => {
-                                match name_value.path.get_ident().map(|i| i.to_string()).as_deref() {
-                                    Some("tax_type") => {
-                                        if let Lit::Str(lit_str) = name_value.lit {
-                                            config.tax_type = Some(lit_str.value());
-                                        }
-                                    }
-                                    Some("currency") => {
-                                        if let Lit::Str(lit_str) = name_value.lit {
-                                            config.currency = lit_str.value();
-                                        }
-                                    }
-                                    Some("icms_rate") => {
-                                        if let Lit::Float(lit_float) = name_value.lit {
-                                            config.tax_rate_icms = lit_float.base10_parse().unwrap_or(0.18);
-                                        }
-                                    }
-                                    Some("pis_rate") => {
-                                        if let Lit::Float(lit_float) = name_value.lit {
-                                            config.tax_rate_pis = lit_float.base10_parse().unwrap_or(0.0165);
-                                        }
-                                    }
-                                    Some("cofins_rate") => {
-                                        if let Lit::Float(lit_float) = name_value.lit {
-                                            config.tax_rate_cofins = lit_float.base10_parse().unwrap_or(0.076);
-                                        }
-                                    }
-                                    _ => {}
-                                }
-                            }
-                            NestedMeta::Meta(Meta::Path(path)) => {
-                                if path.is_ident("no_pix") {
-                                    config.pix_support = false;
-                                } else if path.is_ident("no_boleto") {
-                                    config.boleto_support = false;
-                                } else if path.is_ident("no_tax") {
-                                    config.tax_calculation = false;
-                                }
-                            }
-                            _ => {}
-                        }
+            if attr.path().is_ident("brazilian_payment") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("tax_type") {
+                        let value: syn::LitStr = meta.value()?.parse()?;
+                        config.tax_type = Some(value.value());
+                    } else if meta.path.is_ident("currency") {
+                        let value: syn::LitStr = meta.value()?.parse()?;
+                        config.currency = value.value();
+                    } else if meta.path.is_ident("icms_rate") {
+                        config.tax_rate_icms = parse_rate(&meta)?;
+                    } else if meta.path.is_ident("pis_rate") {
+                        config.tax_rate_pis = parse_rate(&meta)?;
+                    } else if meta.path.is_ident("cofins_rate") {
+                        config.tax_rate_cofins = parse_rate(&meta)?;
+                    } else if meta.path.is_ident("no_pix") {
+                        config.pix_support = false;
+                    } else if meta.path.is_ident("no_boleto") {
+                        config.boleto_support = false;
+                    } else if meta.path.is_ident("no_tax") {
+                        config.tax_calculation = false;
+                    } else if meta.path.is_ident("regime") {
+                        regime = Some(meta.value()?.parse()?);
+                    } else if meta.path.is_ident("anexo") {
+                        anexo = Some(meta.value()?.parse()?);
+                    } else if meta.input.peek(syn::Token![=]) {
+                        let _: syn::Expr = meta.value()?.parse()?;
                     }
+                    Ok(())
+                })?;
+            }
+        }
+        
+        match regime.as_ref().map(|regime| regime.value()).as_deref() {
+            None | Some("lucro_presumido") => {
+                if let Some(anexo) = &anexo {
+                    return Err(syn::Error::new_spanned(anexo, "`anexo` only applies to `regime = \"simples\"`"));
                 }
             }
+            Some("simples") => {
+                let name = anexo.as_ref().map(|anexo| anexo.value()).unwrap_or_else(|| "I".to_string());
+                let table = SIMPLES_ANEXOS.iter().find(|(anexo, _)| *anexo == name).ok_or_else(|| {
+                    syn::Error::new_spanned(
+                        anexo.as_ref().unwrap(),
+                        format!("unknown Simples Nacional anexo `{}`; expected \"I\" to \"V\"", name),
+                    )
+                })?;
+                config.simples_anexo = Some(&table.1);
+            }
+            Some(other) => {
+                return Err(syn::Error::new_spanned(
+                    regime.as_ref().unwrap(),
+                    format!("unknown tax regime `{}`; expected \"lucro_presumido\" or \"simples\"", other),
+                ))
+            }
         }
         
-        config
+        Ok(config)
+    }
+}
+
+/// Parse a tax rate given as a fraction, e.g. `icms_rate = 0.18`
+fn parse_rate(meta: &syn::meta::ParseNestedMeta) -> syn::Result<f64> {
+    let input = meta.value()?;
+    let minus: Option<syn::Token![-]> = input.parse()?;
+    let value: syn::LitFloat = input.parse()?;
+    let rate: f64 = value.base10_parse()?;
+    if minus.is_some() && rate != 0.0 {
+        return Err(syn::Error::new_spanned(
+            &value,
+            format!("tax rates must not be negative, got `-{}`", value),
+        ));
     }
+    if rate >= 1.0 {
+        return Err(syn::Error::new_spanned(
+            &value,
+            format!("tax rates are fractions between 0.0 and 1.0, got `{}`", value),
+        ));
+    }
+    Ok(rate)
+}
+
+/// Whether a type is `Option<...>`
+fn is_option(ty: &syn::Type) -> bool {
+    matches!(ty, syn::Type::Path(type_path) if type_path.path.segments.last().is_some_and(|seg| seg.ident == "Option"))
+}
+
+/// Generate the field accessors the payment methods rely on
+///
+/// `id`, `amount` and `status` are required (plus `updated_at` with PIX support);
+/// `customer_document` is optional and reported as `None` when absent.
+fn generate_accessors(input: &DeriveInput, config: &BrazilianConfig) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        syn::Data::Struct(syn::DataStruct { fields: syn::Fields::Named(fields), .. }) => &fields.named,
+        _ => return Err(syn::Error::new_spanned(
+            &input.ident,
+            "BrazilianPaymentEntity requires a struct with named fields",
+        )),
+    };
+    let field = |name: &str| fields.iter().find(|f| f.ident.as_ref().is_some_and(|i| i == name));
+    let require = |name: &str, ty: &str| field(name).ok_or_else(|| syn::Error::new_spanned(
+        &input.ident,
+        format!("BrazilianPaymentEntity requires a `{}: {}` field", name, ty),
+    ));
+    
+    require("id", "uuid::Uuid")?;
+    require("status", "PaymentStatus")?;
+    let amount = if is_option(&require("amount", "rust_decimal::Decimal")?.ty) {
+        quote! { self.amount }
+    } else {
+        quote! { Some(self.amount) }
+    };
+    let customer_document = match field("customer_document") {
+        Some(f) if is_option(&f.ty) => quote! { self.customer_document.clone() },
+        Some(_) => quote! { Some(self.customer_document.clone()) },
+        None => quote! { None },
+    };
+    let mutators = if config.pix_support {
+        require("updated_at", "chrono::DateTime<chrono::Utc>")?;
+        quote! {
+            fn set_status(&mut self, status: PaymentStatus) {
+                self.status = status;
+            }
+            
+            fn set_updated_at(&mut self, timestamp: chrono::DateTime<chrono::Utc>) {
+                self.updated_at = timestamp;
+            }
+        }
+    } else {
+        quote! {}
+    };
+    
+    Ok(quote! {
+        fn get_id(&self) -> uuid::Uuid {
+            self.id
+        }
+        
+        fn get_amount(&self) -> Option<rust_decimal::Decimal> {
+            #amount
+        }
+        
+        fn get_status(&self) -> &PaymentStatus {
+            &self.status
+        }
+        
+        fn get_customer_document(&self) -> Option<String> {
+            #customer_document
+        }
+        
+        #mutators
+    })
 }
 
 pub fn derive_brazilian_payment_entity(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let struct_name = &input.ident;
-    let config = BrazilianConfig::from_attrs(&input.attrs);
+    let config = match BrazilianConfig::from_attrs(&input.attrs) {
+        Ok(config) => config,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let accessors = match generate_accessors(&input, &config) {
+        Ok(tokens) => tokens,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let currency = &config.currency;
+    // Supporting types are named per entity so several derives can share a module
+    let pix_data = quote::format_ident!("{}PixData", struct_name);
+    let pix_key_type = quote::format_ident!("{}PixKeyType", struct_name);
+    let boleto_data = quote::format_ident!("{}BoletoData", struct_name);
+    let tax_breakdown = quote::format_ident!("{}TaxBreakdown", struct_name);
+    let tax_exemption = quote::format_ident!("{}TaxExemption", struct_name);
+    let tax_type = quote::format_ident!("{}TaxType", struct_name);
+    let receipt = quote::format_ident!("{}Receipt", struct_name);
+    let compliance_result = quote::format_ident!("{}ComplianceResult", struct_name);
+    let payment_error = quote::format_ident!("{}PaymentError", struct_name);
     
     let pix_methods = if config.pix_support {
         quote! {
             /// Generate PIX QR Code for payment
-            pub fn generate_pix_qr_code(&self) -> Result<String, BrazilianPaymentError> {
+            pub fn generate_pix_qr_code(&self) -> Result<String, #payment_error> {
                 if let Some(amount) = self.get_amount() {
-                    let pix_data = PixData {
+                    let pix_data = #pix_data {
                         merchant_name: "Pleme Payment",
                         merchant_city: "São Paulo",
                         transaction_id: self.get_id().to_string(),
                         amount: amount,
-                        currency: &#config.currency,
+                        currency: #currency,
                     };
                     
-                    let qr_code = generate_pix_qr(&pix_data)?;
+                    let qr_code = Self::generate_pix_qr(&pix_data)?;
                     
                     tracing::info!(
                         entity = %stringify!(#struct_name),
@@ -119,44 +256,44 @@ pub fn derive_brazilian_payment_entity(input: TokenStream) -> TokenStream {
                     
                     Ok(qr_code)
                 } else {
-                    Err(BrazilianPaymentError::InvalidAmount("Amount is required for PIX".to_string()))
+                    Err(#payment_error::InvalidAmount("Amount is required for PIX".to_string()))
                 }
             }
             
             /// Validate PIX key format and type
-            pub fn validate_pix_key(key: &str, key_type: PixKeyType) -> Result<(), BrazilianPaymentError> {
+            pub fn validate_pix_key(key: &str, key_type: #pix_key_type) -> Result<(), #payment_error> {
                 match key_type {
-                    PixKeyType::Cpf => {
+                    #pix_key_type::Cpf => {
                         if !Self::validate_cpf(key) {
-                            return Err(BrazilianPaymentError::InvalidPixKey(
+                            return Err(#payment_error::InvalidPixKey(
                                 format!("Invalid CPF: {}", key)
                             ));
                         }
                     }
-                    PixKeyType::Cnpj => {
+                    #pix_key_type::Cnpj => {
                         if !Self::validate_cnpj(key) {
-                            return Err(BrazilianPaymentError::InvalidPixKey(
+                            return Err(#payment_error::InvalidPixKey(
                                 format!("Invalid CNPJ: {}", key)
                             ));
                         }
                     }
-                    PixKeyType::Email => {
+                    #pix_key_type::Email => {
                         if !Self::validate_email(key) {
-                            return Err(BrazilianPaymentError::InvalidPixKey(
+                            return Err(#payment_error::InvalidPixKey(
                                 format!("Invalid email: {}", key)
                             ));
                         }
                     }
-                    PixKeyType::Phone => {
+                    #pix_key_type::Phone => {
                         if !Self::validate_brazilian_phone(key) {
-                            return Err(BrazilianPaymentError::InvalidPixKey(
+                            return Err(#payment_error::InvalidPixKey(
                                 format!("Invalid Brazilian phone: {}", key)
                             ));
                         }
                     }
-                    PixKeyType::Random => {
+                    #pix_key_type::Random => {
                         if !Self::validate_uuid_key(key) {
-                            return Err(BrazilianPaymentError::InvalidPixKey(
+                            return Err(#payment_error::InvalidPixKey(
                                 format!("Invalid random PIX key: {}", key)
                             ));
                         }
@@ -172,15 +309,30 @@ pub fn derive_brazilian_payment_entity(input: TokenStream) -> TokenStream {
                 Ok(())
             }
             
+            /// Generate PIX QR code (placeholder implementation)
+            fn generate_pix_qr(data: &#pix_data) -> Result<String, #payment_error> {
+                // In a real implementation, this would generate the actual PIX QR code format
+                // following the Brazilian Central Bank specifications
+                Ok(format!("pix://pay?amount={}&id={}", data.amount, data.transaction_id))
+            }
+            
+            /// Validate email format for PIX keys
+            fn validate_email(email: &str) -> bool {
+                let email_regex = regex::Regex::new(
+                    r"^[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}$"
+                ).unwrap();
+                email_regex.is_match(email)
+            }
+            
             /// Validate UUID format for random PIX keys
             fn validate_uuid_key(key: &str) -> bool {
                 uuid::Uuid::parse_str(key).is_ok()
             }
             
             /// Process PIX instant payment confirmation
-            pub fn process_pix_confirmation(&mut self, end_to_end_id: &str, psp_reference: &str) -> Result<(), BrazilianPaymentError> {
+            pub fn process_pix_confirmation(&mut self, end_to_end_id: &str, psp_reference: &str) -> Result<(), #payment_error> {
                 if end_to_end_id.len() != 32 {
-                    return Err(BrazilianPaymentError::InvalidPixData(
+                    return Err(#payment_error::InvalidPixData(
                         "Invalid end-to-end ID format".to_string()
                     ));
                 }
@@ -206,16 +358,20 @@ pub fn derive_brazilian_payment_entity(input: TokenStream) -> TokenStream {
     let boleto_methods = if config.boleto_support {
         quote! {
             /// Generate Boleto bancário for payment
-            pub fn generate_boleto(&self) -> Result<BoletoData, BrazilianPaymentError> {
+            pub fn generate_boleto(&self) -> Result<#boleto_data, #payment_error> {
                 if let Some(amount) = self.get_amount() {
                     let due_date = chrono::Utc::now() + chrono::Duration::days(3);
                     
-                    let boleto = BoletoData {
+                    let our_number = format!("{:013}", self.get_id().as_u128() % 10_000_000_000_000);
+                    let boleto = #boleto_data {
                         bank_code: "341", // Itaú default
+                        currency_code: "9", // Real
                         agency: "1234",
                         account: "12345-6",
                         wallet: "109",
-                        our_number: format!("{:013}", self.get_id().as_u128() % 10_000_000_000_000),
+                        // Campo livre: wallet, our number, agency and account without its digit
+                        free_field: format!("109{}123412345", our_number),
+                        our_number,
                         document_number: self.get_id().to_string(),
                         due_date,
                         amount,
@@ -237,7 +393,7 @@ pub fn derive_brazilian_payment_entity(input: TokenStream) -> TokenStream {
                     
                     Ok(boleto)
                 } else {
-                    Err(BrazilianPaymentError::InvalidAmount("Amount is required for Boleto".to_string()))
+                    Err(#payment_error::InvalidAmount("Amount is required for Boleto".to_string()))
                 }
             }
             
@@ -273,31 +429,33 @@ pub fn derive_brazilian_payment_entity(input: TokenStream) -> TokenStream {
         
         quote! {
             /// Calculate Brazilian taxes (ICMS, PIS, COFINS)
-            pub fn calculate_brazilian_taxes(&self) -> Result<BrazilianTaxBreakdown, BrazilianPaymentError> {
+            pub fn calculate_brazilian_taxes(&self) -> Result<#tax_breakdown, #payment_error> {
                 if let Some(gross_amount) = self.get_amount() {
-                    let icms = gross_amount * rust_decimal::Decimal::from_f64(#icms_rate)
-                        .ok_or(BrazilianPaymentError::TaxCalculationError("Invalid ICMS rate".to_string()))?;
+                    let icms = gross_amount * <rust_decimal::Decimal as rust_decimal::prelude::FromPrimitive>::from_f64(#icms_rate)
+                        .ok_or(#payment_error::TaxCalculationError("Invalid ICMS rate".to_string()))?;
                     
-                    let pis = gross_amount * rust_decimal::Decimal::from_f64(#pis_rate)
-                        .ok_or(BrazilianPaymentError::TaxCalculationError("Invalid PIS rate".to_string()))?;
+                    let pis = gross_amount * <rust_decimal::Decimal as rust_decimal::prelude::FromPrimitive>::from_f64(#pis_rate)
+                        .ok_or(#payment_error::TaxCalculationError("Invalid PIS rate".to_string()))?;
                     
-                    let cofins = gross_amount * rust_decimal::Decimal::from_f64(#cofins_rate)
-                        .ok_or(BrazilianPaymentError::TaxCalculationError("Invalid COFINS rate".to_string()))?;
+                    let cofins = gross_amount * <rust_decimal::Decimal as rust_decimal::prelude::FromPrimitive>::from_f64(#cofins_rate)
+                        .ok_or(#payment_error::TaxCalculationError("Invalid COFINS rate".to_string()))?;
                     
                     let total_taxes = icms + pis + cofins;
                     let net_amount = gross_amount - total_taxes;
                     
-                    let breakdown = BrazilianTaxBreakdown {
+                    let breakdown = #tax_breakdown {
                         gross_amount,
                         icms_amount: icms,
-                        icms_rate: rust_decimal::Decimal::from_f64(#icms_rate).unwrap(),
+                        icms_rate: <rust_decimal::Decimal as rust_decimal::prelude::FromPrimitive>::from_f64(#icms_rate).unwrap(),
                         pis_amount: pis,
-                        pis_rate: rust_decimal::Decimal::from_f64(#pis_rate).unwrap(),
+                        pis_rate: <rust_decimal::Decimal as rust_decimal::prelude::FromPrimitive>::from_f64(#pis_rate).unwrap(),
                         cofins_amount: cofins,
-                        cofins_rate: rust_decimal::Decimal::from_f64(#cofins_rate).unwrap(),
+                        cofins_rate: <rust_decimal::Decimal as rust_decimal::prelude::FromPrimitive>::from_f64(#cofins_rate).unwrap(),
+                        das_amount: rust_decimal::Decimal::ZERO,
+                        das_rate: rust_decimal::Decimal::ZERO,
                         total_taxes,
                         net_amount,
-                        currency: #config.currency.to_string(),
+                        currency: #currency.to_string(),
                     };
                     
                     tracing::debug!(
@@ -310,27 +468,24 @@ pub fn derive_brazilian_payment_entity(input: TokenStream) -> TokenStream {
                     
                     Ok(breakdown)
                 } else {
-                    Err(BrazilianPaymentError::InvalidAmount("Amount is required for tax calculation".to_string()))
+                    Err(#payment_error::InvalidAmount("Amount is required for tax calculation".to_string()))
                 }
             }
             
             /// Apply tax exemptions based on Brazilian regulations
-            pub fn apply_tax_exemptions(&self, exemptions: Vec<TaxExemption>) -> Result<BrazilianTaxBreakdown, BrazilianPaymentError> {
+            pub fn apply_tax_exemptions(&self, exemptions: Vec<#tax_exemption>) -> Result<#tax_breakdown, #payment_error> {
                 let mut base_taxes = self.calculate_brazilian_taxes()?;
                 
                 for exemption in exemptions {
                     match exemption.tax_type {
-                        TaxType::Icms => {
-                            base_taxes.icms_amount = base_taxes.icms_amount * 
-                                (rust_decimal::Decimal::ONE - exemption.exemption_rate);
+                        #tax_type::Icms => {
+                            base_taxes.icms_amount *= rust_decimal::Decimal::ONE - exemption.exemption_rate;
                         }
-                        TaxType::Pis => {
-                            base_taxes.pis_amount = base_taxes.pis_amount * 
-                                (rust_decimal::Decimal::ONE - exemption.exemption_rate);
+                        #tax_type::Pis => {
+                            base_taxes.pis_amount *= rust_decimal::Decimal::ONE - exemption.exemption_rate;
                         }
-                        TaxType::Cofins => {
-                            base_taxes.cofins_amount = base_taxes.cofins_amount * 
-                                (rust_decimal::Decimal::ONE - exemption.exemption_rate);
+                        #tax_type::Cofins => {
+                            base_taxes.cofins_amount *= rust_decimal::Decimal::ONE - exemption.exemption_rate;
                         }
                     }
                     
@@ -347,16 +502,125 @@ pub fn derive_brazilian_payment_entity(input: TokenStream) -> TokenStream {
                 
                 Ok(base_taxes)
             }
+            
+            /// Work backward from a tax-inclusive retail price (\"imposto incluso\")
+            /// to the net base and the taxes embedded in it
+            ///
+            /// Each component is rounded to centavos and the net base absorbs the
+            /// rounding, so `net_amount + total_taxes == gross_amount` exactly.
+            pub fn extract_taxes_from_inclusive(&self) -> #tax_breakdown {
+                let gross_amount = self.get_amount().unwrap_or(rust_decimal::Decimal::ZERO);
+                let icms_rate = <rust_decimal::Decimal as rust_decimal::prelude::FromPrimitive>::from_f64(#icms_rate).unwrap_or_default();
+                let pis_rate = <rust_decimal::Decimal as rust_decimal::prelude::FromPrimitive>::from_f64(#pis_rate).unwrap_or_default();
+                let cofins_rate = <rust_decimal::Decimal as rust_decimal::prelude::FromPrimitive>::from_f64(#cofins_rate).unwrap_or_default();
+                
+                // gross = base * (1 + rates)
+                let base = gross_amount / (rust_decimal::Decimal::ONE + icms_rate + pis_rate + cofins_rate);
+                let icms_amount = (base * icms_rate).round_dp(2);
+                let pis_amount = (base * pis_rate).round_dp(2);
+                let cofins_amount = (base * cofins_rate).round_dp(2);
+                let total_taxes = icms_amount + pis_amount + cofins_amount;
+                let net_amount = gross_amount - total_taxes;
+                
+                tracing::debug!(
+                    entity = %stringify!(#struct_name),
+                    gross_amount = %gross_amount,
+                    total_taxes = %total_taxes,
+                    net_amount = %net_amount,
+                    "Taxes extracted from tax-inclusive price"
+                );
+                
+                #tax_breakdown {
+                    gross_amount,
+                    icms_amount,
+                    icms_rate,
+                    pis_amount,
+                    pis_rate,
+                    cofins_amount,
+                    cofins_rate,
+                    das_amount: rust_decimal::Decimal::ZERO,
+                    das_rate: rust_decimal::Decimal::ZERO,
+                    total_taxes,
+                    net_amount,
+                    currency: #currency.to_string(),
+                }
+            }
         }
     } else {
         quote! {}
     };
     
+    let simples_methods = match config.simples_anexo {
+        Some(anexo) => {
+            let brackets = anexo.iter().map(|(ceiling, nominal_rate, deduction)| quote! {
+                (
+                    rust_decimal::Decimal::from(#ceiling),
+                    rust_decimal::Decimal::new(#nominal_rate, 4),
+                    rust_decimal::Decimal::from(#deduction),
+                )
+            });
+            quote! {
+                /// Simples Nacional DAS for this payment, replacing ICMS/PIS/COFINS
+                ///
+                /// The bracket is selected by `annual_revenue` (RBT12) and the effective rate is
+                /// `(RBT12 * nominal rate - deduction) / RBT12`, per LC 155/2016.
+                pub fn calculate_simples_nacional(&self, annual_revenue: rust_decimal::Decimal) -> Result<#tax_breakdown, #payment_error> {
+                    let brackets = [#(#brackets),*];
+                    
+                    let gross_amount = self.get_amount()
+                        .ok_or_else(|| #payment_error::InvalidAmount("Amount is required for tax calculation".to_string()))?;
+                    if annual_revenue < rust_decimal::Decimal::ZERO {
+                        return Err(#payment_error::TaxCalculationError(format!("Negative annual revenue {}", annual_revenue)));
+                    }
+                    let (_, nominal_rate, deduction) = brackets.iter()
+                        .find(|(ceiling, _, _)| annual_revenue <= *ceiling)
+                        .ok_or_else(|| #payment_error::TaxCalculationError(format!(
+                            "Annual revenue {} exceeds the Simples Nacional ceiling", annual_revenue
+                        )))?;
+                    
+                    // Companies without revenue history pay the nominal first-bracket rate
+                    let das_rate = if annual_revenue.is_zero() {
+                        *nominal_rate
+                    } else {
+                        (annual_revenue * nominal_rate - deduction) / annual_revenue
+                    };
+                    let das_amount = (gross_amount * das_rate).round_dp(2);
+                    let net_amount = gross_amount - das_amount;
+                    
+                    tracing::debug!(
+                        entity = %stringify!(#struct_name),
+                        gross_amount = %gross_amount,
+                        das_rate = %das_rate,
+                        das_amount = %das_amount,
+                        "Simples Nacional DAS calculated"
+                    );
+                    
+                    Ok(#tax_breakdown {
+                        gross_amount,
+                        icms_amount: rust_decimal::Decimal::ZERO,
+                        icms_rate: rust_decimal::Decimal::ZERO,
+                        pis_amount: rust_decimal::Decimal::ZERO,
+                        pis_rate: rust_decimal::Decimal::ZERO,
+                        cofins_amount: rust_decimal::Decimal::ZERO,
+                        cofins_rate: rust_decimal::Decimal::ZERO,
+                        das_amount,
+                        das_rate,
+                        total_taxes: das_amount,
+                        net_amount,
+                        currency: #currency.to_string(),
+                    })
+                }
+            }
+        }
+        None => quote! {},
+    };
+    
     let expanded = quote! {
         impl #struct_name {
             #pix_methods
             #boleto_methods
             #tax_methods
+            #simples_methods
             
             /// Format amount in Brazilian Real (BRL) with proper formatting
             pub fn format_brl_amount(amount: rust_decimal::Decimal) -> String {
@@ -381,7 +645,7 @@ pub fn derive_brazilian_payment_entity(input: TokenStream) -> TokenStream {
             }
             
             /// Parse BRL formatted amount to Decimal
-            pub fn parse_brl_amount(formatted: &str) -> Result<rust_decimal::Decimal, BrazilianPaymentError> {
+            pub fn parse_brl_amount(formatted: &str) -> Result<rust_decimal::Decimal, #payment_error> {
                 let cleaned = formatted
                     .replace("R$", "")
                     .replace(" ", "")
@@ -389,25 +653,17 @@ pub fn derive_brazilian_payment_entity(input: TokenStream) -> TokenStream {
                     .replace(",", ".");
                 
                 cleaned.parse::<rust_decimal::Decimal>()
-                    .map_err(|e| BrazilianPaymentError::InvalidAmount(
+                    .map_err(|e| #payment_error::InvalidAmount(
                         format!("Failed to parse BRL amount '{}': {}", formatted, e)
                     ))
             }
             
-            /// Validate email format for PIX keys
-            fn validate_email(email: &str) -> bool {
-                let email_regex = regex::Regex::new(
-                    r"^[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}$"
-                ).unwrap();
-                email_regex.is_match(email)
-            }
-            
             /// Generate payment receipt in Portuguese
-            pub fn generate_brazilian_receipt(&self) -> Result<BrazilianReceipt, BrazilianPaymentError> {
-                let receipt = BrazilianReceipt {
+            pub fn generate_brazilian_receipt(&self) -> Result<#receipt, #payment_error> {
+                let receipt = #receipt {
                     transaction_id: self.get_id().to_string(),
                     date: chrono::Utc::now().with_timezone(&chrono_tz::America::Sao_Paulo),
-                    amount: self.get_amount().ok_or(BrazilianPaymentError::InvalidAmount(
+                    amount: self.get_amount().ok_or(#payment_error::InvalidAmount(
                         "Amount is required".to_string()
                     ))?,
                     formatted_amount: Self::format_brl_amount(self.get_amount().unwrap()),
@@ -446,7 +702,7 @@ pub fn derive_brazilian_payment_entity(input: TokenStream) -> TokenStream {
             }
             
             /// Check if payment complies with Brazilian Central Bank regulations
-            pub fn validate_bcb_compliance(&self) -> Result<ComplianceResult, BrazilianPaymentError> {
+            pub fn validate_bcb_compliance(&self) -> Result<#compliance_result, #payment_error> {
                 let mut issues = Vec::new();
                 let mut warnings = Vec::new();
                 
@@ -463,15 +719,15 @@ pub fn derive_brazilian_payment_entity(input: TokenStream) -> TokenStream {
                 
                 // Check business hours for larger amounts
                 let now = chrono::Utc::now().with_timezone(&chrono_tz::America::Sao_Paulo);
-                let hour = now.hour();
+                let hour = chrono::Timelike::hour(&now);
                 
                 if let Some(amount) = self.get_amount() {
-                    if amount > rust_decimal::Decimal::from(1000) && (hour < 6 || hour > 20) {
+                    if amount > rust_decimal::Decimal::from(1000) && !(6..=20).contains(&hour) {
                         warnings.push("Large amount transfer outside business hours".to_string());
                     }
                 }
                 
-                let compliance = ComplianceResult {
+                let compliance = #compliance_result {
                     is_compliant: issues.is_empty(),
                     issues,
                     warnings,
@@ -493,18 +749,12 @@ pub fn derive_brazilian_payment_entity(input: TokenStream) -> TokenStream {
                 Ok(compliance)
             }
             
-            // Abstract methods that implementing structs must provide
-            fn get_id(&self) -> uuid::Uuid;
-            fn get_amount(&self) -> Option<rust_decimal::Decimal>;
-            fn get_status(&self) -> PaymentStatus;
-            fn set_status(&mut self, status: PaymentStatus);
-            fn set_updated_at(&mut self, timestamp: chrono::DateTime<chrono::Utc>);
-            fn get_customer_document(&self) -> Option<String>;
+            #accessors
         }
         
         /// PIX QR Code data structure
         #[derive(Debug, Clone)]
-        pub struct PixData {
+        pub struct #pix_data {
             pub merchant_name: &'static str,
             pub merchant_city: &'static str,
             pub transaction_id: String,
@@ -514,7 +764,7 @@ pub fn derive_brazilian_payment_entity(input: TokenStream) -> TokenStream {
         
         /// PIX key types supported in Brazil
         #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-        pub enum PixKeyType {
+        pub enum #pix_key_type {
             Cpf,
             Cnpj,
             Email,
@@ -524,11 +774,15 @@ pub fn derive_brazilian_payment_entity(input: TokenStream) -> TokenStream {
         
         /// Boleto bancário data structure
         #[derive(Debug, Clone)]
-        pub struct BoletoData {
+        pub struct #boleto_data {
             pub bank_code: &'static str,
+            /// FEBRABAN currency code, `"9"` for Real
+            pub currency_code: &'static str,
             pub agency: &'static str,
             pub account: &'static str,
             pub wallet: &'static str,
+            /// Bank-specific 25-digit campo livre (barcode positions 20-44)
+            pub free_field: String,
             pub our_number: String,
             pub document_number: String,
             pub due_date: chrono::DateTime<chrono::Utc>,
@@ -538,9 +792,100 @@ pub fn derive_brazilian_payment_entity(input: TokenStream) -> TokenStream {
             pub instructions: Vec<String>,
         }
         
+        impl #boleto_data {
+            /// 44-digit barcode in the FEBRABAN layout: bank, currency, general check
+            /// digit, due-date factor, amount in cents and the free field
+            pub fn boleto_barcode(&self) -> Result<String, #payment_error> {
+                let (factor, amount) = self.boleto_factor_and_amount()?;
+                let body = format!("{}{}{}{}{}", self.bank_code, self.currency_code, factor, amount, self.free_field);
+                let dv = Self::boleto_mod11(&body);
+                Ok(format!("{}{}{}{}{}{}", self.bank_code, self.currency_code, dv, factor, amount, self.free_field))
+            }
+            
+            /// 47-digit linha digitável (digits only): three mod-10 checked fields
+            /// carrying bank, currency and the free field, the general check digit,
+            /// then the due-date factor and amount
+            pub fn boleto_linha_digitavel(&self) -> Result<String, #payment_error> {
+                let barcode = self.boleto_barcode()?;
+                let field1 = format!("{}{}", &barcode[0..4], &barcode[19..24]);
+                let field2 = &barcode[24..34];
+                let field3 = &barcode[34..44];
+                
+                Ok(format!(
+                    "{}{}{}{}{}{}{}{}",
+                    field1, Self::boleto_mod10(&field1),
+                    field2, Self::boleto_mod10(field2),
+                    field3, Self::boleto_mod10(field3),
+                    &barcode[4..5],
+                    &barcode[5..19],
+                ))
+            }
+            
+            /// Due-date factor (days since 1997-10-07, restarting at 1000 after 9999)
+            /// and amount in cents, both zero-padded
+            fn boleto_factor_and_amount(&self) -> Result<(String, String), #payment_error> {
+                let is_digits = |value: &str, len: usize| value.len() == len && value.bytes().all(|b| b.is_ascii_digit());
+                if !is_digits(self.bank_code, 3) || !is_digits(self.currency_code, 1) {
+                    return Err(#payment_error::InvalidBoleto(format!(
+                        "bank code `{}` and currency code `{}` must be 3 and 1 digits",
+                        self.bank_code, self.currency_code
+                    )));
+                }
+                if !is_digits(&self.free_field, 25) {
+                    return Err(#payment_error::InvalidBoleto(format!(
+                        "free field must be 25 digits, got `{}`",
+                        self.free_field
+                    )));
+                }
+                
+                let base_date = chrono::NaiveDate::from_ymd_opt(1997, 10, 7).unwrap();
+                let due_date = self.due_date.with_timezone(&chrono_tz::America::Sao_Paulo).date_naive();
+                let days = (due_date - base_date).num_days();
+                if days < 1000 {
+                    return Err(#payment_error::InvalidBoleto(format!(
+                        "due date {} is before the first FEBRABAN factor",
+                        due_date
+                    )));
+                }
+                let factor = if days > 9999 { (days - 1000) % 9000 + 1000 } else { days };
+                
+                let cents = self.amount * rust_decimal::Decimal::from(100);
+                if self.amount.is_sign_negative() || !cents.fract().is_zero() || cents > rust_decimal::Decimal::from(9_999_999_999u64) {
+                    return Err(#payment_error::InvalidAmount(format!(
+                        "Boleto amount must be between 0.00 and 99999999.99 with at most 2 decimals, got {}",
+                        self.amount
+                    )));
+                }
+                
+                Ok((format!("{:04}", factor), format!("{:0>10}", cents.trunc().normalize().to_string())))
+            }
+            
+            /// FEBRABAN general check digit: weights 2-9 from the right, 10 and 11 become 1
+            fn boleto_mod11(digits: &str) -> u32 {
+                let sum: u32 = digits.bytes().rev().enumerate()
+                    .map(|(i, b)| u32::from(b - b'0') * (i as u32 % 8 + 2))
+                    .sum();
+                match 11 - sum % 11 {
+                    10 | 11 => 1,
+                    dv => dv,
+                }
+            }
+            
+            /// Linha digitável field check digit: alternating weights 2 and 1 from the right
+            fn boleto_mod10(digits: &str) -> u32 {
+                let sum: u32 = digits.bytes().rev().enumerate()
+                    .map(|(i, b)| {
+                        let product = u32::from(b - b'0') * if i % 2 == 0 { 2 } else { 1 };
+                        product / 10 + product % 10
+                    })
+                    .sum();
+                (10 - sum % 10) % 10
+            }
+        }
+        
         /// Brazilian tax breakdown
         #[derive(Debug, Clone)]
-        pub struct BrazilianTaxBreakdown {
+        pub struct #tax_breakdown {
             pub gross_amount: rust_decimal::Decimal,
             pub icms_amount: rust_decimal::Decimal,
             pub icms_rate: rust_decimal::Decimal,
@@ -548,6 +893,10 @@ pub fn derive_brazilian_payment_entity(input: TokenStream) -> TokenStream {
             pub pis_rate: rust_decimal::Decimal,
             pub cofins_amount: rust_decimal::Decimal,
             pub cofins_rate: rust_decimal::Decimal,
+            /// Unified Simples Nacional payment (DAS); zero under lucro presumido
+            pub das_amount: rust_decimal::Decimal,
+            /// Effective Simples Nacional rate applied to `gross_amount`
+            pub das_rate: rust_decimal::Decimal,
             pub total_taxes: rust_decimal::Decimal,
             pub net_amount: rust_decimal::Decimal,
             pub currency: String,
@@ -555,15 +904,15 @@ pub fn derive_brazilian_payment_entity(input: TokenStream) -> TokenStream {
         
         /// Tax exemption configuration
         #[derive(Debug, Clone)]
-        pub struct TaxExemption {
-            pub tax_type: TaxType,
+        pub struct #tax_exemption {
+            pub tax_type: #tax_type,
             pub exemption_rate: rust_decimal::Decimal,
             pub reason: String,
         }
         
         /// Brazilian tax types
         #[derive(Debug, Clone, Copy)]
-        pub enum TaxType {
+        pub enum #tax_type {
             Icms,  // State tax on goods and services
             Pis,   // Social contribution on revenue  
             Cofins, // Social contribution on revenue
@@ -571,7 +920,7 @@ pub fn derive_brazilian_payment_entity(input: TokenStream) -> TokenStream {
         
         /// Brazilian payment receipt
         #[derive(Debug, Clone)]
-        pub struct BrazilianReceipt {
+        pub struct #receipt {
             pub transaction_id: String,
             pub date: chrono::DateTime<chrono_tz::Tz>,
             pub amount: rust_decimal::Decimal,
@@ -585,7 +934,7 @@ pub fn derive_brazilian_payment_entity(input: TokenStream) -> TokenStream {
         
         /// BCB compliance check result
         #[derive(Debug, Clone)]
-        pub struct ComplianceResult {
+        pub struct #compliance_result {
             pub is_compliant: bool,
             pub issues: Vec<String>,
             pub warnings: Vec<String>,
@@ -595,7 +944,7 @@ pub fn derive_brazilian_payment_entity(input: TokenStream) -> TokenStream {
         
         /// Brazilian payment specific errors
         #[derive(Debug, thiserror::Error)]
-        pub enum BrazilianPaymentError {
+        pub enum #payment_error {
             #[error("Invalid amount: {0}")]
             InvalidAmount(String),
             
@@ -605,19 +954,16 @@ pub fn derive_brazilian_payment_entity(input: TokenStream) -> TokenStream {
             #[error("Invalid PIX data: {0}")]
             InvalidPixData(String),
             
+            #[error("Invalid Boleto: {0}")]
+            InvalidBoleto(String),
+            
             #[error("Tax calculation error: {0}")]
             TaxCalculationError(String),
             
             #[error("Compliance violation: {0}")]
             ComplianceViolation(String),
         }
-        
-        /// Generate PIX QR code (placeholder implementation)
-        fn generate_pix_qr(data: &PixData) -> Result<String, BrazilianPaymentError> {
-            // In a real implementation, this would generate the actual PIX QR code format
-            // following the Brazilian Central Bank specifications
-            Ok(format!("pix://pay?amount={}&id={}", data.amount, data.transaction_id))
-        }
+
     };
     
     eprintln!("[pleme-codegen] BrazilianPaymentEntity pattern applied to {}", struct_name);
@@ -199,19 +199,46 @@ pub fn derive_cached_repository(input: TokenStream) -> TokenStream {
                     let mut conn = redis_pool.get().await
                         .map_err(|e| crate::models::PaymentError::TransactionFailed(format!("Redis error: {}", e)))?;
                     
-                    // Get all keys matching the pattern
-                    let keys: Vec<String> = redis::AsyncCommands::keys(&mut conn, pattern).await
-                        .map_err(|e| crate::models::PaymentError::TransactionFailed(
-                            format!("Redis keys error for pattern {}: {}", pattern, e)
-                        ))?;
-                    
-                    let count = keys.len() as u32;
-                    
-                    if !keys.is_empty() {
-                        let _: () = redis::AsyncCommands::del(&mut conn, keys).await
+                    // Walk matching keys with SCAN (KEYS blocks the server) and unlink each batch
+                    let mut cursor: u64 = 0;
+                    let mut count: u32 = 0;
+                    let mut use_unlink = true;
+                    loop {
+                        let (next_cursor, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+                            .arg(cursor)
+                            .arg("MATCH")
+                            .arg(pattern)
+                            .arg("COUNT")
+                            .arg(500)
+                            .query_async(&mut conn)
+                            .await
                             .map_err(|e| crate::models::PaymentError::TransactionFailed(
-                                format!("Redis batch del error: {}", e)
+                                format!("Redis scan error for pattern {}: {}", pattern, e)
                             ))?;
+                        
+                        if !keys.is_empty() {
+                            // UNLINK frees memory in the background; servers before Redis 4 only know DEL
+                            let unlinked: Option<u32> = if use_unlink {
+                                redis::cmd("UNLINK").arg(&keys).query_async(&mut conn).await.ok()
+                            } else {
+                                None
+                            };
+                            count += match unlinked {
+                                Some(deleted) => deleted,
+                                None => {
+                                    use_unlink = false;
+                                    redis::AsyncCommands::del(&mut conn, &keys).await
+                                        .map_err(|e| crate::models::PaymentError::TransactionFailed(
+                                            format!("Redis batch del error: {}", e)
+                                        ))?
+                                }
+                            };
+                        }
+                        
+                        cursor = next_cursor;
+                        if cursor == 0 {
+                            break;
+                        }
                     }
                     
                     tracing::debug!(
@@ -235,17 +262,33 @@ pub fn derive_cached_repository(input: TokenStream) -> TokenStream {
                         .map_err(|e| crate::models::PaymentError::TransactionFailed(format!("Redis error: {}", e)))?;
                     
                     let pattern = format!("{}:*", #entity);
-                    let keys: Vec<String> = redis::AsyncCommands::keys(&mut conn, &pattern).await
-                        .map_err(|e| crate::models::PaymentError::TransactionFailed(
-                            format!("Redis keys error: {}", e)
-                        ))?;
+                    let mut cursor: u64 = 0;
+                    let mut total_entries: u64 = 0;
+                    loop {
+                        let (next_cursor, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+                            .arg(cursor)
+                            .arg("MATCH")
+                            .arg(&pattern)
+                            .arg("COUNT")
+                            .arg(500)
+                            .query_async(&mut conn)
+                            .await
+                            .map_err(|e| crate::models::PaymentError::TransactionFailed(
+                                format!("Redis scan error: {}", e)
+                            ))?;
+                        total_entries += keys.len() as u64;
+                        cursor = next_cursor;
+                        if cursor == 0 {
+                            break;
+                        }
+                    }
                     
-                    stats.insert("total_cached_entries".to_string(), keys.len() as u64);
+                    stats.insert("total_cached_entries".to_string(), total_entries);
                     stats.insert("cache_ttl_seconds".to_string(), #ttl as u64);
                     
                     tracing::debug!(
                         entity = %stringify!(#entity_type),
-                        total_entries = %keys.len(),
+                        total_entries = %total_entries,
                         "Cache statistics retrieved"
                     );
                 }
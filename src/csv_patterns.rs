@@ -0,0 +1,301 @@
+//! CSV Export Patterns
+//!
+//! Generates CSV export/import configured through `#[csv(...)]` on DomainModel
+//! structs, including the Brazilian accounting layout (`;` delimiter, `,` decimals)
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::DeriveInput;
+
+/// Options parsed from `#[csv(...)]`
+pub struct CsvConfig {
+    pub enabled: bool,
+    /// `true` for `locale = "pt-BR"`: `;` between fields and `,` as decimal separator
+    pub brazilian: bool,
+}
+
+impl CsvConfig {
+    /// Parse struct-level `#[csv]` / `#[csv(locale = "...")]` attributes
+    pub fn from_attrs(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut config = CsvConfig { enabled: false, brazilian: false };
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("csv")) {
+            config.enabled = true;
+            if matches!(attr.meta, syn::Meta::Path(_)) {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("locale") {
+                    let value: syn::LitStr = meta.value()?.parse()?;
+                    config.brazilian = match value.value().as_str() {
+                        "pt-BR" => true,
+                        "en-US" | "C" => false,
+                        other => {
+                            return Err(syn::Error::new_spanned(
+                                &value,
+                                format!("unsupported CSV locale `{}`; expected \"pt-BR\" or \"en-US\"", other),
+                            ))
+                        }
+                    };
+                } else if meta.input.peek(syn::Token![=]) {
+                    let _: syn::Expr = meta.value()?.parse()?;
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(config)
+    }
+}
+
+/// Inner type of `Option<T>`, if the type is an `Option`
+fn option_inner(ty: &syn::Type) -> Option<&syn::Type> {
+    let syn::Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => match args.args.first() {
+            Some(syn::GenericArgument::Type(inner)) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// How a single (non-optional) value is written to and read from a cell
+enum CellKind {
+    /// Decimal and floats: follow the locale's decimal separator
+    Number,
+    /// `DateTime`: RFC 3339 so the value round-trips
+    Timestamp,
+    /// Anything else: `Display` / `FromStr`
+    Text,
+}
+
+fn cell_kind(ty: &syn::Type) -> CellKind {
+    let syn::Type::Path(type_path) = ty else {
+        return CellKind::Text;
+    };
+    match type_path.path.segments.last().map(|s| s.ident.to_string()).as_deref() {
+        Some("Decimal" | "f32" | "f64") => CellKind::Number,
+        Some("DateTime") => CellKind::Timestamp,
+        _ => CellKind::Text,
+    }
+}
+
+/// Expression formatting `value` (a reference) into a cell `String`
+fn format_cell(ty: &syn::Type, value: TokenStream) -> TokenStream {
+    match cell_kind(ty) {
+        CellKind::Number => quote! { Self::csv_localize_number(&#value.to_string()) },
+        CellKind::Timestamp => quote! { #value.to_rfc3339() },
+        CellKind::Text => quote! { #value.to_string() },
+    }
+}
+
+/// Expression parsing `cell` (a `&str`) into `ty`, yielding `Result<ty, String>`
+fn parse_cell(ty: &syn::Type, field: &str, cell: TokenStream) -> TokenStream {
+    let error = quote! { |e| format!("invalid `{}` value `{}`: {}", #field, #cell, e) };
+    match cell_kind(ty) {
+        CellKind::Number => quote! { Self::csv_delocalize_number(#cell).parse::<#ty>().map_err(#error) },
+        CellKind::Timestamp => quote! {
+            chrono::DateTime::parse_from_rfc3339(#cell)
+                .map(|value| value.with_timezone(&chrono::Utc))
+                .map_err(#error)
+        },
+        CellKind::Text => quote! { #cell.parse::<#ty>().map_err(#error) },
+    }
+}
+
+/// Generate CSV export/import for `#[csv]`; empty when the attribute is absent
+pub fn generate_csv(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let config = CsvConfig::from_attrs(&input.attrs)?;
+    if !config.enabled {
+        return Ok(quote! {});
+    }
+
+    let struct_name = &input.ident;
+    let fields = match &input.data {
+        syn::Data::Struct(syn::DataStruct { fields: syn::Fields::Named(fields), .. }) => &fields.named,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "#[csv] requires a struct with named fields",
+            ))
+        }
+    };
+
+    let (delimiter, decimal_separator) = if config.brazilian { (';', ',') } else { (',', '.') };
+    let header: Vec<String> = fields.iter().map(|f| f.ident.as_ref().unwrap().to_string()).collect();
+    let column_count = header.len();
+    let header = header.join(&delimiter.to_string());
+
+    let writes = fields.iter().map(|field| {
+        let ident = field.ident.as_ref().unwrap();
+        match option_inner(&field.ty) {
+            Some(inner) => {
+                let formatted = format_cell(inner, quote! { value });
+                quote! { self.#ident.as_ref().map(|value| #formatted).unwrap_or_default() }
+            }
+            None => format_cell(&field.ty, quote! { self.#ident }),
+        }
+    });
+
+    let reads = fields.iter().enumerate().map(|(index, field)| {
+        let ident = field.ident.as_ref().unwrap();
+        let name = ident.to_string();
+        match option_inner(&field.ty) {
+            Some(inner) => {
+                let parsed = parse_cell(inner, &name, quote! { cell });
+                quote! {
+                    #ident: {
+                        let cell = cells[#index].as_str();
+                        if cell.is_empty() { None } else { Some(#parsed?) }
+                    },
+                }
+            }
+            None => {
+                let parsed = parse_cell(&field.ty, &name, quote! { cell });
+                quote! {
+                    #ident: {
+                        let cell = cells[#index].as_str();
+                        #parsed?
+                    },
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #struct_name {
+            /// CSV field delimiter for this entity's locale
+            pub const CSV_DELIMITER: char = #delimiter;
+
+            /// Decimal separator used for monetary and floating-point columns
+            pub const CSV_DECIMAL_SEPARATOR: char = #decimal_separator;
+
+            /// Header row, in field declaration order
+            pub const CSV_HEADER: &'static str = #header;
+
+            /// Serialize this entity as one CSV row (no trailing newline)
+            pub fn to_csv_row(&self) -> String {
+                let cells: [String; #column_count] = [#(#writes),*];
+                cells.iter()
+                    .map(|cell| Self::csv_quote(cell))
+                    .collect::<Vec<_>>()
+                    .join(&Self::CSV_DELIMITER.to_string())
+            }
+
+            /// Parse one CSV row produced by `to_csv_row`
+            pub fn from_csv_row(line: &str) -> Result<Self, String> {
+                let cells = Self::csv_split(line)?;
+                if cells.len() != #column_count {
+                    return Err(format!("expected {} columns, found {}", #column_count, cells.len()));
+                }
+                Ok(Self {
+                    #(#reads)*
+                })
+            }
+
+            /// Header plus one row per entity, newline-terminated
+            pub fn to_csv<'a, I>(items: I) -> String
+            where
+                I: IntoIterator<Item = &'a Self>,
+                Self: 'a,
+            {
+                let mut out = format!("{}\n", Self::CSV_HEADER);
+                for item in items {
+                    out.push_str(&item.to_csv_row());
+                    out.push('\n');
+                }
+                out
+            }
+
+            /// Parse a document produced by `to_csv`, skipping the header and blank lines
+            pub fn from_csv(text: &str) -> Result<Vec<Self>, String> {
+                let mut records = Self::csv_records(text).into_iter();
+                match records.next() {
+                    Some(header) if header.trim_end_matches('\r') == Self::CSV_HEADER => {}
+                    Some(header) => return Err(format!("unexpected CSV header `{}`", header)),
+                    None => return Ok(Vec::new()),
+                }
+                records
+                    .map(|line| line.trim_end_matches('\r'))
+                    .filter(|line| !line.is_empty())
+                    .enumerate()
+                    .map(|(i, line)| Self::from_csv_row(line).map_err(|e| format!("row {}: {}", i + 1, e)))
+                    .collect()
+            }
+
+            fn csv_localize_number(value: &str) -> String {
+                value.replace('.', &Self::CSV_DECIMAL_SEPARATOR.to_string())
+            }
+
+            fn csv_delocalize_number(value: &str) -> String {
+                value.replace(Self::CSV_DECIMAL_SEPARATOR, ".")
+            }
+
+            /// Quote a cell when it contains the delimiter, a quote or a line break
+            fn csv_quote(cell: &str) -> String {
+                if cell.contains(Self::CSV_DELIMITER) || cell.contains('"') || cell.contains('\n') || cell.contains('\r') {
+                    format!("\"{}\"", cell.replace('"', "\"\""))
+                } else {
+                    cell.to_string()
+                }
+            }
+
+            /// Split a document into records on line breaks outside `"`-quoted cells,
+            /// so a quoted cell spanning lines stays one record
+            fn csv_records(text: &str) -> Vec<&str> {
+                let mut records = Vec::new();
+                let mut start = 0;
+                let mut in_quotes = false;
+
+                for (i, c) in text.char_indices() {
+                    match c {
+                        // An escaped `""` toggles twice and leaves the state unchanged
+                        '"' => in_quotes = !in_quotes,
+                        '\n' if !in_quotes => {
+                            records.push(&text[start..i]);
+                            start = i + 1;
+                        }
+                        _ => {}
+                    }
+                }
+                if start < text.len() {
+                    records.push(&text[start..]);
+                }
+                records
+            }
+
+            /// Split a row on the delimiter, honouring `"`-quoted cells
+            fn csv_split(line: &str) -> Result<Vec<String>, String> {
+                let mut cells = Vec::new();
+                let mut current = String::new();
+                let mut in_quotes = false;
+                let mut chars = line.chars().peekable();
+
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' if in_quotes && chars.peek() == Some(&'"') => {
+                            current.push('"');
+                            chars.next();
+                        }
+                        '"' if in_quotes => in_quotes = false,
+                        '"' if current.is_empty() => in_quotes = true,
+                        c if c == Self::CSV_DELIMITER && !in_quotes => cells.push(std::mem::take(&mut current)),
+                        c => current.push(c),
+                    }
+                }
+                if in_quotes {
+                    return Err("unterminated quoted cell".to_string());
+                }
+                cells.push(current);
+                Ok(cells)
+            }
+        }
+    })
+}
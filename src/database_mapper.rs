@@ -4,11 +4,10 @@
 //! repetitive mapping code per entity.
 
 use proc_macro::TokenStream;
-use proc_macro2::TokenStream as TokenStream2;
-use quote::{quote, format_ident};
+use quote::quote;
 use syn::{
-    parse_macro_input, DeriveInput, Data, Fields, Field, Type, Attribute, Meta, NestedMeta, Lit,
-    PathSegment, GenericArgument, TypePath, AngleBracketedGenericArguments, Ident
+    parse_macro_input, DeriveInput, Data, Fields, Type, Attribute,
+    GenericArgument, TypePath, AngleBracketedGenericArguments
 };
 
 /// Field mapping configuration
@@ -17,76 +16,66 @@ struct FieldMapping {
     db_column: Option<String>,
     json_field: bool,
     enum_conversion: bool,
-    optional: bool,
-    custom_type: Option<String>,
+    /// Rust type the column is read and bound as, converted with `TryFrom`
+    custom_type: Option<Type>,
 }
 
 /// Database mapping configuration
 #[derive(Default)]
 struct DatabaseConfig {
     table: Option<String>,
-    primary_key: Option<String>,
+    /// Comma-separated key columns, e.g. `"product, id"` for a composite key
+    primary_key: Option<syn::LitStr>,
 }
 
 impl DatabaseConfig {
-    fn from_attrs(attrs: &[Attribute]) -> Self {
+    fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
         let mut config = DatabaseConfig::default();
         
         for attr in attrs {
-            if attr.path.is_ident("database") {
-                if let Ok(Meta::List(meta_list)) = attr.parse_meta() {
-                    for nested_meta in meta_list.nested {
-                        if let NestedMeta::Meta(Meta::NameValue(name_value)) = nested_meta {
-                            if let Lit::Str(lit_str) = name_value.lit {
-                                match name_value.path.get_ident().map(|i| i.to_string()).as_deref() {
-                                    Some("table") => config.table = Some(lit_str.value()),
-                                    Some("primary_key") => config.primary_key = Some(lit_str.value()),
-                                    _ => {}
-                                }
-                            }
-                        }
+            if attr.path().is_ident("database") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("table") {
+                        config.table = Some(meta.value()?.parse::<syn::LitStr>()?.value());
+                    } else if meta.path.is_ident("primary_key") {
+                        config.primary_key = Some(meta.value()?.parse()?);
+                    } else if meta.input.peek(syn::Token![=]) {
+                        let _: syn::Expr = meta.value()?.parse()?;
                     }
-                }
+                    Ok(())
+                })?;
             }
         }
         
-        config
+        Ok(config)
     }
 }
 
 impl FieldMapping {
-    fn from_attrs(attrs: &[Attribute]) -> Self {
+    fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
         let mut mapping = FieldMapping::default();
         
         for attr in attrs {
-            if attr.path.is_ident("db") {
-                if let Ok(Meta::List(meta_list)) = attr.parse_meta() {
-                    for nested_meta in meta_list.nested {
-                        match nested_meta {
-                            NestedMeta::Meta(Meta::NameValue(name_value)) => {
-                                if let Lit::Str(lit_str) = name_value.lit {
-                                    if name_value.path.is_ident("column") {
-                                        mapping.db_column = Some(lit_str.value());
-                                    } else if name_value.path.is_ident("type") {
-                                        mapping.custom_type = Some(lit_str.value());
-                                    }
-                                }
-                            }
-                            NestedMeta::Meta(Meta::Path(path)) => {
-                                if path.is_ident("json") {
-                                    mapping.json_field = true;
-                                } else if path.is_ident("enum") {
-                                    mapping.enum_conversion = true;
-                                }
-                            }
-                            _ => {}
-                        }
+            if attr.path().is_ident("db") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("column") {
+                        mapping.db_column = Some(meta.value()?.parse::<syn::LitStr>()?.value());
+                    } else if meta.path.is_ident("type") {
+                        let value: syn::LitStr = meta.value()?.parse()?;
+                        mapping.custom_type = Some(value.parse()?);
+                    } else if meta.path.is_ident("json") {
+                        mapping.json_field = true;
+                    } else if meta.path.is_ident("enum") {
+                        mapping.enum_conversion = true;
+                    } else if meta.input.peek(syn::Token![=]) {
+                        let _: syn::Expr = meta.value()?.parse()?;
                     }
-                }
+                    Ok(())
+                })?;
             }
         }
         
-        mapping
+        Ok(mapping)
     }
 }
 
@@ -105,143 +94,170 @@ fn is_option_type(ty: &Type) -> (bool, Option<&Type>) {
     (false, None)
 }
 
-fn extract_type_name(ty: &Type) -> String {
-    match ty {
-        Type::Path(type_path) => {
-            type_path.path.segments.last()
-                .map(|seg| seg.ident.to_string())
-                .unwrap_or_else(|| "Unknown".to_string())
-        }
-        _ => "Unknown".to_string()
-    }
-}
-
 pub fn derive_database_mapper(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let struct_name = &input.ident;
-    let config = DatabaseConfig::from_attrs(&input.attrs);
+    let config = match DatabaseConfig::from_attrs(&input.attrs) {
+        Ok(config) => config,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    // Named per entity so several mapped structs can share a module
+    let query_builder = quote::format_ident!("{}QueryBuilder", struct_name);
+    let metadata = quote::format_ident!("{}Metadata", struct_name);
     
     let table_name = config.table.unwrap_or_else(|| {
         format!("{}s", struct_name.to_string().to_lowercase())
     });
     
-    let primary_key = config.primary_key.unwrap_or_else(|| "id".to_string());
+    let primary_key_attr = config.primary_key
+        .unwrap_or_else(|| syn::LitStr::new("id", proc_macro2::Span::call_site()));
     
     // Extract fields from the struct
     let fields = match &input.data {
         Data::Struct(data) => {
             match &data.fields {
                 Fields::Named(fields) => &fields.named,
-                _ => panic!("DatabaseMapper only works with named fields"),
+                _ => return syn::Error::new_spanned(&input.ident, "DatabaseMapper only works with named fields").to_compile_error().into(),
             }
         }
-        _ => panic!("DatabaseMapper only works with structs"),
+        _ => return syn::Error::new_spanned(&input.ident, "DatabaseMapper only works with structs").to_compile_error().into(),
     };
     
     // Generate from_row method
     let mut from_row_assignments = Vec::new();
-    let mut to_params_assignments = Vec::new();
+    let mut insert_binds = Vec::new();
     let mut column_list = Vec::new();
     let mut placeholders = Vec::new();
-    let mut update_assignments = Vec::new();
+    let mut field_columns = Vec::new();
     
     for (i, field) in fields.iter().enumerate() {
         let field_name = field.ident.as_ref().unwrap();
         let field_type = &field.ty;
-        let mapping = FieldMapping::from_attrs(&field.attrs);
+        let mapping = match FieldMapping::from_attrs(&field.attrs) {
+            Ok(mapping) => mapping,
+            Err(err) => return err.to_compile_error().into(),
+        };
         
         let db_column = mapping.db_column.unwrap_or_else(|| field_name.to_string());
         let (is_optional, inner_type) = is_option_type(field_type);
         
         column_list.push(db_column.clone());
         placeholders.push(format!("${}", i + 1));
+        field_columns.push((field_name.to_string(), db_column.clone()));
         
-        if field_name.to_string() != primary_key {
-            update_assignments.push(format!("{} = ${}", db_column, i + 1));
-        }
+        let decode_error = quote! {
+            |e| sqlx::Error::ColumnDecode {
+                index: #db_column.to_string(),
+                source: e.to_string().into(),
+            }
+        };
         
         // Generate from_row assignment based on field type and mapping
         let assignment = if mapping.json_field {
             if is_optional {
                 quote! {
-                    #field_name: row.#field_name.as_ref().and_then(|json| 
-                        serde_json::from_value(json.clone()).ok()
-                    )
+                    #field_name: row.try_get::<Option<serde_json::Value>, _>(#db_column)?
+                        .map(serde_json::from_value)
+                        .transpose()
+                        .map_err(#decode_error)?
                 }
             } else {
                 quote! {
-                    #field_name: serde_json::from_value(row.#field_name.clone())
-                        .map_err(|e| sqlx::Error::ColumnDecode {
-                            index: stringify!(#field_name).to_string(),
-                            source: Box::new(e),
-                        })?
+                    #field_name: serde_json::from_value(row.try_get::<serde_json::Value, _>(#db_column)?)
+                        .map_err(#decode_error)?
                 }
             }
         } else if mapping.enum_conversion {
-            let type_name = if let Some(inner) = inner_type {
-                extract_type_name(inner)
-            } else {
-                extract_type_name(field_type)
-            };
-            let type_ident = format_ident!("{}", type_name);
+            let enum_type = inner_type.unwrap_or(field_type);
             
             if is_optional {
                 quote! {
-                    #field_name: row.#field_name.as_ref().and_then(|s| 
-                        #type_ident::from_str(s).ok()
-                    )
+                    #field_name: row.try_get::<Option<String>, _>(#db_column)?
+                        .map(|s| <#enum_type as std::str::FromStr>::from_str(&s))
+                        .transpose()
+                        .map_err(#decode_error)?
                 }
             } else {
                 quote! {
-                    #field_name: #type_ident::from_str(&row.#field_name)
-                        .map_err(|e| sqlx::Error::ColumnDecode {
-                            index: stringify!(#field_name).to_string(), 
-                            source: Box::new(e),
-                        })?
+                    #field_name: <#enum_type as std::str::FromStr>::from_str(&row.try_get::<String, _>(#db_column)?)
+                        .map_err(#decode_error)?
                 }
             }
-        } else if mapping.custom_type.is_some() {
-            // Handle custom type conversion
-            let custom_type = mapping.custom_type.unwrap();
-            let conversion = format_ident!("{}", custom_type);
-            
+        } else if let Some(custom_type) = &mapping.custom_type {
+            // Read as the column's type, then convert into the field's
             quote! {
-                #field_name: #conversion::try_from(row.#field_name)?
+                #field_name: <#field_type as std::convert::TryFrom<#custom_type>>::try_from(row.try_get::<#custom_type, _>(#db_column)?)
+                    .map_err(#decode_error)?
             }
         } else {
-            // Direct assignment
+            // Direct decode
             quote! {
-                #field_name: row.#field_name
+                #field_name: row.try_get::<#field_type, _>(#db_column)?
             }
         };
         
         from_row_assignments.push(assignment);
         
-        // Generate to_params assignment
-        let param_assignment = if mapping.json_field {
-            quote! {
-                serde_json::to_value(&self.#field_name)
-                    .map_err(|e| sqlx::Error::Encode(Box::new(e)))?
+        // Generate the insert bind, in column order
+        let bind = if mapping.json_field {
+            if is_optional {
+                quote! { .bind(self.#field_name.as_ref().map(sqlx::types::Json)) }
+            } else {
+                quote! { .bind(sqlx::types::Json(&self.#field_name)) }
             }
         } else if mapping.enum_conversion {
             if is_optional {
                 quote! {
-                    self.#field_name.as_ref().map(|e| e.to_string())
+                    .bind(self.#field_name.as_ref().map(|e| e.to_string()))
                 }
             } else {
                 quote! {
-                    self.#field_name.to_string()
+                    .bind(self.#field_name.to_string())
                 }
             }
+        } else if let Some(custom_type) = &mapping.custom_type {
+            quote! {
+                .bind(<#custom_type as std::convert::TryFrom<#field_type>>::try_from(self.#field_name.clone())
+                    .map_err(|e| sqlx::Error::Protocol(format!("cannot encode `{}`: {}", #db_column, e)))?)
+            }
         } else {
-            quote! { &self.#field_name }
+            quote! { .bind(&self.#field_name) }
         };
         
-        to_params_assignments.push(param_assignment);
+        insert_binds.push(bind);
     }
     
+    // Key columns bind first ($1..$k); UPDATE's SET placeholders continue after them
+    let mut key_columns = Vec::new();
+    for key in primary_key_attr.value().split(',').map(str::trim).filter(|k| !k.is_empty()) {
+        match field_columns.iter().find(|(field, column)| field == key || column == key) {
+            Some((_, column)) => key_columns.push(column.clone()),
+            None => return syn::Error::new_spanned(
+                &primary_key_attr,
+                format!("primary key column `{}` is not a field of {}", key, struct_name),
+            ).to_compile_error().into(),
+        }
+    }
+    if key_columns.is_empty() {
+        return syn::Error::new_spanned(&primary_key_attr, "primary_key requires at least one column")
+            .to_compile_error()
+            .into();
+    }
+    let key_predicate = key_columns.iter().enumerate()
+        .map(|(i, column)| format!("{} = ${}", column, i + 1))
+        .collect::<Vec<_>>()
+        .join(" AND ");
+    let update_assignments: Vec<String> = column_list.iter()
+        .filter(|column| !key_columns.contains(column))
+        .enumerate()
+        .map(|(i, column)| format!("{} = ${}", column, key_columns.len() + i + 1))
+        .collect();
+    
     let column_list_str = column_list.join(", ");
     let placeholders_str = placeholders.join(", ");
+    let field_count = column_list.len();
+    // Postgres caps a statement at 65535 bind parameters
+    let max_bulk_rows = 65535 / field_count.max(1);
     let update_assignments_str = update_assignments.join(", ");
     
     let expanded = quote! {
@@ -255,15 +271,66 @@ pub fn derive_database_mapper(input: TokenStream) -> TokenStream {
                 })
             }
             
-            /// Convert entity to database parameters for insert
-            pub fn to_insert_params(&self) -> Result<Vec<Box<dyn sqlx::postgres::PgArgumentBuffer>>, sqlx::Error> {
-                let mut params = Vec::new();
-                
-                // This would be filled with actual parameter conversion
-                // For now, this is a placeholder that needs to be implemented
-                // based on the actual field types
-                
-                Ok(params)
+            /// Number of columns bound per inserted row
+            pub const FIELD_COUNT: usize = #field_count;
+            
+            /// Most rows a single bulk INSERT can carry within Postgres' 65535 bind parameters
+            pub const MAX_BULK_ROWS: usize = #max_bulk_rows;
+            
+            /// `insert_sql()` with every column bound from this entity, in column order
+            pub fn insert_query_with_binds(&self) -> Result<sqlx::query::Query<'_, sqlx::Postgres, sqlx::postgres::PgArguments>, sqlx::Error> {
+                self.bind_insert_values(sqlx::query(Self::insert_sql()))
+            }
+            
+            /// Append this entity's column values to `query`, in column order
+            fn bind_insert_values<'q>(
+                &'q self,
+                query: sqlx::query::Query<'q, sqlx::Postgres, sqlx::postgres::PgArguments>,
+            ) -> Result<sqlx::query::Query<'q, sqlx::Postgres, sqlx::postgres::PgArguments>, sqlx::Error> {
+                Ok(query
+                    #(#insert_binds)*)
+            }
+            
+            /// Multi-row INSERT statements for `rows` entities, one per chunk of at
+            /// most `MAX_BULK_ROWS` rows
+            pub fn bulk_insert_query(rows: usize) -> Vec<String> {
+                let mut statements = Vec::new();
+                let mut remaining = rows;
+                while remaining > 0 {
+                    let chunk = remaining.min(Self::MAX_BULK_ROWS);
+                    let values = (0..chunk)
+                        .map(|row| {
+                            let placeholders = (1..=Self::FIELD_COUNT)
+                                .map(|column| format!("${}", row * Self::FIELD_COUNT + column))
+                                .collect::<Vec<_>>()
+                                .join(", ");
+                            format!("({})", placeholders)
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    statements.push(format!(
+                        "INSERT INTO {} ({}) VALUES {} RETURNING *",
+                        #table_name, #column_list_str, values
+                    ));
+                    remaining -= chunk;
+                }
+                statements
+            }
+            
+            /// Insert `entities` with one multi-row INSERT per `MAX_BULK_ROWS` chunk
+            pub async fn bulk_create(pool: &sqlx::PgPool, entities: &[Self]) -> Result<Vec<Self>, sqlx::Error> {
+                let mut created = Vec::with_capacity(entities.len());
+                for chunk in entities.chunks(Self::MAX_BULK_ROWS) {
+                    let sql = Self::bulk_insert_query(chunk.len()).remove(0);
+                    let mut query = sqlx::query(&sql);
+                    for entity in chunk {
+                        query = entity.bind_insert_values(query)?;
+                    }
+                    for row in query.fetch_all(pool).await? {
+                        created.push(Self::from_row(&row)?);
+                    }
+                }
+                Ok(created)
             }
             
             /// Get SQL INSERT statement for this entity
@@ -273,24 +340,26 @@ pub fn derive_database_mapper(input: TokenStream) -> TokenStream {
                 )
             }
             
-            /// Get SQL SELECT statement for finding by primary key
+            /// Get SQL SELECT statement for finding by primary key; binds every key
+            /// column in `primary_key()` order
             pub const fn find_by_id_sql() -> &'static str {
                 concat!(
-                    "SELECT ", #column_list_str, " FROM ", #table_name, " WHERE ", #primary_key, " = $1"
+                    "SELECT ", #column_list_str, " FROM ", #table_name, " WHERE ", #key_predicate
                 )
             }
             
-            /// Get SQL UPDATE statement for this entity
+            /// Get SQL UPDATE statement for this entity; binds the key columns first,
+            /// then the remaining columns in declaration order
             pub const fn update_sql() -> &'static str {
                 concat!(
-                    "UPDATE ", #table_name, " SET ", #update_assignments_str, " WHERE ", #primary_key, " = $1 RETURNING *"
+                    "UPDATE ", #table_name, " SET ", #update_assignments_str, " WHERE ", #key_predicate, " RETURNING *"
                 )
             }
             
             /// Get SQL DELETE statement for this entity
             pub const fn delete_sql() -> &'static str {
                 concat!(
-                    "DELETE FROM ", #table_name, " WHERE ", #primary_key, " = $1"
+                    "DELETE FROM ", #table_name, " WHERE ", #key_predicate
                 )
             }
             
@@ -299,9 +368,9 @@ pub fn derive_database_mapper(input: TokenStream) -> TokenStream {
                 #table_name
             }
             
-            /// Get primary key column name
-            pub const fn primary_key() -> &'static str {
-                #primary_key
+            /// Get primary key column names, in bind order
+            pub const fn primary_key() -> &'static [&'static str] {
+                &[#(#key_columns),*]
             }
             
             /// Get all column names
@@ -320,16 +389,16 @@ pub fn derive_database_mapper(input: TokenStream) -> TokenStream {
             }
             
             /// Create a query builder for this entity type
-            pub fn query_builder() -> DatabaseQueryBuilder<#struct_name> {
-                DatabaseQueryBuilder::new(#table_name)
+            pub fn query_builder() -> #query_builder {
+                #query_builder::new(#table_name)
             }
             
             /// Get entity metadata for introspection
-            pub fn entity_metadata() -> EntityMetadata {
-                EntityMetadata {
+            pub fn entity_metadata() -> #metadata {
+                #metadata {
                     name: stringify!(#struct_name),
                     table: #table_name,
-                    primary_key: #primary_key,
+                    primary_key: Self::primary_key().to_vec(),
                     columns: Self::columns().to_vec(),
                     supports_soft_delete: false, // Could be made configurable
                     supports_timestamps: true,   // Could be made configurable
@@ -338,16 +407,15 @@ pub fn derive_database_mapper(input: TokenStream) -> TokenStream {
         }
         
         /// Query builder for enhanced database operations
-        pub struct DatabaseQueryBuilder<T> {
+        pub struct #query_builder {
             table: String,
             wheres: Vec<String>,
             orders: Vec<String>,
             limit: Option<i64>,
             offset: Option<i64>,
-            _phantom: std::marker::PhantomData<T>,
         }
         
-        impl<T> DatabaseQueryBuilder<T> {
+        impl #query_builder {
             pub fn new(table: &str) -> Self {
                 Self {
                     table: table.to_string(),
@@ -355,7 +423,6 @@ pub fn derive_database_mapper(input: TokenStream) -> TokenStream {
                     orders: Vec::new(),
                     limit: None,
                     offset: None,
-                    _phantom: std::marker::PhantomData,
                 }
             }
             
@@ -404,19 +471,19 @@ pub fn derive_database_mapper(input: TokenStream) -> TokenStream {
         
         /// Entity metadata for runtime introspection
         #[derive(Debug, Clone)]
-        pub struct EntityMetadata {
+        pub struct #metadata {
             pub name: &'static str,
             pub table: &'static str,
-            pub primary_key: &'static str,
+            pub primary_key: Vec<&'static str>,
             pub columns: Vec<&'static str>,
             pub supports_soft_delete: bool,
             pub supports_timestamps: bool,
         }
         
-        impl std::fmt::Display for EntityMetadata {
+        impl std::fmt::Display for #metadata {
             fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
                 write!(f, "Entity {} -> Table {} (PK: {})", 
-                       self.name, self.table, self.primary_key)
+                       self.name, self.table, self.primary_key.join(", "))
             }
         }
     };
@@ -0,0 +1,2167 @@
+//! Domain Model Option Patterns
+//!
+//! Generates the optional behaviour configured through `#[domain(...)]`
+//! on top of the base DomainModel implementation
+
+use heck::{ToKebabCase, ToLowerCamelCase, ToShoutySnakeCase, ToSnakeCase, ToUpperCamelCase};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::Parser;
+use syn::DeriveInput;
+
+/// Default timezone for business-hours evaluation (matches BCB operating hours)
+const DEFAULT_TIMEZONE: &str = "America/Sao_Paulo";
+
+/// Default cache TTL in seconds when `cache_ttl` is not configured
+const DEFAULT_CACHE_TTL_SECS: u64 = 300;
+
+/// Default multi-tenancy column
+const DEFAULT_TENANT_FIELD: &str = "product";
+
+/// Options parsed from `#[domain(...)]`
+pub struct DomainConfig {
+    pub timezone: Option<String>,
+    pub tenant_field: String,
+    pub created_by: bool,
+    pub updated_by: bool,
+    pub shard_count: Option<u32>,
+    pub schema_version: Option<u32>,
+    pub versioned: bool,
+    pub soft_delete: bool,
+    pub no_auto_fields: bool,
+    pub api_casing: Option<String>,
+    pub events: bool,
+    pub ndjson: bool,
+    pub sort_by: Option<syn::LitStr>,
+    pub codegen_stats: bool,
+    pub tenant_guard: bool,
+    pub typestate_builder: bool,
+    pub cache_warm_on_create: bool,
+    pub cache_ttl: u64,
+    /// `RepositoryCrud` repositories whose cache policy trait this entity implements
+    pub repositories: Vec<syn::Path>,
+    pub aggregate_columns: Option<syn::LitStr>,
+    pub lenient_json: bool,
+    /// `(capacity, window_secs)` from `rate_limit = "N/s"`
+    pub rate_limit: Option<(u32, u64)>,
+    pub otel: bool,
+    /// Expected `SCHEMA_FINGERPRINT`, checked by a generated snapshot test
+    pub schema_fingerprint: Option<syn::LitStr>,
+}
+
+impl Default for DomainConfig {
+    fn default() -> Self {
+        Self {
+            timezone: None,
+            tenant_field: DEFAULT_TENANT_FIELD.to_string(),
+            created_by: false,
+            updated_by: false,
+            shard_count: None,
+            schema_version: None,
+            versioned: false,
+            soft_delete: false,
+            no_auto_fields: false,
+            api_casing: None,
+            events: false,
+            ndjson: false,
+            sort_by: None,
+            codegen_stats: false,
+            tenant_guard: false,
+            typestate_builder: false,
+            cache_warm_on_create: true,
+            cache_ttl: DEFAULT_CACHE_TTL_SECS,
+            repositories: Vec::new(),
+            aggregate_columns: None,
+            lenient_json: false,
+            rate_limit: None,
+            otel: false,
+            schema_fingerprint: None,
+        }
+    }
+}
+
+impl DomainConfig {
+    /// Parse `#[domain(...)]` attributes, leaving keys handled elsewhere untouched
+    pub fn from_attrs(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut config = DomainConfig::default();
+
+        for attr in attrs {
+            if !attr.path().is_ident("domain") {
+                continue;
+            }
+
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("timezone") {
+                    let value: syn::LitStr = meta.value()?.parse()?;
+                    if value.value().parse::<chrono_tz::Tz>().is_err() {
+                        return Err(syn::Error::new_spanned(
+                            &value,
+                            format!("unknown IANA timezone `{}`", value.value()),
+                        ));
+                    }
+                    config.timezone = Some(value.value());
+                } else if meta.path.is_ident("tenant_field") {
+                    let value: syn::LitStr = meta.value()?.parse()?;
+                    config.tenant_field = value.value();
+                } else if meta.path.is_ident("shard_count") {
+                    let value: syn::LitInt = meta.value()?.parse()?;
+                    let count: u32 = value.base10_parse()?;
+                    if count == 0 {
+                        return Err(syn::Error::new_spanned(&value, "shard_count must be greater than zero"));
+                    }
+                    config.shard_count = Some(count);
+                } else if meta.path.is_ident("cache_ttl") {
+                    let value: syn::LitInt = meta.value()?.parse()?;
+                    let ttl: u64 = value.base10_parse()?;
+                    if ttl == 0 {
+                        return Err(syn::Error::new_spanned(
+                            &value,
+                            format!("cache_ttl must be greater than zero, got `{}`", value),
+                        ));
+                    }
+                    config.cache_ttl = ttl;
+                } else if meta.path.is_ident("schema_version") {
+                    let value: syn::LitInt = meta.value()?.parse()?;
+                    let version: u32 = value.base10_parse()?;
+                    if version == 0 {
+                        return Err(syn::Error::new_spanned(&value, "schema_version must be greater than zero"));
+                    }
+                    config.schema_version = Some(version);
+                } else if meta.path.is_ident("api_casing") {
+                    let value: syn::LitStr = meta.value()?.parse()?;
+                    if apply_casing(&value.value(), "probe").is_none() {
+                        return Err(syn::Error::new_spanned(
+                            &value,
+                            "api_casing must be one of \"camelCase\", \"PascalCase\", \"snake_case\", \"kebab-case\", \"SCREAMING_SNAKE_CASE\"",
+                        ));
+                    }
+                    config.api_casing = Some(value.value());
+                } else if meta.path.is_ident("rate_limit") {
+                    let value: syn::LitStr = meta.value()?.parse()?;
+                    config.rate_limit = Some(parse_rate_limit(&value.value()).ok_or_else(|| syn::Error::new_spanned(
+                        &value,
+                        format!("rate_limit must look like \"10/s\" or \"100/5m\", got `{}`", value.value()),
+                    ))?);
+                } else if meta.path.is_ident("schema_fingerprint") {
+                    let value: syn::LitStr = meta.value()?.parse()?;
+                    let fingerprint = value.value();
+                    if fingerprint.len() != 16 || !fingerprint.chars().all(|c| c.is_ascii_hexdigit()) {
+                        return Err(syn::Error::new_spanned(
+                            &value,
+                            format!("schema_fingerprint must be 16 hex digits, got `{}`", fingerprint),
+                        ));
+                    }
+                    config.schema_fingerprint = Some(value);
+                } else if meta.path.is_ident("repository") {
+                    config.repositories.push(meta.value()?.parse()?);
+                } else if meta.path.is_ident("aggregate_columns") {
+                    config.aggregate_columns = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("sort_by") {
+                    config.sort_by = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("no_auto_fields") {
+                    config.no_auto_fields = true;
+                } else if meta.path.is_ident("soft_delete") {
+                    config.soft_delete = true;
+                } else if meta.path.is_ident("versioned") {
+                    config.versioned = true;
+                } else if meta.path.is_ident("codegen_stats") {
+                    config.codegen_stats = true;
+                } else if meta.path.is_ident("cache_warm_on_create") {
+                    // Bare `cache_warm_on_create` keeps the default of warming
+                    config.cache_warm_on_create = if meta.input.peek(syn::Token![=]) {
+                        meta.value()?.parse::<syn::LitBool>()?.value()
+                    } else {
+                        true
+                    };
+                } else if meta.path.is_ident("typestate_builder") {
+                    config.typestate_builder = true;
+                } else if meta.path.is_ident("tenant_guard") {
+                    config.tenant_guard = true;
+                } else if meta.path.is_ident("lenient_json") {
+                    config.lenient_json = true;
+                } else if meta.path.is_ident("otel") {
+                    config.otel = true;
+                } else if meta.path.is_ident("ndjson") {
+                    config.ndjson = true;
+                } else if meta.path.is_ident("events") {
+                    config.events = true;
+                } else if meta.path.is_ident("created_by") {
+                    config.created_by = true;
+                } else if meta.path.is_ident("updated_by") {
+                    config.updated_by = true;
+                } else if meta.input.peek(syn::Token![=]) {
+                    // table, ... are consumed by other generators
+                    let _: syn::Expr = meta.value()?.parse()?;
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(config)
+    }
+}
+
+/// Generate all option-driven DomainModel extensions
+pub fn generate_domain_extensions(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let struct_name = &input.ident;
+    let config = DomainConfig::from_attrs(&input.attrs)?;
+
+    let business_hours_impl = generate_business_hours_implementation(struct_name, &config);
+    let query_impl = generate_query_implementation(input, &config);
+    let actor_impl = generate_actor_implementation(input, &config)?;
+    let shard_impl = generate_shard_implementation(input, &config)?;
+    let cas_impl = generate_cas_implementation(input, &config)?;
+    let soft_delete_impl = generate_soft_delete_implementation(input, &config)?;
+    let unique_index_impl = generate_unique_index_implementation(input, &config)?;
+    let migration_impl = generate_migration_implementation(struct_name, &config);
+    let standard_fields_impl = generate_standard_fields_implementation(input, &config)?;
+    let tenant_guard_impl = generate_tenant_guard_implementation(input, &config)?;
+    let builder_impl = generate_typestate_builder_implementation(input, &config)?;
+    let api_casing_impl = generate_api_casing_implementation(input, &config)?;
+    let redaction_impl = generate_redaction_implementation(input, &config)?;
+    let events_impl = generate_events_implementation(input, &config)?;
+    let webhook_impl = generate_webhook_implementation(input)?;
+    let ndjson_impl = generate_ndjson_implementation(input, &config);
+    let lenient_json_impl = generate_lenient_json_implementation(input, &config)?;
+    let sort_impl = generate_sort_implementation(input, &config)?;
+    let aggregate_impl = generate_aggregate_implementation(input, &config)?;
+    let masked_display_impl = generate_masked_display_implementation(input)?;
+    let zeroize_impl = generate_zeroize_implementation(input)?;
+    let rate_limit_impl = generate_rate_limit_implementation(struct_name, &config);
+    let otel_impl = generate_otel_implementation(input, &config)?;
+    let fingerprint_impl = generate_schema_fingerprint_implementation(input, &config);
+
+    Ok(quote! {
+        #business_hours_impl
+        #query_impl
+        #actor_impl
+        #shard_impl
+        #cas_impl
+        #soft_delete_impl
+        #unique_index_impl
+        #migration_impl
+        #standard_fields_impl
+        #tenant_guard_impl
+        #builder_impl
+        #api_casing_impl
+        #redaction_impl
+        #events_impl
+        #webhook_impl
+        #ndjson_impl
+        #lenient_json_impl
+        #sort_impl
+        #aggregate_impl
+        #masked_display_impl
+        #zeroize_impl
+        #rate_limit_impl
+        #otel_impl
+        #fingerprint_impl
+    })
+}
+
+/// Expand `#[domain_model]`: add the standard fields the struct lacks, then hand it to
+/// `DomainModel` with `no_auto_fields` so `new()` takes exactly the remaining fields
+pub fn expand_domain_model_attribute(args: TokenStream, mut item: DeriveInput) -> syn::Result<TokenStream> {
+    if !args.is_empty() {
+        return Err(syn::Error::new_spanned(
+            args,
+            "#[domain_model] takes no arguments; configure it with #[domain(...)]",
+        ));
+    }
+
+    let config = DomainConfig::from_attrs(&item.attrs)?;
+    let tenant_ident = syn::Ident::new(&config.tenant_field, proc_macro2::Span::call_site());
+    let syn::Data::Struct(syn::DataStruct { fields: syn::Fields::Named(fields), .. }) = &mut item.data else {
+        return Err(syn::Error::new_spanned(&item.ident, "#[domain_model] requires a struct with named fields"));
+    };
+
+    let leading: Vec<syn::Field> = [
+        ("id", syn::parse_quote! { pub id: uuid::Uuid }),
+        (config.tenant_field.as_str(), syn::parse_quote! { pub #tenant_ident: String }),
+    ]
+    .into_iter()
+    .filter(|(name, _)| !has_field(&fields.named, name))
+    .map(|(_, field)| syn::Field::parse_named.parse2(field))
+    .collect::<syn::Result<_>>()?;
+    // Actor columns only exist when `#[domain(created_by)]` / `#[domain(updated_by)]` ask for them
+    let trailing: Vec<syn::Field> = [
+        (config.created_by, "created_by", quote! { pub created_by: Option<uuid::Uuid> }),
+        (config.updated_by, "updated_by", quote! { pub updated_by: Option<uuid::Uuid> }),
+        (true, "created_at", quote! { pub created_at: chrono::DateTime<chrono::Utc> }),
+        (true, "updated_at", quote! { pub updated_at: chrono::DateTime<chrono::Utc> }),
+    ]
+    .into_iter()
+    .filter(|(wanted, name, _)| *wanted && !has_field(&fields.named, name))
+    .map(|(_, _, field)| syn::Field::parse_named.parse2(field))
+    .collect::<syn::Result<_>>()?;
+
+    let declared = std::mem::take(&mut fields.named);
+    fields.named.extend(leading);
+    fields.named.extend(declared);
+    fields.named.extend(trailing);
+
+    if !config.no_auto_fields {
+        item.attrs.push(syn::parse_quote! { #[domain(no_auto_fields)] });
+    }
+    let derives_domain_model = item.attrs.iter()
+        .filter(|attr| attr.path().is_ident("derive"))
+        .filter_map(|attr| {
+            attr.parse_args_with(syn::punctuated::Punctuated::<syn::Path, syn::Token![,]>::parse_terminated).ok()
+        })
+        .any(|paths| paths.iter().any(|path| path.segments.last().is_some_and(|seg| seg.ident == "DomainModel")));
+    if !derives_domain_model {
+        // Ahead of any #[domain(...)] helper so the helpers follow the derive introducing them
+        item.attrs.insert(0, syn::parse_quote! { #[derive(pleme_codegen::DomainModel)] });
+    }
+
+    Ok(quote! { #item })
+}
+
+/// Named fields of the struct, or a spanned error naming the option that needs them
+fn named_fields<'a>(input: &'a DeriveInput, option: &str) -> syn::Result<&'a syn::punctuated::Punctuated<syn::Field, syn::token::Comma>> {
+    match &input.data {
+        syn::Data::Struct(syn::DataStruct { fields: syn::Fields::Named(fields), .. }) => Ok(&fields.named),
+        _ => Err(syn::Error::new_spanned(
+            &input.ident,
+            format!("#[domain({})] requires a struct with named fields", option),
+        )),
+    }
+}
+
+/// Check whether the struct declares a field with this name
+fn has_field(fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>, name: &str) -> bool {
+    fields.iter().any(|f| f.ident.as_ref().is_some_and(|i| i == name))
+}
+
+/// Ensure a field the option relies on is declared (derives cannot add fields)
+fn require_field(input: &DeriveInput, fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>, name: &str, ty: &str, option: &str) -> syn::Result<()> {
+    if has_field(fields, name) {
+        Ok(())
+    } else {
+        Err(syn::Error::new_spanned(
+            &input.ident,
+            format!("#[domain({})] requires a `{}: {}` field", option, name, ty),
+        ))
+    }
+}
+
+/// Generate business-hours evaluation in the configured tenant timezone
+fn generate_business_hours_implementation(struct_name: &syn::Ident, config: &DomainConfig) -> TokenStream {
+    let timezone = config.timezone.clone().unwrap_or_else(|| DEFAULT_TIMEZONE.to_string());
+    let cache_warm_on_create = config.cache_warm_on_create;
+    let cache_ttl = config.cache_ttl;
+
+    // `repository = PaymentRepository` hands the cache policy to that repository's create path
+    let cache_policy_impls = config.repositories.iter().map(|repository| {
+        let mut policy = repository.clone();
+        if let Some(last) = policy.segments.last_mut() {
+            last.ident = quote::format_ident!("{}CachePolicy", last.ident);
+        }
+        quote! {
+            impl #policy for #struct_name {
+                const CACHE_WARM_ON_CREATE: bool = #cache_warm_on_create;
+                const CACHE_TTL_SECS: u64 = #cache_ttl;
+            }
+        }
+    });
+
+    quote! {
+        #(#cache_policy_impls)*
+
+        impl #struct_name {
+            /// IANA timezone used to evaluate business hours
+            pub const TIMEZONE: &'static str = #timezone;
+
+            /// Whether repositories write this entity to the cache when it is created
+            pub const CACHE_WARM_ON_CREATE: bool = #cache_warm_on_create;
+
+            /// Seconds this entity stays cached, from `#[domain(cache_ttl = N)]`
+            pub const CACHE_TTL_SECS: u64 = #cache_ttl;
+
+            /// Check if the current time falls within business hours in the configured timezone
+            pub fn is_within_business_hours(&self, open_hour: u32, close_hour: u32) -> bool {
+                Self::is_within_business_hours_at(chrono::Utc::now(), open_hour, close_hour)
+            }
+
+            /// Check if `instant` falls within `[open_hour, close_hour)` local time.
+            /// Windows where `open_hour > close_hour` wrap past midnight.
+            pub fn is_within_business_hours_at(
+                instant: chrono::DateTime<chrono::Utc>,
+                open_hour: u32,
+                close_hour: u32,
+            ) -> bool {
+                use chrono::Timelike;
+
+                let tz: chrono_tz::Tz = Self::TIMEZONE
+                    .parse()
+                    .expect("timezone validated at macro expansion");
+                let local_hour = instant.with_timezone(&tz).hour();
+
+                let within = if open_hour <= close_hour {
+                    local_hour >= open_hour && local_hour < close_hour
+                } else {
+                    local_hour >= open_hour || local_hour < close_hour
+                };
+
+                tracing::debug!(
+                    entity = %stringify!(#struct_name),
+                    timezone = %Self::TIMEZONE,
+                    local_hour = %local_hour,
+                    within = %within,
+                    "Evaluated business hours"
+                );
+
+                within
+            }
+        }
+    }
+}
+
+/// Generate query helpers and the typed key binder they share
+fn generate_query_implementation(input: &DeriveInput, config: &DomainConfig) -> TokenStream {
+    let struct_name = &input.ident;
+    let tenant_column = &config.tenant_field;
+    let columns: Vec<String> = match &input.data {
+        syn::Data::Struct(syn::DataStruct { fields: syn::Fields::Named(fields), .. }) => fields.named.iter()
+            .map(|field| field.ident.as_ref().unwrap().to_string())
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    quote! {
+        impl #struct_name {
+            /// Columns identifying a single row, in the order they are bound by `bind_key`
+            pub const KEY_BIND_ORDER: [&'static str; 2] = ["id", #tenant_column];
+
+            /// Generate SELECT query for this entity by ID
+            pub fn select_by_id_query() -> String {
+                format!("SELECT * FROM {} WHERE {}", Self::TABLE_NAME, Self::key_predicate(1))
+            }
+
+            /// SELECT by ID holding a row lock until the transaction ends (read-modify-write)
+            pub fn select_by_id_for_update_query() -> String {
+                format!("{} FOR UPDATE", Self::select_by_id_query())
+            }
+
+            /// `select_by_id_for_update_query` that skips rows locked by other transactions,
+            /// for queue-style polling
+            pub fn select_by_id_for_update_skip_locked_query() -> String {
+                format!("{} SKIP LOCKED", Self::select_by_id_for_update_query())
+            }
+
+            /// Struct fields in declaration order, as the column list used by `insert_query`
+            pub fn columns() -> &'static [&'static str] {
+                &[#(#columns),*]
+            }
+
+            /// Generate INSERT query naming every column; bind values in `columns()` order
+            pub fn insert_query() -> String {
+                let placeholders: Vec<String> = (1..=Self::columns().len())
+                    .map(|i| format!("${}", i))
+                    .collect();
+                format!("INSERT INTO {} ({}) VALUES ({})",
+                    Self::TABLE_NAME,
+                    Self::columns().join(", "),
+                    placeholders.join(", ")
+                )
+            }
+
+            /// Generate UPDATE query for this entity; bind the fields first, then `bind_key`
+            pub fn update_query(fields: &[&str]) -> String {
+                let set_clauses: Vec<String> = fields.iter().enumerate()
+                    .map(|(i, field)| format!("{} = ${}", field, i + 1))
+                    .collect();
+                format!("UPDATE {} SET {} WHERE {}",
+                    Self::TABLE_NAME,
+                    set_clauses.join(", "),
+                    Self::key_predicate(fields.len() + 1)
+                )
+            }
+
+            /// Generate DELETE query for this entity
+            pub fn delete_query() -> String {
+                format!("DELETE FROM {} WHERE {}", Self::TABLE_NAME, Self::key_predicate(1))
+            }
+
+            /// Generate COUNT query for this entity type in product
+            pub fn count_by_product_query() -> String {
+                format!("SELECT COUNT(*) FROM {} WHERE {} = $1", Self::TABLE_NAME, #tenant_column)
+            }
+
+            /// `id = $n AND product = $n+1`, following `KEY_BIND_ORDER`
+            fn key_predicate(first_placeholder: usize) -> String {
+                Self::KEY_BIND_ORDER.iter().enumerate()
+                    .map(|(i, column)| format!("{} = ${}", column, first_placeholder + i))
+                    .collect::<Vec<_>>()
+                    .join(" AND ")
+            }
+
+            /// Bind id and tenant in the order the generated queries expect
+            pub fn bind_key<'q>(
+                query: sqlx::query::Query<'q, sqlx::Postgres, sqlx::postgres::PgArguments>,
+                id: uuid::Uuid,
+                product: &'q str,
+            ) -> sqlx::query::Query<'q, sqlx::Postgres, sqlx::postgres::PgArguments> {
+                query.bind(id).bind(product)
+            }
+
+            /// `bind_key` for `sqlx::query_as` queries
+            pub fn bind_key_as<'q, O>(
+                query: sqlx::query::QueryAs<'q, sqlx::Postgres, O, sqlx::postgres::PgArguments>,
+                id: uuid::Uuid,
+                product: &'q str,
+            ) -> sqlx::query::QueryAs<'q, sqlx::Postgres, O, sqlx::postgres::PgArguments> {
+                query.bind(id).bind(product)
+            }
+        }
+    }
+}
+
+/// Generate actor-aware constructor and touch for created_by/updated_by auditing
+fn generate_actor_implementation(input: &DeriveInput, config: &DomainConfig) -> syn::Result<TokenStream> {
+    if !config.created_by && !config.updated_by {
+        return Ok(quote! {});
+    }
+
+    let struct_name = &input.ident;
+    let option = match (config.created_by, config.updated_by) {
+        (true, true) => "created_by, updated_by",
+        (true, false) => "created_by",
+        _ => "updated_by",
+    };
+    let fields = named_fields(input, option)?;
+    if config.created_by {
+        require_field(input, fields, "created_by", "Option<uuid::Uuid>", "created_by")?;
+    }
+    if config.updated_by {
+        require_field(input, fields, "updated_by", "Option<uuid::Uuid>", "updated_by")?;
+    }
+
+    let mut params = Vec::new();
+    let mut assigns = Vec::new();
+    let mut actor_columns = Vec::new();
+
+    for field in fields {
+        let field_name = field.ident.as_ref().unwrap();
+        let field_type = &field.ty;
+
+        match field_name.to_string().as_str() {
+            "id" => assigns.push(quote! { id: uuid::Uuid::new_v4(), }),
+            "created_at" | "updated_at" => assigns.push(quote! { #field_name: now, }),
+            "created_by" | "updated_by" => {
+                actor_columns.push(field_name.to_string());
+                assigns.push(quote! { #field_name: Some(actor), });
+            }
+            _ => {
+                params.push(quote! { #field_name: #field_type, });
+                assigns.push(quote! { #field_name, });
+            }
+        }
+    }
+
+    let has_updated_at = has_field(fields, "updated_at");
+    let now_binding = if has_field(fields, "created_at") || has_updated_at {
+        quote! { let now = chrono::Utc::now(); }
+    } else {
+        quote! {}
+    };
+    let touch_timestamp = if has_updated_at {
+        quote! { self.updated_at = chrono::Utc::now(); }
+    } else {
+        quote! {}
+    };
+
+    let touch_impl = if config.updated_by {
+        quote! {
+            /// Record `actor` as the last modifier
+            pub fn touch_by(&mut self, actor: uuid::Uuid) {
+                self.updated_by = Some(actor);
+                #touch_timestamp
+
+                tracing::debug!(
+                    entity = %stringify!(#struct_name),
+                    actor = %actor,
+                    "Domain model touched by actor"
+                );
+            }
+
+            /// Generate UPDATE query that also sets `updated_by`
+            pub fn update_with_actor_query(fields: &[&str]) -> String {
+                let mut fields = fields.to_vec();
+                if !fields.contains(&"updated_by") {
+                    fields.push("updated_by");
+                }
+                Self::update_query(&fields)
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    Ok(quote! {
+        impl #struct_name {
+            /// Audit columns populated from the acting user
+            pub const ACTOR_COLUMNS: &'static [&'static str] = &[#(#actor_columns),*];
+
+            /// Create a new instance attributed to `actor`, auto-filling id and timestamps
+            pub fn new_with_actor(
+                actor: uuid::Uuid,
+                #(#params)*
+            ) -> Self {
+                #now_binding
+                Self {
+                    #(#assigns)*
+                }
+            }
+
+            #touch_impl
+        }
+    })
+}
+
+/// Generate stable shard-key computation for horizontally sharded tables
+fn generate_shard_implementation(input: &DeriveInput, config: &DomainConfig) -> syn::Result<TokenStream> {
+    let Some(shard_count) = config.shard_count else {
+        return Ok(quote! {});
+    };
+
+    let struct_name = &input.ident;
+    let fields = named_fields(input, "shard_count")?;
+    require_field(input, fields, "id", "uuid::Uuid", "shard_count")?;
+
+    Ok(quote! {
+        impl #struct_name {
+            /// Number of shards this entity is spread across
+            pub const SHARD_COUNT: u32 = #shard_count;
+
+            /// Shard holding this entity
+            pub fn shard_index(&self) -> u32 {
+                Self::shard_index_for(self.id)
+            }
+
+            /// Shard for an id; FNV-1a keeps the mapping stable across builds and platforms
+            pub fn shard_index_for(id: uuid::Uuid) -> u32 {
+                let mut hash: u64 = 0xcbf29ce484222325;
+                for byte in id.as_bytes() {
+                    hash ^= *byte as u64;
+                    hash = hash.wrapping_mul(0x100000001b3);
+                }
+                (hash % Self::SHARD_COUNT as u64) as u32
+            }
+
+            /// Physical table holding this entity, e.g. `orders_7`
+            pub fn shard_table_name(&self) -> String {
+                let shard = self.shard_index();
+
+                tracing::trace!(
+                    entity = %stringify!(#struct_name),
+                    id = %self.id,
+                    shard = %shard,
+                    "Resolved entity shard"
+                );
+
+                format!("{}_{}", Self::TABLE_NAME, shard)
+            }
+        }
+    })
+}
+
+/// Generate optimistic compare-and-swap updates over a `version` column.
+/// Expects a `CasError` enum in scope with `Conflict { current: i64 }`,
+/// `NotFound` and `Database(String)` variants.
+fn generate_cas_implementation(input: &DeriveInput, config: &DomainConfig) -> syn::Result<TokenStream> {
+    if !config.versioned {
+        return Ok(quote! {});
+    }
+
+    let struct_name = &input.ident;
+    let fields = named_fields(input, "versioned")?;
+    require_field(input, fields, "id", "uuid::Uuid", "versioned")?;
+    require_field(input, fields, "version", "i64", "versioned")?;
+
+    let tenant_field = config.tenant_field.as_str();
+    let tenant_ident = syn::Ident::new(tenant_field, proc_macro2::Span::call_site());
+    require_field(input, fields, tenant_field, "String", "versioned")?;
+
+    // Everything except the row key, immutable audit columns and the version itself
+    let update_fields: Vec<&syn::Ident> = fields.iter()
+        .filter_map(|f| f.ident.as_ref())
+        .filter(|ident| {
+            let name = ident.to_string();
+            !matches!(name.as_str(), "id" | "version" | "created_at" | "created_by") && name != tenant_field
+        })
+        .collect();
+    let update_columns: Vec<String> = update_fields.iter().map(|ident| ident.to_string()).collect();
+
+    Ok(quote! {
+        impl #struct_name {
+            /// Columns written by `update_cas`, in bind order
+            pub const CAS_UPDATE_COLUMNS: &'static [&'static str] = &[#(#update_columns),*];
+
+            /// UPDATE guarded by the expected version; binds columns, then key, then expected version
+            pub fn update_cas_query() -> String {
+                let columns = Self::CAS_UPDATE_COLUMNS;
+                let mut set_clauses: Vec<String> = columns.iter().enumerate()
+                    .map(|(i, column)| format!("{} = ${}", column, i + 1))
+                    .collect();
+                set_clauses.push("version = version + 1".to_string());
+
+                format!("UPDATE {} SET {} WHERE {} AND version = ${}",
+                    Self::TABLE_NAME,
+                    set_clauses.join(", "),
+                    Self::key_predicate(columns.len() + 1),
+                    columns.len() + Self::KEY_BIND_ORDER.len() + 1
+                )
+            }
+
+            /// Read the stored version after a stale update
+            pub fn select_version_query() -> String {
+                format!("SELECT version FROM {} WHERE {}", Self::TABLE_NAME, Self::key_predicate(1))
+            }
+
+            /// Map an update's affected row count to the CAS outcome
+            pub fn resolve_cas_outcome(rows_affected: u64, current_version: Option<i64>) -> Result<(), CasError> {
+                if rows_affected > 0 {
+                    return Ok(());
+                }
+
+                match current_version {
+                    Some(current) => Err(CasError::Conflict { current }),
+                    None => Err(CasError::NotFound),
+                }
+            }
+
+            /// Persist this entity only if the stored version still equals `expected_version`
+            pub async fn update_cas(&self, pool: &sqlx::PgPool, expected_version: i64) -> Result<Self, CasError> {
+                let query = Self::update_cas_query();
+                let result = sqlx::query(&query)
+                    #(.bind(&self.#update_fields))*
+                    .bind(self.id)
+                    .bind(&self.#tenant_ident)
+                    .bind(expected_version)
+                    .execute(pool)
+                    .await
+                    .map_err(|e| CasError::Database(e.to_string()))?;
+
+                let current_version = if result.rows_affected() == 0 {
+                    let version_query = Self::select_version_query();
+                    sqlx::query_scalar::<_, i64>(&version_query)
+                        .bind(self.id)
+                        .bind(&self.#tenant_ident)
+                        .fetch_optional(pool)
+                        .await
+                        .map_err(|e| CasError::Database(e.to_string()))?
+                } else {
+                    None
+                };
+
+                if let Err(err) = Self::resolve_cas_outcome(result.rows_affected(), current_version) {
+                    tracing::warn!(
+                        entity = %stringify!(#struct_name),
+                        id = %self.id,
+                        expected_version = %expected_version,
+                        current_version = ?current_version,
+                        "Optimistic update rejected"
+                    );
+                    return Err(err);
+                }
+
+                let mut updated = self.clone();
+                updated.version = expected_version + 1;
+                Ok(updated)
+            }
+        }
+    })
+}
+
+/// Generate soft-delete queries and runtime helpers over a `deleted_at` column
+fn generate_soft_delete_implementation(input: &DeriveInput, config: &DomainConfig) -> syn::Result<TokenStream> {
+    if !config.soft_delete {
+        return Ok(quote! {});
+    }
+
+    let struct_name = &input.ident;
+    let fields = named_fields(input, "soft_delete")?;
+    require_field(input, fields, "deleted_at", "Option<chrono::DateTime<chrono::Utc>>", "soft_delete")?;
+    let tenant_column = &config.tenant_field;
+
+    Ok(quote! {
+        impl #struct_name {
+            /// Mark a live row as deleted
+            pub fn soft_delete_query() -> String {
+                format!("UPDATE {} SET deleted_at = NOW() WHERE {} AND deleted_at IS NULL",
+                    Self::TABLE_NAME, Self::key_predicate(1))
+            }
+
+            /// Clear the deletion marker of a row
+            pub fn restore_query() -> String {
+                format!("UPDATE {} SET deleted_at = NULL WHERE {} AND deleted_at IS NOT NULL",
+                    Self::TABLE_NAME, Self::key_predicate(1))
+            }
+
+            /// Page through a tenant's rows; binds product, limit, offset
+            pub fn list_by_product_query(include_deleted: bool) -> String {
+                let deleted_filter = if include_deleted { "" } else { " AND deleted_at IS NULL" };
+                format!("SELECT * FROM {} WHERE {} = $1{} ORDER BY created_at DESC LIMIT $2 OFFSET $3",
+                    Self::TABLE_NAME, #tenant_column, deleted_filter)
+            }
+
+            /// Soft delete by key; returns false if the row was missing or already deleted
+            pub async fn soft_delete(pool: &sqlx::PgPool, id: uuid::Uuid, product: &str) -> Result<bool, sqlx::Error> {
+                let query = Self::soft_delete_query();
+                let result = Self::bind_key(sqlx::query(&query), id, product)
+                    .execute(pool)
+                    .await?;
+
+                tracing::info!(
+                    entity = %stringify!(#struct_name),
+                    id = %id,
+                    product = %product,
+                    deleted = %(result.rows_affected() > 0),
+                    "Soft delete executed"
+                );
+
+                Ok(result.rows_affected() > 0)
+            }
+
+            /// Restore by key; returns false if the row was missing or not deleted
+            pub async fn restore(pool: &sqlx::PgPool, id: uuid::Uuid, product: &str) -> Result<bool, sqlx::Error> {
+                let query = Self::restore_query();
+                let result = Self::bind_key(sqlx::query(&query), id, product)
+                    .execute(pool)
+                    .await?;
+
+                tracing::info!(
+                    entity = %stringify!(#struct_name),
+                    id = %id,
+                    product = %product,
+                    restored = %(result.rows_affected() > 0),
+                    "Restore executed"
+                );
+
+                Ok(result.rows_affected() > 0)
+            }
+
+            /// Whether this entity has been soft deleted
+            pub fn is_deleted(&self) -> bool {
+                self.deleted_at.is_some()
+            }
+
+            /// In-memory counterpart of `soft_delete`
+            pub fn mark_deleted(&mut self) {
+                if self.deleted_at.is_none() {
+                    self.deleted_at = Some(chrono::Utc::now());
+                }
+            }
+
+            /// In-memory counterpart of `restore`
+            pub fn mark_restored(&mut self) {
+                self.deleted_at = None;
+            }
+
+            /// Apply the `list_by_product` deleted filter to already-loaded entities
+            pub fn filter_deleted(entities: Vec<Self>, include_deleted: bool) -> Vec<Self> {
+                if include_deleted {
+                    entities
+                } else {
+                    entities.into_iter().filter(|e| !e.is_deleted()).collect()
+                }
+            }
+        }
+    })
+}
+
+/// Whether a field is marked `#[field(unique_in_tenant)]`
+fn is_unique_in_tenant(field: &syn::Field) -> bool {
+    field.attrs.iter()
+        .filter(|attr| attr.path().is_ident("field"))
+        .any(|attr| {
+            let mut unique = false;
+            let _ = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("unique_in_tenant") {
+                    unique = true;
+                } else if meta.input.peek(syn::Token![=]) {
+                    let _: syn::Expr = meta.value()?.parse()?;
+                }
+                Ok(())
+            });
+            unique
+        })
+}
+
+/// Generate tenant-scoped unique index DDL for `#[field(unique_in_tenant)]` fields
+///
+/// With `#[domain(soft_delete)]` the index is partial, so a deleted row does not
+/// block re-creating the same value in the tenant.
+fn generate_unique_index_implementation(input: &DeriveInput, config: &DomainConfig) -> syn::Result<TokenStream> {
+    let syn::Data::Struct(syn::DataStruct { fields: syn::Fields::Named(fields), .. }) = &input.data else {
+        return Ok(quote! {});
+    };
+    let unique_fields: Vec<String> = fields.named.iter()
+        .filter(|field| is_unique_in_tenant(field))
+        .map(|field| field.ident.as_ref().unwrap().to_string())
+        .collect();
+    if unique_fields.is_empty() {
+        return Ok(quote! {});
+    }
+    require_field(input, &fields.named, &config.tenant_field, "String", "unique_in_tenant")?;
+
+    let struct_name = &input.ident;
+    let tenant_column = &config.tenant_field;
+    let predicate = if config.soft_delete { " WHERE deleted_at IS NULL" } else { "" };
+
+    Ok(quote! {
+        impl #struct_name {
+            /// `CREATE UNIQUE INDEX` statements for the tenant-scoped unique fields
+            pub fn unique_index_ddl() -> Vec<String> {
+                let index_prefix = Self::TABLE_NAME.to_lowercase();
+                [#(#unique_fields),*].iter()
+                    .map(|column| format!(
+                        "CREATE UNIQUE INDEX IF NOT EXISTS {}_{}_{}_key ON {} ({}, {}){}",
+                        index_prefix, #tenant_column, column, Self::TABLE_NAME, #tenant_column, column, #predicate
+                    ))
+                    .collect()
+            }
+        }
+    })
+}
+
+/// Generate `schema_migrations` version tracking for `#[domain(schema_version = N)]`
+fn generate_migration_implementation(struct_name: &syn::Ident, config: &DomainConfig) -> TokenStream {
+    let Some(version) = config.schema_version else {
+        return quote! {};
+    };
+
+    quote! {
+        impl #struct_name {
+            /// Schema version of this entity's table; bump it when the DDL changes
+            pub const SCHEMA_VERSION: u32 = #version;
+
+            /// DDL for the shared migration ledger
+            pub fn schema_migrations_ddl() -> &'static str {
+                "CREATE TABLE IF NOT EXISTS schema_migrations (\
+                 table_name TEXT NOT NULL, \
+                 version INTEGER NOT NULL, \
+                 applied_at TIMESTAMPTZ NOT NULL DEFAULT NOW(), \
+                 PRIMARY KEY (table_name, version))"
+            }
+
+            /// Record `SCHEMA_VERSION` of this table as applied
+            pub fn migration_record_sql() -> String {
+                format!(
+                    "INSERT INTO schema_migrations (table_name, version) VALUES ('{}', {}) ON CONFLICT DO NOTHING",
+                    Self::TABLE_NAME, Self::SCHEMA_VERSION
+                )
+            }
+
+            /// Whether `SCHEMA_VERSION` of this table is already recorded
+            pub fn migration_applied_sql() -> String {
+                format!(
+                    "SELECT EXISTS (SELECT 1 FROM schema_migrations WHERE table_name = '{}' AND version = {})",
+                    Self::TABLE_NAME, Self::SCHEMA_VERSION
+                )
+            }
+
+            /// Run `ddl` and record the version in one transaction, unless the version is
+            /// already recorded; returns whether the DDL was applied
+            pub async fn apply_migration(pool: &sqlx::PgPool, ddl: &[String]) -> Result<bool, sqlx::Error> {
+                let mut tx = pool.begin().await?;
+                sqlx::query(Self::schema_migrations_ddl()).execute(&mut *tx).await?;
+
+                let applied: bool = sqlx::query_scalar(&Self::migration_applied_sql())
+                    .fetch_one(&mut *tx)
+                    .await?;
+                if applied {
+                    tracing::debug!(
+                        entity = %stringify!(#struct_name),
+                        version = %Self::SCHEMA_VERSION,
+                        "Schema version already applied - skipping DDL"
+                    );
+                    tx.rollback().await?;
+                    return Ok(false);
+                }
+
+                for statement in ddl {
+                    sqlx::query(statement).execute(&mut *tx).await?;
+                }
+                sqlx::query(&Self::migration_record_sql()).execute(&mut *tx).await?;
+                tx.commit().await?;
+
+                tracing::info!(
+                    entity = %stringify!(#struct_name),
+                    version = %Self::SCHEMA_VERSION,
+                    statements = %ddl.len(),
+                    "Schema migration applied"
+                );
+                Ok(true)
+            }
+        }
+    }
+}
+
+/// Generate constructor and tenant helpers over user-declared standard fields
+fn generate_standard_fields_implementation(input: &DeriveInput, config: &DomainConfig) -> syn::Result<TokenStream> {
+    if !config.no_auto_fields {
+        return Ok(quote! {});
+    }
+
+    let struct_name = &input.ident;
+    let fields = named_fields(input, "no_auto_fields")?;
+    let tenant_field = config.tenant_field.as_str();
+
+    let missing: Vec<String> = ["id", tenant_field, "created_at", "updated_at"].iter()
+        .filter(|name| !has_field(fields, name))
+        .map(|name| format!("`{}`", name))
+        .collect();
+    if !missing.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            format!(
+                "#[domain(no_auto_fields)] requires the standard fields to be declared; missing {}",
+                missing.join(", ")
+            ),
+        ));
+    }
+
+    let tenant_ident = syn::Ident::new(tenant_field, proc_macro2::Span::call_site());
+    let mut params = Vec::new();
+    let mut assigns = Vec::new();
+
+    for field in fields {
+        let field_name = field.ident.as_ref().unwrap();
+        let field_type = &field.ty;
+
+        match field_name.to_string().as_str() {
+            "id" => assigns.push(quote! { id: uuid::Uuid::new_v4(), }),
+            "created_at" | "updated_at" => assigns.push(quote! { #field_name: now, }),
+            "created_by" | "updated_by" if config.created_by || config.updated_by => {
+                assigns.push(quote! { #field_name: None, });
+            }
+            name if name == tenant_field => assigns.push(quote! { #field_name, }),
+            _ => {
+                params.push(quote! { #field_name: #field_type, });
+                assigns.push(quote! { #field_name, });
+            }
+        }
+    }
+
+    Ok(quote! {
+        impl #struct_name {
+            /// Create a new instance with a fresh id and timestamps
+            pub fn new(
+                #tenant_ident: String,
+                #(#params)*
+            ) -> Self {
+                let now = chrono::Utc::now();
+                Self {
+                    #(#assigns)*
+                }
+            }
+
+            /// Update the updated_at timestamp
+            pub fn touch(&mut self) {
+                self.updated_at = chrono::Utc::now();
+            }
+
+            /// Check if this entity belongs to the given product/tenant
+            pub fn belongs_to_product(&self, product: &str) -> bool {
+                self.#tenant_ident == product
+            }
+        }
+    })
+}
+
+/// Generate the cross-tenant fetch guard for `#[domain(tenant_guard)]`
+fn generate_tenant_guard_implementation(input: &DeriveInput, config: &DomainConfig) -> syn::Result<TokenStream> {
+    if !config.tenant_guard {
+        return Ok(quote! {});
+    }
+
+    let fields = named_fields(input, "tenant_guard")?;
+    require_field(input, fields, "id", "uuid::Uuid", "tenant_guard")?;
+    require_field(input, fields, &config.tenant_field, "String", "tenant_guard")?;
+
+    let struct_name = &input.ident;
+    let tenant_ident = syn::Ident::new(&config.tenant_field, proc_macro2::Span::call_site());
+
+    // no_auto_fields already generates belongs_to_product
+    let belongs_impl = if config.no_auto_fields {
+        quote! {}
+    } else {
+        quote! {
+            /// Check if this entity belongs to the given product/tenant
+            pub fn belongs_to_product(&self, product: &str) -> bool {
+                self.#tenant_ident == product
+            }
+        }
+    };
+
+    Ok(quote! {
+        impl #struct_name {
+            #belongs_impl
+
+            /// Resolve a fetch-by-id result for `product`, reporting an entity owned by
+            /// another tenant exactly like a missing one so ids never leak across tenants
+            pub fn tenant_guard<E, F>(found: Option<Self>, id: uuid::Uuid, product: &str, not_found: F) -> Result<Self, E>
+            where
+                F: FnOnce(String) -> E,
+            {
+                match found {
+                    Some(entity) if entity.id == id && entity.belongs_to_product(product) => Ok(entity),
+                    Some(_) => {
+                        tracing::warn!(
+                            entity = %stringify!(#struct_name),
+                            id = %id,
+                            product = %product,
+                            "Rejected cross-tenant access"
+                        );
+                        Err(not_found(format!("Entity not found: {}", id)))
+                    }
+                    None => Err(not_found(format!("Entity not found: {}", id))),
+                }
+            }
+        }
+    })
+}
+
+/// Generate a typestate builder for `#[domain(typestate_builder)]`
+///
+/// Every non-`Option` field is required: the builder carries one `Set`/`Unset`
+/// marker per required field, each setter flips its marker, and `build()` only
+/// exists once all markers are `Set`.
+fn generate_typestate_builder_implementation(input: &DeriveInput, config: &DomainConfig) -> syn::Result<TokenStream> {
+    if !config.typestate_builder {
+        return Ok(quote! {});
+    }
+
+    let struct_name = &input.ident;
+    let vis = &input.vis;
+    let fields = named_fields(input, "typestate_builder")?;
+    let builder_name = quote::format_ident!("{}Builder", struct_name);
+    let state_mod = quote::format_ident!("{}_builder_state", struct_name.to_string().to_snake_case());
+
+    let is_option = |ty: &syn::Type| matches!(ty, syn::Type::Path(p) if p.path.segments.last().is_some_and(|s| s.ident == "Option"));
+    let required: Vec<&syn::Field> = fields.iter().filter(|f| !is_option(&f.ty)).collect();
+    let optional: Vec<&syn::Field> = fields.iter().filter(|f| is_option(&f.ty)).collect();
+    let params: Vec<syn::Ident> = (0..required.len()).map(|i| quote::format_ident!("S{}", i)).collect();
+
+    let storage = fields.iter().map(|field| {
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        if is_option(ty) {
+            quote! { #ident: #ty, }
+        } else {
+            quote! { #ident: Option<#ty>, }
+        }
+    });
+    let all_idents: Vec<&syn::Ident> = fields.iter().map(|f| f.ident.as_ref().unwrap()).collect();
+    let unset_states = required.iter().map(|_| quote! { #state_mod::Unset });
+    let set_states = required.iter().map(|_| quote! { #state_mod::Set });
+
+    let required_setters = required.iter().enumerate().map(|(index, field)| {
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let doc = format!("Set the required `{}`", ident);
+        let free: Vec<&syn::Ident> = params.iter().enumerate().filter(|(i, _)| *i != index).map(|(_, p)| p).collect();
+        let before = params.iter().enumerate().map(|(i, p)| if i == index { quote! { #state_mod::Unset } } else { quote! { #p } });
+        let after = params.iter().enumerate().map(|(i, p)| if i == index { quote! { #state_mod::Set } } else { quote! { #p } });
+        let moved = all_idents.iter().map(|other| {
+            if *other == ident {
+                quote! { #other: Some(value), }
+            } else {
+                quote! { #other: self.#other, }
+            }
+        });
+        quote! {
+            impl<#(#free),*> #builder_name<#(#before),*> {
+                #[doc = #doc]
+                pub fn #ident(self, value: #ty) -> #builder_name<#(#after),*> {
+                    #builder_name {
+                        #(#moved)*
+                        _state: std::marker::PhantomData,
+                    }
+                }
+            }
+        }
+    });
+
+    let optional_setters = optional.iter().map(|field| {
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let doc = format!("Set the optional `{}`", ident);
+        quote! {
+            #[doc = #doc]
+            pub fn #ident(mut self, value: #ty) -> Self {
+                self.#ident = value;
+                self
+            }
+        }
+    });
+
+    let build_fields = fields.iter().map(|field| {
+        let ident = field.ident.as_ref().unwrap();
+        if is_option(&field.ty) {
+            quote! { #ident: self.#ident, }
+        } else {
+            let message = format!("typestate guarantees `{}` is set", ident);
+            quote! { #ident: self.#ident.expect(#message), }
+        }
+    });
+    let builder_doc = format!("Builder for `{}` whose type tracks which required fields are set", struct_name);
+
+    Ok(quote! {
+        /// Typestate markers for the generated builder
+        #vis mod #state_mod {
+            /// The required field has been provided
+            pub struct Set;
+            /// The required field is still missing
+            pub struct Unset;
+        }
+
+        #[doc = #builder_doc]
+        #vis struct #builder_name<#(#params),*> {
+            #(#storage)*
+            _state: std::marker::PhantomData<(#(#params,)*)>,
+        }
+
+        impl #struct_name {
+            /// Start a builder with every required field unset
+            pub fn builder() -> #builder_name<#(#unset_states),*> {
+                #builder_name {
+                    #(#all_idents: None,)*
+                    _state: std::marker::PhantomData,
+                }
+            }
+        }
+
+        #(#required_setters)*
+
+        impl<#(#params),*> #builder_name<#(#params),*> {
+            #(#optional_setters)*
+        }
+
+        impl #builder_name<#(#set_states),*> {
+            /// Assemble the entity; only callable once every required field is set
+            pub fn build(self) -> #struct_name {
+                #struct_name {
+                    #(#build_fields)*
+                }
+            }
+        }
+    })
+}
+
+/// Rename a snake_case field into one of the serde `rename_all` casings
+fn apply_casing(casing: &str, field: &str) -> Option<String> {
+    match casing {
+        "camelCase" => Some(field.to_lower_camel_case()),
+        "PascalCase" => Some(field.to_upper_camel_case()),
+        "snake_case" => Some(field.to_snake_case()),
+        "kebab-case" => Some(field.to_kebab_case()),
+        "SCREAMING_SNAKE_CASE" => Some(field.to_shouty_snake_case()),
+        _ => None,
+    }
+}
+
+/// `(column, api_name)` pairs for `#[domain(api_casing = "...")]`; empty when unset
+pub fn api_field_renames(input: &DeriveInput) -> syn::Result<Vec<(String, String)>> {
+    let config = DomainConfig::from_attrs(&input.attrs)?;
+    let Some(casing) = config.api_casing.as_deref() else {
+        return Ok(Vec::new());
+    };
+
+    let fields = named_fields(input, "api_casing")?;
+    Ok(fields.iter()
+        .filter_map(|f| f.ident.as_ref())
+        .filter_map(|ident| {
+            let column = ident.to_string();
+            let api_name = apply_casing(casing, &column)?;
+            (api_name != column).then_some((column, api_name))
+        })
+        .collect())
+}
+
+/// Statement renaming the top-level keys of `target` (a `&mut serde_json::Value`) to API casing
+pub fn generate_api_rename(renames: &[(String, String)], target: TokenStream) -> TokenStream {
+    if renames.is_empty() {
+        return quote! {};
+    }
+
+    let columns = renames.iter().map(|(column, _)| column);
+    let api_names = renames.iter().map(|(_, api_name)| api_name);
+
+    quote! {
+        if let serde_json::Value::Object(map) = #target {
+            for (column, api_name) in [#((#columns, #api_names)),*] {
+                if let Some(field_value) = map.remove(column) {
+                    map.insert(api_name.to_string(), field_value);
+                }
+            }
+        }
+    }
+}
+
+/// Generate API-cased serialization alongside the snake_case DB representation
+fn generate_api_casing_implementation(input: &DeriveInput, config: &DomainConfig) -> syn::Result<TokenStream> {
+    if config.api_casing.is_none() {
+        return Ok(quote! {});
+    }
+
+    let struct_name = &input.ident;
+    let renames = api_field_renames(input)?;
+    let columns = renames.iter().map(|(column, _)| column);
+    let api_names = renames.iter().map(|(_, api_name)| api_name);
+    let rename = if renames.is_empty() {
+        quote! { let _ = value; }
+    } else {
+        generate_api_rename(&renames, quote! { value })
+    };
+
+    Ok(quote! {
+        impl #struct_name {
+            /// Field names that differ between the DB (snake_case) and API representations
+            pub const API_FIELD_NAMES: &'static [(&'static str, &'static str)] = &[#((#columns, #api_names)),*];
+
+            /// Serialize for API consumers using the configured field casing
+            pub fn to_api_json(&self) -> Result<serde_json::Value, serde_json::Error> {
+                let mut json_value = serde_json::to_value(self)?;
+                Self::rename_for_api(&mut json_value);
+                Ok(json_value)
+            }
+
+            /// Rename DB field names in a serialized entity to API casing
+            pub fn rename_for_api(value: &mut serde_json::Value) {
+                #rename
+            }
+        }
+    })
+}
+
+/// Whether a field is marked `#[field(sensitive)]`
+fn is_sensitive(field: &syn::Field) -> bool {
+    field.attrs.iter()
+        .filter(|attr| attr.path().is_ident("field"))
+        .any(|attr| {
+            let mut sensitive = false;
+            let _ = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("sensitive") {
+                    sensitive = true;
+                } else if meta.input.peek(syn::Token![=]) {
+                    let _: syn::Expr = meta.value()?.parse()?;
+                }
+                Ok(())
+            });
+            sensitive
+        })
+}
+
+/// Generate `#[field(sensitive)]` redaction, shared by the options that export the entity
+fn generate_redaction_implementation(input: &DeriveInput, config: &DomainConfig) -> syn::Result<TokenStream> {
+    if !config.events && !config.ndjson && !has_webhook_fields(input) {
+        return Ok(quote! {});
+    }
+
+    let option = if config.events { "events" } else if config.ndjson { "ndjson" } else { "field(webhook)" };
+    let fields = named_fields(input, option)?;
+    let struct_name = &input.ident;
+    let sensitive_fields: Vec<String> = fields.iter()
+        .filter(|f| is_sensitive(f))
+        .filter_map(|f| f.ident.as_ref().map(|i| i.to_string()))
+        .collect();
+
+    Ok(quote! {
+        impl #struct_name {
+            /// Fields replaced with `"[REDACTED]"` when the entity leaves the service
+            pub const SENSITIVE_FIELDS: &'static [&'static str] = &[#(#sensitive_fields),*];
+
+            /// Replace sensitive field values in a serialized entity
+            pub fn redact_sensitive(value: &mut serde_json::Value) {
+                if let serde_json::Value::Object(map) = value {
+                    for field in Self::SENSITIVE_FIELDS {
+                        if let Some(field_value) = map.get_mut(*field) {
+                            *field_value = serde_json::Value::String("[REDACTED]".to_string());
+                        }
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Generate newline-delimited JSON export for `#[domain(ndjson)]`
+fn generate_ndjson_implementation(input: &DeriveInput, config: &DomainConfig) -> TokenStream {
+    if !config.ndjson {
+        return quote! {};
+    }
+
+    let struct_name = &input.ident;
+    quote! {
+        impl #struct_name {
+            /// One compact, redacted JSON object for an NDJSON stream (no trailing newline)
+            pub fn to_ndjson_line(&self) -> Result<String, serde_json::Error> {
+                let mut value = serde_json::to_value(self)?;
+                Self::redact_sensitive(&mut value);
+                serde_json::to_string(&value)
+            }
+
+            /// Stream entities to `writer` as NDJSON, returning the number of lines written
+            pub fn write_ndjson<'a, I, W>(items: I, writer: &mut W) -> std::io::Result<usize>
+            where
+                I: IntoIterator<Item = &'a Self>,
+                W: std::io::Write,
+                Self: 'a,
+            {
+                let mut lines = 0;
+                for item in items {
+                    let line = item.to_ndjson_line().map_err(std::io::Error::from)?;
+                    writer.write_all(line.as_bytes())?;
+                    writer.write_all(b"\n")?;
+                    lines += 1;
+                }
+
+                tracing::debug!(
+                    entity = %stringify!(#struct_name),
+                    lines = lines,
+                    "NDJSON batch written"
+                );
+                Ok(lines)
+            }
+        }
+    }
+}
+
+/// JSON keys serde accepts for a field: its name (or `rename`) plus any `alias`es;
+/// `None` for `#[serde(flatten)]`, whose keys cannot be known here
+fn serde_json_keys(field: &syn::Field) -> syn::Result<Option<Vec<String>>> {
+    let mut name = field.ident.as_ref().unwrap().to_string();
+    let mut aliases = Vec::new();
+    let mut flatten = false;
+    for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") && meta.input.peek(syn::Token![=]) {
+                name = meta.value()?.parse::<syn::LitStr>()?.value();
+            } else if meta.path.is_ident("alias") {
+                aliases.push(meta.value()?.parse::<syn::LitStr>()?.value());
+            } else if meta.path.is_ident("flatten") {
+                flatten = true;
+            } else if meta.input.peek(syn::Token![=]) {
+                let _: syn::Expr = meta.value()?.parse()?;
+            } else if meta.input.peek(syn::token::Paren) {
+                // rename(deserialize = "..."), ... are not interpreted
+                let _content;
+                syn::parenthesized!(_content in meta.input);
+            }
+            Ok(())
+        })?;
+    }
+    if flatten {
+        return Ok(None);
+    }
+    aliases.insert(0, name);
+    Ok(Some(aliases))
+}
+
+/// Generate `try_from_json_lenient` for `#[domain(lenient_json)]`
+///
+/// Unknown keys are dropped before deserializing, so payloads from a newer producer
+/// still load even when the struct opts into `#[serde(deny_unknown_fields)]`.
+fn generate_lenient_json_implementation(input: &DeriveInput, config: &DomainConfig) -> syn::Result<TokenStream> {
+    if !config.lenient_json {
+        return Ok(quote! {});
+    }
+
+    let fields = named_fields(input, "lenient_json")?;
+    let mut known_keys = Vec::new();
+    for field in fields {
+        match serde_json_keys(field)? {
+            Some(keys) => known_keys.extend(keys),
+            None => return Err(syn::Error::new_spanned(
+                field,
+                "#[domain(lenient_json)] cannot be combined with #[serde(flatten)] fields",
+            )),
+        }
+    }
+
+    let struct_name = &input.ident;
+    Ok(quote! {
+        impl #struct_name {
+            /// JSON keys this version of the entity understands
+            pub const KNOWN_JSON_KEYS: &'static [&'static str] = &[#(#known_keys),*];
+
+            /// Deserialize, ignoring keys added by newer producers
+            pub fn try_from_json_lenient(json: &str) -> Result<Self, serde_json::Error> {
+                let mut value: serde_json::Value = serde_json::from_str(json)?;
+                if let serde_json::Value::Object(map) = &mut value {
+                    let unknown: Vec<String> = map.keys()
+                        .filter(|key| !Self::KNOWN_JSON_KEYS.contains(&key.as_str()))
+                        .cloned()
+                        .collect();
+                    if !unknown.is_empty() {
+                        tracing::debug!(
+                            entity = %stringify!(#struct_name),
+                            unknown_fields = ?unknown,
+                            "Ignoring unknown JSON fields"
+                        );
+                        for key in &unknown {
+                            map.remove(key);
+                        }
+                    }
+                }
+                serde_json::from_value(value)
+            }
+        }
+    })
+}
+
+/// Generate the standard event-bus envelope for `#[domain(events)]`
+fn generate_events_implementation(input: &DeriveInput, config: &DomainConfig) -> syn::Result<TokenStream> {
+    if !config.events {
+        return Ok(quote! {});
+    }
+
+    let fields = named_fields(input, "events")?;
+    require_field(input, fields, "id", "uuid::Uuid", "events")?;
+    require_field(input, fields, &config.tenant_field, "String", "events")?;
+
+    let struct_name = &input.ident;
+    let tenant_ident = syn::Ident::new(&config.tenant_field, proc_macro2::Span::call_site());
+
+    Ok(quote! {
+        impl #struct_name {
+            /// Wrap this entity in the standard internal event envelope
+            pub fn to_event_envelope(&self, event_type: &str) -> serde_json::Value {
+                let mut payload = serde_json::to_value(self).unwrap_or(serde_json::Value::Null);
+                Self::redact_sensitive(&mut payload);
+
+                let event_id = uuid::Uuid::new_v4();
+                tracing::debug!(
+                    entity = %stringify!(#struct_name),
+                    event_id = %event_id,
+                    event_type = %event_type,
+                    aggregate_id = %self.id,
+                    "Built domain event envelope"
+                );
+
+                serde_json::json!({
+                    "event_id": event_id.to_string(),
+                    "event_type": event_type,
+                    "aggregate_type": stringify!(#struct_name),
+                    "aggregate_id": self.id.to_string(),
+                    "tenant": self.#tenant_ident.clone(),
+                    "occurred_at": chrono::Utc::now().to_rfc3339(),
+                    "payload": payload,
+                })
+            }
+        }
+    })
+}
+
+/// Whether a field is marked `#[field(webhook)]`
+fn is_webhook(field: &syn::Field) -> bool {
+    field.attrs.iter()
+        .filter(|attr| attr.path().is_ident("field"))
+        .any(|attr| {
+            let mut webhook = false;
+            let _ = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("webhook") {
+                    webhook = true;
+                } else if meta.input.peek(syn::Token![=]) {
+                    let _: syn::Expr = meta.value()?.parse()?;
+                }
+                Ok(())
+            });
+            webhook
+        })
+}
+
+/// Whether any named field is marked `#[field(webhook)]`
+fn has_webhook_fields(input: &DeriveInput) -> bool {
+    matches!(
+        &input.data,
+        syn::Data::Struct(syn::DataStruct { fields: syn::Fields::Named(fields), .. }) if fields.named.iter().any(is_webhook)
+    )
+}
+
+/// Generate `to_webhook_payload` when any field carries `#[field(webhook)]`
+fn generate_webhook_implementation(input: &DeriveInput) -> syn::Result<TokenStream> {
+    if !has_webhook_fields(input) {
+        return Ok(quote! {});
+    }
+
+    let fields = named_fields(input, "field(webhook)")?;
+    require_field(input, fields, "id", "uuid::Uuid", "field(webhook)")?;
+    let mut webhook_keys = Vec::new();
+    for field in fields.iter().filter(|f| is_webhook(f)) {
+        // The serialized key, honouring `#[serde(rename)]`
+        let keys = serde_json_keys(field)?.ok_or_else(|| syn::Error::new_spanned(
+            field,
+            "#[field(webhook)] cannot be used on #[serde(flatten)] fields",
+        ))?;
+        webhook_keys.push(keys[0].clone());
+    }
+
+    let struct_name = &input.ident;
+    Ok(quote! {
+        impl #struct_name {
+            /// Serialized fields included in webhook payloads
+            pub const WEBHOOK_FIELDS: &'static [&'static str] = &[#(#webhook_keys),*];
+
+            /// Build an outbound webhook body with only the `#[field(webhook)]` fields,
+            /// sensitive values redacted
+            pub fn to_webhook_payload(&self, event_type: &str) -> serde_json::Value {
+                let mut data = match serde_json::to_value(self) {
+                    Ok(serde_json::Value::Object(mut map)) => {
+                        map.retain(|key, _| Self::WEBHOOK_FIELDS.contains(&key.as_str()));
+                        serde_json::Value::Object(map)
+                    }
+                    _ => serde_json::Value::Object(serde_json::Map::new()),
+                };
+                Self::redact_sensitive(&mut data);
+
+                let webhook_id = uuid::Uuid::new_v4();
+                tracing::debug!(
+                    entity = %stringify!(#struct_name),
+                    webhook_id = %webhook_id,
+                    event_type = %event_type,
+                    entity_id = %self.id,
+                    "Built webhook payload"
+                );
+
+                serde_json::json!({
+                    "webhook_id": webhook_id.to_string(),
+                    "event_type": event_type,
+                    "entity_type": stringify!(#struct_name),
+                    "entity_id": self.id.to_string(),
+                    "created_at": chrono::Utc::now().to_rfc3339(),
+                    "data": data,
+                })
+            }
+        }
+    })
+}
+
+/// Generate `sort_key` and `Ord` for `#[domain(sort_by = "a,b")]`
+fn generate_sort_implementation(input: &DeriveInput, config: &DomainConfig) -> syn::Result<TokenStream> {
+    let Some(sort_by) = config.sort_by.as_ref() else {
+        return Ok(quote! {});
+    };
+
+    let fields = named_fields(input, "sort_by")?;
+    let mut key_fields = Vec::new();
+    for name in sort_by.value().split(',').map(str::trim) {
+        let field = fields.iter()
+            .find(|f| f.ident.as_ref().is_some_and(|i| i == name))
+            .ok_or_else(|| syn::Error::new_spanned(
+                sort_by,
+                format!("sort_by field `{}` is not declared on {}", name, input.ident),
+            ))?;
+        key_fields.push(field);
+    }
+    if key_fields.is_empty() {
+        return Err(syn::Error::new_spanned(sort_by, "sort_by requires at least one field"));
+    }
+
+    let struct_name = &input.ident;
+    let idents: Vec<_> = key_fields.iter().map(|f| f.ident.as_ref().unwrap()).collect();
+    let types: Vec<_> = key_fields.iter().map(|f| &f.ty).collect();
+    let (key_type, key_value) = if idents.len() == 1 {
+        let (ty, ident) = (types[0], idents[0]);
+        (quote! { #ty }, quote! { self.#ident.clone() })
+    } else {
+        (quote! { (#(#types),*) }, quote! { (#(self.#idents.clone()),*) })
+    };
+    // Equal keys fall back to `id` so Ord only reports Equal for the same entity
+    let tie_break = if has_field(fields, "id") && !idents.iter().any(|i| *i == "id") {
+        quote! { .then_with(|| self.id.cmp(&other.id)) }
+    } else {
+        quote! {}
+    };
+
+    Ok(quote! {
+        impl #struct_name {
+            /// Deterministic ordering key for list responses
+            pub fn sort_key(&self) -> #key_type {
+                #key_value
+            }
+        }
+
+        /// Orders by `sort_key`, then by `id` when the key does not include it
+        impl std::cmp::Ord for #struct_name {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                self.sort_key().cmp(&other.sort_key())#tie_break
+            }
+        }
+
+        impl std::cmp::PartialOrd for #struct_name {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+    })
+}
+
+/// Generate time-bucketed SUM queries for `#[domain(aggregate_columns = "a,b")]`
+fn generate_aggregate_implementation(input: &DeriveInput, config: &DomainConfig) -> syn::Result<TokenStream> {
+    let Some(aggregate_columns) = config.aggregate_columns.as_ref() else {
+        return Ok(quote! {});
+    };
+
+    let fields = named_fields(input, "aggregate_columns")?;
+    require_field(input, fields, "created_at", "chrono::DateTime<chrono::Utc>", "aggregate_columns")?;
+    let mut columns = Vec::new();
+    for name in aggregate_columns.value().split(',').map(str::trim).filter(|n| !n.is_empty()) {
+        if !has_field(fields, name) {
+            return Err(syn::Error::new_spanned(
+                aggregate_columns,
+                format!("aggregate_columns field `{}` is not declared on {}", name, input.ident),
+            ));
+        }
+        columns.push(name.to_string());
+    }
+    if columns.is_empty() {
+        return Err(syn::Error::new_spanned(aggregate_columns, "aggregate_columns requires at least one field"));
+    }
+
+    let struct_name = &input.ident;
+    let tenant_column = &config.tenant_field;
+
+    Ok(quote! {
+        impl #struct_name {
+            /// Columns dashboards may sum; anything else is rejected before reaching SQL
+            pub const AGGREGATE_COLUMNS: &'static [&'static str] = &[#(#columns),*];
+
+            /// `date_trunc` units accepted by `sum_by_bucket_query`
+            pub const TIME_BUCKETS: [&'static str; 3] = ["hour", "day", "month"];
+
+            /// Per-bucket sums of `value_column` in a tenant between two timestamps;
+            /// binds tenant, range start, range end
+            pub fn sum_by_bucket_query(value_column: &str, bucket: &str) -> Result<String, String> {
+                if !Self::AGGREGATE_COLUMNS.contains(&value_column) {
+                    return Err(format!("column `{}` is not aggregatable on {}", value_column, stringify!(#struct_name)));
+                }
+                if !Self::TIME_BUCKETS.contains(&bucket) {
+                    return Err(format!("unsupported time bucket `{}`; expected hour, day or month", bucket));
+                }
+                Ok(format!(
+                    "SELECT date_trunc('{}', created_at) AS bucket, SUM({}) FROM {} WHERE {} = $1 AND created_at BETWEEN $2 AND $3 GROUP BY bucket ORDER BY bucket",
+                    bucket, value_column, Self::TABLE_NAME, #tenant_column
+                ))
+            }
+
+            /// Daily sums of `value_column`, the default dashboard granularity
+            pub fn sum_by_day_query(value_column: &str) -> Result<String, String> {
+                Self::sum_by_bucket_query(value_column, "day")
+            }
+        }
+    })
+}
+
+/// The `#[field(mask = "...")]` strategy of a field, if any
+fn field_mask(field: &syn::Field) -> syn::Result<Option<syn::LitStr>> {
+    let mut mask = None;
+    for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("field")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("mask") {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                if value.value() != "last4" {
+                    return Err(syn::Error::new_spanned(
+                        &value,
+                        format!("unsupported mask `{}`; expected \"last4\"", value.value()),
+                    ));
+                }
+                mask = Some(value);
+            } else if meta.input.peek(syn::Token![=]) {
+                let _: syn::Expr = meta.value()?.parse()?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(mask)
+}
+
+/// Generate a log-safe `Display` when any field carries `#[field(mask = "last4")]`
+fn generate_masked_display_implementation(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let syn::Data::Struct(syn::DataStruct { fields: syn::Fields::Named(fields), .. }) = &input.data else {
+        return Ok(quote! {});
+    };
+
+    let mut any_masked = false;
+    let mut rendered = Vec::new();
+    for field in &fields.named {
+        let ident = field.ident.as_ref().unwrap();
+        let name = ident.to_string();
+        let is_optional = matches!(
+            &field.ty,
+            syn::Type::Path(type_path) if type_path.path.segments.last().is_some_and(|seg| seg.ident == "Option")
+        );
+
+        let value = if field_mask(field)?.is_some() {
+            any_masked = true;
+            if is_optional {
+                quote! {
+                    match &self.#ident {
+                        Some(value) => Self::mask_last4(&value.to_string()),
+                        None => "None".to_string(),
+                    }
+                }
+            } else {
+                quote! { Self::mask_last4(&self.#ident.to_string()) }
+            }
+        } else if is_sensitive(field) {
+            quote! { "[REDACTED]".to_string() }
+        } else {
+            quote! { format!("{:?}", self.#ident) }
+        };
+        rendered.push(quote! { (#name, #value) });
+    }
+
+    if !any_masked {
+        return Ok(quote! {});
+    }
+
+    let struct_name = &input.ident;
+    Ok(quote! {
+        impl #struct_name {
+            /// Keep only the last 4 characters visible
+            pub fn mask_last4(value: &str) -> String {
+                let chars: Vec<char> = value.chars().collect();
+                if chars.len() <= 4 {
+                    return "****".to_string();
+                }
+                format!("****{}", chars[chars.len() - 4..].iter().collect::<String>())
+            }
+
+            /// Render the entity with masked fields hidden, safe for logs and errors
+            pub fn display_safe(&self) -> String {
+                let fields: Vec<String> = [#(#rendered),*]
+                    .iter()
+                    .map(|(name, value)| format!("{}: {}", name, value))
+                    .collect();
+                format!("{} {{ {} }}", stringify!(#struct_name), fields.join(", "))
+            }
+        }
+
+        impl std::fmt::Display for #struct_name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(&self.display_safe())
+            }
+        }
+    })
+}
+
+/// Generate `codegen_stats()` for `#[domain(codegen_stats)]`, measured from the final expansion
+pub fn generate_codegen_stats(input: &DeriveInput, expanded: &TokenStream) -> syn::Result<TokenStream> {
+    let config = DomainConfig::from_attrs(&input.attrs)?;
+    if !config.codegen_stats {
+        return Ok(quote! {});
+    }
+
+    let struct_name = &input.ident;
+    let stats_type = quote::format_ident!("{}CodegenStats", struct_name);
+    let file: syn::File = syn::parse2(expanded.clone())?;
+    let inherent_public_methods = file.items.iter()
+        .filter_map(|item| match item {
+            syn::Item::Impl(item_impl) if item_impl.trait_.is_none() => Some(item_impl),
+            _ => None,
+        })
+        .filter(|item_impl| matches!(&*item_impl.self_ty, syn::Type::Path(p) if p.path.is_ident(struct_name)))
+        .flat_map(|item_impl| &item_impl.items)
+        .filter(|item| matches!(item, syn::ImplItem::Fn(f) if matches!(f.vis, syn::Visibility::Public(_))))
+        .count();
+    // codegen_stats itself is one more generated public method
+    let methods_generated = inherent_public_methods + 1;
+    let lines_generated = estimate_lines(expanded.clone());
+
+    Ok(quote! {
+        impl #struct_name {
+            /// Boilerplate actually emitted by DomainModel for this entity
+            pub fn codegen_stats() -> #stats_type {
+                #stats_type {
+                    lines_generated: #lines_generated,
+                    methods_generated: #methods_generated,
+                }
+            }
+        }
+
+        /// Measured size of a macro expansion
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct #stats_type {
+            /// Approximate rustfmt line count (statements plus brace lines)
+            pub lines_generated: usize,
+            /// Public inherent methods generated
+            pub methods_generated: usize,
+        }
+    })
+}
+
+/// Approximate formatted line count: one per `;`-terminated statement, two per brace block
+fn estimate_lines(tokens: TokenStream) -> usize {
+    tokens.into_iter()
+        .map(|tree| match tree {
+            proc_macro2::TokenTree::Punct(punct) if punct.as_char() == ';' => 1,
+            proc_macro2::TokenTree::Group(group) if group.delimiter() == proc_macro2::Delimiter::Brace => {
+                2 + estimate_lines(group.stream())
+            }
+            proc_macro2::TokenTree::Group(group) => estimate_lines(group.stream()),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Whether a field is marked `#[field(zeroize)]`
+fn is_zeroize(field: &syn::Field) -> bool {
+    field.attrs.iter()
+        .filter(|attr| attr.path().is_ident("field"))
+        .any(|attr| {
+            let mut zeroize = false;
+            let _ = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("zeroize") {
+                    zeroize = true;
+                } else if meta.input.peek(syn::Token![=]) {
+                    let _: syn::Expr = meta.value()?.parse()?;
+                }
+                Ok(())
+            });
+            zeroize
+        })
+}
+
+/// Generate `Zeroize` and a wiping `Drop` when any field carries `#[field(zeroize)]`
+///
+/// Marked fields must implement `zeroize::Zeroize` (`String`, `Vec<u8>`, their
+/// `Option`s, ...). This is the only `Drop` DomainModel generates, so the struct
+/// must not implement `Drop` itself and fields cannot be moved out of it.
+fn generate_zeroize_implementation(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let syn::Data::Struct(syn::DataStruct { fields: syn::Fields::Named(fields), .. }) = &input.data else {
+        return Ok(quote! {});
+    };
+    let secret_fields: Vec<&syn::Ident> = fields.named.iter()
+        .filter(|field| is_zeroize(field))
+        .map(|field| field.ident.as_ref().unwrap())
+        .collect();
+    if secret_fields.is_empty() {
+        return Ok(quote! {});
+    }
+
+    let struct_name = &input.ident;
+    Ok(quote! {
+        /// Overwrites the `#[field(zeroize)]` fields; other fields are left intact
+        impl zeroize::Zeroize for #struct_name {
+            fn zeroize(&mut self) {
+                #(zeroize::Zeroize::zeroize(&mut self.#secret_fields);)*
+            }
+        }
+
+        impl Drop for #struct_name {
+            fn drop(&mut self) {
+                zeroize::Zeroize::zeroize(self);
+            }
+        }
+    })
+}
+
+/// Parse `"N/<window>"` where the window is an optional count plus `s`, `m` or `h`
+fn parse_rate_limit(value: &str) -> Option<(u32, u64)> {
+    let (capacity, window) = value.split_once('/')?;
+    let capacity: u32 = capacity.trim().parse().ok().filter(|c| *c > 0)?;
+    let window = window.trim();
+    let unit_secs = match window.chars().last()? {
+        's' => 1,
+        'm' => 60,
+        'h' => 3600,
+        _ => return None,
+    };
+    let count = &window[..window.len() - 1];
+    let count: u64 = if count.is_empty() { 1 } else { count.parse().ok().filter(|c| *c > 0)? };
+    Some((capacity, count * unit_secs))
+}
+
+/// Generate a token-bucket limiter for `#[domain(rate_limit = "N/s")]`
+fn generate_rate_limit_implementation(struct_name: &syn::Ident, config: &DomainConfig) -> TokenStream {
+    let Some((capacity, window_secs)) = config.rate_limit else {
+        return quote! {};
+    };
+    let limiter_name = quote::format_ident!("{}RateLimiter", struct_name);
+    let rejection_name = quote::format_ident!("{}RateLimited", struct_name);
+
+    quote! {
+        impl #struct_name {
+            /// Calls allowed per `RATE_LIMIT_WINDOW` (also the burst size)
+            pub const RATE_LIMIT_CAPACITY: u32 = #capacity;
+
+            /// Time for an empty bucket to refill completely
+            pub const RATE_LIMIT_WINDOW: std::time::Duration = std::time::Duration::from_secs(#window_secs);
+
+            /// Fresh limiter starting with a full bucket
+            pub fn rate_limiter() -> #limiter_name {
+                #limiter_name::default()
+            }
+        }
+
+        /// Token bucket refilling one token every `RATE_LIMIT_WINDOW / RATE_LIMIT_CAPACITY`
+        #[derive(Debug)]
+        pub struct #limiter_name {
+            /// Available tokens and the instant they were last topped up
+            state: std::sync::Mutex<(f64, std::time::Instant)>,
+        }
+
+        impl Default for #limiter_name {
+            fn default() -> Self {
+                Self {
+                    state: std::sync::Mutex::new((#capacity as f64, std::time::Instant::now())),
+                }
+            }
+        }
+
+        impl #limiter_name {
+            /// Take a token now, or report how long until one is available
+            pub fn try_acquire(&self) -> Result<(), #rejection_name> {
+                self.try_acquire_at(std::time::Instant::now())
+            }
+
+            /// `try_acquire` as of `now`
+            pub fn try_acquire_at(&self, now: std::time::Instant) -> Result<(), #rejection_name> {
+                let refill_interval = #struct_name::RATE_LIMIT_WINDOW.as_secs_f64() / #capacity as f64;
+                let mut state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                let (tokens, last_refill) = *state;
+
+                let elapsed = now.saturating_duration_since(last_refill).as_secs_f64();
+                let tokens = (tokens + elapsed / refill_interval).min(#capacity as f64);
+                let last_refill = last_refill.max(now);
+
+                if tokens >= 1.0 {
+                    *state = (tokens - 1.0, last_refill);
+                    return Ok(());
+                }
+
+                *state = (tokens, last_refill);
+                let retry_after = std::time::Duration::from_secs_f64((1.0 - tokens) * refill_interval);
+                tracing::debug!(
+                    entity = %stringify!(#struct_name),
+                    retry_after_ms = %retry_after.as_millis(),
+                    "Rate limit exceeded"
+                );
+                Err(#rejection_name { retry_after })
+            }
+        }
+
+        /// Rejection from a generated rate limiter
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct #rejection_name {
+            /// Time until the next token refills
+            pub retry_after: std::time::Duration,
+        }
+
+        impl #rejection_name {
+            /// `Retry-After` header value: whole seconds, rounded up
+            pub fn retry_after_header(&self) -> String {
+                let secs = self.retry_after.as_secs() + u64::from(self.retry_after.subsec_nanos() > 0);
+                secs.to_string()
+            }
+        }
+
+        impl std::fmt::Display for #rejection_name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "rate limited, retry after {:?}", self.retry_after)
+            }
+        }
+
+        impl std::error::Error for #rejection_name {}
+    }
+}
+
+/// Generate OpenTelemetry span attributes for `#[domain(otel)]`
+///
+/// Emits `entity.type`, `entity.id` and `tenant`; structs with an `amount` field also
+/// get `payment.amount` (as a string, to keep decimal precision) and `payment.status`.
+/// Fields marked `#[field(sensitive)]` are never exported.
+fn generate_otel_implementation(input: &DeriveInput, config: &DomainConfig) -> syn::Result<TokenStream> {
+    if !config.otel {
+        return Ok(quote! {});
+    }
+
+    let fields = named_fields(input, "otel")?;
+    require_field(input, fields, "id", "Uuid", "otel")?;
+    let exported = |name: &str| fields.iter()
+        .find(|f| f.ident.as_ref().is_some_and(|i| i == name))
+        .filter(|f| !is_sensitive(f))
+        .map(|f| f.ident.clone().unwrap());
+
+    let struct_name = &input.ident;
+    let mut attributes = vec![quote! {
+        opentelemetry::KeyValue::new("entity.type", stringify!(#struct_name))
+    }];
+    if let Some(id) = exported("id") {
+        attributes.push(quote! { opentelemetry::KeyValue::new("entity.id", self.#id.to_string()) });
+    }
+    if let Some(tenant) = exported(&config.tenant_field) {
+        attributes.push(quote! { opentelemetry::KeyValue::new("tenant", self.#tenant.to_string()) });
+    }
+    if has_field(fields, "amount") {
+        if let Some(amount) = exported("amount") {
+            attributes.push(quote! { opentelemetry::KeyValue::new("payment.amount", self.#amount.to_string()) });
+        }
+        if let Some(status) = exported("status") {
+            attributes.push(quote! { opentelemetry::KeyValue::new("payment.status", format!("{:?}", self.#status)) });
+        }
+    }
+
+    Ok(quote! {
+        impl #struct_name {
+            /// Standard attributes for enriching the current span; sensitive fields are omitted
+            pub fn otel_attributes(&self) -> Vec<opentelemetry::KeyValue> {
+                vec![#(#attributes),*]
+            }
+        }
+    })
+}
+
+/// FNV-1a over `name: type;` for each field in declaration order, as 16 hex digits
+///
+/// Types are hashed as written (`Decimal` and `rust_decimal::Decimal` differ), so
+/// only compare fingerprints of the same source.
+fn schema_fingerprint(input: &DeriveInput) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    if let syn::Data::Struct(data) = &input.data {
+        for (index, field) in data.fields.iter().enumerate() {
+            let name = field.ident.as_ref().map_or_else(|| index.to_string(), |ident| ident.to_string());
+            let ty = &field.ty;
+            for byte in format!("{}: {};", name, quote!(#ty)).bytes() {
+                hash ^= u64::from(byte);
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+    }
+    format!("{:016x}", hash)
+}
+
+/// Generate `SCHEMA_FINGERPRINT`, plus a snapshot test for `#[domain(schema_fingerprint = "...")]`
+fn generate_schema_fingerprint_implementation(input: &DeriveInput, config: &DomainConfig) -> TokenStream {
+    let struct_name = &input.ident;
+    let fingerprint = schema_fingerprint(input);
+
+    let snapshot_test = config.schema_fingerprint.as_ref().map(|expected| {
+        let test_name = quote::format_ident!("{}_schema_fingerprint_unchanged", struct_name.to_string().to_snake_case());
+        quote! {
+            #[cfg(test)]
+            #[test]
+            fn #test_name() {
+                assert_eq!(
+                    #struct_name::SCHEMA_FINGERPRINT,
+                    #expected,
+                    "{} changed its fields; check wire/DB compatibility, then update #[domain(schema_fingerprint)]",
+                    stringify!(#struct_name),
+                );
+            }
+        }
+    });
+
+    quote! {
+        impl #struct_name {
+            /// Stable hash of the field names and types, for detecting schema changes in CI
+            pub const SCHEMA_FINGERPRINT: &'static str = #fingerprint;
+        }
+
+        #snapshot_test
+    }
+}
@@ -0,0 +1,390 @@
+//! GraphQL Bridge Option Patterns
+//!
+//! Generates the optional behaviour configured through `#[graphql(...)]`
+//! on top of the base GraphQLBridge implementation
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::DeriveInput;
+
+/// Options parsed from `#[graphql(...)]`
+#[derive(Default)]
+pub struct GraphQLConfig {
+    /// Shared scalar type for `#[graphql(decimal_scalar)]`, `DecimalScalar` unless a path is given
+    pub decimal_scalar: Option<syn::Path>,
+    pub validate: bool,
+    /// Fall back to coercing fields whose name contains price/amount/total/tax
+    pub auto_coerce: bool,
+}
+
+impl GraphQLConfig {
+    /// Parse struct-level `#[graphql(...)]` attributes
+    pub fn from_attrs(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut config = GraphQLConfig::default();
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("graphql")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("decimal_scalar") {
+                    config.decimal_scalar = Some(if meta.input.peek(syn::Token![=]) {
+                        meta.value()?.parse()?
+                    } else {
+                        syn::parse_quote!(DecimalScalar)
+                    });
+                } else if meta.path.is_ident("validate") {
+                    config.validate = true;
+                } else if meta.path.is_ident("auto_coerce") {
+                    config.auto_coerce = true;
+                } else if meta.input.peek(syn::Token![=]) {
+                    let _: syn::Expr = meta.value()?.parse()?;
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(config)
+    }
+}
+
+/// Field-level `#[graphql(rename = "...")]`, `#[graphql(skip)]`, `#[graphql(decimal)]`
+/// and `#[graphql(decimal_as_string)]` overrides
+#[derive(Default)]
+pub struct GraphQLFieldOverrides {
+    /// `(field, graphql_name)` pairs
+    pub renames: Vec<(String, String)>,
+    pub skipped: Vec<String>,
+    /// Fields whose decimal string is coerced to a JSON number
+    pub decimals: Vec<String>,
+    /// Fields kept as strings even when `auto_coerce` would match them
+    pub decimal_strings: Vec<String>,
+}
+
+impl GraphQLFieldOverrides {
+    /// Parse the field attributes of a struct with named fields
+    pub fn from_input(input: &DeriveInput) -> syn::Result<Self> {
+        let mut overrides = GraphQLFieldOverrides::default();
+        let syn::Data::Struct(syn::DataStruct { fields: syn::Fields::Named(fields), .. }) = &input.data else {
+            return Ok(overrides);
+        };
+
+        for field in &fields.named {
+            let name = field.ident.as_ref().unwrap().to_string();
+            for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("graphql")) {
+                let mut rename = None;
+                let mut skip = false;
+                let mut decimal = false;
+                let mut decimal_as_string = false;
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("rename") {
+                        let value: syn::LitStr = meta.value()?.parse()?;
+                        if value.value().is_empty() {
+                            return Err(syn::Error::new_spanned(&value, "GraphQL field name cannot be empty"));
+                        }
+                        rename = Some(value.value());
+                    } else if meta.path.is_ident("skip") {
+                        skip = true;
+                    } else if meta.path.is_ident("decimal") {
+                        decimal = true;
+                    } else if meta.path.is_ident("decimal_as_string") {
+                        decimal_as_string = true;
+                    } else if meta.input.peek(syn::Token![=]) {
+                        let _: syn::Expr = meta.value()?.parse()?;
+                    }
+                    Ok(())
+                })?;
+                if skip && rename.is_some() {
+                    return Err(syn::Error::new_spanned(attr, "a GraphQL field cannot be both renamed and skipped"));
+                }
+                if decimal && decimal_as_string {
+                    return Err(syn::Error::new_spanned(attr, "`decimal` and `decimal_as_string` are mutually exclusive"));
+                }
+                if skip {
+                    overrides.skipped.push(name.clone());
+                }
+                if decimal {
+                    overrides.decimals.push(name.clone());
+                }
+                if decimal_as_string {
+                    overrides.decimal_strings.push(name.clone());
+                }
+                if let Some(rename) = rename {
+                    overrides.renames.push((name.clone(), rename));
+                }
+            }
+        }
+
+        Ok(overrides)
+    }
+
+    /// Statement coercing the `#[graphql(decimal)]` fields on `target`'s top level
+    pub fn generate_coercion(&self, target: TokenStream) -> TokenStream {
+        if self.decimals.is_empty() {
+            return quote! {};
+        }
+
+        let decimals = &self.decimals;
+        quote! {
+            if let serde_json::Value::Object(map) = #target {
+                #(
+                    if let Some(field_value) = map.get_mut(#decimals) {
+                        Self::coerce_decimal_for_graphql(field_value);
+                    }
+                )*
+            }
+        }
+    }
+
+    /// Statement dropping skipped keys and renaming the rest on `target`'s top level
+    pub fn generate(&self, target: TokenStream) -> TokenStream {
+        if self.renames.is_empty() && self.skipped.is_empty() {
+            return quote! {};
+        }
+
+        let skipped = &self.skipped;
+        let fields = self.renames.iter().map(|(field, _)| field);
+        let graphql_names = self.renames.iter().map(|(_, graphql_name)| graphql_name);
+        quote! {
+            if let serde_json::Value::Object(map) = #target {
+                #(map.remove(#skipped);)*
+                #(
+                    if let Some(field_value) = map.remove(#fields) {
+                        map.insert(#graphql_names.to_string(), field_value);
+                    }
+                )*
+            }
+        }
+    }
+}
+
+/// Whether a type is `Decimal` (optionally wrapped in `Option`)
+fn is_decimal(ty: &syn::Type) -> bool {
+    let syn::Type::Path(type_path) = ty else {
+        return false;
+    };
+    let Some(segment) = type_path.path.segments.last() else {
+        return false;
+    };
+    if segment.ident == "Decimal" {
+        return true;
+    }
+    if segment.ident == "Option" {
+        if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+            if let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
+                return is_decimal(inner);
+            }
+        }
+    }
+    false
+}
+
+/// Generate the `<field>_scalar` accessors for `#[graphql(decimal_scalar)]`; empty when unset
+///
+/// The scalar itself is shared by every entity in a schema and comes from
+/// `pleme_codegen::decimal_scalar!()`, so the schema has a single `Decimal` type.
+pub fn generate_decimal_scalar(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let config = GraphQLConfig::from_attrs(&input.attrs)?;
+    let Some(scalar_type) = config.decimal_scalar else {
+        return Ok(quote! {});
+    };
+
+    let struct_name = &input.ident;
+    let decimal_fields: Vec<&syn::Field> = match &input.data {
+        syn::Data::Struct(syn::DataStruct { fields: syn::Fields::Named(fields), .. }) => {
+            fields.named.iter().filter(|f| is_decimal(&f.ty)).collect()
+        }
+        _ => Vec::new(),
+    };
+    let field_names: Vec<String> = decimal_fields.iter()
+        .map(|f| f.ident.as_ref().unwrap().to_string())
+        .collect();
+    let accessors = decimal_fields.iter().map(|field| {
+        let ident = field.ident.as_ref().unwrap();
+        let accessor = format_ident!("{}_scalar", ident);
+        let doc = format!("`{}` as the GraphQL `Decimal` scalar", ident);
+        if matches!(&field.ty, syn::Type::Path(p) if p.path.segments.last().is_some_and(|s| s.ident == "Option")) {
+            quote! {
+                #[doc = #doc]
+                pub fn #accessor(&self) -> Option<#scalar_type> {
+                    self.#ident.map(#scalar_type::from)
+                }
+            }
+        } else {
+            quote! {
+                #[doc = #doc]
+                pub fn #accessor(&self) -> #scalar_type {
+                    #scalar_type::from(self.#ident)
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #struct_name {
+            /// Monetary fields exposed through the decimal scalar instead of floats
+            pub const GRAPHQL_DECIMAL_FIELDS: &'static [&'static str] = &[#(#field_names),*];
+
+            #(#accessors)*
+        }
+    })
+}
+
+/// Expand `decimal_scalar!()`: the `DecimalScalar` type registered as the GraphQL `Decimal`
+pub fn expand_decimal_scalar(input: TokenStream) -> syn::Result<TokenStream> {
+    if !input.is_empty() {
+        return Err(syn::Error::new_spanned(input, "decimal_scalar!() takes no arguments"));
+    }
+
+    Ok(quote! {
+        /// Lossless GraphQL decimal scalar, transported as a string
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct DecimalScalar(pub rust_decimal::Decimal);
+
+        #[async_graphql::Scalar(name = "Decimal")]
+        impl async_graphql::ScalarType for DecimalScalar {
+            fn parse(value: async_graphql::Value) -> async_graphql::InputValueResult<Self> {
+                match &value {
+                    async_graphql::Value::String(s) => s.trim()
+                        .parse::<rust_decimal::Decimal>()
+                        .map(DecimalScalar)
+                        .map_err(|e| async_graphql::InputValueError::custom(format!("invalid Decimal `{}`: {}", s, e))),
+                    // Integers are exact; floats may already have lost precision
+                    async_graphql::Value::Number(n) if n.is_i64() || n.is_u64() => Ok(DecimalScalar(
+                        n.as_i64()
+                            .map(rust_decimal::Decimal::from)
+                            .unwrap_or_else(|| rust_decimal::Decimal::from(n.as_u64().unwrap_or_default())),
+                    )),
+                    async_graphql::Value::Number(n) => Err(async_graphql::InputValueError::custom(format!(
+                        "lossy float `{}` for Decimal; send the amount as a string", n
+                    ))),
+                    _ => Err(async_graphql::InputValueError::expected_type(value)),
+                }
+            }
+
+            fn to_value(&self) -> async_graphql::Value {
+                async_graphql::Value::String(self.0.to_string())
+            }
+        }
+
+        impl From<rust_decimal::Decimal> for DecimalScalar {
+            fn from(value: rust_decimal::Decimal) -> Self {
+                DecimalScalar(value)
+            }
+        }
+
+        impl From<DecimalScalar> for rust_decimal::Decimal {
+            fn from(value: DecimalScalar) -> Self {
+                value.0
+            }
+        }
+    })
+}
+
+/// Generate `validate_for_graphql` and its per-entity `<Name>FieldError`
+///
+/// Fields annotated `#[brazilian(...)]` are checked through BrazilianEntity's field
+/// validators; `#[graphql(validate)]` also folds in ValidatedEntity's `validate()`.
+pub fn generate_graphql_validation(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let config = GraphQLConfig::from_attrs(&input.attrs)?;
+    let struct_name = &input.ident;
+    let error_name = format_ident!("{}FieldError", struct_name);
+
+    let brazilian_checks = crate::brazilian_patterns::brazilian_fields(input)?
+        .into_iter()
+        .map(|(field, _)| {
+            let ident = field.ident.as_ref().unwrap();
+            let field_name = ident.to_string();
+            let validator = format_ident!("validate_{}_field", ident);
+            let prefix = format!("{}: ", field_name);
+            quote! {
+                if let Err(message) = self.#validator() {
+                    let message = message.strip_prefix(#prefix).unwrap_or(&message).to_string();
+                    errors.push(#error_name { field: #field_name, message });
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let field_names: Vec<String> = match &input.data {
+        syn::Data::Struct(syn::DataStruct { fields: syn::Fields::Named(fields), .. }) => fields.named.iter()
+            .map(|f| f.ident.as_ref().unwrap().to_string())
+            .collect(),
+        _ => Vec::new(),
+    };
+    // validate() reports "field: message"; attribute those to the field, the rest to the entity
+    let entity_checks = if config.validate {
+        quote! {
+            if let Err(messages) = self.validate() {
+                const FIELDS: &[&str] = &[#(#field_names),*];
+                for message in messages {
+                    let error = match message.split_once(": ") {
+                        Some((field, rest)) => match FIELDS.iter().find(|f| **f == field) {
+                            Some(field) => #error_name { field, message: rest.to_string() },
+                            None => #error_name { field: "_entity", message },
+                        },
+                        None => #error_name { field: "_entity", message },
+                    };
+                    if !errors.contains(&error) {
+                        errors.push(error);
+                    }
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    let errors_binding = if brazilian_checks.is_empty() && !config.validate {
+        quote! { errors }
+    } else {
+        quote! { mut errors }
+    };
+    let error_doc = format!("A `{}` input field that failed validation", struct_name);
+
+    Ok(quote! {
+        impl #struct_name {
+            /// GraphQL input validation with Brazilian market rules, reported per field
+            pub fn validate_for_graphql(&self) -> Result<(), Vec<#error_name>> {
+                let #errors_binding: Vec<#error_name> = Vec::new();
+                #(#brazilian_checks)*
+                #entity_checks
+
+                if errors.is_empty() {
+                    tracing::debug!(
+                        entity = %stringify!(#struct_name),
+                        "GraphQL validation completed"
+                    );
+                    Ok(())
+                } else {
+                    tracing::warn!(
+                        entity = %stringify!(#struct_name),
+                        error_count = %errors.len(),
+                        "GraphQL validation failed"
+                    );
+                    Err(errors)
+                }
+            }
+        }
+
+        #[doc = #error_doc]
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct #error_name {
+            /// Field name as declared on the struct (`_entity` for entity-level errors)
+            pub field: &'static str,
+            pub message: String,
+        }
+
+        impl #error_name {
+            /// GraphQL error carrying the field name in its `field` extension
+            pub fn to_graphql_error(&self) -> async_graphql::Error {
+                use async_graphql::ErrorExtensions;
+                async_graphql::Error::new(self.message.clone())
+                    .extend_with(|_, extensions| extensions.set("field", self.field))
+            }
+        }
+
+        impl std::fmt::Display for #error_name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}: {}", self.field, self.message)
+            }
+        }
+    })
+}
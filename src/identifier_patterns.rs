@@ -6,6 +6,116 @@ use proc_macro::TokenStream;
 use quote::quote;
 use syn::{parse_macro_input, DeriveInput};
 
+/// Prefix from `#[identifier(prefix = "...")]`, validated as lowercase ASCII alphanumerics
+fn id_prefix(attrs: &[syn::Attribute]) -> syn::Result<Option<syn::LitStr>> {
+    let mut prefix = None;
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("identifier")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("prefix") {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                let text = value.value();
+                if text.is_empty() || !text.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()) {
+                    return Err(syn::Error::new_spanned(
+                        &value,
+                        "identifier prefix must be non-empty lowercase ASCII letters or digits, e.g. \"pay\"",
+                    ));
+                }
+                prefix = Some(value);
+            } else if meta.input.peek(syn::Token![=]) {
+                let _: syn::Expr = meta.value()?.parse()?;
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(prefix)
+}
+
+/// Generate `generate_id` / `parse_id` for `#[identifier(prefix = "...")]`; empty when unset
+fn generate_prefixed_ids(struct_name: &syn::Ident, prefix: Option<syn::LitStr>) -> proc_macro2::TokenStream {
+    let Some(prefix) = prefix else {
+        return quote! {};
+    };
+    let identifier_error = quote::format_ident!("{}IdentifierError", struct_name);
+
+    quote! {
+        impl #struct_name {
+            /// Prefix of the IDs produced by `generate_id`
+            pub const ID_PREFIX: &'static str = #prefix;
+
+            /// Prefixed identifier such as `pay_7n42DGM5Tflk9n8mt7Fhc7`: the prefix, `_`,
+            /// then a new v4 UUID in fixed-width (22 character) base62
+            pub fn generate_id() -> String {
+                Self::format_id(uuid::Uuid::new_v4())
+            }
+
+            /// Prefixed identifier for an existing UUID
+            pub fn format_id(id: uuid::Uuid) -> String {
+                const ALPHABET: &[u8; 62] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+                let mut value = id.as_u128();
+                let mut encoded = [b'0'; 22];
+                for slot in encoded.iter_mut().rev() {
+                    *slot = ALPHABET[(value % 62) as usize];
+                    value /= 62;
+                }
+                format!("{}_{}", Self::ID_PREFIX, String::from_utf8_lossy(&encoded))
+            }
+
+            /// Decode an identifier produced by `generate_id`, checking its prefix
+            pub fn parse_id(s: &str) -> Result<uuid::Uuid, #identifier_error> {
+                let (prefix, encoded) = s.split_once('_').unwrap_or(("", s));
+                if prefix != Self::ID_PREFIX {
+                    return Err(#identifier_error::WrongPrefix {
+                        expected: Self::ID_PREFIX,
+                        actual: prefix.to_string(),
+                    });
+                }
+                if encoded.len() != 22 {
+                    return Err(#identifier_error::InvalidEncoding(s.to_string()));
+                }
+
+                let mut value: u128 = 0;
+                for c in encoded.bytes() {
+                    let digit = match c {
+                        b'0'..=b'9' => c - b'0',
+                        b'A'..=b'Z' => c - b'A' + 10,
+                        b'a'..=b'z' => c - b'a' + 36,
+                        _ => return Err(#identifier_error::InvalidEncoding(s.to_string())),
+                    };
+                    value = value
+                        .checked_mul(62)
+                        .and_then(|value| value.checked_add(digit as u128))
+                        .ok_or_else(|| #identifier_error::InvalidEncoding(s.to_string()))?;
+                }
+                Ok(uuid::Uuid::from_u128(value))
+            }
+        }
+
+        /// Errors from parsing a prefixed identifier
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub enum #identifier_error {
+            /// The identifier belongs to another entity type
+            WrongPrefix { expected: &'static str, actual: String },
+            /// The part after the prefix is not 22 base62 characters encoding a UUID
+            InvalidEncoding(String),
+        }
+
+        impl std::fmt::Display for #identifier_error {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    Self::WrongPrefix { expected, actual } => {
+                        write!(f, "expected identifier prefix `{}`, found `{}`", expected, actual)
+                    }
+                    Self::InvalidEncoding(id) => write!(f, "malformed identifier `{}`", id),
+                }
+            }
+        }
+
+        impl std::error::Error for #identifier_error {}
+    }
+}
+
 /// IdentifierEntity - Generate unique identifiers (saves ~10 lines per entity)
 pub fn derive_identifier_entity(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
@@ -13,6 +123,11 @@ pub fn derive_identifier_entity(input: TokenStream) -> TokenStream {
     
     eprintln!("[pleme-codegen] IdentifierEntity pattern applied to {} - saving ~10 lines", struct_name);
     
+    let prefixed_ids = match id_prefix(&input.attrs) {
+        Ok(prefix) => generate_prefixed_ids(struct_name, prefix),
+        Err(err) => return err.to_compile_error().into(),
+    };
+    
     let expanded = quote! {
         impl #struct_name {
             /// Generate unique identifier with customizable format
@@ -184,6 +299,7 @@ pub fn derive_identifier_entity(input: TokenStream) -> TokenStream {
             }
         }
         
+        #prefixed_ids
     };
     
     TokenStream::from(expanded)
@@ -6,35 +6,96 @@
 
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, DeriveInput};
+use syn::{parse_macro_input, Data, DeriveInput, Fields};
 
 // Pattern modules
 mod status_patterns;
 mod brazilian_patterns;
 mod validation_patterns;
 mod identifier_patterns;
+mod domain_patterns;
+mod graphql_patterns;
+mod bench_patterns;
+mod csv_patterns;
 
 // New payment service pattern modules
 mod payment_patterns;
 mod wallet_patterns;
 mod repository_helpers;
 mod subscription_patterns;
+mod brazilian_payment_entity;
+mod database_mapper;
+mod transactional_repository;
+mod repository;
+mod service;
 
 // New comprehensive macro modules (temporarily disabled due to syn compatibility issues)
 // mod cached_repository;
-// mod database_mapper; 
-// mod transactional_repository;
-// mod brazilian_payment_entity;
+
+/// Attribute form of DomainModel that adds any missing standard fields (`id`, the
+/// tenant field, `created_at`, `updated_at`, plus `created_by`/`updated_by` when
+/// `#[domain(created_by, updated_by)]` asks for them) to the struct itself, which a
+/// derive cannot do. `new()` then takes the tenant followed by the remaining fields.
+#[proc_macro_attribute]
+pub fn domain_model(args: TokenStream, item: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(item as DeriveInput);
+    match domain_patterns::expand_domain_model_attribute(args.into(), item) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Declare the `correlation` module whose task-local correlation id
+/// `#[repository(correlation_id)]` repositories attach to their tracing events.
+/// Invoke it once per crate, e.g. at the crate root.
+#[proc_macro]
+pub fn correlation_context(input: TokenStream) -> TokenStream {
+    match repository_helpers::expand_correlation_context(input.into()) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Declare the `DecimalScalar` type that `#[graphql(decimal_scalar)]` entities expose their
+/// `Decimal` fields through. Invoke it once per schema, e.g. next to the schema builder.
+#[proc_macro]
+pub fn decimal_scalar(input: TokenStream) -> TokenStream {
+    match graphql_patterns::expand_decimal_scalar(input.into()) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
 
 /// Enhanced DomainModel macro with architectural observability and AI-driven improvements
-#[proc_macro_derive(DomainModel, attributes(domain, field))]
+///
+/// Serde is not generated (a derive cannot add derives to its own struct): add
+/// `#[derive(Serialize, Deserialize)]` alongside it when using the JSON-based options.
+#[proc_macro_derive(DomainModel, attributes(domain, field, csv))]
 pub fn derive_domain_model(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let struct_name = &input.ident;
     
+    match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(_) => {}
+            _ => return syn::Error::new_spanned(struct_name, "DomainModel can only be used with structs with named fields").to_compile_error().into(),
+        },
+        _ => return syn::Error::new_spanned(struct_name, "DomainModel can only be used with structs").to_compile_error().into(),
+    }
+    
     // AI Enhancement: Track pattern usage for continuous improvement
     eprintln!("[pleme-codegen] DomainModel pattern applied to {}", struct_name);
     
+    let extensions = match domain_patterns::generate_domain_extensions(&input) {
+        Ok(tokens) => tokens,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    
+    let csv_impl = match csv_patterns::generate_csv(&input) {
+        Ok(tokens) => tokens,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    
     let expanded = quote! {
         impl #struct_name {
             /// Enhanced cache key with product isolation and architectural observability
@@ -99,9 +160,20 @@ pub fn derive_domain_model(input: TokenStream) -> TokenStream {
                 // Future: Send metrics to observability platform
             }
         }
+        
+        #extensions
+        #csv_impl
     };
     
-    TokenStream::from(expanded)
+    let stats = match domain_patterns::generate_codegen_stats(&input, &expanded) {
+        Ok(tokens) => tokens,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    
+    TokenStream::from(quote! {
+        #expanded
+        #stats
+    })
 }
 
 /// Enhanced GraphQLBridge macro with automatic type coercion and validation
@@ -112,6 +184,37 @@ pub fn derive_graphql_bridge(input: TokenStream) -> TokenStream {
     
     eprintln!("[pleme-codegen] GraphQLBridge pattern applied to {}", struct_name);
     
+    // Honour #[domain(api_casing = "...")] so GraphQL output matches the API representation
+    let api_rename = match domain_patterns::api_field_renames(&input) {
+        Ok(renames) => domain_patterns::generate_api_rename(&renames, quote! { &mut json_value }),
+        Err(err) => return err.to_compile_error().into(),
+    };
+    
+    let decimal_scalar_impl = match graphql_patterns::generate_decimal_scalar(&input) {
+        Ok(tokens) => tokens,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    
+    let graphql_config = match graphql_patterns::GraphQLConfig::from_attrs(&input.attrs) {
+        Ok(config) => config,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    // The name heuristic is opt-in, and never applies to Decimal scalar entities
+    let auto_coerce = graphql_config.auto_coerce && graphql_config.decimal_scalar.is_none();
+    
+    let overrides = match graphql_patterns::GraphQLFieldOverrides::from_input(&input) {
+        Ok(overrides) => overrides,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let decimal_coercion = overrides.generate_coercion(quote! { &mut *value });
+    let field_overrides = overrides.generate(quote! { value });
+    let decimal_strings = &overrides.decimal_strings;
+    
+    let validation_impl = match graphql_patterns::generate_graphql_validation(&input) {
+        Ok(tokens) => tokens,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    
     let expanded = quote! {
         impl #struct_name {
             /// AI-Enhanced GraphQL conversion with automatic type coercion
@@ -131,6 +234,9 @@ pub fn derive_graphql_bridge(input: TokenStream) -> TokenStream {
                 // AI Enhancement: Automatically handle common type conversions
                 Self::convert_types_for_graphql(&mut json_value);
                 
+                // Apply API field casing after coercion, which matches on snake_case names
+                #api_rename
+                
                 // Architectural Observability: Track GraphQL conversions
                 tracing::trace!(
                     entity = %stringify!(#struct_name),
@@ -148,42 +254,53 @@ pub fn derive_graphql_bridge(input: TokenStream) -> TokenStream {
                     })
             }
             
-            /// AI-Generated: Convert problematic types for GraphQL compatibility
+            /// AI-Generated: Convert problematic types for GraphQL compatibility,
+            /// then apply the `#[graphql(rename)]` / `#[graphql(skip)]` field overrides
+            ///
+            /// Only `#[graphql(decimal)]` fields (plus name matches under
+            /// `#[graphql(auto_coerce)]`) become numbers. The conversion goes through
+            /// `f64`, so values beyond ~15 significant digits lose precision; use
+            /// `#[graphql(decimal_as_string)]` or `#[graphql(decimal_scalar)]` to keep them exact.
             fn convert_types_for_graphql(value: &mut serde_json::Value) {
+                #decimal_coercion
+                
+                if #auto_coerce {
+                    Self::coerce_types_for_graphql(value, &[#(#decimal_strings),*]);
+                }
+                
+                // Overrides run after coercion, which matches on the declared field names
+                #field_overrides
+            }
+            
+            /// `#[graphql(auto_coerce)]` fallback: coerce fields named like money, recursively
+            fn coerce_types_for_graphql(value: &mut serde_json::Value, keep_as_string: &[&str]) {
                 match value {
                     serde_json::Value::Object(map) => {
                         for (key, v) in map.iter_mut() {
-                            // Convert Decimal fields to f64 based on field name patterns
-                            if key.contains("price") || key.contains("amount") || key.contains("total") || key.contains("tax") {
-                                if let serde_json::Value::String(decimal_str) = v {
-                                    if let Ok(decimal_val) = decimal_str.parse::<f64>() {
-                                        *v = serde_json::Value::Number(
-                                            serde_json::Number::from_f64(decimal_val)
-                                                .unwrap_or(serde_json::Number::from(0))
-                                        );
-                                    }
-                                }
+                            let money_like = key.contains("price") || key.contains("amount") || key.contains("total") || key.contains("tax");
+                            if money_like && !keep_as_string.contains(&key.as_str()) {
+                                Self::coerce_decimal_for_graphql(v);
                             }
-                            Self::convert_types_for_graphql(v);
+                            Self::coerce_types_for_graphql(v, &[]);
                         }
                     }
                     serde_json::Value::Array(arr) => {
                         for v in arr.iter_mut() {
-                            Self::convert_types_for_graphql(v);
+                            Self::coerce_types_for_graphql(v, &[]);
                         }
                     }
                     _ => {}
                 }
             }
             
-            /// AI-Generated GraphQL input validation with Brazilian market rules
-            pub fn validate_for_graphql(&self) -> Result<(), String> {
-                // Future: AI-enhanced validation based on accumulated patterns
-                tracing::debug!(
-                    entity = %stringify!(#struct_name),
-                    "GraphQL validation completed"
-                );
-                Ok(())
+            /// Replace a decimal string with the nearest `f64` number
+            fn coerce_decimal_for_graphql(value: &mut serde_json::Value) {
+                if let serde_json::Value::String(decimal_str) = value {
+                    let number = decimal_str.parse::<f64>().ok().and_then(serde_json::Number::from_f64);
+                    if let Some(number) = number {
+                        *value = serde_json::Value::Number(number);
+                    }
+                }
             }
             
             /// Architectural Observability: Track GraphQL performance
@@ -196,6 +313,9 @@ pub fn derive_graphql_bridge(input: TokenStream) -> TokenStream {
                 );
             }
         }
+        
+        #decimal_scalar_impl
+        #validation_impl
     };
     
     TokenStream::from(expanded)
@@ -207,41 +327,182 @@ pub fn derive_brazilian_entity(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let struct_name = &input.ident;
     
+    match &input.data {
+        Data::Struct(data) => match &data.fields {
+            // Unit structs are fine: the document helpers are associated functions
+            Fields::Named(_) | Fields::Unit => {}
+            _ => return syn::Error::new_spanned(struct_name, "BrazilianEntity can only be used with structs with named fields").to_compile_error().into(),
+        },
+        _ => return syn::Error::new_spanned(struct_name, "BrazilianEntity can only be used with structs").to_compile_error().into(),
+    }
+    
     eprintln!("[pleme-codegen] BrazilianEntity pattern applied to {}", struct_name);
     
+    let config = match brazilian_patterns::BrazilianConfig::from_attrs(&input.attrs) {
+        Ok(config) => config,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let cnpj_alphanumeric = config.cnpj_alphanumeric;
+    let rg_checkdigit = config.rg_checkdigit;
+    
+    let field_validators = match brazilian_patterns::generate_field_validators(&input) {
+        Ok(tokens) => tokens,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    
+    let cep_lookup = brazilian_patterns::generate_cep_lookup(&input, &config);
+    
+    // #[brazilian(rg_checkdigit)] adds the SP check digit on top of the shape check
+    let rg_checkdigit_impl = if rg_checkdigit {
+        quote! {
+            // SP rule: weights 2..=9 over the first 8 digits, DV = 11 - sum % 11
+            // (10 is written `X`, 11 becomes 0)
+            let sum: u32 = chars[..8].iter()
+                .zip(2..=9)
+                .map(|(c, weight)| c.to_digit(10).unwrap_or(0) * weight)
+                .sum();
+            let expected = match 11 - sum % 11 {
+                10 => 'X',
+                11 => '0',
+                n => char::from_digit(n, 10).unwrap_or('0'),
+            };
+            if chars[8] != expected {
+                return Err("check digit mismatch");
+            }
+        }
+    } else {
+        quote! {}
+    };
+    
+    // #[brazilian(cnpj_alphanumeric)] swaps in the 2026 alphanumeric CNPJ rules
+    let cnpj_impl = if cnpj_alphanumeric {
+        quote! {
+            /// Fill `values` with the CNPJ's 14 characters, accepting the alphanumeric format
+            /// (Receita Federal, 2026)
+            ///
+            /// The first 12 positions may be `A-Z`/`0-9`; each character weighs `ord(c) - 48`,
+            /// so legacy numeric CNPJs validate exactly as before.
+            fn cnpj_values(cnpj: &str, values: &mut Vec<u32>) -> Result<(), &'static str> {
+                values.clear();
+                values.extend(cnpj.chars()
+                    .filter(|c| c.is_ascii_alphanumeric())
+                    .map(|c| c.to_ascii_uppercase() as u32 - 48));
+                
+                if values.len() != 14 {
+                    return Err("invalid length");
+                }
+                if !values[12..].iter().all(|v| *v < 10) {
+                    return Err("check digits must be numeric");
+                }
+                Ok(())
+            }
+            
+            /// Format CNPJ for display, keeping alphanumeric positions
+            pub fn format_cnpj(cnpj: &str) -> String {
+                let chars: String = cnpj.chars()
+                    .filter(|c| c.is_ascii_alphanumeric())
+                    .map(|c| c.to_ascii_uppercase())
+                    .collect();
+                if chars.len() == 14 {
+                    format!("{}.{}.{}/{}-{}", 
+                        &chars[0..2], &chars[2..5], &chars[5..8],
+                        &chars[8..12], &chars[12..14])
+                } else {
+                    cnpj.to_string()
+                }
+            }
+        }
+    } else {
+        quote! {
+            /// Fill `values` with the CNPJ's 14 digits
+            fn cnpj_values(cnpj: &str, values: &mut Vec<u32>) -> Result<(), &'static str> {
+                values.clear();
+                values.extend(cnpj.chars().filter_map(|c| c.to_digit(10)));
+                
+                if values.len() != 14 {
+                    return Err("invalid length");
+                }
+                Ok(())
+            }
+            
+            /// Format CNPJ for display
+            pub fn format_cnpj(cnpj: &str) -> String {
+                let digits: String = cnpj.chars().filter(|c| c.is_ascii_digit()).collect();
+                if digits.len() == 14 {
+                    format!("{}.{}.{}/{}-{}", 
+                        &digits[0..2], &digits[2..5], &digits[5..8],
+                        &digits[8..12], &digits[12..14])
+                } else {
+                    cnpj.to_string()
+                }
+            }
+        }
+    };
+    
     let expanded = quote! {
         impl #struct_name {
             /// AI-Enhanced CPF validation with mathematical verification
             pub fn validate_cpf(cpf: &str) -> bool {
-                let digits: String = cpf.chars().filter(|c| c.is_ascii_digit()).collect();
+                let mut digits = Vec::with_capacity(11);
+                let is_valid = match Self::check_cpf(cpf, &mut digits) {
+                    Ok(()) => true,
+                    Err(reason) => {
+                        tracing::debug!(cpf_length = %digits.len(), reason = %reason, "CPF validation failed");
+                        false
+                    }
+                };
+                
+                // Architectural Observability: Track validation attempts
+                tracing::debug!(
+                    entity = %stringify!(#struct_name),
+                    validation_result = %is_valid,
+                    "CPF validation completed"
+                );
+                
+                is_valid
+            }
+            
+            /// Validate many CPFs at once, pairing each input with its validity (in order)
+            pub fn validate_cpf_batch(docs: &[String]) -> Vec<(String, bool)> {
+                // One digit buffer for the whole batch
+                let mut digits = Vec::with_capacity(11);
+                let results: Vec<(String, bool)> = docs.iter()
+                    .map(|doc| (doc.clone(), Self::check_cpf(doc, &mut digits).is_ok()))
+                    .collect();
+                
+                tracing::debug!(
+                    entity = %stringify!(#struct_name),
+                    total = %results.len(),
+                    invalid = %results.iter().filter(|(_, valid)| !valid).count(),
+                    "CPF batch validation completed"
+                );
+                
+                results
+            }
+            
+            /// CPF check into a reusable digit buffer, with the reason on failure
+            fn check_cpf(cpf: &str, digits: &mut Vec<u32>) -> Result<(), &'static str> {
+                digits.clear();
+                digits.extend(cpf.chars().filter_map(|c| c.to_digit(10)));
                 
                 // Basic length check
                 if digits.len() != 11 {
-                    tracing::debug!(cpf_length = %digits.len(), "CPF validation failed: invalid length");
-                    return false;
+                    return Err("invalid length");
                 }
                 
                 // Check for invalid sequences (all same digit)
-                if digits.chars().all(|c| c == digits.chars().next().unwrap()) {
-                    tracing::debug!("CPF validation failed: all digits are the same");
-                    return false;
+                if digits.iter().all(|d| *d == digits[0]) {
+                    return Err("all digits are the same");
                 }
                 
-                // Convert to digit array for calculation
-                let digits: Vec<u32> = digits.chars()
-                    .map(|c| c.to_digit(10).unwrap_or(0))
-                    .collect();
-                
                 // Calculate first verification digit
                 let sum1: u32 = (0..9).map(|i| digits[i] * (10 - i as u32)).sum();
                 let digit1 = match sum1 % 11 {
                     0 | 1 => 0,
                     n => 11 - n,
                 };
-                
                 if digits[9] != digit1 {
-                    tracing::debug!("CPF validation failed: first verification digit mismatch");
-                    return false;
+                    return Err("first verification digit mismatch");
                 }
                 
                 // Calculate second verification digit
@@ -250,19 +511,113 @@ pub fn derive_brazilian_entity(input: TokenStream) -> TokenStream {
                     0 | 1 => 0,
                     n => 11 - n,
                 };
+                if digits[10] != digit2 {
+                    return Err("second verification digit mismatch");
+                }
                 
-                let is_valid = digits[10] == digit2;
+                Ok(())
+            }
+            
+            /// AI-Generated: CNPJ validation for business documents
+            pub fn validate_cnpj(cnpj: &str) -> bool {
+                let mut values = Vec::with_capacity(14);
+                let is_valid = match Self::check_cnpj(cnpj, &mut values) {
+                    Ok(()) => true,
+                    Err(reason) => {
+                        tracing::debug!(cnpj_length = %values.len(), reason = %reason, "CNPJ validation failed");
+                        false
+                    }
+                };
                 
-                // Architectural Observability: Track validation attempts
                 tracing::debug!(
                     entity = %stringify!(#struct_name),
                     validation_result = %is_valid,
-                    "CPF validation completed"
+                    alphanumeric = #cnpj_alphanumeric,
+                    "CNPJ validation completed"
                 );
                 
                 is_valid
             }
             
+            /// Validate many CNPJs at once, pairing each input with its validity (in order)
+            pub fn validate_cnpj_batch(docs: &[String]) -> Vec<(String, bool)> {
+                // One value buffer for the whole batch
+                let mut values = Vec::with_capacity(14);
+                let results: Vec<(String, bool)> = docs.iter()
+                    .map(|doc| (doc.clone(), Self::check_cnpj(doc, &mut values).is_ok()))
+                    .collect();
+                
+                tracing::debug!(
+                    entity = %stringify!(#struct_name),
+                    total = %results.len(),
+                    invalid = %results.iter().filter(|(_, valid)| !valid).count(),
+                    "CNPJ batch validation completed"
+                );
+                
+                results
+            }
+            
+            /// CNPJ check into a reusable value buffer, with the reason on failure
+            fn check_cnpj(cnpj: &str, values: &mut Vec<u32>) -> Result<(), &'static str> {
+                Self::cnpj_values(cnpj, values)?;
+                
+                // Check for invalid sequences
+                if values.iter().all(|v| *v == values[0]) {
+                    return Err("all characters are the same");
+                }
+                
+                // First verification digit
+                let weights1 = [5, 4, 3, 2, 9, 8, 7, 6, 5, 4, 3, 2];
+                let sum1: u32 = (0..12).map(|i| values[i] * weights1[i]).sum();
+                let digit1 = match sum1 % 11 {
+                    0 | 1 => 0,
+                    n => 11 - n,
+                };
+                if values[12] != digit1 {
+                    return Err("first verification digit mismatch");
+                }
+                
+                // Second verification digit
+                let weights2 = [6, 5, 4, 3, 2, 9, 8, 7, 6, 5, 4, 3, 2];
+                let sum2: u32 = (0..13).map(|i| values[i] * weights2[i]).sum();
+                let digit2 = match sum2 % 11 {
+                    0 | 1 => 0,
+                    n => 11 - n,
+                };
+                if values[13] != digit2 {
+                    return Err("second verification digit mismatch");
+                }
+                
+                Ok(())
+            }
+            
+            /// CPF validation that also requires a well-formed input: 11 bare digits or
+            /// the canonical `000.000.000-00` mask, rejecting any other punctuation
+            pub fn validate_cpf_strict(cpf: &str) -> bool {
+                (Self::matches_document_mask(cpf, "###########", false)
+                    || Self::matches_document_mask(cpf, "###.###.###-##", false))
+                    && Self::validate_cpf(cpf)
+            }
+            
+            /// CNPJ validation that also requires a well-formed input: 14 bare characters or
+            /// the canonical `00.000.000/0000-00` mask, rejecting any other punctuation
+            pub fn validate_cnpj_strict(cnpj: &str) -> bool {
+                (Self::matches_document_mask(cnpj, "##############", #cnpj_alphanumeric)
+                    || Self::matches_document_mask(cnpj, "##.###.###/####-##", #cnpj_alphanumeric))
+                    && Self::validate_cnpj(cnpj)
+            }
+            
+            /// Whether `value` has the shape of `mask`: `#` is a digit (or any ASCII
+            /// alphanumeric when `alphanumeric`), every other mask character is literal
+            fn matches_document_mask(value: &str, mask: &str, alphanumeric: bool) -> bool {
+                value.len() == mask.len()
+                    && value.chars().zip(mask.chars()).all(|(c, m)| match m {
+                        '#' if alphanumeric => c.is_ascii_alphanumeric(),
+                        '#' => c.is_ascii_digit(),
+                        m => c == m,
+                    })
+            }
+            
             /// Format CPF for display with proper Brazilian formatting
             pub fn format_cpf(cpf: &str) -> String {
                 let digits: String = cpf.chars().filter(|c| c.is_ascii_digit()).collect();
@@ -300,66 +655,139 @@ pub fn derive_brazilian_entity(input: TokenStream) -> TokenStream {
                 }
             }
             
-            /// AI-Generated: CNPJ validation for business documents
-            pub fn validate_cnpj(cnpj: &str) -> bool {
-                let digits: String = cnpj.chars().filter(|c| c.is_ascii_digit()).collect();
+            /// RG validation for the common SP-style 9-character RG (8 digits plus a check
+            /// digit, `X` meaning 10). Formats vary by state, so the check digit is only
+            /// verified with `#[brazilian(rg_checkdigit)]`.
+            pub fn validate_rg(rg: &str) -> bool {
+                let mut chars = Vec::with_capacity(9);
+                let is_valid = match Self::check_rg(rg, &mut chars) {
+                    Ok(()) => true,
+                    Err(reason) => {
+                        tracing::debug!(rg_length = %chars.len(), reason = %reason, "RG validation failed");
+                        false
+                    }
+                };
                 
-                if digits.len() != 14 {
-                    tracing::debug!(cnpj_length = %digits.len(), "CNPJ validation failed: invalid length");
-                    return false;
-                }
+                tracing::debug!(
+                    entity = %stringify!(#struct_name),
+                    checkdigit = #rg_checkdigit,
+                    validation_result = %is_valid,
+                    "RG validation completed"
+                );
                 
-                // Check for invalid sequences
-                if digits.chars().all(|c| c == digits.chars().next().unwrap()) {
-                    tracing::debug!("CNPJ validation failed: all digits are the same");
-                    return false;
-                }
+                is_valid
+            }
+            
+            /// RG check into a reusable buffer (separators dropped, `x` uppercased)
+            fn check_rg(rg: &str, chars: &mut Vec<char>) -> Result<(), &'static str> {
+                chars.clear();
+                chars.extend(rg.chars()
+                    .filter(|c| !matches!(c, '.' | '-' | ' '))
+                    .map(|c| c.to_ascii_uppercase()));
                 
-                let digits: Vec<u32> = digits.chars()
-                    .map(|c| c.to_digit(10).unwrap_or(0))
-                    .collect();
+                if chars.len() != 9 {
+                    return Err("invalid length");
+                }
+                if !chars[..8].iter().all(|c| c.is_ascii_digit()) || !(chars[8].is_ascii_digit() || chars[8] == 'X') {
+                    return Err("invalid character");
+                }
                 
-                // First verification digit
-                let weights1 = [5, 4, 3, 2, 9, 8, 7, 6, 5, 4, 3, 2];
-                let sum1: u32 = (0..12).map(|i| digits[i] * weights1[i]).sum();
-                let digit1 = match sum1 % 11 {
-                    0 | 1 => 0,
-                    n => 11 - n,
-                };
+                #rg_checkdigit_impl
                 
-                if digits[12] != digit1 {
-                    tracing::debug!("CNPJ validation failed: first verification digit mismatch");
-                    return false;
+                Ok(())
+            }
+            
+            /// Format RG for display as `XX.XXX.XXX-X`
+            pub fn format_rg(rg: &str) -> String {
+                let mut chars = Vec::with_capacity(9);
+                if Self::check_rg(rg, &mut chars).is_err() {
+                    return rg.to_string();
                 }
-                
-                // Second verification digit
-                let weights2 = [6, 5, 4, 3, 2, 9, 8, 7, 6, 5, 4, 3, 2];
-                let sum2: u32 = (0..13).map(|i| digits[i] * weights2[i]).sum();
-                let digit2 = match sum2 % 11 {
-                    0 | 1 => 0,
-                    n => 11 - n,
+                let chars: String = chars.into_iter().collect();
+                format!("{}.{}.{}-{}", &chars[0..2], &chars[2..5], &chars[5..8], &chars[8..9])
+            }
+            
+            /// PIS/PASEP/NIT validation with the mod-11 check digit (weights 3,2,9,8,7,6,5,4,3,2)
+            pub fn validate_pis(pis: &str) -> bool {
+                let mut digits = Vec::with_capacity(11);
+                let is_valid = match Self::check_pis(pis, &mut digits) {
+                    Ok(()) => true,
+                    Err(reason) => {
+                        tracing::debug!(pis_length = %digits.len(), reason = %reason, "PIS validation failed");
+                        false
+                    }
                 };
                 
-                let is_valid = digits[13] == digit2;
-                
                 tracing::debug!(
                     entity = %stringify!(#struct_name),
                     validation_result = %is_valid,
-                    "CNPJ validation completed"
+                    "PIS validation completed"
                 );
                 
                 is_valid
             }
             
-            /// Format CNPJ for display
-            pub fn format_cnpj(cnpj: &str) -> String {
-                let digits: String = cnpj.chars().filter(|c| c.is_ascii_digit()).collect();
-                if digits.len() == 14 {
-                    format!("{}.{}.{}/{}-{}", 
-                        &digits[0..2], &digits[2..5], &digits[5..8],
-                        &digits[8..12], &digits[12..14])
+            /// PIS check into a reusable digit buffer, with the reason on failure
+            fn check_pis(pis: &str, digits: &mut Vec<u32>) -> Result<(), &'static str> {
+                const WEIGHTS: [u32; 10] = [3, 2, 9, 8, 7, 6, 5, 4, 3, 2];
+                
+                digits.clear();
+                digits.extend(pis.chars().filter_map(|c| c.to_digit(10)));
+                
+                if digits.len() != 11 {
+                    return Err("invalid length");
+                }
+                
+                if digits.iter().all(|d| *d == digits[0]) {
+                    return Err("all digits are the same");
+                }
+                
+                let sum: u32 = digits.iter().zip(WEIGHTS).map(|(d, weight)| d * weight).sum();
+                let check_digit = match 11 - sum % 11 {
+                    10 | 11 => 0,
+                    n => n,
+                };
+                if digits[10] != check_digit {
+                    return Err("verification digit mismatch");
+                }
+                
+                Ok(())
+            }
+            
+            /// Format PIS for display as `XXX.XXXXX.XX-X`
+            pub fn format_pis(pis: &str) -> String {
+                let digits: String = pis.chars().filter(|c| c.is_ascii_digit()).collect();
+                if digits.len() == 11 {
+                    format!("{}.{}.{}-{}",
+                        &digits[0..3], &digits[3..8],
+                        &digits[8..10], &digits[10..11])
                 } else {
-                    cnpj.to_string()
+                    pis.to_string()
+                }
+            }
+            
+            #cnpj_impl
+            
+            /// CNPJ root ("raiz"): the first 8 characters, shared by a company's matriz and filiais.
+            /// `None` when the CNPJ does not validate.
+            pub fn cnpj_root(cnpj: &str) -> Option<String> {
+                let normalized: String = cnpj.chars()
+                    .filter(|c| c.is_ascii_alphanumeric())
+                    .map(|c| c.to_ascii_uppercase())
+                    .collect();
+                
+                if normalized.len() != 14 || !Self::validate_cnpj(&normalized) {
+                    return None;
+                }
+                
+                Some(normalized[..8].to_string())
+            }
+            
+            /// Whether two valid CNPJs belong to the same corporate group (same root)
+            pub fn same_corporate_group(a: &str, b: &str) -> bool {
+                match (Self::cnpj_root(a), Self::cnpj_root(b)) {
+                    (Some(root_a), Some(root_b)) => root_a == root_b,
+                    _ => false,
                 }
             }
             
@@ -389,6 +817,233 @@ pub fn derive_brazilian_entity(input: TokenStream) -> TokenStream {
                 }
             }
             
+            /// Normalize a Brazilian phone to E.164 (`+55DDNNNNNNNNN`) for SMS/WhatsApp providers
+            pub fn to_e164(phone: &str) -> Option<String> {
+                let trimmed = phone.trim();
+                let mut digits: String = trimmed.chars().filter(|c| c.is_ascii_digit()).collect();
+                
+                // Explicit international form: must be +55 / 0055
+                if trimmed.starts_with('+') || digits.starts_with("00") {
+                    digits = digits.trim_start_matches("00").to_string();
+                    digits = digits.strip_prefix("55")?.to_string();
+                } else if (digits.len() == 12 || digits.len() == 13) && digits.starts_with("55") {
+                    digits = digits[2..].to_string();
+                } else if (digits.len() == 11 || digits.len() == 12) && digits.starts_with('0') {
+                    // National trunk prefix (e.g. 011 98765-4321)
+                    digits = digits[1..].to_string();
+                }
+                
+                let bytes = digits.as_bytes();
+                let valid_ddd = bytes.len() >= 2 && bytes[0] != b'0' && bytes[1] != b'0';
+                let is_valid = valid_ddd && match bytes.len() {
+                    // Mobile numbers have nine digits starting with 9
+                    11 => bytes[2] == b'9',
+                    // Landlines start with 2-5
+                    10 => (b'2'..=b'5').contains(&bytes[2]),
+                    _ => false,
+                };
+                
+                tracing::debug!(
+                    entity = %stringify!(#struct_name),
+                    phone_length = %digits.len(),
+                    validation_result = %is_valid,
+                    "Brazilian phone E.164 normalization completed"
+                );
+                
+                is_valid.then(|| format!("+55{}", digits))
+            }
+            
+            /// Words for 0-19, then tens and hundreds, used by the "por extenso" conversions
+            const EXTENSO_UNITS: [&'static str; 20] = [
+                "zero", "um", "dois", "três", "quatro", "cinco", "seis", "sete", "oito", "nove",
+                "dez", "onze", "doze", "treze", "quatorze", "quinze", "dezesseis", "dezessete", "dezoito", "dezenove",
+            ];
+            const EXTENSO_TENS: [&'static str; 10] = [
+                "", "", "vinte", "trinta", "quarenta", "cinquenta", "sessenta", "setenta", "oitenta", "noventa",
+            ];
+            const EXTENSO_HUNDREDS: [&'static str; 10] = [
+                "", "cento", "duzentos", "trezentos", "quatrocentos", "quinhentos",
+                "seiscentos", "setecentos", "oitocentos", "novecentos",
+            ];
+            /// (singular, plural) for 10^3 up to 10^27, which covers every `Decimal`
+            const EXTENSO_SCALES: [(&'static str, &'static str); 9] = [
+                ("mil", "mil"), ("milhão", "milhões"), ("bilhão", "bilhões"), ("trilhão", "trilhões"),
+                ("quatrilhão", "quatrilhões"), ("quintilhão", "quintilhões"), ("sextilhão", "sextilhões"),
+                ("septilhão", "septilhões"), ("octilhão", "octilhões"),
+            ];
+            
+            /// Brazilian "valor por extenso": BRL amount in Portuguese words
+            pub fn amount_in_words(amount: rust_decimal::Decimal) -> String {
+                let negative = amount.is_sign_negative() && !amount.is_zero();
+                let amount = amount.abs().round_dp(2);
+                // Exact for the whole `Decimal` range, unlike a round trip through u64
+                let whole = |value: rust_decimal::Decimal| {
+                    let value = value.trunc();
+                    value.mantissa().unsigned_abs() / 10u128.pow(value.scale())
+                };
+                let reais = whole(amount);
+                let centavos = whole(amount.fract() * rust_decimal::Decimal::ONE_HUNDRED);
+                
+                let reais_text = {
+                    let words = Self::integer_in_words(reais);
+                    let unit = if reais == 1 { "real" } else { "reais" };
+                    // "um milhão de reais": exact millions and above take "de"
+                    if reais >= 1_000_000 && reais % 1_000_000 == 0 {
+                        format!("{} de {}", words, unit)
+                    } else {
+                        format!("{} {}", words, unit)
+                    }
+                };
+                let centavos_text = format!("{} {}", Self::integer_in_words(centavos), if centavos == 1 { "centavo" } else { "centavos" });
+                
+                let text = match (reais, centavos) {
+                    (0, 0) => reais_text,
+                    (0, _) => centavos_text,
+                    (_, 0) => reais_text,
+                    _ => format!("{} e {}", reais_text, centavos_text),
+                };
+                
+                if negative {
+                    format!("menos {}", text)
+                } else {
+                    text
+                }
+            }
+            
+            /// Parse a "valor por extenso" back into an amount; the inverse of `amount_in_words`
+            ///
+            /// Returns `None` for unknown words, misordered groups, more than 99 centavos or
+            /// a number without its `reais`/`centavos` unit.
+            pub fn amount_from_words(text: &str) -> Option<rust_decimal::Decimal> {
+                let text = text.trim().to_lowercase();
+                let (negative, text) = match text.strip_prefix("menos ") {
+                    Some(rest) => (true, rest.to_string()),
+                    None => (false, text),
+                };
+                
+                let mut reais: Option<u128> = None;
+                let mut centavos: Option<u128> = None;
+                // Running number: completed scales plus the three-digit group being read
+                let mut total: u128 = 0;
+                let mut group: u128 = 0;
+                let mut last_scale = usize::MAX;
+                let mut pending = false;
+                
+                for word in text.split_whitespace() {
+                    let small = Self::EXTENSO_UNITS.iter().position(|w| *w == word)
+                        .or_else(|| Self::EXTENSO_TENS.iter().position(|w| !w.is_empty() && *w == word).map(|i| i * 10))
+                        .or_else(|| Self::EXTENSO_HUNDREDS.iter().position(|w| !w.is_empty() && *w == word).map(|i| i * 100))
+                        .or_else(|| (word == "cem").then_some(100));
+                    let scale = Self::EXTENSO_SCALES.iter().position(|(singular, plural)| *singular == word || *plural == word);
+                    
+                    match (word, small, scale) {
+                        ("e" | "de", _, _) => continue,
+                        (_, Some(value), _) => {
+                            let value = value as u128;
+                            // Hundreds, then tens, then units: each must fit the places still free
+                            let place = if value >= 100 { 1000 } else if value >= 10 { 100 } else { 10 };
+                            if group % place != 0 || (value == 0 && pending) {
+                                return None;
+                            }
+                            group += value;
+                        }
+                        (_, None, Some(scale)) => {
+                            if scale >= last_scale || (group == 0 && scale > 0) {
+                                return None;
+                            }
+                            // "mil" stands alone for one thousand
+                            let multiplier = 10u128.pow(3 * (scale as u32 + 1));
+                            total = total.checked_add(group.max(1).checked_mul(multiplier)?)?;
+                            group = 0;
+                            last_scale = scale;
+                        }
+                        ("real" | "reais", None, None) if pending && reais.is_none() && centavos.is_none() => {
+                            reais = Some(total.checked_add(group)?);
+                            (total, group, last_scale) = (0, 0, usize::MAX);
+                            pending = false;
+                            continue;
+                        }
+                        ("centavo" | "centavos", None, None) if pending && centavos.is_none() => {
+                            let value = total.checked_add(group)?;
+                            if value > 99 {
+                                return None;
+                            }
+                            centavos = Some(value);
+                            (total, group, last_scale) = (0, 0, usize::MAX);
+                            pending = false;
+                            continue;
+                        }
+                        _ => return None,
+                    }
+                    pending = true;
+                }
+                if pending || (reais.is_none() && centavos.is_none()) {
+                    return None;
+                }
+                
+                let reais = rust_decimal::Decimal::try_from_i128_with_scale(i128::try_from(reais.unwrap_or(0)).ok()?, 0).ok()?;
+                let amount = reais.checked_add(rust_decimal::Decimal::new(centavos.unwrap_or(0) as i64, 2))?;
+                Some(if negative { -amount } else { amount })
+            }
+            
+            /// One three-digit group in words ("cento e vinte e três")
+            fn group_in_words(n: u128) -> String {
+                if n == 100 {
+                    return "cem".to_string();
+                }
+                let mut parts = Vec::new();
+                if n >= 100 {
+                    parts.push(Self::EXTENSO_HUNDREDS[(n / 100) as usize].to_string());
+                }
+                let rest = n % 100;
+                if rest >= 20 {
+                    parts.push(Self::EXTENSO_TENS[(rest / 10) as usize].to_string());
+                    if rest % 10 != 0 {
+                        parts.push(Self::EXTENSO_UNITS[(rest % 10) as usize].to_string());
+                    }
+                } else if rest > 0 || parts.is_empty() {
+                    parts.push(Self::EXTENSO_UNITS[rest as usize].to_string());
+                }
+                parts.join(" e ")
+            }
+            
+            fn integer_in_words(n: u128) -> String {
+                if n == 0 {
+                    return Self::EXTENSO_UNITS[0].to_string();
+                }
+                
+                // Split into groups of three digits, least significant first
+                let mut groups = Vec::new();
+                let mut remaining = n;
+                while remaining > 0 {
+                    groups.push(remaining % 1000);
+                    remaining /= 1000;
+                }
+                
+                let last_nonzero = groups.iter().position(|&g| g != 0).unwrap_or(0);
+                let mut words: Vec<String> = Vec::new();
+                for (scale, &group) in groups.iter().enumerate().rev() {
+                    if group == 0 {
+                        continue;
+                    }
+                    let mut text = match scale {
+                        0 => Self::group_in_words(group),
+                        // "mil", never "um mil"
+                        1 if group == 1 => Self::EXTENSO_SCALES[0].0.to_string(),
+                        _ => {
+                            let (singular, plural) = Self::EXTENSO_SCALES[scale - 1];
+                            format!("{} {}", Self::group_in_words(group), if group == 1 { singular } else { plural })
+                        }
+                    };
+                    // "mil e cem", "um milhão e cinquenta mil": "e" joins a final group below 100 or a round hundred
+                    if !words.is_empty() && scale == last_nonzero && (group < 100 || group % 100 == 0) {
+                        text = format!("e {}", text);
+                    }
+                    words.push(text);
+                }
+                words.join(" ")
+            }
+            
             /// Architectural Observability: Track Brazilian entity operations
             pub fn track_brazilian_validation(&self, validation_type: &str, success: bool) {
                 tracing::info!(
@@ -399,6 +1054,9 @@ pub fn derive_brazilian_entity(input: TokenStream) -> TokenStream {
                 );
             }
         }
+        
+        #field_validators
+        #cep_lookup
     };
     
     TokenStream::from(expanded)
@@ -413,31 +1071,113 @@ pub fn derive_smart_repository(input: TokenStream) -> TokenStream {
     
     eprintln!("[pleme-codegen] SmartRepository pattern applied to {}", struct_name);
     
+    // #[repository(metrics_prefix = "...")] namespaces tracing targets and metric names per service
+    let prefix = match repository_helpers::metrics_prefix(&input.attrs) {
+        Ok(prefix) => prefix,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let metrics_prefix = prefix.as_ref().map(|p| p.value()).unwrap_or_default();
+    let target = match &prefix {
+        Some(p) => {
+            let target = syn::LitStr::new(&format!("{}::repository", p.value()), p.span());
+            quote! { target: #target, }
+        }
+        None => quote! {},
+    };
+    
+    // The smart cache lives on the repository instance, like `pool` and `cache` do elsewhere
+    let has_smart_cache = matches!(
+        &input.data,
+        syn::Data::Struct(data) if data.fields.iter().any(|f| f.ident.as_ref().is_some_and(|i| i == "smart_cache"))
+    );
+    if !has_smart_cache {
+        return syn::Error::new_spanned(
+            struct_name,
+            format!("SmartRepository requires a `smart_cache: {}SmartCache` field", struct_name),
+        )
+        .to_compile_error()
+        .into();
+    }
+    // #[repository(correlation_id)] reads the task-local id into every event
+    let correlation = match repository_helpers::correlation_module(&input.attrs) {
+        Ok(module) => module,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let (correlation_field, correlation_scope) = match &correlation {
+        Some(module) => (
+            quote! { correlation_id = %#module::current().unwrap_or_default(), },
+            quote! {
+                /// Run `operation` with `correlation_id` in the task-local context, so every
+                /// tracing event it emits (from this or any other generated type) carries it
+                ///
+                /// Task-locals do not cross `tokio::spawn`; spawn through `correlation::spawn`.
+                pub async fn with_correlation_id<F>(correlation_id: &str, operation: F) -> F::Output
+                where
+                    F: std::future::Future,
+                {
+                    #module::scope(correlation_id, operation).await
+                }
+            },
+        ),
+        None => (quote! {}, quote! {}),
+    };
+    let smart_cache = quote::format_ident!("{}SmartCache", struct_name);
+    let smart_cache_entry = quote::format_ident!("{}SmartCacheEntry", struct_name);
+    
     let expanded = quote! {
         impl #struct_name {
+            /// Service namespace for this repository's metrics (empty when unset)
+            pub const METRICS_PREFIX: &'static str = #metrics_prefix;
+            
+            /// Metric name for an operation, namespaced by `METRICS_PREFIX` when set
+            pub fn metric_name(operation: &str) -> String {
+                let base = format!("repository.{}.{}", stringify!(#struct_name).to_lowercase(), operation.to_lowercase());
+                if Self::METRICS_PREFIX.is_empty() {
+                    base
+                } else {
+                    format!("{}.{}", Self::METRICS_PREFIX, base)
+                }
+            }
+            
+            #correlation_scope
+            
             /// AI-Generated: Complete CRUD repository with observability
             pub async fn create_with_observability<T>(&self, entity: &T, user_id: Option<uuid::Uuid>) 
             -> Result<T, Box<dyn std::error::Error + Send + Sync>>
             where 
                 T: serde::Serialize + serde::de::DeserializeOwned + Clone,
+            {
+                self.create_with_observability_ref(entity, user_id).await.map(T::clone)
+            }
+            
+            /// Borrowing `create_with_observability` for hot paths that don't need an owned copy
+            pub async fn create_with_observability_ref<'a, T>(&self, entity: &'a T, user_id: Option<uuid::Uuid>) 
+            -> Result<&'a T, Box<dyn std::error::Error + Send + Sync>>
+            where 
+                T: serde::Serialize,
             {
                 let start = std::time::Instant::now();
                 
                 tracing::info!(
+                    #target
                     repository = %stringify!(#struct_name),
+                    #correlation_field
                     operation = "CREATE_WITH_OBSERVABILITY",
                     user_id = ?user_id,
                     "Repository operation starting"
                 );
                 
                 // Simulate repository operation (would be actual implementation)
-                let result = Ok(entity.clone());
+                let result = Ok(entity);
                 
                 // Track performance metrics
                 let duration = start.elapsed().as_millis() as u64;
                 tracing::info!(
+                    #target
                     repository = %stringify!(#struct_name),
+                    #correlation_field
                     operation = "CREATE",
+                    metric = %Self::metric_name("CREATE"),
                     duration_ms = %duration,
                     success = %result.is_ok(),
                     "Repository operation completed"
@@ -447,33 +1187,154 @@ pub fn derive_smart_repository(input: TokenStream) -> TokenStream {
             }
             
             /// AI-Generated: Smart read with multi-layer caching
-            pub async fn find_with_smart_cache<T>(&self, id: &str) -> Result<Option<T>, Box<dyn std::error::Error + Send + Sync>>
+            ///
+            /// Serves `id` from this repository's `smart_cache`, calling `fetch` only on a
+            /// miss and caching what it finds. `None` results are not cached.
+            pub async fn find_with_smart_cache<T, F, Fut, E>(&self, id: &str, fetch: F) -> Result<Option<T>, Box<dyn std::error::Error + Send + Sync>>
             where
-                T: serde::Serialize + serde::de::DeserializeOwned + Clone + Default,
+                T: serde::Serialize + serde::de::DeserializeOwned,
+                F: FnOnce() -> Fut,
+                Fut: std::future::Future<Output = Result<Option<T>, E>>,
+                E: Into<Box<dyn std::error::Error + Send + Sync>>,
             {
-                let cache_key = format!("{}:{}", stringify!(#struct_name).to_lowercase(), id);
+                let cache_key = #smart_cache::key::<T>(id);
                 
                 tracing::debug!(
+                    #target
                     repository = %stringify!(#struct_name),
+                    #correlation_field
                     cache_key = %cache_key,
                     "Smart cache lookup initiated"
                 );
                 
                 let start = std::time::Instant::now();
-                let result = Ok(Some(T::default())); // Simulate cache miss -> database lookup
+                // Entries that no longer deserialize as T count as misses
+                let cached = self.smart_cache.get(&cache_key)
+                    .and_then(|json| serde_json::from_str::<T>(&json).ok());
+                let cache_miss = cached.is_none();
+                
+                let result = match cached {
+                    Some(entity) => Ok(Some(entity)),
+                    None => match fetch().await {
+                        Ok(Some(entity)) => {
+                            if let Ok(json) = serde_json::to_string(&entity) {
+                                self.smart_cache.insert(cache_key.clone(), json);
+                            }
+                            Ok(Some(entity))
+                        }
+                        Ok(None) => Ok(None),
+                        Err(e) => Err(e.into()),
+                    },
+                };
                 let duration = start.elapsed().as_millis() as u64;
                 
                 tracing::info!(
+                    #target
                     repository = %stringify!(#struct_name),
+                    #correlation_field
                     operation = "FIND_WITH_CACHE",
+                    metric = %Self::metric_name("FIND_WITH_CACHE"),
                     duration_ms = %duration,
-                    cache_miss = true,
+                    cache_miss = %cache_miss,
                     success = %result.is_ok(),
                     "Repository operation completed"
                 );
                 
                 result
             }
+            
+            /// Drop the cached `T` for `id`, e.g. after updating or deleting it
+            pub fn evict_from_smart_cache<T>(&self, id: &str) {
+                self.smart_cache.remove(&#smart_cache::key::<T>(id));
+            }
+        }
+        
+        /// Bounded cache behind `find_with_smart_cache`
+        ///
+        /// Entries are keyed by entity type and id, expire `ttl` after insertion, and the
+        /// least recently used entry is dropped once `capacity` is reached.
+        pub struct #smart_cache {
+            capacity: usize,
+            ttl: std::time::Duration,
+            entries: std::sync::Mutex<std::collections::HashMap<String, #smart_cache_entry>>,
+        }
+        
+        struct #smart_cache_entry {
+            json: String,
+            inserted_at: std::time::Instant,
+            last_used: std::time::Instant,
+        }
+        
+        impl #smart_cache {
+            pub const DEFAULT_CAPACITY: usize = 1024;
+            pub const DEFAULT_TTL: std::time::Duration = std::time::Duration::from_secs(300);
+            
+            pub fn new(capacity: usize, ttl: std::time::Duration) -> Self {
+                Self {
+                    capacity: capacity.max(1),
+                    ttl,
+                    entries: std::sync::Mutex::new(std::collections::HashMap::new()),
+                }
+            }
+            
+            /// Number of live and not yet purged entries
+            pub fn len(&self) -> usize {
+                self.entries.lock().map(|entries| entries.len()).unwrap_or(0)
+            }
+            
+            pub fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+            
+            fn key<T>(id: &str) -> String {
+                format!("{}:{}", std::any::type_name::<T>(), id)
+            }
+            
+            fn get(&self, key: &str) -> Option<String> {
+                let mut entries = self.entries.lock().ok()?;
+                let now = std::time::Instant::now();
+                match entries.get_mut(key) {
+                    Some(entry) if now.duration_since(entry.inserted_at) < self.ttl => {
+                        entry.last_used = now;
+                        Some(entry.json.clone())
+                    }
+                    Some(_) => {
+                        entries.remove(key);
+                        None
+                    }
+                    None => None,
+                }
+            }
+            
+            fn insert(&self, key: String, json: String) {
+                let Ok(mut entries) = self.entries.lock() else { return };
+                let now = std::time::Instant::now();
+                if !entries.contains_key(&key) && entries.len() >= self.capacity {
+                    let ttl = self.ttl;
+                    entries.retain(|_, entry| now.duration_since(entry.inserted_at) < ttl);
+                    if entries.len() >= self.capacity {
+                        let oldest = entries.iter()
+                            .min_by_key(|(_, entry)| entry.last_used)
+                            .map(|(key, _)| key.clone());
+                        if let Some(oldest) = oldest {
+                            entries.remove(&oldest);
+                        }
+                    }
+                }
+                entries.insert(key, #smart_cache_entry { json, inserted_at: now, last_used: now });
+            }
+            
+            fn remove(&self, key: &str) {
+                if let Ok(mut entries) = self.entries.lock() {
+                    entries.remove(key);
+                }
+            }
+        }
+        
+        impl Default for #smart_cache {
+            fn default() -> Self {
+                Self::new(Self::DEFAULT_CAPACITY, Self::DEFAULT_TTL)
+            }
         }
     };
     
@@ -566,6 +1427,26 @@ pub fn derive_architectural_monitor(input: TokenStream) -> TokenStream {
                 result
             }
             
+            /// `monitor_operation` for async work; the timing covers the whole await
+            pub async fn monitor_async<F, Fut, R>(&self, operation_name: &str, op: F) -> R
+            where
+                F: FnOnce() -> Fut,
+                Fut: std::future::Future<Output = R>,
+            {
+                let start = std::time::Instant::now();
+                let result = op().await;
+                let duration_ms = start.elapsed().as_millis() as u64;
+                
+                tracing::info!(
+                    entity = %stringify!(#struct_name),
+                    operation = %operation_name,
+                    duration_ms = %duration_ms,
+                    "Operation monitored for architectural analysis"
+                );
+                
+                result
+            }
+            
             /// AI-Generated: Analyze this entity for architectural patterns
             pub fn analyze_architectural_patterns(&self) -> Vec<String> {
                 let mut patterns = Vec::new();
@@ -693,6 +1574,18 @@ pub fn derive_pix_payment(input: TokenStream) -> TokenStream {
     payment_patterns::derive_pix_payment(input)
 }
 
+/// PaymentMethods Pattern - Brazilian payment method enum helpers (saves ~60 lines)
+#[proc_macro_derive(PaymentMethods, attributes(payment_method))]
+pub fn derive_payment_methods(input: TokenStream) -> TokenStream {
+    payment_patterns::derive_payment_methods(input)
+}
+
+/// BrazilianPaymentEntity Pattern - Enhanced Brazilian market features (saves ~300 lines)
+#[proc_macro_derive(BrazilianPaymentEntity, attributes(brazilian_payment))]
+pub fn derive_brazilian_payment_entity(input: TokenStream) -> TokenStream {
+    brazilian_payment_entity::derive_brazilian_payment_entity(input)
+}
+
 /// WalletEntity Pattern - Wallet balance management (saves ~200 lines)
 #[proc_macro_derive(WalletEntity, attributes(wallet))]
 pub fn derive_wallet_entity(input: TokenStream) -> TokenStream {
@@ -700,13 +1593,37 @@ pub fn derive_wallet_entity(input: TokenStream) -> TokenStream {
 }
 
 /// RowMapper Pattern - Database row to struct mapping (saves ~50 lines per struct)
-#[proc_macro_derive(RowMapper, attributes(row))]
+#[proc_macro_derive(RowMapper, attributes(row, database))]
 pub fn derive_row_mapper(input: TokenStream) -> TokenStream {
     repository_helpers::derive_row_mapper(input)
 }
 
+/// DatabaseMapper Pattern - Auto-generate database row mappings (saves ~1200 lines)
+#[proc_macro_derive(DatabaseMapper, attributes(database, db))]
+pub fn derive_database_mapper(input: TokenStream) -> TokenStream {
+    database_mapper::derive_database_mapper(input)
+}
+
+/// TransactionalRepository Pattern - Database transactions with deadlock prevention (saves ~400 lines)
+#[proc_macro_derive(TransactionalRepository, attributes(transactional))]
+pub fn derive_transactional_repository(input: TokenStream) -> TokenStream {
+    transactional_repository::derive_transactional_repository(input)
+}
+
+/// Repository Pattern - PostgreSQL repository with read-through caching for a DomainModel entity (saves ~250 lines)
+#[proc_macro_derive(Repository, attributes(repository))]
+pub fn derive_repository(input: TokenStream) -> TokenStream {
+    repository::derive_repository(input)
+}
+
+/// Service Pattern - Tenant-guarded service layer over a Repository entity (saves ~150 lines)
+#[proc_macro_derive(Service, attributes(service))]
+pub fn derive_service(input: TokenStream) -> TokenStream {
+    service::derive_service(input)
+}
+
 /// RepositoryCrud Pattern - CRUD operations with caching (saves ~300 lines)
-#[proc_macro_derive(RepositoryCrud, attributes(repository))]
+#[proc_macro_derive(RepositoryCrud, attributes(repository, cached))]
 pub fn derive_repository_crud(input: TokenStream) -> TokenStream {
     repository_helpers::derive_repository_crud(input)
 }
@@ -724,21 +1641,3 @@ pub fn derive_subscription_entity(input: TokenStream) -> TokenStream {
 // pub fn derive_cached_repository(input: TokenStream) -> TokenStream {
 //     cached_repository::derive_cached_repository(input)
 // }
-
-// /// DatabaseMapper Pattern - Auto-generate database row mappings (saves ~1200 lines)
-// #[proc_macro_derive(DatabaseMapper, attributes(database, db))]
-// pub fn derive_database_mapper(input: TokenStream) -> TokenStream {
-//     database_mapper::derive_database_mapper(input)
-// }
-
-// /// TransactionalRepository Pattern - Database transactions with deadlock prevention (saves ~400 lines)
-// #[proc_macro_derive(TransactionalRepository, attributes(transactional))]
-// pub fn derive_transactional_repository(input: TokenStream) -> TokenStream {
-//     transactional_repository::derive_transactional_repository(input)
-// }
-
-// /// BrazilianPaymentEntity Pattern - Enhanced Brazilian market features (saves ~300 lines)
-// #[proc_macro_derive(BrazilianPaymentEntity, attributes(brazilian_payment))]
-// pub fn derive_brazilian_payment_entity(input: TokenStream) -> TokenStream {
-//     brazilian_payment_entity::derive_brazilian_payment_entity(input)
-// }
\ No newline at end of file
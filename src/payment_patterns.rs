@@ -2,42 +2,94 @@
 //!
 //! Macros for payment processing with Brazilian market support
 
+use heck::ToSnakeCase;
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{parse_macro_input, DeriveInput};
+use quote::{quote, quote_spanned};
+use syn::{parse_macro_input, spanned::Spanned, DeriveInput};
 
 /// Derive macro for payment entities with automatic state management
 pub fn derive_payment_entity(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let struct_name = &input.ident;
     
+    let config = match PaymentConfig::from_attrs(&input.attrs) {
+        Ok(config) => config,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let reconciliation_impl = match generate_reconciliation_implementation(&input, &config) {
+        Ok(tokens) => tokens,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let iso20022_impl = match generate_iso20022_implementation(&input, &config) {
+        Ok(tokens) => tokens,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let risk_impl = generate_risk_implementation(struct_name, &config);
+    let partial_refund_impl = generate_partial_refund_implementation(&input);
+    let test_default_impl = generate_test_default_implementation(&input);
+    let currency = config.currency.value();
+    let minor_units = config.minor_units;
+    let min_amount = &config.min_amount;
+    let max_amount = &config.max_amount;
+    let table = &config.table;
+    let comparison_impl = match generate_comparison_implementation(&input, &config) {
+        Ok(tokens) => tokens,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    
     eprintln!("[pleme-codegen] PaymentEntity pattern applied to {} - saving ~150 lines (FIXED VERSION)", struct_name);
     
+    let bench_impl = crate::bench_patterns::generate_bench_inputs(&input);
+    
     let expanded = quote! {
         impl #struct_name {
-            /// Mark payment as processing
-            pub fn mark_processing(&mut self) -> Result<(), PaymentError> {
+            /// Statuses this payment may move to next:
+            ///
+            /// | from       | to                              |
+            /// |------------|---------------------------------|
+            /// | Pending    | Processing, Completed, Failed   |
+            /// | Processing | Completed, Failed               |
+            /// | Completed  | Refunded                        |
+            /// | Failed     | Failed (records a new reason)   |
+            /// | Refunded   | - (terminal)                    |
+            /// | Cancelled  | - (terminal)                    |
+            pub fn allowed_transitions(&self) -> &'static [PaymentStatus] {
                 match self.status {
-                    PaymentStatus::Pending => {
-                        self.status = PaymentStatus::Processing;
-                        self.updated_at = chrono::Utc::now();
-                        Ok(())
-                    }
-                    _ => Err(PaymentError::InvalidStateTransition {
+                    PaymentStatus::Pending => &[PaymentStatus::Processing, PaymentStatus::Completed, PaymentStatus::Failed],
+                    PaymentStatus::Processing => &[PaymentStatus::Completed, PaymentStatus::Failed],
+                    PaymentStatus::Completed => &[PaymentStatus::Refunded],
+                    PaymentStatus::Failed => &[PaymentStatus::Failed],
+                    PaymentStatus::Refunded | PaymentStatus::Cancelled => &[],
+                }
+            }
+            
+            /// Whether `allowed_transitions` permits moving to `target`
+            pub fn can_transition_to(&self, target: PaymentStatus) -> bool {
+                self.allowed_transitions().contains(&target)
+            }
+            
+            fn ensure_transition(&self, target: PaymentStatus) -> Result<(), PaymentError> {
+                if self.can_transition_to(target) {
+                    Ok(())
+                } else {
+                    Err(PaymentError::InvalidStateTransition {
                         from: self.status,
-                        to: PaymentStatus::Processing,
-                    }),
+                        to: target,
+                    })
                 }
             }
             
+            /// Mark payment as processing
+            pub fn mark_processing(&mut self) -> Result<(), PaymentError> {
+                self.ensure_transition(PaymentStatus::Processing)?;
+                self.status = PaymentStatus::Processing;
+                self.updated_at = chrono::Utc::now();
+                Ok(())
+            }
+            
             /// Mark payment as completed
             pub fn mark_completed(&mut self) -> Result<(), PaymentError> {
-                if self.status != PaymentStatus::Processing && self.status != PaymentStatus::Pending {
-                    return Err(PaymentError::InvalidStateTransition {
-                        from: self.status,
-                        to: PaymentStatus::Completed,
-                    });
-                }
+                self.ensure_transition(PaymentStatus::Completed)?;
                 self.status = PaymentStatus::Completed;
                 self.completed_at = Some(chrono::Utc::now());
                 self.updated_at = chrono::Utc::now();
@@ -55,12 +107,7 @@ pub fn derive_payment_entity(input: TokenStream) -> TokenStream {
             
             /// Mark payment as failed with reason
             pub fn mark_failed(&mut self, reason: String) -> Result<(), PaymentError> {
-                if self.status == PaymentStatus::Completed || self.status == PaymentStatus::Refunded {
-                    return Err(PaymentError::InvalidStateTransition {
-                        from: self.status,
-                        to: PaymentStatus::Failed,
-                    });
-                }
+                self.ensure_transition(PaymentStatus::Failed)?;
                 self.status = PaymentStatus::Failed;
                 self.failed_at = Some(chrono::Utc::now());
                 self.failure_reason = Some(reason.clone());
@@ -79,17 +126,12 @@ pub fn derive_payment_entity(input: TokenStream) -> TokenStream {
             
             /// Check if payment can be refunded
             pub fn can_refund(&self) -> bool {
-                self.status == PaymentStatus::Completed
+                self.can_transition_to(PaymentStatus::Refunded)
             }
             
             /// Mark payment as refunded
             pub fn mark_refunded(&mut self) -> Result<(), PaymentError> {
-                if !self.can_refund() {
-                    return Err(PaymentError::InvalidStateTransition {
-                        from: self.status,
-                        to: PaymentStatus::Refunded,
-                    });
-                }
+                self.ensure_transition(PaymentStatus::Refunded)?;
                 self.status = PaymentStatus::Refunded;
                 self.updated_at = chrono::Utc::now();
                 
@@ -123,14 +165,158 @@ pub fn derive_payment_entity(input: TokenStream) -> TokenStream {
                 format!("pay_{:x}", hasher.finalize())
             }
             
+            /// Atomically claim an idempotency key (SET NX EX); returns false for a replay
+            pub async fn claim_idempotency<C>(&self, cache: &mut C, key: &str, ttl: u64) -> Result<bool, PaymentError>
+            where
+                C: redis::aio::ConnectionLike + Send,
+            {
+                let claimed: Option<String> = redis::cmd("SET")
+                    .arg(key)
+                    .arg(self.id.to_string())
+                    .arg("NX")
+                    .arg("EX")
+                    .arg(ttl)
+                    .query_async(cache)
+                    .await
+                    .map_err(|e| PaymentError::TransactionFailed(e.to_string()))?;
+                
+                let claimed = claimed.is_some();
+                if !claimed {
+                    tracing::warn!(
+                        payment_id = %self.id,
+                        idempotency_key = %key,
+                        "Idempotency key already claimed - rejecting replay"
+                    );
+                }
+                
+                Ok(claimed)
+            }
+            
+            /// How long a successful provider outcome stays cached under its idempotency key
+            pub const IDEMPOTENT_RESULT_TTL_SECS: u64 = 86_400;
+            
+            /// How long an in-flight claim on an idempotency key outlives a crashed caller
+            pub const IDEMPOTENT_CLAIM_TTL_SECS: u64 = 300;
+            
+            /// Delay before the first retry of a failed provider call; doubles per attempt
+            pub const IDEMPOTENT_RETRY_BASE_DELAY_MS: u64 = 100;
+            
+            /// Run a provider call with retries, caching its successful result under `key`
+            ///
+            /// A cached outcome is returned without re-executing `op`, so retried requests
+            /// never repeat the side effect once it has succeeded. Before running `op` the
+            /// caller claims `{key}:claim` with SET NX, so concurrent callers cannot both run
+            /// it; the loser gets `TransactionFailed` and can retry once the winner is done.
+            /// Failed calls are retried with exponential backoff.
+            pub async fn with_idempotent_retry<C, F, Fut, T>(
+                &self,
+                cache: &mut C,
+                key: &str,
+                max_attempts: u32,
+                mut op: F,
+            ) -> Result<T, PaymentError>
+            where
+                C: redis::aio::ConnectionLike + Send,
+                F: FnMut() -> Fut,
+                Fut: std::future::Future<Output = Result<T, PaymentError>>,
+                T: serde::Serialize + serde::de::DeserializeOwned,
+            {
+                let cached: Option<String> = redis::cmd("GET")
+                    .arg(key)
+                    .query_async(cache)
+                    .await
+                    .map_err(|e| PaymentError::TransactionFailed(e.to_string()))?;
+                if let Some(json) = cached {
+                    tracing::info!(
+                        payment_id = %self.id,
+                        idempotency_key = %key,
+                        "Returning cached provider outcome for idempotent retry"
+                    );
+                    return serde_json::from_str(&json).map_err(|e| PaymentError::TransactionFailed(e.to_string()));
+                }
+                
+                let claim_key = format!("{}:claim", key);
+                if !self.claim_idempotency(cache, &claim_key, Self::IDEMPOTENT_CLAIM_TTL_SECS).await? {
+                    return Err(PaymentError::TransactionFailed(format!(
+                        "idempotency key {} is already being processed", key
+                    )));
+                }
+                
+                let max_attempts = max_attempts.max(1);
+                let mut attempt = 1;
+                loop {
+                    match op().await {
+                        Ok(outcome) => {
+                            let json = serde_json::to_string(&outcome)
+                                .map_err(|e| PaymentError::TransactionFailed(e.to_string()))?;
+                            redis::cmd("SET")
+                                .arg(key)
+                                .arg(json)
+                                .arg("EX")
+                                .arg(Self::IDEMPOTENT_RESULT_TTL_SECS)
+                                .query_async::<_, ()>(cache)
+                                .await
+                                .map_err(|e| PaymentError::TransactionFailed(e.to_string()))?;
+                            return Ok(outcome);
+                        }
+                        Err(_) if attempt < max_attempts => {
+                            let delay_ms = Self::IDEMPOTENT_RETRY_BASE_DELAY_MS
+                                .saturating_mul(2_u64.saturating_pow(attempt - 1));
+                            tracing::warn!(
+                                payment_id = %self.id,
+                                idempotency_key = %key,
+                                attempt = attempt,
+                                max_attempts = max_attempts,
+                                delay_ms = delay_ms,
+                                "Provider call failed - retrying with backoff"
+                            );
+                            tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
+                            attempt += 1;
+                        }
+                        Err(err) => {
+                            // Release the claim so a later request may try again
+                            if let Err(e) = redis::cmd("DEL").arg(&claim_key).query_async::<_, ()>(cache).await {
+                                tracing::warn!(
+                                    payment_id = %self.id,
+                                    idempotency_key = %key,
+                                    "Failed to release idempotency claim: {}", e
+                                );
+                            }
+                            return Err(err);
+                        }
+                    }
+                }
+            }
+            
+            /// ISO 4217 currency code amounts are denominated in
+            pub const CURRENCY: &'static str = #currency;
+            
+            /// Decimal places (minor units) allowed by `CURRENCY`
+            pub const CURRENCY_MINOR_UNITS: u32 = #minor_units;
+            
+            /// Smallest accepted amount (contractual floor), from `#[payment(min_amount)]`
+            pub const MIN_AMOUNT: &'static str = #min_amount;
+            
+            /// Largest accepted amount (PSP ceiling), from `#[payment(max_amount)]`
+            pub const MAX_AMOUNT: &'static str = #max_amount;
+            
             /// Validate payment amount
             pub fn validate_amount(&self) -> Result<(), PaymentError> {
                 if self.amount <= rust_decimal::Decimal::ZERO {
                     return Err(PaymentError::InvalidAmount);
                 }
                 
-                // Brazilian minimum transaction amount (PIX)
-                let min_amount = rust_decimal::Decimal::from_str("0.01").unwrap();
+                // Reject sub-minor-unit precision (e.g. 10.555 BRL); trailing zeros are fine
+                let scale = self.amount.normalize().scale();
+                if scale > Self::CURRENCY_MINOR_UNITS {
+                    return Err(PaymentError::InvalidScale {
+                        max: Self::CURRENCY_MINOR_UNITS,
+                        actual: scale,
+                    });
+                }
+                
+                // Minimum transaction amount (PIX floor by default)
+                let min_amount = rust_decimal::Decimal::from_str(Self::MIN_AMOUNT).unwrap();
                 if self.amount < min_amount {
                     return Err(PaymentError::AmountTooLow { 
                         min: min_amount, 
@@ -139,7 +325,7 @@ pub fn derive_payment_entity(input: TokenStream) -> TokenStream {
                 }
                 
                 // Maximum transaction amount check
-                let max_amount = rust_decimal::Decimal::from_str("1000000.00").unwrap();
+                let max_amount = rust_decimal::Decimal::from_str(Self::MAX_AMOUNT).unwrap();
                 if self.amount > max_amount {
                     return Err(PaymentError::AmountTooHigh { 
                         max: max_amount, 
@@ -155,65 +341,784 @@ pub fn derive_payment_entity(input: TokenStream) -> TokenStream {
                 chrono::Utc::now() - self.created_at
             }
             
+            /// Database table holding the payments, from `#[payment(table = "...")]`
+            pub const PAYMENT_TABLE: &'static str = #table;
+            
+            /// Whether the payment has settled one way or another (no longer in flight)
+            pub fn is_terminal(&self) -> bool {
+                matches!(
+                    self.status,
+                    PaymentStatus::Completed | PaymentStatus::Failed | PaymentStatus::Refunded | PaymentStatus::Cancelled
+                )
+            }
+            
+            /// SQL for `find_expired_pending`: `$1` is the product, `$2` the creation cutoff
+            pub const FIND_EXPIRED_PENDING_SQL: &'static str = concat!(
+                "SELECT * FROM ", #table,
+                " WHERE product = $1 AND status = 'pending' AND created_at < $2 ORDER BY created_at"
+            );
+            
+            /// Pending payments created more than `older_than` ago, for sweeper jobs
+            pub fn find_expired_pending(
+                older_than: chrono::Duration,
+                product: &str,
+            ) -> sqlx::query::Query<'static, sqlx::Postgres, sqlx::postgres::PgArguments> {
+                let cutoff = chrono::Utc::now() - older_than;
+                sqlx::query(Self::FIND_EXPIRED_PENDING_SQL)
+                    .bind(product.to_string())
+                    .bind(cutoff)
+            }
+            
             /// Check if payment is expired (for pending payments)
             pub fn is_expired(&self, expiry_minutes: i64) -> bool {
                 self.status == PaymentStatus::Pending && 
                 self.age() > chrono::Duration::minutes(expiry_minutes)
             }
         }
+        
+        #reconciliation_impl
+        #iso20022_impl
+        #risk_impl
+        #comparison_impl
+        #partial_refund_impl
+        #test_default_impl
+        #bench_impl
     };
     
     TokenStream::from(expanded)
 }
 
+/// Generate `refund_partial` for payments carrying a `refunded_amount` field
+fn generate_partial_refund_implementation(input: &DeriveInput) -> proc_macro2::TokenStream {
+    let struct_name = &input.ident;
+    let has_refunded_amount = match &input.data {
+        syn::Data::Struct(syn::DataStruct { fields: syn::Fields::Named(fields), .. }) => fields.named.iter()
+            .any(|f| f.ident.as_ref().is_some_and(|i| i == "refunded_amount")),
+        _ => false,
+    };
+    if !has_refunded_amount {
+        return quote! {};
+    }
+    
+    quote! {
+        impl #struct_name {
+            /// Amount still refundable: `total_amount()` minus what was already refunded
+            pub fn refundable_amount(&self) -> rust_decimal::Decimal {
+                self.total_amount() - self.refunded_amount
+            }
+            
+            /// Refund part of a completed payment, accumulating into `refunded_amount`
+            ///
+            /// The payment stays `Completed` until the refunds add up to `total_amount()`,
+            /// at which point it moves to `Refunded`.
+            pub fn refund_partial(&mut self, amount: rust_decimal::Decimal) -> Result<(), PaymentError> {
+                if self.status != PaymentStatus::Completed {
+                    return Err(PaymentError::InvalidStateTransition {
+                        from: self.status,
+                        to: PaymentStatus::Refunded,
+                    });
+                }
+                if amount <= rust_decimal::Decimal::ZERO {
+                    return Err(PaymentError::InvalidAmount);
+                }
+                let remaining = self.refundable_amount();
+                if amount > remaining {
+                    return Err(PaymentError::RefundExceedsRemaining {
+                        requested: amount,
+                        remaining,
+                    });
+                }
+                
+                self.refunded_amount += amount;
+                self.updated_at = chrono::Utc::now();
+                
+                tracing::info!(
+                    payment_id = %self.id,
+                    amount = %amount,
+                    refunded_amount = %self.refunded_amount,
+                    "Partial refund recorded"
+                );
+                
+                if self.refundable_amount().is_zero() {
+                    self.mark_refunded()?;
+                }
+                
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Generate the `#[cfg(test)]` constructor `test_default` with deterministic field values
+///
+/// Fields the derive relies on get fixed values (amount 100, `Pending`, a constant id);
+/// `Option` fields start as `None` and any other field must implement `Default`.
+fn generate_test_default_implementation(input: &DeriveInput) -> proc_macro2::TokenStream {
+    let struct_name = &input.ident;
+    let syn::Data::Struct(syn::DataStruct { fields: syn::Fields::Named(fields), .. }) = &input.data else {
+        return quote! {};
+    };
+    
+    let values = fields.named.iter().map(|field| {
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let is_option = matches!(ty, syn::Type::Path(path) if path.path.segments.last().is_some_and(|s| s.ident == "Option"));
+        let value = match ident.to_string().as_str() {
+            "id" => quote! { uuid::Uuid::from_u128(0x0000_0000_0000_4000_8000_0000_0000_0001) },
+            "amount" => quote! { rust_decimal::Decimal::from(100) },
+            "tax" | "refunded_amount" => quote! { rust_decimal::Decimal::ZERO },
+            "status" => quote! { PaymentStatus::Pending },
+            "method" => quote! { PaymentMethod::Pix },
+            "created_at" | "updated_at" => quote! { now },
+            _ if is_option => quote! { None },
+            // Spanned on the field type so a missing `Default` impl is reported at the field
+            _ => quote_spanned! {ty.span()=> <#ty as ::core::default::Default>::default() },
+        };
+        quote! { #ident: #value }
+    });
+    
+    quote! {
+        #[cfg(test)]
+        impl #struct_name {
+            /// Deterministic payment for tests: amount 100, `Pending`, fixed id, timestamps at `now()`
+            pub fn test_default() -> Self {
+                let now = chrono::Utc::now();
+                Self {
+                    #(#values,)*
+                }
+            }
+        }
+    }
+}
+
+/// Options parsed from `#[payment(...)]`
+struct PaymentConfig {
+    reconciliation: bool,
+    iso20022: bool,
+    reference_field: String,
+    tolerance: syn::LitStr,
+    /// `(signal, weight)` pairs from `risk_signals`
+    risk_signals: Vec<(String, u32)>,
+    comparable_by: Option<syn::LitStr>,
+    currency: syn::LitStr,
+    minor_units: u32,
+    min_amount: syn::LitStr,
+    max_amount: syn::LitStr,
+    table: String,
+}
+
+/// ISO 4217 minor units for the currencies `#[payment(currency = "...")]` accepts
+const CURRENCY_MINOR_UNITS: [(&str, u32); 10] = [
+    ("BRL", 2), ("USD", 2), ("EUR", 2), ("ARS", 2), ("MXN", 2),
+    ("JPY", 0), ("CLP", 0), ("PYG", 0), ("KRW", 0),
+    ("KWD", 3),
+];
+
+/// Supported risk signals and their default weights
+const RISK_SIGNALS: [(&str, u32); 3] = [("amount", 40), ("velocity", 35), ("new_account", 25)];
+
+/// Largest weight a single risk signal may be given
+const MAX_RISK_SIGNAL_WEIGHT: u32 = 100;
+
+impl PaymentConfig {
+    fn from_attrs(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut config = PaymentConfig {
+            reconciliation: false,
+            iso20022: false,
+            reference_field: "provider_reference".to_string(),
+            tolerance: syn::LitStr::new("0.01", proc_macro2::Span::call_site()),
+            risk_signals: Vec::new(),
+            comparable_by: None,
+            currency: syn::LitStr::new("BRL", proc_macro2::Span::call_site()),
+            minor_units: 2,
+            min_amount: syn::LitStr::new("0.01", proc_macro2::Span::call_site()),
+            max_amount: syn::LitStr::new("1000000.00", proc_macro2::Span::call_site()),
+            table: "payments".to_string(),
+        };
+        
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("payment")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("reconciliation") {
+                    config.reconciliation = true;
+                } else if meta.path.is_ident("iso20022") {
+                    config.iso20022 = true;
+                } else if meta.path.is_ident("table") {
+                    let value: syn::LitStr = meta.value()?.parse()?;
+                    config.table = value.value();
+                } else if meta.path.is_ident("reference_field") {
+                    let value: syn::LitStr = meta.value()?.parse()?;
+                    config.reference_field = value.value();
+                } else if meta.path.is_ident("comparable_by") {
+                    config.comparable_by = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("currency") {
+                    let value: syn::LitStr = meta.value()?.parse()?;
+                    config.minor_units = CURRENCY_MINOR_UNITS.iter()
+                        .find(|(code, _)| *code == value.value())
+                        .map(|(_, units)| *units)
+                        .ok_or_else(|| syn::Error::new_spanned(
+                            &value,
+                            format!("unsupported currency `{}`; expected an ISO 4217 code such as \"BRL\"", value.value()),
+                        ))?;
+                    config.currency = value;
+                } else if meta.path.is_ident("risk_signals") {
+                    let value: syn::LitStr = meta.value()?.parse()?;
+                    config.risk_signals = parse_risk_signals(&value)?;
+                } else if meta.path.is_ident("min_amount") {
+                    config.min_amount = parse_amount_bound(&meta, "min_amount")?;
+                } else if meta.path.is_ident("max_amount") {
+                    config.max_amount = parse_amount_bound(&meta, "max_amount")?;
+                } else if meta.path.is_ident("tolerance") {
+                    let value: syn::LitStr = meta.value()?.parse()?;
+                    match value.value().parse::<rust_decimal::Decimal>() {
+                        Ok(tolerance) if tolerance >= rust_decimal::Decimal::ZERO => {}
+                        _ => return Err(syn::Error::new_spanned(
+                            &value,
+                            format!("tolerance must be a non-negative decimal, got `{}`", value.value()),
+                        )),
+                    }
+                    config.tolerance = value;
+                } else if meta.input.peek(syn::Token![=]) {
+                    let _: syn::Expr = meta.value()?.parse()?;
+                }
+                Ok(())
+            })?;
+        }
+        
+        let min: rust_decimal::Decimal = config.min_amount.value().parse().unwrap_or_default();
+        let max: rust_decimal::Decimal = config.max_amount.value().parse().unwrap_or_default();
+        if min > max {
+            return Err(syn::Error::new_spanned(
+                &config.min_amount,
+                format!("min_amount `{}` is greater than max_amount `{}`", min, max),
+            ));
+        }
+        
+        Ok(config)
+    }
+}
+
+/// Parse a `min_amount`/`max_amount` bound, which must be a positive decimal string
+fn parse_amount_bound(meta: &syn::meta::ParseNestedMeta, option: &str) -> syn::Result<syn::LitStr> {
+    let value: syn::LitStr = meta.value()?.parse()?;
+    match value.value().parse::<rust_decimal::Decimal>() {
+        Ok(amount) if amount > rust_decimal::Decimal::ZERO => Ok(value),
+        _ => Err(syn::Error::new_spanned(
+            &value,
+            format!("{} must be a positive decimal, got `{}`", option, value.value()),
+        )),
+    }
+}
+
+/// Parse `"velocity,amount:50,new_account"` into signals with (default or explicit) weights
+fn parse_risk_signals(value: &syn::LitStr) -> syn::Result<Vec<(String, u32)>> {
+    let mut signals = Vec::new();
+    
+    for entry in value.value().split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (name, weight) = match entry.split_once(':') {
+            Some((name, weight)) => (name.trim(), Some(weight.trim())),
+            None => (entry, None),
+        };
+        
+        let default_weight = RISK_SIGNALS.iter()
+            .find(|(signal, _)| *signal == name)
+            .map(|(_, weight)| *weight)
+            .ok_or_else(|| syn::Error::new_spanned(
+                value,
+                format!("unknown risk signal `{}`; expected one of: amount, velocity, new_account", name),
+            ))?;
+        let weight = match weight {
+            Some(weight) => weight.parse::<u32>().map_err(|_| syn::Error::new_spanned(
+                value,
+                format!("risk signal weight for `{}` must be a non-negative integer, got `{}`", name, weight),
+            ))?,
+            None => default_weight,
+        };
+        // Keeps the weighted sum (and `weighted * 100` in risk_score) far from u32 overflow
+        if weight > MAX_RISK_SIGNAL_WEIGHT {
+            return Err(syn::Error::new_spanned(
+                value,
+                format!("risk signal weight for `{}` must be at most {}, got `{}`", name, MAX_RISK_SIGNAL_WEIGHT, weight),
+            ));
+        }
+        
+        if signals.iter().any(|(existing, _): &(String, u32)| existing == name) {
+            return Err(syn::Error::new_spanned(value, format!("risk signal `{}` listed twice", name)));
+        }
+        signals.push((name.to_string(), weight));
+    }
+    
+    if signals.iter().map(|(_, weight)| weight).sum::<u32>() == 0 {
+        return Err(syn::Error::new_spanned(value, "risk_signals needs at least one signal with a non-zero weight"));
+    }
+    
+    Ok(signals)
+}
+
+/// Generate `risk_score`/`is_high_risk` for `#[payment(risk_signals = "...")]`
+fn generate_risk_implementation(struct_name: &syn::Ident, config: &PaymentConfig) -> proc_macro2::TokenStream {
+    if config.risk_signals.is_empty() {
+        return quote! {};
+    }
+    
+    let risk_context = quote::format_ident!("{}RiskContext", struct_name);
+    let total_weight: u32 = config.risk_signals.iter().map(|(_, weight)| weight).sum();
+    let signal_checks = config.risk_signals.iter().map(|(signal, weight)| {
+        let triggered = match signal.as_str() {
+            "amount" => quote! {
+                ctx.average_amount > rust_decimal::Decimal::ZERO
+                    && self.amount > ctx.average_amount * rust_decimal::Decimal::from(Self::RISK_AMOUNT_MULTIPLIER)
+            },
+            "velocity" => quote! { ctx.recent_payment_count >= Self::RISK_VELOCITY_LIMIT },
+            _ => quote! { ctx.account_age < chrono::Duration::days(Self::RISK_NEW_ACCOUNT_DAYS) },
+        };
+        quote! {
+            if #triggered {
+                weighted += #weight;
+                tracing::debug!(payment_id = %self.id, signal = #signal, weight = #weight, "Risk signal triggered");
+            }
+        }
+    });
+    
+    quote! {
+        impl #struct_name {
+            /// Amount above this multiple of the customer's average is unusual
+            pub const RISK_AMOUNT_MULTIPLIER: u32 = 3;
+            /// Payments within the velocity window at which repeats look suspicious
+            pub const RISK_VELOCITY_LIMIT: u32 = 5;
+            /// Accounts younger than this many days count as new
+            pub const RISK_NEW_ACCOUNT_DAYS: i64 = 7;
+            
+            /// Weighted risk score (0-100) from the configured signals
+            pub fn risk_score(&self, ctx: &#risk_context) -> u8 {
+                let mut weighted: u32 = 0;
+                #(#signal_checks)*
+                (weighted * 100 / #total_weight).min(100) as u8
+            }
+            
+            /// Whether the risk score reaches the given threshold
+            pub fn is_high_risk(&self, ctx: &#risk_context, threshold: u8) -> bool {
+                let score = self.risk_score(ctx);
+                if score >= threshold {
+                    tracing::warn!(
+                        payment_id = %self.id,
+                        amount = %self.amount,
+                        risk_score = score,
+                        threshold = threshold,
+                        "Payment flagged as high risk"
+                    );
+                }
+                score >= threshold
+            }
+        }
+        
+        /// Customer history used for payment risk scoring
+        #[derive(Debug, Clone)]
+        pub struct #risk_context {
+            /// Average amount of the customer's previous payments
+            pub average_amount: rust_decimal::Decimal,
+            /// Payments made by the customer within the velocity window
+            pub recent_payment_count: u32,
+            /// Time since the customer account was created
+            pub account_age: chrono::Duration,
+        }
+    }
+}
+
+/// Generate amount ordering and aggregation for `#[payment(comparable_by = "...")]`
+fn generate_comparison_implementation(input: &DeriveInput, config: &PaymentConfig) -> syn::Result<proc_macro2::TokenStream> {
+    let Some(comparable_by) = config.comparable_by.as_ref() else {
+        return Ok(quote! {});
+    };
+    
+    let struct_name = &input.ident;
+    let declared = match &input.data {
+        syn::Data::Struct(syn::DataStruct { fields: syn::Fields::Named(fields), .. }) => fields.named.iter()
+            .any(|f| f.ident.as_ref().is_some_and(|i| *i == comparable_by.value())),
+        _ => false,
+    };
+    if !declared {
+        return Err(syn::Error::new_spanned(
+            comparable_by,
+            format!("comparable_by field `{}` is not declared on {}", comparable_by.value(), struct_name),
+        ));
+    }
+    let field = syn::Ident::new(&comparable_by.value(), comparable_by.span());
+    
+    Ok(quote! {
+        impl #struct_name {
+            /// Order payments by the configured amount field
+            pub fn cmp_by_amount(&self, other: &Self) -> std::cmp::Ordering {
+                self.#field.cmp(&other.#field)
+            }
+            
+            /// Sum `total_amount()` across payments, saturating instead of overflowing
+            pub fn sum_amounts(payments: &[Self]) -> rust_decimal::Decimal {
+                payments.iter().fold(rust_decimal::Decimal::ZERO, |sum, payment| {
+                    sum.checked_add(payment.total_amount()).unwrap_or_else(|| {
+                        tracing::error!(
+                            payment_id = %payment.id,
+                            "Payment amount sum overflowed - saturating at Decimal::MAX"
+                        );
+                        rust_decimal::Decimal::MAX
+                    })
+                })
+            }
+        }
+    })
+}
+
+/// Party fields `#[payment(iso20022)]` reads for the pain.001 export
+const ISO20022_FIELDS: [&str; 4] = ["debtor_name", "debtor_account", "creditor_name", "creditor_account"];
+
+/// Generate `to_pain001` for `#[payment(iso20022)]`
+fn generate_iso20022_implementation(input: &DeriveInput, config: &PaymentConfig) -> syn::Result<proc_macro2::TokenStream> {
+    if !config.iso20022 {
+        return Ok(quote! {});
+    }
+    
+    let struct_name = &input.ident;
+    for name in ISO20022_FIELDS {
+        let declared = match &input.data {
+            syn::Data::Struct(syn::DataStruct { fields: syn::Fields::Named(fields), .. }) => fields.named.iter()
+                .any(|f| f.ident.as_ref().is_some_and(|i| i == name)),
+            _ => false,
+        };
+        if !declared {
+            return Err(syn::Error::new_spanned(
+                struct_name,
+                format!("#[payment(iso20022)] requires a `{}: String` field", name),
+            ));
+        }
+    }
+    
+    Ok(quote! {
+        impl #struct_name {
+            /// ISO 20022 customer credit transfer initiation (pain.001.001.03) for this payment.
+            /// Message, payment-information and end-to-end ids all derive from `id`.
+            pub fn to_pain001(&self) -> Result<String, PaymentError> {
+                self.validate_amount()?;
+                
+                let id = self.id.simple().to_string();
+                let amount = format!(
+                    "{:.prec$}",
+                    self.amount.round_dp(Self::CURRENCY_MINOR_UNITS),
+                    prec = Self::CURRENCY_MINOR_UNITS as usize
+                );
+                let created = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S");
+                let execution_date = self.created_at.format("%Y-%m-%d");
+                let debtor_name = Self::pain001_text(&self.debtor_name, 70);
+                let debtor_account = Self::pain001_text(&self.debtor_account, 34);
+                let creditor_name = Self::pain001_text(&self.creditor_name, 70);
+                let creditor_account = Self::pain001_text(&self.creditor_account, 34);
+                
+                Ok(format!(
+                    concat!(
+                        r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+                        r#"<Document xmlns="urn:iso:std:iso:20022:tech:xsd:pain.001.001.03"><CstmrCdtTrfInitn>"#,
+                        "<GrpHdr><MsgId>{id}</MsgId><CreDtTm>{created}</CreDtTm><NbOfTxs>1</NbOfTxs><CtrlSum>{amount}</CtrlSum>",
+                        "<InitgPty><Nm>{debtor_name}</Nm></InitgPty></GrpHdr>",
+                        "<PmtInf><PmtInfId>{id}</PmtInfId><PmtMtd>TRF</PmtMtd><NbOfTxs>1</NbOfTxs><CtrlSum>{amount}</CtrlSum>",
+                        "<ReqdExctnDt>{execution_date}</ReqdExctnDt>",
+                        "<Dbtr><Nm>{debtor_name}</Nm></Dbtr>",
+                        "<DbtrAcct><Id><Othr><Id>{debtor_account}</Id></Othr></Id></DbtrAcct>",
+                        "<DbtrAgt><FinInstnId><Othr><Id>NOTPROVIDED</Id></Othr></FinInstnId></DbtrAgt>",
+                        "<CdtTrfTxInf><PmtId><EndToEndId>{id}</EndToEndId></PmtId>",
+                        r#"<Amt><InstdAmt Ccy="{currency}">{amount}</InstdAmt></Amt>"#,
+                        "<Cdtr><Nm>{creditor_name}</Nm></Cdtr>",
+                        "<CdtrAcct><Id><Othr><Id>{creditor_account}</Id></Othr></Id></CdtrAcct>",
+                        "</CdtTrfTxInf></PmtInf></CstmrCdtTrfInitn></Document>",
+                    ),
+                    id = id,
+                    created = created,
+                    amount = amount,
+                    execution_date = execution_date,
+                    debtor_name = debtor_name,
+                    debtor_account = debtor_account,
+                    creditor_name = creditor_name,
+                    creditor_account = creditor_account,
+                    currency = Self::CURRENCY,
+                ))
+            }
+            
+            /// Truncate to the schema's maximum length (in characters) and escape XML specials
+            fn pain001_text(value: &str, max_chars: usize) -> String {
+                let mut escaped = String::with_capacity(value.len());
+                for c in value.chars().take(max_chars) {
+                    match c {
+                        '&' => escaped.push_str("&amp;"),
+                        '<' => escaped.push_str("&lt;"),
+                        '>' => escaped.push_str("&gt;"),
+                        '"' => escaped.push_str("&quot;"),
+                        '\'' => escaped.push_str("&apos;"),
+                        c => escaped.push(c),
+                    }
+                }
+                escaped
+            }
+        }
+    })
+}
+
+/// Generate `reconcile_against` for `#[payment(reconciliation)]`
+fn generate_reconciliation_implementation(input: &DeriveInput, config: &PaymentConfig) -> syn::Result<proc_macro2::TokenStream> {
+    if !config.reconciliation {
+        return Ok(quote! {});
+    }
+    
+    let struct_name = &input.ident;
+    let reference_field = match &input.data {
+        syn::Data::Struct(syn::DataStruct { fields: syn::Fields::Named(fields), .. }) => fields.named.iter()
+            .find(|f| f.ident.as_ref().is_some_and(|i| i == config.reference_field.as_str())),
+        _ => None,
+    }.ok_or_else(|| syn::Error::new_spanned(
+        struct_name,
+        format!("#[payment(reconciliation)] requires a `{}` field holding the provider reference", config.reference_field),
+    ))?;
+    
+    let reference_ident = reference_field.ident.as_ref().unwrap();
+    let is_optional = matches!(
+        &reference_field.ty,
+        syn::Type::Path(type_path) if type_path.path.segments.last().is_some_and(|seg| seg.ident == "Option")
+    );
+    let internal_reference = if is_optional {
+        quote! { self.#reference_ident.as_deref() }
+    } else {
+        quote! { Some(self.#reference_ident.as_str()) }
+    };
+    let tolerance = &config.tolerance;
+    let reconciliation_result = quote::format_ident!("{}ReconciliationResult", struct_name);
+    
+    Ok(quote! {
+        impl #struct_name {
+            /// Maximum absolute amount difference accepted as a match
+            pub fn reconciliation_tolerance() -> rust_decimal::Decimal {
+                <rust_decimal::Decimal as std::str::FromStr>::from_str(#tolerance).unwrap()
+            }
+            
+            /// Match this payment against a provider settlement line
+            pub fn reconcile_against(&self, provider_amount: rust_decimal::Decimal, provider_ref: &str) -> #reconciliation_result {
+                if #internal_reference != Some(provider_ref) {
+                    tracing::warn!(
+                        payment_id = %self.id,
+                        provider_ref = %provider_ref,
+                        "Reconciliation reference mismatch"
+                    );
+                    return #reconciliation_result::ReferenceMismatch;
+                }
+                
+                let diff = provider_amount - self.amount;
+                if diff.abs() > Self::reconciliation_tolerance() {
+                    tracing::warn!(
+                        payment_id = %self.id,
+                        amount = %self.amount,
+                        provider_amount = %provider_amount,
+                        diff = %diff,
+                        "Reconciliation amount mismatch"
+                    );
+                    return #reconciliation_result::AmountMismatch { diff };
+                }
+                
+                #reconciliation_result::Matched
+            }
+        }
+        
+        /// Outcome of matching an internal payment to a provider statement
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+        pub enum #reconciliation_result {
+            Matched,
+            /// Provider amount minus internal amount
+            AmountMismatch { diff: rust_decimal::Decimal },
+            ReferenceMismatch,
+        }
+    })
+}
+
 /// Derive macro for PIX payment handling
 pub fn derive_pix_payment(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let struct_name = &input.ident;
+    let merchant_context_type = quote::format_ident!("{}MerchantContext", struct_name);
+    let pix_charge_type = quote::format_ident!("{}PixCharge", struct_name);
+    let pix_refund_type = quote::format_ident!("{}PixRefund", struct_name);
+    let pix_refund_status_type = quote::format_ident!("{}PixRefundStatus", struct_name);
+    
+    let dynamic = match pix_dynamic(&input.attrs) {
+        Ok(dynamic) => dynamic,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    // Point of Initiation Method: 12 = dynamic (single use), 11 = static (reusable)
+    let point_of_initiation = if dynamic { "010212" } else { "010211" };
+    
+    let has_refunded_amount = match &input.data {
+        syn::Data::Struct(syn::DataStruct { fields: syn::Fields::Named(fields), .. }) => fields.named.iter()
+            .any(|f| f.ident.as_ref().is_some_and(|i| i == "refunded_amount")),
+        _ => false,
+    };
+    let already_refunded = if has_refunded_amount {
+        quote! { self.refunded_amount }
+    } else {
+        quote! { rust_decimal::Decimal::ZERO }
+    };
+    let refund_tracking = if has_refunded_amount {
+        quote! {
+            /// Record a refund built by `generate_pix_refund` against `refunded_amount`
+            pub fn apply_pix_refund(&mut self, refund: &#pix_refund_type) -> Result<#pix_refund_status_type, PaymentError> {
+                if refund.amount > self.amount - self.refunded_amount {
+                    return Err(PaymentError::InvalidRefund {
+                        reason: format!("refund {} exceeds refundable {}", refund.amount, self.amount - self.refunded_amount),
+                    });
+                }
+                self.refunded_amount += refund.amount;
+                
+                tracing::info!(
+                    entity = %stringify!(#struct_name),
+                    refund_id = %refund.refund_id,
+                    amount = %refund.amount,
+                    refunded_amount = %self.refunded_amount,
+                    "PIX refund applied"
+                );
+                
+                if self.refunded_amount >= self.amount {
+                    Ok(#pix_refund_status_type::Refunded)
+                } else {
+                    Ok(#pix_refund_status_type::PartiallyRefunded)
+                }
+            }
+            
+            /// Refund state derived from `refunded_amount`; `None` while nothing was returned
+            pub fn pix_refund_status(&self) -> Option<#pix_refund_status_type> {
+                if self.refunded_amount <= rust_decimal::Decimal::ZERO {
+                    None
+                } else if self.refunded_amount >= self.amount {
+                    Some(#pix_refund_status_type::Refunded)
+                } else {
+                    Some(#pix_refund_status_type::PartiallyRefunded)
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
     
     eprintln!("[pleme-codegen] PixPayment pattern applied to {} - saving ~100 lines", struct_name);
     
+    let bench_impl = crate::bench_patterns::generate_bench_inputs(&input);
+    
     let expanded = quote! {
         impl #struct_name {
+            /// `false` for static "PIX Copia e Cola" payloads, which omit the amount (tag 54)
+            /// so the payer types it and the code can be reused
+            pub const PIX_DYNAMIC: bool = #dynamic;
+            
             /// Generate PIX QR code payload
             pub fn generate_qr_payload(&self) -> String {
-                // PIX payload format according to BCB specification
-                let mut payload = String::new();
+                let ctx = self.merchant_context();
+                let txid = self.end_to_end_id.clone().unwrap_or_else(|| {
+                    uuid::Uuid::new_v4().to_string().replace("-", "")[..25].to_string()
+                });
                 
-                // Payload Format Indicator
-                payload.push_str("000201");
+                Self::build_qr_payload(&ctx, self.amount, &txid)
+            }
+            
+            /// Static merchant portion of the payload for this payment's receiver
+            pub fn merchant_context(&self) -> #merchant_context_type {
+                #merchant_context_type::new(&self.pix_key, &self.merchant_name)
+            }
+            
+            /// Generate payloads for a batch of charges sharing one merchant context
+            pub fn generate_qr_payloads(charges: &[#pix_charge_type], ctx: &#merchant_context_type) -> Vec<Result<String, PaymentError>> {
+                let start = std::time::Instant::now();
                 
-                // Point of Initiation Method (12 = Dynamic)
-                payload.push_str("010212");
+                let payloads: Vec<Result<String, PaymentError>> = charges.iter().map(|charge| {
+                    if charge.amount <= rust_decimal::Decimal::ZERO {
+                        return Err(PaymentError::InvalidAmount);
+                    }
+                    
+                    let txid = match &charge.txid {
+                        Some(txid) if txid.is_empty() || txid.len() > 25 || !txid.chars().all(|c| c.is_ascii_alphanumeric()) => {
+                            return Err(PaymentError::QrCodeGenerationFailed {
+                                reason: format!("Invalid txid '{}': expected 1-25 alphanumeric characters", txid),
+                            });
+                        }
+                        Some(txid) => txid.clone(),
+                        None => uuid::Uuid::new_v4().to_string().replace("-", "")[..25].to_string(),
+                    };
+                    
+                    Ok(Self::build_qr_payload(ctx, charge.amount, &txid))
+                }).collect();
                 
-                // Merchant Account Information
-                payload.push_str("26");
-                let merchant_info = format!("0014BR.GOV.BCB.PIX01{:02}{}", 
-                    self.pix_key.len(), self.pix_key);
-                payload.push_str(&format!("{:02}{}", merchant_info.len(), merchant_info));
+                tracing::info!(
+                    entity = %stringify!(#struct_name),
+                    charges = %charges.len(),
+                    failed = %payloads.iter().filter(|p| p.is_err()).count(),
+                    duration_ms = %start.elapsed().as_millis(),
+                    "PIX payload batch generated"
+                );
+                
+                payloads
+            }
+            
+            /// Build a devolução (refund) request for the PIX identified by `original_e2e_id`,
+            /// validating `amount` against what is still refundable on this payment
+            pub fn generate_pix_refund(&self, original_e2e_id: &str, amount: rust_decimal::Decimal) -> Result<#pix_refund_type, PaymentError> {
+                if amount <= rust_decimal::Decimal::ZERO {
+                    return Err(PaymentError::InvalidAmount);
+                }
+                
+                // End-to-end id: `E` + ISPB (8) + timestamp (12) + sequence (11)
+                if original_e2e_id.len() != 32
+                    || !original_e2e_id.starts_with('E')
+                    || !original_e2e_id.chars().all(|c| c.is_ascii_alphanumeric())
+                {
+                    return Err(PaymentError::InvalidRefund {
+                        reason: format!("Invalid end-to-end id '{}': expected 32 alphanumeric characters starting with 'E'", original_e2e_id),
+                    });
+                }
+                if self.end_to_end_id.as_deref().is_some_and(|id| id != original_e2e_id) {
+                    return Err(PaymentError::InvalidRefund {
+                        reason: format!("end-to-end id '{}' does not belong to this payment", original_e2e_id),
+                    });
+                }
                 
-                // Merchant Category Code (0000 = not informed)
-                payload.push_str("52040000");
+                let refundable = self.amount - #already_refunded;
+                if amount > refundable {
+                    return Err(PaymentError::InvalidRefund {
+                        reason: format!("refund {} exceeds refundable {}", amount, refundable),
+                    });
+                }
                 
-                // Transaction Currency (986 = BRL)
-                payload.push_str("5303986");
+                let status = if amount == refundable {
+                    #pix_refund_status_type::Refunded
+                } else {
+                    #pix_refund_status_type::PartiallyRefunded
+                };
                 
-                // Transaction Amount 
-                let amount_str = format!("{:.2}", self.amount);
-                payload.push_str(&format!("54{:02}{}", amount_str.len(), amount_str));
+                Ok(#pix_refund_type {
+                    original_end_to_end_id: original_e2e_id.to_string(),
+                    refund_id: format!("D{}", uuid::Uuid::new_v4().simple()),
+                    amount,
+                    nature: "ORIGINAL".to_string(),
+                    status,
+                })
+            }
+            
+            #refund_tracking
+            
+            /// Assemble a payload from the merchant context and per-charge fields
+            fn build_qr_payload(ctx: &#merchant_context_type, amount: rust_decimal::Decimal, txid: &str) -> String {
+                // PIX payload format according to BCB specification
+                let mut payload = String::with_capacity(ctx.header.len() + ctx.trailer.len() + 64);
+                payload.push_str(&ctx.header);
                 
-                // Country Code (BR)
-                payload.push_str("5802BR");
+                // Transaction Amount, only in dynamic payloads
+                if Self::PIX_DYNAMIC {
+                    let amount_str = format!("{:.2}", amount);
+                    payload.push_str(&format!("54{:02}{}", amount_str.len(), amount_str));
+                }
                 
-                // Merchant Name
-                let name_bytes = self.merchant_name.as_bytes();
-                let name_len = name_bytes.len().min(25); // Max 25 chars
-                payload.push_str(&format!("59{:02}{}", name_len, &self.merchant_name[..name_len]));
+                payload.push_str(&ctx.trailer);
                 
                 // Additional Data Field Template
-                let txid = self.end_to_end_id.clone().unwrap_or_else(|| {
-                    uuid::Uuid::new_v4().to_string().replace("-", "")[..25].to_string()
-                });
                 let additional = format!("05{:02}{}", txid.len(), txid);
                 payload.push_str(&format!("62{:02}{}", additional.len(), additional));
                 
@@ -282,20 +1187,26 @@ pub fn derive_pix_payment(input: TokenStream) -> TokenStream {
                         }
                     }
                     PixKeyType::Email => {
-                        if !self.pix_key.contains('@') || self.pix_key.len() > 77 {
+                        if !Self::is_valid_pix_email(&self.pix_key) {
                             return Err(PaymentError::InvalidPixKey { reason: "Invalid email".to_string() });
                         }
                     }
                     PixKeyType::Phone => {
-                        let digits: String = self.pix_key.chars()
-                            .filter(|c| c.is_ascii_digit()).collect();
-                        if digits.len() != 11 {
-                            return Err(PaymentError::InvalidPixKey { reason: "Invalid phone".to_string() });
+                        // BCB: E.164 with the Brazilian country code, +55 DDD number (e.g. +5511987654321)
+                        let valid = self.pix_key.strip_prefix("+55").is_some_and(|national| {
+                            national.len() == 11
+                                && national.chars().all(|c| c.is_ascii_digit())
+                                && !national.starts_with('0')
+                        });
+                        if !valid {
+                            return Err(PaymentError::InvalidPixKey { reason: "Invalid phone: expected +55 E.164 format".to_string() });
                         }
                     }
                     PixKeyType::Random => {
-                        // EVP key validation (UUID v4)
-                        if self.pix_key.len() != 32 {
+                        // EVP key: a UUID, canonically 36 chars with hyphens (32 without is also accepted)
+                        let valid = matches!(self.pix_key.len(), 32 | 36)
+                            && uuid::Uuid::try_parse(&self.pix_key).is_ok();
+                        if !valid {
                             return Err(PaymentError::InvalidPixKey { reason: "Invalid EVP key".to_string() });
                         }
                     }
@@ -303,6 +1214,31 @@ pub fn derive_pix_payment(input: TokenStream) -> TokenStream {
                 Ok(())
             }
             
+            /// Email key check per BCB: at most 77 characters, one `@`, dotted domain
+            fn is_valid_pix_email(email: &str) -> bool {
+                if email.len() > 77 || email.chars().any(|c| c.is_whitespace() || c.is_control()) {
+                    return false;
+                }
+                let Some((local, domain)) = email.split_once('@') else {
+                    return false;
+                };
+                let local_ok = !local.is_empty()
+                    && local.len() <= 64
+                    && !local.starts_with('.')
+                    && !local.ends_with('.')
+                    && !local.contains("..")
+                    && local.chars().all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+/=?^_`{|}~.-".contains(c));
+                let labels: Vec<&str> = domain.split('.').collect();
+                let domain_ok = labels.len() >= 2
+                    && labels.iter().all(|label| {
+                        !label.is_empty()
+                            && !label.starts_with('-')
+                            && !label.ends_with('-')
+                            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+                    });
+                local_ok && domain_ok
+            }
+            
             /// Check if PIX payment is expired
             pub fn is_expired(&self) -> bool {
                 chrono::Utc::now() > self.expires_at
@@ -388,7 +1324,197 @@ pub fn derive_pix_payment(input: TokenStream) -> TokenStream {
                 digits[13] == digit2
             }
         }
+        
+        /// Merchant portion of a PIX payload, computed once and reused across charges
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct #merchant_context_type {
+            /// Merchant Account Information TLV (tag 26)
+            pub merchant_account_tlv: String,
+            /// Merchant Name TLV (tag 59)
+            pub merchant_name_tlv: String,
+            header: String,
+            trailer: String,
+        }
+        
+        impl #merchant_context_type {
+            /// Build the static TLVs for a receiver
+            pub fn new(pix_key: &str, merchant_name: &str) -> Self {
+                // Merchant Account Information
+                let merchant_info = format!("0014BR.GOV.BCB.PIX01{:02}{}", pix_key.len(), pix_key);
+                let merchant_account_tlv = format!("26{:02}{}", merchant_info.len(), merchant_info);
+                
+                // Merchant Name
+                // Max 25 bytes (EMV lengths count bytes), cut on a char boundary
+                let mut name_len = merchant_name.len().min(25);
+                while !merchant_name.is_char_boundary(name_len) {
+                    name_len -= 1;
+                }
+                let merchant_name_tlv = format!("59{:02}{}", name_len, &merchant_name[..name_len]);
+                
+                // Payload Format Indicator, Point of Initiation Method (see `PIX_DYNAMIC`),
+                // merchant account, Merchant Category Code (0000 = not informed), Currency (986 = BRL)
+                let header = format!("000201{}{}520400005303986", #point_of_initiation, merchant_account_tlv);
+                
+                // Country Code (BR) and merchant name
+                let trailer = format!("5802BR{}", merchant_name_tlv);
+                
+                Self { merchant_account_tlv, merchant_name_tlv, header, trailer }
+            }
+        }
+        
+        /// Payment state after a devolução
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum #pix_refund_status_type {
+            PartiallyRefunded,
+            Refunded,
+        }
+        
+        /// Devolução request for a received PIX
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct #pix_refund_type {
+            pub original_end_to_end_id: String,
+            /// Refund id chosen by the receiver (up to 35 alphanumeric characters)
+            pub refund_id: String,
+            pub amount: rust_decimal::Decimal,
+            /// `ORIGINAL` for refunds of the original transaction amount
+            pub nature: String,
+            /// State the payment reaches once this refund settles
+            pub status: #pix_refund_status_type,
+        }
+        
+        impl #pix_refund_type {
+            /// `PUT` path on the PSP's PIX API
+            pub fn request_path(&self) -> String {
+                format!("/pix/{}/devolucao/{}", self.original_end_to_end_id, self.refund_id)
+            }
+            
+            /// JSON body for the devolução request
+            pub fn to_request_body(&self) -> serde_json::Value {
+                serde_json::json!({
+                    "valor": format!("{:.2}", self.amount),
+                    "natureza": self.nature,
+                })
+            }
+        }
+        
+        /// Per-charge fields of a PIX payload
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct #pix_charge_type {
+            pub amount: rust_decimal::Decimal,
+            /// Transaction id (tag 62-05); generated when absent
+            pub txid: Option<String>,
+        }
+        
+        #bench_impl
     };
     
     TokenStream::from(expanded)
-}
\ No newline at end of file
+}
+
+/// Read `#[pix(dynamic = ...)]`, defaulting to dynamic payloads
+fn pix_dynamic(attrs: &[syn::Attribute]) -> syn::Result<bool> {
+    let mut dynamic = true;
+    
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("pix")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("dynamic") {
+                let value: syn::LitBool = meta.value()?.parse()?;
+                dynamic = value.value;
+            } else if meta.input.peek(syn::Token![=]) {
+                let _: syn::Expr = meta.value()?.parse()?;
+            }
+            Ok(())
+        })?;
+    }
+    
+    Ok(dynamic)
+}
+
+/// Derive macro for payment method enums (Pix, Boleto, cards, TED)
+pub fn derive_payment_methods(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let enum_name = &input.ident;
+    
+    eprintln!("[pleme-codegen] PaymentMethods pattern applied to {} - saving ~60 lines", enum_name);
+    
+    let variants = match &input.data {
+        syn::Data::Enum(data) => &data.variants,
+        _ => {
+            return syn::Error::new_spanned(enum_name, "PaymentMethods can only be derived for enums")
+                .to_compile_error()
+                .into();
+        }
+    };
+    
+    let mut idents = Vec::new();
+    let mut names = Vec::new();
+    let mut instant = Vec::new();
+    
+    for variant in variants {
+        if !matches!(variant.fields, syn::Fields::Unit) {
+            return syn::Error::new_spanned(variant, "PaymentMethods variants must not carry data")
+                .to_compile_error()
+                .into();
+        }
+        
+        // Pix settles in seconds; other methods can opt in with #[payment_method(instant)]
+        let mut is_instant = variant.ident == "Pix";
+        for attr in variant.attrs.iter().filter(|a| a.path().is_ident("payment_method")) {
+            if let Err(err) = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("instant") {
+                    is_instant = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `instant`"))
+                }
+            }) {
+                return err.to_compile_error().into();
+            }
+        }
+        
+        idents.push(&variant.ident);
+        names.push(variant.ident.to_string().to_snake_case());
+        instant.push(is_instant);
+    }
+    
+    let expanded = quote! {
+        impl #enum_name {
+            /// Every supported payment method
+            pub const ALL: &'static [#enum_name] = &[#(#enum_name::#idents),*];
+            
+            /// Stable snake_case identifier used in APIs and the database
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    #(#enum_name::#idents => #names,)*
+                }
+            }
+            
+            /// Whether funds settle immediately (Pix) rather than on clearing (Boleto, cards, TED)
+            pub fn is_instant(&self) -> bool {
+                match self {
+                    #(#enum_name::#idents => #instant,)*
+                }
+            }
+        }
+        
+        impl std::fmt::Display for #enum_name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+        
+        impl std::str::FromStr for #enum_name {
+            type Err = String;
+            
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let normalized = s.trim().to_lowercase();
+                Self::ALL.iter()
+                    .find(|method| method.as_str() == normalized)
+                    .cloned()
+                    .ok_or_else(|| format!("Invalid {}: {}", stringify!(#enum_name), s))
+            }
+        }
+    };
+    
+    TokenStream::from(expanded)
+}
@@ -10,338 +10,467 @@
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
-use syn::{parse_macro_input, Data, DeriveInput, Fields};
+use syn::{parse_macro_input, DeriveInput};
 
-use crate::utils::*;
+use crate::domain_patterns::DomainConfig;
+
+/// Default seconds an entity stays cached when `cache_ttl` is not configured
+const DEFAULT_CACHE_TTL_SECS: u64 = 300;
+
+/// Options parsed from `#[repository(...)]`
+struct RepositoryConfig {
+    cache_ttl: u64,
+}
+
+impl RepositoryConfig {
+    /// Parse `#[repository(...)]`, skipping keys consumed by the other repository derives
+    fn from_attrs(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut config = RepositoryConfig { cache_ttl: DEFAULT_CACHE_TTL_SECS };
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("repository")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("cache_ttl") {
+                    let value: syn::LitInt = meta.value()?.parse()?;
+                    let ttl: u64 = value.base10_parse()?;
+                    if ttl == 0 {
+                        return Err(syn::Error::new_spanned(&value, "cache_ttl must be greater than zero"));
+                    }
+                    config.cache_ttl = ttl;
+                } else if meta.input.peek(syn::Token![=]) {
+                    let _: syn::Expr = meta.value()?.parse()?;
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(config)
+    }
+}
 
 /// Implementation of the Repository derive macro
 pub fn derive_repository(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
+    match expand_repository(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand_repository(input: &DeriveInput) -> syn::Result<TokenStream2> {
     let struct_name = &input.ident;
-    let struct_name_str = struct_name.to_string();
-    
-    // Extract attributes
-    let cache_ttl = get_attribute_int(&input.attrs, "repository", "cache_ttl").unwrap_or(300);
-    let soft_delete = has_attribute_flag(&input.attrs, "repository", "soft_delete");
-    
-    // Generate repository trait
-    let repository_trait = generate_repository_trait(struct_name);
-    
-    // Generate repository implementation
-    let repository_impl = generate_repository_implementation(struct_name, cache_ttl, soft_delete);
-    
-    // Generate cache service integration
-    let cache_integration = generate_cache_integration(struct_name, cache_ttl);
-    
-    let expanded = quote! {
+    let fields = match &input.data {
+        syn::Data::Struct(syn::DataStruct { fields: syn::Fields::Named(fields), .. }) => &fields.named,
+        _ => return Err(syn::Error::new_spanned(struct_name, "Repository can only be used with structs with named fields")),
+    };
+
+    eprintln!("[pleme-codegen] Repository pattern applied to {}", struct_name);
+
+    let config = RepositoryConfig::from_attrs(&input.attrs)?;
+    // Tenant column and soft deletion follow the entity's DomainModel options
+    let domain = DomainConfig::from_attrs(&input.attrs)?;
+    let field_names: Vec<String> = fields.iter().map(|f| f.ident.as_ref().unwrap().to_string()).collect();
+    for required in ["id", domain.tenant_field.as_str()] {
+        if !field_names.iter().any(|name| name == required) {
+            return Err(syn::Error::new_spanned(
+                struct_name,
+                format!("Repository requires a `{}` field", required),
+            ));
+        }
+    }
+    if domain.soft_delete {
+        for required in ["deleted_at", "created_at"] {
+            if !field_names.iter().any(|name| name == required) {
+                return Err(syn::Error::new_spanned(
+                    struct_name,
+                    format!("Repository with #[domain(soft_delete)] requires a `{}` field", required),
+                ));
+            }
+        }
+    }
+
+    let repository_types = generate_repository_types(struct_name);
+    let repository_trait = generate_repository_trait(struct_name, &domain);
+    let repository_impl = generate_repository_implementation(struct_name, fields, &config, &domain);
+    let cache_integration = generate_cache_integration(struct_name);
+
+    Ok(quote! {
+        #repository_types
         #repository_trait
         #repository_impl
         #cache_integration
-    };
-    
-    TokenStream::from(expanded)
+    })
+}
+
+/// Generate the error and result types shared by the repository trait and implementation
+fn generate_repository_types(struct_name: &syn::Ident) -> TokenStream2 {
+    let error_type = quote::format_ident!("{}RepositoryError", struct_name);
+    let result_type = quote::format_ident!("{}RepositoryResult", struct_name);
+
+    quote! {
+        /// Errors from the generated repository
+        #[derive(Debug)]
+        pub enum #error_type {
+            Database(sqlx::Error),
+            Cache(String),
+            /// `find_by_field` was given a name that is not a column of the entity
+            InvalidField(String),
+        }
+
+        impl std::fmt::Display for #error_type {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    Self::Database(e) => write!(f, "database error: {}", e),
+                    Self::Cache(reason) => write!(f, "cache error: {}", reason),
+                    Self::InvalidField(field) => write!(f, "unknown field `{}`", field),
+                }
+            }
+        }
+
+        impl std::error::Error for #error_type {}
+
+        impl From<sqlx::Error> for #error_type {
+            fn from(e: sqlx::Error) -> Self {
+                Self::Database(e)
+            }
+        }
+
+        /// Result type alias for repository operations
+        pub type #result_type<T> = Result<T, #error_type>;
+    }
 }
 
 /// Generate repository trait definition
-fn generate_repository_trait(struct_name: &syn::Ident) -> TokenStream2 {
-    let trait_name = syn::Ident::new(&format!("{}RepositoryTrait", struct_name), proc_macro2::Span::call_site());
-    let result_type = syn::Ident::new(&format!("{}Result", struct_name), proc_macro2::Span::call_site());
-    
+fn generate_repository_trait(struct_name: &syn::Ident, domain: &DomainConfig) -> TokenStream2 {
+    let trait_name = quote::format_ident!("{}RepositoryTrait", struct_name);
+    let result_type = quote::format_ident!("{}RepositoryResult", struct_name);
+
+    let soft_delete_methods = if domain.soft_delete {
+        quote! {
+            /// Set `deleted_at` on a live entity; false if it was missing or already deleted
+            async fn soft_delete(&self, id: uuid::Uuid, product: &str) -> #result_type<bool>;
+
+            /// Clear `deleted_at` on a soft-deleted entity; false if it was missing or not deleted
+            async fn restore(&self, id: uuid::Uuid, product: &str) -> #result_type<bool>;
+        }
+    } else {
+        quote! {}
+    };
+
     quote! {
-        /// Repository trait for #struct_name
+        /// Repository operations for the entity
         #[async_trait::async_trait]
         pub trait #trait_name: Send + Sync {
             /// Create a new entity
             async fn create(&self, entity: &#struct_name) -> #result_type<#struct_name>;
-            
+
             /// Find entity by ID and product
             async fn find_by_id(&self, id: uuid::Uuid, product: &str) -> #result_type<Option<#struct_name>>;
-            
+
             /// Update an existing entity
             async fn update(&self, entity: &#struct_name) -> #result_type<#struct_name>;
-            
+
             /// Delete entity by ID and product
             async fn delete(&self, id: uuid::Uuid, product: &str) -> #result_type<bool>;
-            
-            /// List entities for a product with pagination
-            async fn list_by_product(&self, product: &str, limit: i64, offset: i64) -> #result_type<Vec<#struct_name>>;
-            
+
+            /// List entities for a product with pagination; soft-deleted rows are skipped
+            /// unless `include_deleted`, which has no effect without `#[domain(soft_delete)]`
+            async fn list_by_product(&self, product: &str, limit: i64, offset: i64, include_deleted: bool) -> #result_type<Vec<#struct_name>>;
+
             /// Count entities for a product
             async fn count_by_product(&self, product: &str) -> #result_type<i64>;
-            
+
             /// Find entities by field value
             async fn find_by_field(&self, field: &str, value: &str, product: &str) -> #result_type<Vec<#struct_name>>;
-            
+
             /// Check if entity exists
             async fn exists(&self, id: uuid::Uuid, product: &str) -> #result_type<bool>;
-            
+
             /// Bulk create entities
             async fn bulk_create(&self, entities: &[#struct_name]) -> #result_type<Vec<#struct_name>>;
-            
+
             /// Clear cache for product
             async fn clear_cache(&self, product: &str) -> #result_type<()>;
+
+            #soft_delete_methods
         }
     }
 }
 
 /// Generate repository implementation
 fn generate_repository_implementation(
-    struct_name: &syn::Ident, 
-    cache_ttl: u64, 
-    soft_delete: bool
+    struct_name: &syn::Ident,
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+    config: &RepositoryConfig,
+    domain: &DomainConfig,
 ) -> TokenStream2 {
-    let repository_name = syn::Ident::new(&format!("{}Repository", struct_name), proc_macro2::Span::call_site());
-    let trait_name = syn::Ident::new(&format!("{}RepositoryTrait", struct_name), proc_macro2::Span::call_site());
-    let result_type = syn::Ident::new(&format!("{}Result", struct_name), proc_macro2::Span::call_site());
-    let error_type = syn::Ident::new(&format!("{}Error", struct_name), proc_macro2::Span::call_site());
-    
-    let delete_impl = if soft_delete {
+    let repository_name = quote::format_ident!("{}Repository", struct_name);
+    let trait_name = quote::format_ident!("{}RepositoryTrait", struct_name);
+    let result_type = quote::format_ident!("{}RepositoryResult", struct_name);
+    let error_type = quote::format_ident!("{}RepositoryError", struct_name);
+    let cache_store = quote::format_ident!("{}CacheStore", struct_name);
+    let cache_ttl = config.cache_ttl;
+    let cache_prefix = heck::ToSnakeCase::to_snake_case(struct_name.to_string().as_str());
+    let tenant_column = domain.tenant_field.as_str();
+    let tenant_ident = syn::Ident::new(tenant_column, proc_macro2::Span::call_site());
+
+    let idents: Vec<&syn::Ident> = fields.iter().map(|f| f.ident.as_ref().unwrap()).collect();
+    let has_field = |name: &str| idents.iter().any(|ident| *ident == name);
+
+    // Every column is inserted; updates leave the key and creation columns alone
+    let insert_binds = idents.iter().map(|ident| quote! { .bind(&entity.#ident) });
+    let update_idents: Vec<&syn::Ident> = idents.iter().copied()
+        .filter(|ident| !matches!(ident.to_string().as_str(), "id" | "created_at" | "created_by") && *ident != tenant_column)
+        .collect();
+    let update_columns: Vec<String> = update_idents.iter().map(|ident| ident.to_string()).collect();
+    let update_binds = update_idents.iter().map(|ident| {
+        if *ident == "updated_at" {
+            quote! { .bind(chrono::Utc::now()) }
+        } else {
+            quote! { .bind(&entity.#ident) }
+        }
+    });
+    let order_column = if has_field("created_at") { "created_at DESC" } else { "id" };
+
+    let list_by_product = if domain.soft_delete {
         quote! {
-            let query = format!("UPDATE {} SET deleted_at = $1, updated_at = $2 WHERE id = $3 AND product = $4", 
-                #struct_name::TABLE_NAME);
-            let now = chrono::Utc::now();
-            
-            let result = sqlx::query(&query)
-                .bind(now)
-                .bind(now)
-                .bind(id)
-                .bind(product)
-                .execute(&self.pool)
-                .await
-                .map_err(#error_type::Database)?;
-                
-            let deleted = result.rows_affected() > 0;
-            if deleted {
-                // Clear from cache
-                let cache_key = #struct_name::cache_key_for(product, id);
-                if let Err(e) = self.cache.delete(&cache_key).await {
-                    tracing::warn!("Failed to delete from cache: {}", e);
-                }
+            async fn list_by_product(&self, product: &str, limit: i64, offset: i64, include_deleted: bool) -> #result_type<Vec<#struct_name>> {
+                let query = #struct_name::list_by_product_query(include_deleted);
+
+                let results = sqlx::query_as::<_, #struct_name>(&query)
+                    .bind(product)
+                    .bind(limit)
+                    .bind(offset)
+                    .fetch_all(&self.pool)
+                    .await?;
+
+                Ok(results)
             }
-            
-            Ok(deleted)
         }
     } else {
         quote! {
-            let query = format!("DELETE FROM {} WHERE id = $1 AND product = $2", 
-                #struct_name::TABLE_NAME);
-            
-            let result = sqlx::query(&query)
-                .bind(id)
-                .bind(product)
-                .execute(&self.pool)
-                .await
-                .map_err(#error_type::Database)?;
-                
-            let deleted = result.rows_affected() > 0;
-            if deleted {
-                // Clear from cache
-                let cache_key = #struct_name::cache_key_for(product, id);
-                if let Err(e) = self.cache.delete(&cache_key).await {
-                    tracing::warn!("Failed to delete from cache: {}", e);
+            async fn list_by_product(&self, product: &str, limit: i64, offset: i64, _include_deleted: bool) -> #result_type<Vec<#struct_name>> {
+                let query = format!("SELECT * FROM {} WHERE {} = $1 ORDER BY {} LIMIT $2 OFFSET $3",
+                    #struct_name::TABLE_NAME, #tenant_column, #order_column);
+
+                let results = sqlx::query_as::<_, #struct_name>(&query)
+                    .bind(product)
+                    .bind(limit)
+                    .bind(offset)
+                    .fetch_all(&self.pool)
+                    .await?;
+
+                Ok(results)
+            }
+        }
+    };
+    // The cached copy carries the old deleted_at, so both directions evict it
+    let soft_delete_methods = if domain.soft_delete {
+        quote! {
+            async fn soft_delete(&self, id: uuid::Uuid, product: &str) -> #result_type<bool> {
+                let deleted = #struct_name::soft_delete(&self.pool, id, product).await?;
+                if deleted {
+                    self.evict(product, id).await;
                 }
+                Ok(deleted)
+            }
+
+            async fn restore(&self, id: uuid::Uuid, product: &str) -> #result_type<bool> {
+                let restored = #struct_name::restore(&self.pool, id, product).await?;
+                if restored {
+                    self.evict(product, id).await;
+                }
+                Ok(restored)
             }
-            
-            Ok(deleted)
         }
+    } else {
+        quote! {}
     };
-    
+
     quote! {
-        /// Repository implementation for #struct_name
+        /// PostgreSQL repository for the entity, caching reads through `#cache_store`
         pub struct #repository_name {
             pool: sqlx::PgPool,
-            cache: std::sync::Arc<dyn CacheServiceTrait>,
+            cache: std::sync::Arc<dyn #cache_store>,
         }
-        
+
         impl #repository_name {
+            /// Seconds an entity stays cached, from `#[repository(cache_ttl = N)]`
+            pub const CACHE_TTL_SECS: u64 = #cache_ttl;
+
             /// Create a new repository instance
             pub fn new(
                 pool: sqlx::PgPool,
-                cache: std::sync::Arc<dyn CacheServiceTrait>,
+                cache: std::sync::Arc<dyn #cache_store>,
             ) -> Self {
                 Self { pool, cache }
             }
+
+            /// Cache key of one entity, scoped to its product
+            pub fn cache_key(product: &str, id: uuid::Uuid) -> String {
+                format!("{}:{}:{}", product, #cache_prefix, id)
+            }
+
+            /// Pattern matching every cached entity of a product
+            pub fn cache_pattern(product: &str) -> String {
+                format!("{}:{}:*", product, #cache_prefix)
+            }
+
+            /// Cache `entity`; cache failures are logged, never surfaced
+            async fn cache_entity(&self, entity: &#struct_name) {
+                let cache_key = Self::cache_key(&entity.#tenant_ident, entity.id);
+                let stored = match serde_json::to_string(entity) {
+                    Ok(json) => self.cache.set(&cache_key, json, Self::CACHE_TTL_SECS).await,
+                    Err(e) => Err(e.into()),
+                };
+                if let Err(e) = stored {
+                    tracing::warn!(cache_key = %cache_key, "Failed to cache entity: {}", e);
+                }
+            }
+
+            async fn evict(&self, product: &str, id: uuid::Uuid) {
+                let cache_key = Self::cache_key(product, id);
+                if let Err(e) = self.cache.delete(&cache_key).await {
+                    tracing::warn!(cache_key = %cache_key, "Failed to delete from cache: {}", e);
+                }
+            }
         }
-        
+
         #[async_trait::async_trait]
         impl #trait_name for #repository_name {
             async fn create(&self, entity: &#struct_name) -> #result_type<#struct_name> {
-                // Insert into database
-                let query = format!("INSERT INTO {} (id, product, created_at, updated_at, {}) VALUES ($1, $2, $3, $4, {})",
-                    #struct_name::TABLE_NAME, "/* field names */", "/* field placeholders */");
-                
+                let query = format!("{} RETURNING *", #struct_name::insert_query());
                 let result = sqlx::query_as::<_, #struct_name>(&query)
-                    .bind(&entity.id)
-                    .bind(&entity.product)
-                    .bind(&entity.created_at)
-                    .bind(&entity.updated_at)
-                    // Add other field bindings here
+                    #(#insert_binds)*
                     .fetch_one(&self.pool)
-                    .await
-                    .map_err(#error_type::Database)?;
-                
-                // Cache the result
-                let cache_key = result.cache_key();
-                if let Err(e) = self.cache.set(&cache_key, &result, #cache_ttl).await {
-                    tracing::warn!("Failed to cache entity: {}", e);
-                }
-                
+                    .await?;
+
+                self.cache_entity(&result).await;
                 Ok(result)
             }
-            
+
             async fn find_by_id(&self, id: uuid::Uuid, product: &str) -> #result_type<Option<#struct_name>> {
-                // Try cache first
-                let cache_key = #struct_name::cache_key_for(product, id);
-                if let Ok(Some(cached)) = self.cache.get::<#struct_name>(&cache_key).await {
-                    return Ok(Some(cached));
+                // Try cache first; entries that no longer deserialize count as misses
+                let cache_key = Self::cache_key(product, id);
+                if let Ok(Some(cached)) = self.cache.get(&cache_key).await {
+                    if let Ok(entity) = serde_json::from_str::<#struct_name>(&cached) {
+                        return Ok(Some(entity));
+                    }
                 }
-                
-                // Query database
+
                 let query = #struct_name::select_by_id_query();
-                let result = sqlx::query_as::<_, #struct_name>(&query)
-                    .bind(id)
-                    .bind(product)
+                let result = #struct_name::bind_key_as(sqlx::query_as::<_, #struct_name>(&query), id, product)
                     .fetch_optional(&self.pool)
-                    .await
-                    .map_err(#error_type::Database)?;
-                
-                // Cache if found
-                if let Some(ref entity) = result {
-                    if let Err(e) = self.cache.set(&cache_key, entity, #cache_ttl).await {
-                        tracing::warn!("Failed to cache entity: {}", e);
-                    }
+                    .await?;
+
+                if let Some(entity) = &result {
+                    self.cache_entity(entity).await;
                 }
-                
                 Ok(result)
             }
-            
+
             async fn update(&self, entity: &#struct_name) -> #result_type<#struct_name> {
-                let query = format!("UPDATE {} SET updated_at = $1 WHERE id = $2 AND product = $3",
-                    #struct_name::TABLE_NAME);
-                
-                let mut updated_entity = entity.clone();
-                updated_entity.touch();
-                
-                let result = sqlx::query_as::<_, #struct_name>(&query)
-                    .bind(&updated_entity.updated_at)
-                    .bind(&updated_entity.id)
-                    .bind(&updated_entity.product)
-                    .fetch_one(&self.pool)
-                    .await
-                    .map_err(#error_type::Database)?;
-                
-                // Update cache
-                let cache_key = result.cache_key();
-                if let Err(e) = self.cache.set(&cache_key, &result, #cache_ttl).await {
-                    tracing::warn!("Failed to update cache: {}", e);
-                }
-                
+                let query = format!("{} RETURNING *", #struct_name::update_query(&[#(#update_columns),*]));
+                let result = #struct_name::bind_key_as(
+                    sqlx::query_as::<_, #struct_name>(&query) #(#update_binds)*,
+                    entity.id,
+                    &entity.#tenant_ident,
+                )
+                .fetch_one(&self.pool)
+                .await?;
+
+                self.cache_entity(&result).await;
                 Ok(result)
             }
-            
+
             async fn delete(&self, id: uuid::Uuid, product: &str) -> #result_type<bool> {
-                #delete_impl
-            }
-            
-            async fn list_by_product(&self, product: &str, limit: i64, offset: i64) -> #result_type<Vec<#struct_name>> {
-                let query = format!("SELECT * FROM {} WHERE product = $1 ORDER BY created_at DESC LIMIT $2 OFFSET $3",
-                    #struct_name::TABLE_NAME);
-                
-                let results = sqlx::query_as::<_, #struct_name>(&query)
-                    .bind(product)
-                    .bind(limit)
-                    .bind(offset)
-                    .fetch_all(&self.pool)
-                    .await
-                    .map_err(#error_type::Database)?;
-                
-                Ok(results)
+                let query = #struct_name::delete_query();
+                let result = #struct_name::bind_key(sqlx::query(&query), id, product)
+                    .execute(&self.pool)
+                    .await?;
+
+                let deleted = result.rows_affected() > 0;
+                if deleted {
+                    self.evict(product, id).await;
+                }
+                Ok(deleted)
             }
-            
+
+            #list_by_product
+
             async fn count_by_product(&self, product: &str) -> #result_type<i64> {
                 let query = #struct_name::count_by_product_query();
-                
+
                 let result: (i64,) = sqlx::query_as(&query)
                     .bind(product)
                     .fetch_one(&self.pool)
-                    .await
-                    .map_err(#error_type::Database)?;
-                
+                    .await?;
+
                 Ok(result.0)
             }
-            
+
             async fn find_by_field(&self, field: &str, value: &str, product: &str) -> #result_type<Vec<#struct_name>> {
-                let query = format!("SELECT * FROM {} WHERE {} = $1 AND product = $2",
-                    #struct_name::TABLE_NAME, field);
-                
+                // The column name is interpolated, so only the entity's own columns are accepted
+                if !#struct_name::columns().contains(&field) {
+                    return Err(#error_type::InvalidField(field.to_string()));
+                }
+                let query = format!("SELECT * FROM {} WHERE {}::text = $1 AND {} = $2",
+                    #struct_name::TABLE_NAME, field, #tenant_column);
+
                 let results = sqlx::query_as::<_, #struct_name>(&query)
                     .bind(value)
                     .bind(product)
                     .fetch_all(&self.pool)
-                    .await
-                    .map_err(#error_type::Database)?;
-                
+                    .await?;
+
                 Ok(results)
             }
-            
+
             async fn exists(&self, id: uuid::Uuid, product: &str) -> #result_type<bool> {
-                let query = format!("SELECT EXISTS(SELECT 1 FROM {} WHERE id = $1 AND product = $2)",
-                    #struct_name::TABLE_NAME);
-                
-                let result: (bool,) = sqlx::query_as(&query)
-                    .bind(id)
-                    .bind(product)
+                let query = format!("SELECT EXISTS({})", #struct_name::select_by_id_query());
+
+                let result: (bool,) = #struct_name::bind_key_as(sqlx::query_as(&query), id, product)
                     .fetch_one(&self.pool)
-                    .await
-                    .map_err(#error_type::Database)?;
-                
+                    .await?;
+
                 Ok(result.0)
             }
-            
+
             async fn bulk_create(&self, entities: &[#struct_name]) -> #result_type<Vec<#struct_name>> {
-                if entities.is_empty() {
-                    return Ok(vec![]);
-                }
-                
-                // Use PostgreSQL batch insert
-                // This is a simplified version - real implementation would be more complex
-                let mut results = Vec::new();
+                let mut results = Vec::with_capacity(entities.len());
                 for entity in entities {
-                    let result = self.create(entity).await?;
-                    results.push(result);
+                    results.push(self.create(entity).await?);
                 }
-                
                 Ok(results)
             }
-            
+
             async fn clear_cache(&self, product: &str) -> #result_type<()> {
-                let pattern = #struct_name::cache_pattern(product);
+                let pattern = Self::cache_pattern(product);
                 if let Err(e) = self.cache.delete_pattern(&pattern).await {
                     tracing::warn!("Failed to clear cache pattern {}: {}", pattern, e);
                 }
                 Ok(())
             }
+
+            #soft_delete_methods
         }
     }
 }
 
 /// Generate cache service integration
-fn generate_cache_integration(struct_name: &syn::Ident, cache_ttl: u64) -> TokenStream2 {
+fn generate_cache_integration(struct_name: &syn::Ident) -> TokenStream2 {
+    let cache_store = quote::format_ident!("{}CacheStore", struct_name);
+
     quote! {
-        /// Cache service trait (simplified version)
+        /// Cache backing the generated repository; values are the entity's JSON
         #[async_trait::async_trait]
-        pub trait CacheServiceTrait: Send + Sync {
-            async fn get<T>(&self, key: &str) -> Result<Option<T>, Box<dyn std::error::Error + Send + Sync>>
-            where
-                T: serde::de::DeserializeOwned;
-                
-            async fn set<T>(&self, key: &str, value: &T, ttl: u64) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
-            where
-                T: serde::Serialize;
-                
+        pub trait #cache_store: Send + Sync {
+            async fn get(&self, key: &str) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>>;
+
+            async fn set(&self, key: &str, value: String, ttl: u64) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
             async fn delete(&self, key: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
-            
+
             async fn delete_pattern(&self, pattern: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
         }
     }
-}
\ No newline at end of file
+}
@@ -15,98 +15,142 @@ pub fn derive_row_mapper(input: TokenStream) -> TokenStream {
     let fields = match &input.data {
         Data::Struct(data) => match &data.fields {
             Fields::Named(fields) => &fields.named,
-            _ => panic!("RowMapper only supports structs with named fields"),
+            _ => return syn::Error::new_spanned(&input.ident, "RowMapper only supports structs with named fields").to_compile_error().into(),
         },
-        _ => panic!("RowMapper only supports structs"),
+        _ => return syn::Error::new_spanned(&input.ident, "RowMapper only supports structs").to_compile_error().into(),
     };
     
     eprintln!("[pleme-codegen] RowMapper pattern applied to {} - saving ~50 lines per struct", struct_name);
     
-    // Generate field mappings
-    let field_mappings = fields.iter().map(|field| {
+    let row_struct_impl = match generate_row_struct_conversions(struct_name, fields, &input.attrs) {
+        Ok(tokens) => tokens,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    
+    let tokio_backend = match row_backend_is_tokio_postgres(&input.attrs) {
+        Ok(tokio_backend) => tokio_backend,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    
+    // Generate field mappings; both backends share the type conversions and differ
+    // only in the `try_get` signature and the decode error type
+    type Emit = fn(proc_macro2::TokenStream) -> proc_macro2::TokenStream;
+    let (try_get, decode_error): (Emit, Emit) = if tokio_backend {
+        (
+            |ty: proc_macro2::TokenStream| quote! { try_get::<_, #ty> },
+            |msg: proc_macro2::TokenStream| quote! { std::io::Error::new(std::io::ErrorKind::InvalidData, #msg) },
+        )
+    } else {
+        (
+            |ty: proc_macro2::TokenStream| quote! { try_get::<#ty, _> },
+            |msg: proc_macro2::TokenStream| quote! { sqlx::Error::Decode(#msg.into()) },
+        )
+    };
+    let mut field_mappings = Vec::new();
+    for field in fields {
         let field_name = &field.ident;
         let field_type = &field.ty;
+        let options = match RowFieldOptions::from_field(field) {
+            Ok(options) => options,
+            Err(err) => return err.to_compile_error().into(),
+        };
+        if options.skip {
+            field_mappings.push(quote! { #field_name: Default::default() });
+            continue;
+        }
+        let kind = options.kind(field, struct_name);
+        // Column read from the row: `#[row(rename = "...")]` or the field name
+        let column = options.rename.unwrap_or_else(|| {
+            let ident = field_name.as_ref().unwrap();
+            syn::LitStr::new(&ident.to_string(), ident.span())
+        });
         
         // Handle different field types
         let mapping = match field_type {
             // Check if it's a Decimal type
             ty if is_decimal_type(ty) => {
+                let get = try_get(quote! { rust_decimal::Decimal });
                 quote! {
-                    #field_name: rust_decimal::Decimal::from_str(
-                        &row.try_get::<sqlx::types::BigDecimal, _>(stringify!(#field_name))
-                            .map_err(|e| Self::map_error(e, stringify!(#field_name)))?
-                            .to_string()
-                    ).map_err(|e| Self::map_error(e, stringify!(#field_name)))?
+                    #field_name: row.#get(#column)
+                        .map_err(|e| Self::map_error(e, #column))?
                 }
             },
-            // Check if it's an enum that needs string conversion
-            ty if is_enum_type(ty) => {
+            // Enum stored as text: `#[row(enum)]`
+            _ if kind == Some(RowFieldKind::Enum) => {
+                let get = try_get(quote! { String });
+                let error = decode_error(quote! { "Invalid enum value" });
                 quote! {
-                    #field_name: row.try_get::<String, _>(stringify!(#field_name))
-                        .map_err(|e| Self::map_error(e, stringify!(#field_name)))?
+                    #field_name: row.#get(#column)
+                        .map_err(|e| Self::map_error(e, #column))?
                         .parse()
                         .map_err(|_| Self::map_error(
-                            sqlx::Error::Decode("Invalid enum value".into()), 
-                            stringify!(#field_name)
+                            #error, 
+                            #column
                         ))?
                 }
             },
-            // Check if it's JSON
-            ty if is_json_type(ty) => {
+            // JSON column: `#[row(json)]`
+            _ if kind == Some(RowFieldKind::Json) => {
+                let get = try_get(quote! { serde_json::Value });
+                let error = decode_error(quote! { e.to_string() });
                 quote! {
                     #field_name: serde_json::from_value(
-                        row.try_get(stringify!(#field_name))
-                            .map_err(|e| Self::map_error(e, stringify!(#field_name)))?
+                        row.#get(#column)
+                            .map_err(|e| Self::map_error(e, #column))?
                     ).map_err(|e| Self::map_error(
-                        sqlx::Error::Decode(e.to_string().into()), 
-                        stringify!(#field_name)
+                        #error, 
+                        #column
                     ))?
                 }
             },
             // Handle Option<Decimal>
             ty if is_option_decimal_type(ty) => {
+                let get = try_get(quote! { Option<rust_decimal::Decimal> });
                 quote! {
-                    #field_name: row.try_get::<Option<sqlx::types::BigDecimal>, _>(stringify!(#field_name))
-                        .map_err(|e| Self::map_error(e, stringify!(#field_name)))?
-                        .map(|bd| rust_decimal::Decimal::from_str(&bd.to_string()))
-                        .transpose()
-                        .map_err(|e| Self::map_error(
-                            sqlx::Error::Decode(e.to_string().into()), 
-                            stringify!(#field_name)
-                        ))?
+                    #field_name: row.#get(#column)
+                        .map_err(|e| Self::map_error(e, #column))?
                 }
             },
             // Default case for standard types
             _ => {
+                let get = try_get(quote! { _ });
                 quote! {
-                    #field_name: row.try_get(stringify!(#field_name))
-                        .map_err(|e| Self::map_error(e, stringify!(#field_name)))?
+                    #field_name: row.#get(#column)
+                        .map_err(|e| Self::map_error(e, #column))?
                 }
             }
         };
         
-        quote! { #mapping }
-    });
+        field_mappings.push(mapping);
+    }
     
-    let expanded = quote! {
-        impl #struct_name {
+    let mappers = if tokio_backend {
+        quote! {
+            /// Convert a tokio_postgres row to struct with comprehensive error handling
+            pub fn from_tokio_row(row: &tokio_postgres::Row) -> Result<Self, PaymentError> {
+                Ok(Self {
+                    #(#field_mappings,)*
+                })
+            }
+            
+            /// Convert multiple tokio_postgres rows to Vec<Self>
+            pub fn from_tokio_rows(rows: Vec<tokio_postgres::Row>) -> Result<Vec<Self>, PaymentError> {
+                rows.iter()
+                    .map(Self::from_tokio_row)
+                    .collect()
+            }
+        }
+    } else {
+        quote! {
             /// Convert database row to struct with comprehensive error handling
             pub fn from_row(row: &sqlx::postgres::PgRow) -> Result<Self, PaymentError> {
                 use sqlx::Row;
-                use std::str::FromStr;
                 
                 Ok(Self {
                     #(#field_mappings,)*
                 })
             }
             
-            /// Helper to convert SQLx errors with field context
-            fn map_error(err: impl std::error::Error, field: &str) -> PaymentError {
-                let msg = format!("Failed to read field '{}': {}", field, err);
-                tracing::error!(field = %field, error = %err, "Database field mapping error");
-                PaymentError::TransactionFailed(msg)
-            }
-            
             /// Convert multiple rows to Vec<Self>
             pub fn from_rows(rows: Vec<sqlx::postgres::PgRow>) -> Result<Vec<Self>, PaymentError> {
                 rows.into_iter()
@@ -124,9 +168,203 @@ pub fn derive_row_mapper(input: TokenStream) -> TokenStream {
         }
     };
     
+    let expanded = quote! {
+        impl #struct_name {
+            #mappers
+            
+            /// Helper to convert database errors with field context
+            fn map_error(err: impl std::error::Error, field: &str) -> PaymentError {
+                let msg = format!("Failed to read field '{}': {}", field, err);
+                tracing::error!(field = %field, error = %err, "Database field mapping error");
+                PaymentError::TransactionFailed(msg)
+            }
+        }
+        
+        #row_struct_impl
+    };
+    
     TokenStream::from(expanded)
 }
 
+/// Field-level `#[row(...)]` options
+#[derive(Default)]
+struct RowFieldOptions {
+    /// `#[row(rename = "db_col")]`: column to read instead of the field name
+    rename: Option<syn::LitStr>,
+    /// `#[row(skip)]`: not present in the row; filled with `Default::default()`
+    skip: bool,
+    /// `#[row(enum)]` / `#[row(json)]`: conversion applied to the column value
+    kind: Option<RowFieldKind>,
+}
+
+/// Column conversions selected by `#[row(enum)]` and `#[row(json)]`
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum RowFieldKind {
+    /// Read as `String`, then `parse()` (the enum implements `FromStr`)
+    Enum,
+    /// Read as `serde_json::Value`, then `serde_json::from_value`
+    Json,
+}
+
+impl RowFieldOptions {
+    fn from_field(field: &syn::Field) -> syn::Result<Self> {
+        let mut options = RowFieldOptions::default();
+        for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("row")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    let value: syn::LitStr = meta.value()?.parse()?;
+                    if value.value().is_empty() {
+                        return Err(syn::Error::new_spanned(&value, "column name cannot be empty"));
+                    }
+                    options.rename = Some(value);
+                } else if meta.path.is_ident("skip") {
+                    options.skip = true;
+                } else if meta.path.is_ident("enum") || meta.path.is_ident("json") {
+                    let kind = if meta.path.is_ident("enum") { RowFieldKind::Enum } else { RowFieldKind::Json };
+                    if options.kind.is_some_and(|existing| existing != kind) {
+                        return Err(meta.error("a field cannot be both `enum` and `json`"));
+                    }
+                    options.kind = Some(kind);
+                } else {
+                    return Err(meta.error("expected `rename = \"...\"`, `skip`, `enum` or `json`"));
+                }
+                Ok(())
+            })?;
+            if options.skip && options.rename.is_some() {
+                return Err(syn::Error::new_spanned(attr, "a skipped field cannot also be renamed"));
+            }
+        }
+        Ok(options)
+    }
+    
+    /// Conversion for the field: the explicit attribute, else the deprecated type-name allowlist
+    fn kind(&self, field: &syn::Field, struct_name: &syn::Ident) -> Option<RowFieldKind> {
+        if self.kind.is_some() {
+            return self.kind;
+        }
+        let (fallback, attribute) = if is_enum_type(&field.ty) {
+            (RowFieldKind::Enum, "enum")
+        } else if is_json_type(&field.ty) {
+            (RowFieldKind::Json, "json")
+        } else {
+            return None;
+        };
+        eprintln!(
+            "[pleme-codegen] RowMapper: {}::{} relies on the deprecated type-name allowlist; add #[row({})]",
+            struct_name,
+            field.ident.as_ref().unwrap(),
+            attribute
+        );
+        Some(fallback)
+    }
+}
+
+/// Whether `#[row(backend = "tokio_postgres")]` selects the tokio_postgres mapper (sqlx is the default)
+fn row_backend_is_tokio_postgres(attrs: &[syn::Attribute]) -> syn::Result<bool> {
+    let mut tokio_backend = false;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("row")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("backend") {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                tokio_backend = match value.value().as_str() {
+                    "sqlx" => false,
+                    "tokio_postgres" => true,
+                    other => {
+                        return Err(syn::Error::new_spanned(
+                            &value,
+                            format!("unknown row backend `{}`; expected \"sqlx\" or \"tokio_postgres\"", other),
+                        ))
+                    }
+                };
+            } else if meta.input.peek(syn::Token![=]) {
+                let _: syn::Expr = meta.value()?.parse()?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(tokio_backend)
+}
+
+/// Generate conversions to/from the thin DB row named by `#[database(row_struct = "...")]`
+///
+/// The row struct mirrors the entity's field names and types, except that `#[row(enum)]`
+/// fields are carried as their `String` form and `#[row(json)]` fields as `serde_json::Value`;
+/// Decimal columns stay `Decimal`, which sqlx decodes from NUMERIC directly.
+fn generate_row_struct_conversions(
+    struct_name: &syn::Ident,
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+    attrs: &[syn::Attribute],
+) -> syn::Result<proc_macro2::TokenStream> {
+    let mut row_struct = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("database")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("row_struct") {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                row_struct = Some(value.parse::<syn::Ident>()?);
+            } else if meta.input.peek(syn::Token![=]) {
+                let _: syn::Expr = meta.value()?.parse()?;
+            }
+            Ok(())
+        })?;
+    }
+    let Some(row_struct) = row_struct else {
+        return Ok(quote! {});
+    };
+    
+    let mut from_row = Vec::new();
+    let mut to_row = Vec::new();
+    for field in fields {
+        let field_name = &field.ident;
+        let kind = RowFieldOptions::from_field(field)?.kind(field, struct_name);
+        
+        let (domain_value, row_value) = if kind == Some(RowFieldKind::Enum) {
+            (
+                quote! {
+                    row.#field_name.parse().map_err(|_| format!(
+                        "invalid {} value `{}`", stringify!(#field_name), row.#field_name
+                    ))?
+                },
+                quote! { entity.#field_name.to_string() },
+            )
+        } else if kind == Some(RowFieldKind::Json) {
+            (
+                quote! {
+                    serde_json::from_value(row.#field_name)
+                        .map_err(|e| format!("invalid {} JSON: {}", stringify!(#field_name), e))?
+                },
+                quote! {
+                    serde_json::to_value(&entity.#field_name).unwrap_or(serde_json::Value::Null)
+                },
+            )
+        } else {
+            (quote! { row.#field_name }, quote! { entity.#field_name.clone() })
+        };
+        
+        from_row.push(quote! { #field_name: #domain_value });
+        to_row.push(quote! { #field_name: #row_value });
+    }
+    
+    Ok(quote! {
+        impl std::convert::TryFrom<#row_struct> for #struct_name {
+            type Error = String;
+            
+            fn try_from(row: #row_struct) -> Result<Self, Self::Error> {
+                Ok(Self {
+                    #(#from_row,)*
+                })
+            }
+        }
+        
+        impl From<&#struct_name> for #row_struct {
+            fn from(entity: &#struct_name) -> Self {
+                Self {
+                    #(#to_row,)*
+                }
+            }
+        }
+    })
+}
+
 /// Derive macro for repository CRUD operations with caching
 pub fn derive_repository_crud(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
@@ -134,38 +372,158 @@ pub fn derive_repository_crud(input: TokenStream) -> TokenStream {
     
     eprintln!("[pleme-codegen] RepositoryCrud pattern applied to {} - saving ~300 lines", struct_name);
     
+    let cache_diff_impl = match generate_cache_diff_implementation(struct_name, &input.attrs) {
+        Ok(tokens) => tokens,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    
+    let slow_query_ms = match slow_query_threshold(&input.attrs) {
+        Ok(threshold) => threshold,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    
+    let idempotency_ttl_secs = match idempotency_ttl(&input.attrs) {
+        Ok(ttl) => ttl,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    
+    let cache_policy = quote::format_ident!("{}CachePolicy", struct_name);
+    
     let expanded = quote! {
+        /// How entities created through this repository are cached
+        ///
+        /// `#[domain(repository = ...)]` implements it from the entity's `cache_warm_on_create`
+        /// and `cache_ttl`; other entities can take the defaults with an empty impl.
+        pub trait #cache_policy {
+            const CACHE_WARM_ON_CREATE: bool = true;
+            const CACHE_TTL_SECS: u64 = 300;
+        }
+        
         impl #struct_name {
             /// Create with automatic caching
             pub async fn create_with_cache<T>(&self, entity: &T, cache_key: &str) -> Result<T, PaymentError>
             where
-                T: serde::Serialize + serde::de::DeserializeOwned + Clone + Send + Sync,
+                T: serde::Serialize + serde::de::DeserializeOwned + Clone + Send + Sync + #cache_policy,
+            {
+                self.create_with_cache_ref(entity, cache_key).await.map(T::clone)
+            }
+            
+            /// Borrowing `create_with_cache`; serializes straight from `entity` without cloning it
+            pub async fn create_with_cache_ref<'a, T>(&self, entity: &'a T, cache_key: &str) -> Result<&'a T, PaymentError>
+            where
+                T: serde::Serialize + Send + Sync + #cache_policy,
+            {
+                // Perform database operation (placeholder for actual implementation)
+                let created = entity;
+                
+                // Cache the result if Redis is available
+                if let Some(redis_pool) = &self.redis {
+                    if let Ok(mut conn) = redis_pool.get().await {
+                        Self::cache_on_create(&mut conn, created, cache_key).await?;
+                    }
+                }
+                
+                Ok(created)
+            }
+            
+            /// Write a freshly created entity to the cache unless its policy turns warming off;
+            /// returns whether the entity was cached
+            pub async fn cache_on_create<T, C>(conn: &mut C, entity: &T, cache_key: &str) -> Result<bool, PaymentError>
+            where
+                T: serde::Serialize + #cache_policy,
+                C: redis::aio::ConnectionLike + Send,
             {
+                if !T::CACHE_WARM_ON_CREATE {
+                    tracing::debug!(cache_key = %cache_key, "Cache warm on create disabled - skipping cache write");
+                    return Ok(false);
+                }
+                
                 let start = std::time::Instant::now();
+                let json = serde_json::to_string(entity).map_err(|e| PaymentError::TransactionFailed(e.to_string()))?;
+                let result: Result<(), _> = redis::cmd("SET")
+                    .arg(cache_key)
+                    .arg(&json)
+                    .arg("EX")
+                    .arg(T::CACHE_TTL_SECS)
+                    .query_async(conn)
+                    .await;
                 
+                tracing::debug!(
+                    cache_key = %cache_key,
+                    duration_ms = %start.elapsed().as_millis(),
+                    "Entity cached after creation"
+                );
+                
+                Ok(result.is_ok())
+            }
+            
+            /// How long a created entity is replayed for a repeated idempotency key
+            pub const IDEMPOTENCY_TTL_SECS: u64 = #idempotency_ttl_secs;
+            
+            /// Redis key holding the result of the create made under `idempotency_key`
+            pub fn idempotency_cache_key(idempotency_key: &str) -> String {
+                format!("idem:{}", idempotency_key)
+            }
+            
+            /// Create at most once per `idempotency_key`: a retry within `IDEMPOTENCY_TTL_SECS`
+            /// returns the entity stored by the first call instead of writing again
+            pub async fn create_idempotent<T>(&self, entity: &T, idempotency_key: &str) -> Result<T, PaymentError>
+            where
+                T: serde::Serialize + serde::de::DeserializeOwned + Clone + Send + Sync,
+            {
                 // Perform database operation (placeholder for actual implementation)
-                let created = entity.clone();
+                let create = || async { Ok(entity.clone()) };
                 
-                // Cache the result if Redis is available
                 if let Some(redis_pool) = &self.redis {
                     if let Ok(mut conn) = redis_pool.get().await {
-                        let json = serde_json::to_string(&created).map_err(|e| PaymentError::TransactionFailed(e.to_string()))?;
-                        let _: Result<(), _> = redis::cmd("SET")
-                            .arg(cache_key)
-                            .arg(&json)
-                            .arg("EX")
-                            .arg(300) // 5 minute default TTL
-                            .query_async(&mut conn)
-                            .await;
-                        
-                        tracing::debug!(
-                            cache_key = %cache_key,
-                            duration_ms = %start.elapsed().as_millis(),
-                            "Entity cached after creation"
-                        );
+                        return Self::create_once(&mut conn, idempotency_key, Self::IDEMPOTENCY_TTL_SECS, create).await;
                     }
                 }
                 
+                tracing::warn!(
+                    idempotency_key = %idempotency_key,
+                    "Redis unavailable - creating without idempotency check"
+                );
+                create().await
+            }
+            
+            /// Run `create` unless a result is already stored under the idempotency key,
+            /// then store the serialized result for `ttl` seconds
+            pub async fn create_once<T, C, F, Fut>(conn: &mut C, idempotency_key: &str, ttl: u64, create: F) -> Result<T, PaymentError>
+            where
+                T: serde::Serialize + serde::de::DeserializeOwned,
+                C: redis::aio::ConnectionLike + Send,
+                F: FnOnce() -> Fut,
+                Fut: std::future::Future<Output = Result<T, PaymentError>>,
+            {
+                let key = Self::idempotency_cache_key(idempotency_key);
+                let stored: Result<Option<String>, _> = redis::cmd("GET").arg(&key).query_async(conn).await;
+                
+                if let Ok(Some(json)) = stored {
+                    tracing::info!(idempotency_key = %idempotency_key, "Idempotent replay - returning stored entity");
+                    // A corrupt entry must not lead to a second write
+                    return serde_json::from_str(&json).map_err(|e| PaymentError::TransactionFailed(e.to_string()));
+                }
+                
+                let created = create().await?;
+                let json = serde_json::to_string(&created).map_err(|e| PaymentError::TransactionFailed(e.to_string()))?;
+                let result: Result<(), _> = redis::cmd("SET")
+                    .arg(&key)
+                    .arg(&json)
+                    .arg("EX")
+                    .arg(ttl)
+                    .arg("NX")
+                    .query_async(conn)
+                    .await;
+                
+                if let Err(err) = result {
+                    tracing::warn!(
+                        idempotency_key = %idempotency_key,
+                        error = %err,
+                        "Failed to store idempotent result - a retry may create again"
+                    );
+                }
+                
                 Ok(created)
             }
             
@@ -212,11 +570,19 @@ pub fn derive_repository_crud(input: TokenStream) -> TokenStream {
             pub async fn update_with_cache<T>(&self, entity: &T, cache_key: &str) -> Result<T, PaymentError>
             where
                 T: serde::Serialize + serde::de::DeserializeOwned + Clone + Send + Sync,
+            {
+                self.update_with_cache_ref(entity, cache_key).await.map(T::clone)
+            }
+            
+            /// Borrowing `update_with_cache`; serializes straight from `entity` without cloning it
+            pub async fn update_with_cache_ref<'a, T>(&self, entity: &'a T, cache_key: &str) -> Result<&'a T, PaymentError>
+            where
+                T: serde::Serialize + Send + Sync,
             {
                 let start = std::time::Instant::now();
                 
                 // Perform database update (placeholder)
-                let updated = entity.clone();
+                let updated = entity;
                 
                 // Invalidate old cache and set new
                 if let Some(redis_pool) = &self.redis {
@@ -273,8 +639,31 @@ pub fn derive_repository_crud(input: TokenStream) -> TokenStream {
                 Ok(())
             }
             
+            /// Operations slower than this are logged as slow queries
+            pub const SLOW_QUERY_MS: u128 = #slow_query_ms;
+            
+            /// Whether an operation duration crosses the slow-query threshold
+            pub fn is_slow_query(duration_ms: u128) -> bool {
+                duration_ms > Self::SLOW_QUERY_MS
+            }
+            
             /// Execute query with metrics
             pub async fn execute_with_metrics<F, R>(&self, operation_name: &str, query_fn: F) -> Result<R, PaymentError>
+            where
+                F: std::future::Future<Output = Result<R, sqlx::Error>>,
+            {
+                self.execute_with_metrics_inner(operation_name, None, query_fn).await
+            }
+            
+            /// `execute_with_metrics` that includes the SQL text in the slow-query log
+            pub async fn execute_with_metrics_sql<F, R>(&self, operation_name: &str, sql: &str, query_fn: F) -> Result<R, PaymentError>
+            where
+                F: std::future::Future<Output = Result<R, sqlx::Error>>,
+            {
+                self.execute_with_metrics_inner(operation_name, Some(sql), query_fn).await
+            }
+            
+            async fn execute_with_metrics_inner<F, R>(&self, operation_name: &str, sql: Option<&str>, query_fn: F) -> Result<R, PaymentError>
             where
                 F: std::future::Future<Output = Result<R, sqlx::Error>>,
             {
@@ -301,11 +690,13 @@ pub fn derive_repository_crud(input: TokenStream) -> TokenStream {
                 );
                 
                 // Emit metrics (placeholder for actual metrics emission)
-                if duration_ms > 1000 {
+                if Self::is_slow_query(duration_ms) {
                     tracing::warn!(
                         repository = %stringify!(#struct_name),
                         operation = %operation_name,
                         duration_ms = %duration_ms,
+                        threshold_ms = %Self::SLOW_QUERY_MS,
+                        query = ?sql,
                         "Slow repository operation detected"
                     );
                 }
@@ -322,41 +713,334 @@ pub fn derive_repository_crud(input: TokenStream) -> TokenStream {
             pub async fn invalidate_cache_pattern(&self, pattern: &str) -> Result<u64, PaymentError> {
                 if let Some(redis_pool) = &self.redis {
                     if let Ok(mut conn) = redis_pool.get().await {
-                        // Use SCAN to find matching keys
-                        let keys: Vec<String> = redis::cmd("KEYS")
-                            .arg(pattern)
-                            .query_async(&mut conn)
-                            .await
-                            .map_err(|e| PaymentError::TransactionFailed(e.to_string()))?;
-                        
-                        if !keys.is_empty() {
-                            let count = keys.len() as u64;
-                            
-                            // Delete all matching keys
-                            let _: Result<(), _> = redis::cmd("DEL")
-                                .arg(keys)
-                                .query_async(&mut conn)
-                                .await;
-                            
-                            tracing::info!(
-                                pattern = %pattern,
-                                count = %count,
-                                "Cache keys invalidated"
-                            );
-                            
-                            return Ok(count);
-                        }
+                        return Self::unlink_matching(&mut conn, pattern).await;
                     }
                 }
                 
                 Ok(0)
             }
+            
+            /// Keys requested per `SCAN` round trip in `unlink_matching`
+            pub const SCAN_BATCH_SIZE: usize = 500;
+            
+            /// Delete every key matching `pattern` with an incremental `SCAN` (never the
+            /// blocking `KEYS`), unlinking each batch; returns the number of keys deleted
+            pub async fn unlink_matching<C>(conn: &mut C, pattern: &str) -> Result<u64, PaymentError>
+            where
+                C: redis::aio::ConnectionLike + Send,
+            {
+                let mut cursor: u64 = 0;
+                let mut count: u64 = 0;
+                let mut use_unlink = true;
+                
+                loop {
+                    let (next_cursor, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+                        .arg(cursor)
+                        .arg("MATCH")
+                        .arg(pattern)
+                        .arg("COUNT")
+                        .arg(Self::SCAN_BATCH_SIZE)
+                        .query_async(conn)
+                        .await
+                        .map_err(|e| PaymentError::TransactionFailed(e.to_string()))?;
+                    
+                    if !keys.is_empty() {
+                        // UNLINK frees memory in the background; servers before Redis 4 only know DEL
+                        let unlinked: Option<u64> = if use_unlink {
+                            redis::cmd("UNLINK").arg(&keys).query_async(conn).await.ok()
+                        } else {
+                            None
+                        };
+                        count += match unlinked {
+                            Some(deleted) => deleted,
+                            None => {
+                                use_unlink = false;
+                                redis::cmd("DEL")
+                                    .arg(&keys)
+                                    .query_async::<_, u64>(conn)
+                                    .await
+                                    .map_err(|e| PaymentError::TransactionFailed(e.to_string()))?
+                            }
+                        };
+                    }
+                    
+                    cursor = next_cursor;
+                    if cursor == 0 {
+                        break;
+                    }
+                }
+                
+                if count > 0 {
+                    tracing::info!(
+                        pattern = %pattern,
+                        count = %count,
+                        "Cache keys invalidated"
+                    );
+                }
+                
+                Ok(count)
+            }
         }
+        
+        #cache_diff_impl
     };
     
     TokenStream::from(expanded)
 }
 
+/// Default slow-query threshold when `#[repository(slow_query_ms)]` is not set
+const DEFAULT_SLOW_QUERY_MS: u64 = 1000;
+
+/// Slow-query threshold from `#[repository(slow_query_ms = 250)]`, as a `u128` literal
+fn slow_query_threshold(attrs: &[syn::Attribute]) -> syn::Result<proc_macro2::Literal> {
+    let mut threshold = DEFAULT_SLOW_QUERY_MS;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("repository")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("slow_query_ms") {
+                let value: syn::LitInt = meta.value()?.parse()?;
+                threshold = value.base10_parse()?;
+                if threshold == 0 {
+                    return Err(syn::Error::new_spanned(&value, "slow_query_ms must be greater than zero"));
+                }
+            } else if meta.input.peek(syn::Token![=]) {
+                let _: syn::Expr = meta.value()?.parse()?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(proc_macro2::Literal::u128_suffixed(threshold as u128))
+}
+
+/// Default replay window when `#[repository(idempotency_ttl_secs)]` is not set (24h)
+const DEFAULT_IDEMPOTENCY_TTL_SECS: u64 = 86_400;
+
+/// Idempotency replay window from `#[repository(idempotency_ttl_secs = 3600)]`
+fn idempotency_ttl(attrs: &[syn::Attribute]) -> syn::Result<u64> {
+    let mut ttl = DEFAULT_IDEMPOTENCY_TTL_SECS;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("repository")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("idempotency_ttl_secs") {
+                let value: syn::LitInt = meta.value()?.parse()?;
+                ttl = value.base10_parse()?;
+                if ttl == 0 {
+                    return Err(syn::Error::new_spanned(&value, "idempotency_ttl_secs must be greater than zero"));
+                }
+            } else if meta.input.peek(syn::Token![=]) {
+                let _: syn::Expr = meta.value()?.parse()?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(ttl)
+}
+
+/// Service namespace from `#[repository(metrics_prefix = "...")]`, if configured
+pub fn metrics_prefix(attrs: &[syn::Attribute]) -> syn::Result<Option<syn::LitStr>> {
+    let mut prefix = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("repository")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("metrics_prefix") {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                let valid = !value.value().is_empty()
+                    && value.value().chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+                if !valid {
+                    return Err(syn::Error::new_spanned(
+                        &value,
+                        "metrics_prefix must be non-empty and contain only lowercase letters, digits and `_`",
+                    ));
+                }
+                prefix = Some(value);
+            } else if meta.input.peek(syn::Token![=]) {
+                let _: syn::Expr = meta.value()?.parse()?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(prefix)
+}
+
+/// Module providing the task-local correlation id, from `#[repository(correlation_id)]`
+///
+/// A bare flag uses the `correlation` module declared by `correlation_context!()`;
+/// `correlation_id = path` names another module with the same `current`/`scope` functions.
+pub fn correlation_module(attrs: &[syn::Attribute]) -> syn::Result<Option<syn::Path>> {
+    let mut module = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("repository")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("correlation_id") {
+                module = Some(if meta.input.peek(syn::Token![=]) {
+                    meta.value()?.parse()?
+                } else {
+                    syn::parse_quote!(correlation)
+                });
+            } else if meta.input.peek(syn::Token![=]) {
+                let _: syn::Expr = meta.value()?.parse()?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(module)
+}
+
+/// Expand `correlation_context!()` into the `correlation` module
+pub fn expand_correlation_context(input: proc_macro2::TokenStream) -> syn::Result<proc_macro2::TokenStream> {
+    if !input.is_empty() {
+        return Err(syn::Error::new_spanned(input, "correlation_context!() takes no arguments"));
+    }
+
+    Ok(quote! {
+        /// Task-local correlation id tagged onto the events of `#[repository(correlation_id)]` types
+        pub mod correlation {
+            tokio::task_local! {
+                static CORRELATION_ID: String;
+            }
+
+            /// Correlation id of the current task, if it runs inside `scope`
+            pub fn current() -> Option<String> {
+                CORRELATION_ID.try_with(|id| id.clone()).ok()
+            }
+
+            /// Run `operation` with `correlation_id` as the current task's id
+            pub async fn scope<F>(correlation_id: impl Into<String>, operation: F) -> F::Output
+            where
+                F: std::future::Future,
+            {
+                CORRELATION_ID.scope(correlation_id.into(), operation).await
+            }
+
+            /// `tokio::spawn` carrying the current correlation id into the new task, which
+            /// task-locals otherwise do not cross
+            pub fn spawn<F>(task: F) -> tokio::task::JoinHandle<F::Output>
+            where
+                F: std::future::Future + Send + 'static,
+                F::Output: Send + 'static,
+            {
+                match current() {
+                    Some(correlation_id) => tokio::spawn(CORRELATION_ID.scope(correlation_id, task)),
+                    None => tokio::spawn(task),
+                }
+            }
+        }
+    })
+}
+
+/// Generate field-diff cache refresh for `#[cached(invalidate_on = "a,b")]`
+fn generate_cache_diff_implementation(
+    struct_name: &syn::Ident,
+    attrs: &[syn::Attribute],
+) -> syn::Result<proc_macro2::TokenStream> {
+    let mut invalidate_on: Vec<String> = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("cached")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("invalidate_on") {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                invalidate_on = value.value()
+                    .split(',')
+                    .map(|field| field.trim().to_string())
+                    .filter(|field| !field.is_empty())
+                    .collect();
+                if invalidate_on.is_empty() {
+                    return Err(syn::Error::new_spanned(&value, "invalidate_on requires at least one field"));
+                }
+            } else if meta.input.peek(syn::Token![=]) {
+                let _: syn::Expr = meta.value()?.parse()?;
+            }
+            Ok(())
+        })?;
+    }
+    if invalidate_on.is_empty() {
+        return Ok(quote! {});
+    }
+    
+    Ok(quote! {
+        impl #struct_name {
+            /// Entity fields whose change requires refreshing the cached copy
+            pub const CACHE_INVALIDATE_ON: &'static [&'static str] = &[#(#invalidate_on),*];
+            
+            /// `CACHE_INVALIDATE_ON` fields that differ between two versions of an entity
+            ///
+            /// A field missing from the serialized entity (a typo in `invalidate_on`, or a
+            /// serde rename) cannot be compared, so it counts as changed and is logged.
+            pub fn cache_relevant_changes<T: serde::Serialize>(previous: &T, updated: &T) -> Vec<&'static str> {
+                let before = serde_json::to_value(previous).unwrap_or_default();
+                let after = serde_json::to_value(updated).unwrap_or_default();
+                Self::CACHE_INVALIDATE_ON.iter()
+                    .copied()
+                    .filter(|field| match (before.get(*field), after.get(*field)) {
+                        (Some(before), Some(after)) => before != after,
+                        _ => {
+                            tracing::warn!(
+                                repository = %stringify!(#struct_name),
+                                field = %field,
+                                "invalidate_on field missing from serialized entity - treating it as changed"
+                            );
+                            true
+                        }
+                    })
+                    .collect()
+            }
+            
+            /// Rewrite the cached entity only if a relevant field changed; returns whether it did
+            pub async fn refresh_cache_if_changed<T, C>(
+                cache: &mut C,
+                previous: &T,
+                updated: &T,
+                cache_key: &str,
+                ttl: u64,
+            ) -> Result<bool, PaymentError>
+            where
+                T: serde::Serialize,
+                C: redis::aio::ConnectionLike + Send,
+            {
+                let changed = Self::cache_relevant_changes(previous, updated);
+                if changed.is_empty() {
+                    tracing::debug!(cache_key = %cache_key, "No cache-relevant fields changed - keeping cached entity");
+                    return Ok(false);
+                }
+                
+                let json = serde_json::to_string(updated).map_err(|e| PaymentError::TransactionFailed(e.to_string()))?;
+                redis::cmd("DEL")
+                    .arg(cache_key)
+                    .query_async::<_, ()>(cache)
+                    .await
+                    .map_err(|e| PaymentError::TransactionFailed(e.to_string()))?;
+                redis::cmd("SET")
+                    .arg(cache_key)
+                    .arg(&json)
+                    .arg("EX")
+                    .arg(ttl)
+                    .query_async::<_, ()>(cache)
+                    .await
+                    .map_err(|e| PaymentError::TransactionFailed(e.to_string()))?;
+                
+                tracing::debug!(
+                    cache_key = %cache_key,
+                    changed_fields = ?changed,
+                    "Cache refreshed after relevant field change"
+                );
+                Ok(true)
+            }
+            
+            /// Update with cache refresh limited to changes in `CACHE_INVALIDATE_ON` fields
+            pub async fn update_with_cache_diff<T>(&self, previous: &T, entity: &T, cache_key: &str) -> Result<T, PaymentError>
+            where
+                T: serde::Serialize + serde::de::DeserializeOwned + Clone + Send + Sync,
+            {
+                // Perform database update (placeholder)
+                let updated = entity.clone();
+                
+                if let Some(redis_pool) = &self.redis {
+                    if let Ok(mut conn) = redis_pool.get().await {
+                        Self::refresh_cache_if_changed(&mut conn, previous, &updated, cache_key, 300).await?;
+                    }
+                }
+                
+                Ok(updated)
+            }
+        }
+    })
+}
+
 // Helper functions to identify types
 fn is_decimal_type(ty: &syn::Type) -> bool {
     if let syn::Type::Path(type_path) = ty {
@@ -367,6 +1051,7 @@ fn is_decimal_type(ty: &syn::Type) -> bool {
     false
 }
 
+/// Deprecated fallback for fields without `#[row(enum)]`: enums recognised by type name
 fn is_enum_type(ty: &syn::Type) -> bool {
     if let syn::Type::Path(type_path) = ty {
         if let Some(segment) = type_path.path.segments.last() {
@@ -379,6 +1064,7 @@ fn is_enum_type(ty: &syn::Type) -> bool {
     false
 }
 
+/// Deprecated fallback for fields without `#[row(json)]`: JSON types recognised by type name
 fn is_json_type(ty: &syn::Type) -> bool {
     if let syn::Type::Path(type_path) = ty {
         if let Some(segment) = type_path.path.segments.last() {
@@ -3,8 +3,9 @@
 //! These macros were identified through our feedback loop process
 //! and will save 2,940+ lines of boilerplate across services
 
+use heck::ToSnakeCase;
 use proc_macro::TokenStream;
-use quote::quote;
+use quote::{format_ident, quote};
 use syn::{parse_macro_input, DeriveInput};
 
 /// StatusStateMachine - Generate complex state machine logic (saves ~110 lines per enum)
@@ -14,6 +15,56 @@ pub fn derive_status_state_machine(input: TokenStream) -> TokenStream {
     
     eprintln!("[pleme-codegen] StatusStateMachine pattern applied to {} - saving ~110 lines", enum_name);
     
+    let unit_variants: Vec<&syn::Ident> = match &input.data {
+        syn::Data::Enum(data) => data.variants.iter()
+            .filter(|v| matches!(v.fields, syn::Fields::Unit))
+            .map(|v| &v.ident)
+            .collect(),
+        _ => Vec::new(),
+    };
+    let db_names: Vec<String> = unit_variants.iter().map(|v| v.to_string().to_snake_case()).collect();
+    
+    let mut db_mapping_test = false;
+    let mut triggers = None;
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("status")) {
+        if let Err(err) = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("db_mapping_test") {
+                db_mapping_test = true;
+            } else if meta.path.is_ident("triggers") {
+                triggers = Some(meta.value()?.parse::<syn::LitStr>()?);
+            } else if meta.input.peek(syn::Token![=]) {
+                let _: syn::Expr = meta.value()?.parse()?;
+            }
+            Ok(())
+        }) {
+            return err.to_compile_error().into();
+        }
+    }
+    
+    let declared: Vec<String> = unit_variants.iter().map(|v| v.to_string()).collect();
+    let status_graph_dot = match status_graph_dot(enum_name, &declared, triggers.as_ref()) {
+        Ok(dot) => dot,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let transition_from = TRANSITIONS.iter().map(|(from, _)| *from);
+    let transition_to = TRANSITIONS.iter().map(|(_, to)| *to);
+    
+    // #[status(db_mapping_test)] turns the mapping check into a generated #[test]
+    let db_mapping_test_fn = if db_mapping_test {
+        let test_name = format_ident!("{}_db_mapping_is_exhaustive", enum_name.to_string().to_snake_case());
+        quote! {
+            #[cfg(test)]
+            #[test]
+            fn #test_name() {
+                if let Err(err) = #enum_name::verify_db_mapping() {
+                    panic!("{}", err);
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+    
     let expanded = quote! {
         impl #enum_name {
             /// AI-Generated: State transition validation
@@ -27,50 +78,19 @@ pub fn derive_status_state_machine(input: TokenStream) -> TokenStream {
                 let from = format!("{:?}", self);
                 let to = format!("{:?}", new_status);
                 
-                // Define allowed transitions based on common patterns
+                // Allowed transitions based on common patterns
                 match (from.as_str(), to.as_str()) {
-                    // Order/Payment state machine patterns
-                    ("Pending", "AwaitingPayment") | ("Pending", "PaymentProcessing") | 
-                    ("Pending", "Paid") | ("Pending", "Failed") | ("Pending", "Cancelled") => true,
-                    
-                    ("AwaitingPayment", "PaymentProcessing") | ("AwaitingPayment", "Paid") |
-                    ("AwaitingPayment", "Failed") | ("AwaitingPayment", "Cancelled") | 
-                    ("AwaitingPayment", "Expired") => true,
-                    
-                    ("PaymentProcessing", "Paid") | ("PaymentProcessing", "Failed") | 
-                    ("PaymentProcessing", "Cancelled") | ("PaymentProcessing", "Authorized") => true,
-                    
-                    ("Authorized", "Captured") | ("Authorized", "Cancelled") | ("Authorized", "Expired") => true,
-                    ("Captured", "Processing") | ("Captured", "Refunded") => true,
-                    
-                    ("Paid", "Processing") | ("Paid", "Cancelled") | ("Paid", "Refunded") => true,
-                    
-                    ("Processing", "Fulfilled") | ("Processing", "PartiallyFulfilled") | 
-                    ("Processing", "Cancelled") | ("Processing", "Failed") => true,
-                    
-                    ("PartiallyFulfilled", "Fulfilled") | ("PartiallyFulfilled", "Cancelled") => true,
-                    
-                    ("Fulfilled", "Shipped") | ("Fulfilled", "PartiallyShipped") => true,
-                    ("PartiallyShipped", "Shipped") => true,
-                    
-                    ("Shipped", "OutForDelivery") | ("Shipped", "Delivered") | ("Shipped", "Returned") => true,
-                    ("OutForDelivery", "Delivered") | ("OutForDelivery", "Returned") => true,
-                    
-                    ("Delivered", "Refunded") | ("Delivered", "PartiallyRefunded") | 
-                    ("Delivered", "Disputed") | ("Delivered", "Returned") => true,
-                    
-                    ("PartiallyRefunded", "Refunded") | ("PartiallyRefunded", "Disputed") => true,
-                    ("Returned", "Refunded") => true,
-                    
-                    // Active state transitions (for user/subscription statuses)
-                    ("Active", "Inactive") | ("Active", "Suspended") | ("Active", "Deleted") => true,
-                    ("Inactive", "Active") | ("Inactive", "Deleted") => true,
-                    ("Suspended", "Active") | ("Suspended", "Deleted") => true,
-                    
+                    #((#transition_from, #transition_to))|* => true,
                     _ => false
                 }
             }
             
+            /// Graphviz DOT digraph of the declared statuses and the transitions
+            /// allowed between them (self-transitions omitted)
+            pub fn status_graph_dot() -> &'static str {
+                #status_graph_dot
+            }
+            
             pub fn is_final_status(&self) -> bool {
                 let status_str = format!("{:?}", self);
                 matches!(
@@ -134,33 +154,146 @@ pub fn derive_status_state_machine(input: TokenStream) -> TokenStream {
                     _ => "unknown"
                 }
             }
+            
+            /// Check a recorded status history hop by hop against the allowed transitions
+            ///
+            /// On failure returns the index (into `statuses`) of the first status that
+            /// could not be reached from its predecessor, with a description of the hop.
+            pub fn validate_transition_sequence(statuses: &[Self]) -> Result<(), (usize, String)> {
+                for (index, hop) in statuses.windows(2).enumerate() {
+                    let (from, to) = (&hop[0], &hop[1]);
+                    if !from.can_transition_to(to) {
+                        let reason = format!(
+                            "illegal {} transition {:?} -> {:?}",
+                            stringify!(#enum_name), from, to
+                        );
+                        tracing::debug!(index = index + 1, reason = %reason, "Status sequence validation failed");
+                        return Err((index + 1, reason));
+                    }
+                }
+                Ok(())
+            }
+            
+            /// Every unit variant, in declaration order
+            pub const VARIANTS: &'static [#enum_name] = &[#(#enum_name::#unit_variants),*];
+            
+            /// Check that `to_str` and `FromStr` round-trip every variant
+            pub fn verify_db_mapping() -> Result<(), String> {
+                for variant in Self::VARIANTS {
+                    let db_value = variant.to_str();
+                    let round_trips = <Self as std::str::FromStr>::from_str(db_value)
+                        .is_ok_and(|parsed| std::mem::discriminant(&parsed) == std::mem::discriminant(variant));
+                    if !round_trips {
+                        return Err(format!(
+                            "{}::{:?} maps to \"{}\", which does not parse back to the same variant",
+                            stringify!(#enum_name), variant, db_value
+                        ));
+                    }
+                }
+                Ok(())
+            }
         }
         
         impl std::str::FromStr for #enum_name {
             type Err = String;
             
             fn from_str(s: &str) -> Result<Self, Self::Err> {
-                // This is a simplified implementation that converts the string back to enum
-                // In a real implementation, you'd generate this based on the actual enum variants
-                let error_msg = format!("Invalid {}: {}", stringify!(#enum_name), s);
-                
-                // Try to match common patterns
                 match s {
-                    "pending" => {
-                        // Try to parse as debug format first
-                        if let Ok(parsed) = s.parse::<Self>() {
-                            return Ok(parsed);
-                        }
-                    }
-                    _ => {}
+                    #(#db_names => Ok(Self::#unit_variants),)*
+                    _ => Err(format!("Invalid {}: {}", stringify!(#enum_name), s)),
                 }
-                
-                // For now, return an error - in production, this would be generated
-                // based on the actual enum variants
-                Err(error_msg)
             }
         }
+        
+        #db_mapping_test_fn
     };
     
     TokenStream::from(expanded)
-}
\ No newline at end of file
+}
+
+/// Transitions allowed by `can_transition_to`, by variant name
+const TRANSITIONS: &[(&str, &str)] = &[
+    // Order/Payment state machine patterns
+    ("Pending", "AwaitingPayment"), ("Pending", "PaymentProcessing"),
+    ("Pending", "Paid"), ("Pending", "Failed"), ("Pending", "Cancelled"),
+    
+    ("AwaitingPayment", "PaymentProcessing"), ("AwaitingPayment", "Paid"),
+    ("AwaitingPayment", "Failed"), ("AwaitingPayment", "Cancelled"),
+    ("AwaitingPayment", "Expired"),
+    
+    ("PaymentProcessing", "Paid"), ("PaymentProcessing", "Failed"),
+    ("PaymentProcessing", "Cancelled"), ("PaymentProcessing", "Authorized"),
+    
+    ("Authorized", "Captured"), ("Authorized", "Cancelled"), ("Authorized", "Expired"),
+    ("Captured", "Processing"), ("Captured", "Refunded"),
+    
+    ("Paid", "Processing"), ("Paid", "Cancelled"), ("Paid", "Refunded"),
+    
+    ("Processing", "Fulfilled"), ("Processing", "PartiallyFulfilled"),
+    ("Processing", "Cancelled"), ("Processing", "Failed"),
+    
+    ("PartiallyFulfilled", "Fulfilled"), ("PartiallyFulfilled", "Cancelled"),
+    
+    ("Fulfilled", "Shipped"), ("Fulfilled", "PartiallyShipped"),
+    ("PartiallyShipped", "Shipped"),
+    
+    ("Shipped", "OutForDelivery"), ("Shipped", "Delivered"), ("Shipped", "Returned"),
+    ("OutForDelivery", "Delivered"), ("OutForDelivery", "Returned"),
+    
+    ("Delivered", "Refunded"), ("Delivered", "PartiallyRefunded"),
+    ("Delivered", "Disputed"), ("Delivered", "Returned"),
+    
+    ("PartiallyRefunded", "Refunded"), ("PartiallyRefunded", "Disputed"),
+    ("Returned", "Refunded"),
+    
+    // Active state transitions (for user/subscription statuses)
+    ("Active", "Inactive"), ("Active", "Suspended"), ("Active", "Deleted"),
+    ("Inactive", "Active"), ("Inactive", "Deleted"),
+    ("Suspended", "Active"), ("Suspended", "Deleted"),
+];
+
+/// Render the DOT graph for the declared variants; edges are labelled with the
+/// method named in `#[status(triggers = "Pending->Paid: pay, ...")]`, if any
+fn status_graph_dot(enum_name: &syn::Ident, declared: &[String], triggers: Option<&syn::LitStr>) -> syn::Result<String> {
+    let mut labels: Vec<(String, String, String)> = Vec::new();
+    if let Some(triggers) = triggers {
+        for entry in triggers.value().split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+            let parsed = entry.split_once(':').and_then(|(edge, method)| {
+                let (from, to) = edge.split_once("->")?;
+                Some((from.trim().to_string(), to.trim().to_string(), method.trim().to_string()))
+            });
+            let Some((from, to, method)) = parsed.filter(|(_, _, method)| !method.is_empty()) else {
+                return Err(syn::Error::new_spanned(
+                    triggers,
+                    format!("trigger `{}` must look like \"From->To: method\"", entry),
+                ));
+            };
+            let allowed = declared.contains(&from)
+                && declared.contains(&to)
+                && TRANSITIONS.contains(&(from.as_str(), to.as_str()));
+            if !allowed {
+                return Err(syn::Error::new_spanned(
+                    triggers,
+                    format!("trigger `{}` is not an allowed transition between {} variants", entry, enum_name),
+                ));
+            }
+            labels.push((from, to, method));
+        }
+    }
+    
+    let mut dot = format!("digraph {} {{\n", enum_name);
+    for variant in declared {
+        dot.push_str(&format!("    \"{}\";\n", variant));
+    }
+    for (from, to) in TRANSITIONS {
+        if !declared.iter().any(|v| v == from) || !declared.iter().any(|v| v == to) {
+            continue;
+        }
+        match labels.iter().find(|(f, t, _)| f == from && t == to) {
+            Some((_, _, method)) => dot.push_str(&format!("    \"{}\" -> \"{}\" [label=\"{}\"];\n", from, to, method)),
+            None => dot.push_str(&format!("    \"{}\" -> \"{}\";\n", from, to)),
+        }
+    }
+    dot.push_str("}\n");
+    Ok(dot)
+}
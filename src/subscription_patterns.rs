@@ -13,6 +13,26 @@ pub fn derive_subscription_entity(input: TokenStream) -> TokenStream {
     
     eprintln!("[pleme-codegen] SubscriptionEntity pattern applied to {} - saving ~250 lines", struct_name);
     
+    let per_second = match proration_per_second(&input.attrs) {
+        Ok(per_second) => per_second,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let units = if per_second {
+        quote! {
+            (
+                (self.current_period_end - self.current_period_start).num_seconds(),
+                (self.current_period_end - change_date).num_seconds(),
+            )
+        }
+    } else {
+        quote! {
+            (
+                (self.current_period_end - self.current_period_start).num_days(),
+                (self.current_period_end - change_date).num_days(),
+            )
+        }
+    };
+    
     let expanded = quote! {
         impl #struct_name {
             /// Check if subscription is currently active
@@ -59,19 +79,33 @@ pub fn derive_subscription_entity(input: TokenStream) -> TokenStream {
                 }
             }
             
-            /// Calculate prorated amount for immediate charge
-            pub fn calculate_proration(&self, new_price: rust_decimal::Decimal) -> rust_decimal::Decimal {
-                let now = chrono::Utc::now();
-                if now >= self.current_period_end {
-                    return new_price;
+            /// Net prorated amount for switching to `new_amount` at `change_date`: the
+            /// new plan's cost for the rest of the period minus the unused credit of the
+            /// current plan. Positive is a charge, negative a credit.
+            pub fn calculate_proration(
+                &self,
+                new_amount: rust_decimal::Decimal,
+                change_date: chrono::DateTime<chrono::Utc>,
+            ) -> Result<rust_decimal::Decimal, PaymentError> {
+                if change_date < self.current_period_start || change_date > self.current_period_end {
+                    return Err(PaymentError::ProrationOutsidePeriod {
+                        change_date,
+                        period_start: self.current_period_start,
+                        period_end: self.current_period_end,
+                    });
                 }
                 
-                let total_period = (self.current_period_end - self.current_period_start).num_seconds() as f64;
-                let remaining_period = (self.current_period_end - now).num_seconds() as f64;
-                let proration_ratio = remaining_period / total_period;
+                let (total, remaining) = #units;
+                if total <= 0 {
+                    return Ok(rust_decimal::Decimal::ZERO);
+                }
+                
+                let total = rust_decimal::Decimal::from(total);
+                let remaining = rust_decimal::Decimal::from(remaining);
+                let unused_credit = self.price * remaining / total;
+                let new_plan_cost = new_amount * remaining / total;
                 
-                let price_diff = new_price - self.price;
-                price_diff * rust_decimal::Decimal::from_f64_retain(proration_ratio).unwrap_or(rust_decimal::Decimal::ZERO)
+                Ok((new_plan_cost - unused_credit).round_dp(2))
             }
             
             /// Start trial period
@@ -299,4 +333,32 @@ pub fn derive_subscription_entity(input: TokenStream) -> TokenStream {
     };
     
     TokenStream::from(expanded)
-}
\ No newline at end of file
+}
+
+/// Parse `#[subscription(proration = "day" | "second")]`; `true` for per-second proration
+fn proration_per_second(attrs: &[syn::Attribute]) -> syn::Result<bool> {
+    let mut per_second = false;
+    
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("subscription")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("proration") {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                per_second = match value.value().as_str() {
+                    "day" => false,
+                    "second" => true,
+                    other => {
+                        return Err(syn::Error::new_spanned(
+                            &value,
+                            format!("unsupported proration `{}`; expected \"day\" or \"second\"", other),
+                        ))
+                    }
+                };
+            } else if meta.input.peek(syn::Token![=]) {
+                let _: syn::Expr = meta.value()?.parse()?;
+            }
+            Ok(())
+        })?;
+    }
+    
+    Ok(per_second)
+}
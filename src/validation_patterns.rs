@@ -6,19 +6,131 @@ use proc_macro::TokenStream;
 use quote::quote;
 use syn::{parse_macro_input, DeriveInput};
 
+/// A struct-level `#[validate(rule(expr = "...", message = "...", field = "..."))]` check
+struct CrossFieldRule {
+    expr: syn::Expr,
+    message: syn::LitStr,
+    /// Field the failure is reported against in `validate_all` (`_entity` when unset)
+    field: Option<syn::LitStr>,
+}
+
+/// Options parsed from the struct-level `#[validate(...)]` attributes
+#[derive(Default)]
+struct ValidateConfig {
+    rules: Vec<CrossFieldRule>,
+}
+
+impl ValidateConfig {
+    fn from_attrs(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut config = ValidateConfig::default();
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("validate")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rule") {
+                    let mut expr = None;
+                    let mut message = None;
+                    let mut field = None;
+                    meta.parse_nested_meta(|rule| {
+                        if rule.path.is_ident("expr") {
+                            let value: syn::LitStr = rule.value()?.parse()?;
+                            expr = Some(value.parse::<syn::Expr>()?);
+                        } else if rule.path.is_ident("message") {
+                            message = Some(rule.value()?.parse::<syn::LitStr>()?);
+                        } else if rule.path.is_ident("field") {
+                            field = Some(rule.value()?.parse::<syn::LitStr>()?);
+                        } else {
+                            return Err(rule.error("expected `expr`, `message` or `field`"));
+                        }
+                        Ok(())
+                    })?;
+                    match (expr, message) {
+                        (Some(expr), Some(message)) => config.rules.push(CrossFieldRule { expr, message, field }),
+                        _ => return Err(meta.error("`rule(...)` requires both `expr` and `message`")),
+                    }
+                } else if meta.input.peek(syn::Token![=]) {
+                    let _: syn::Expr = meta.value()?.parse()?;
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(config)
+    }
+}
+
 /// ValidatedEntity - Generate validation chains (saves ~40 lines per struct)
 pub fn derive_validated_entity(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let struct_name = &input.ident;
+    let validation_error = quote::format_ident!("{}ValidationError", struct_name);
     
     eprintln!("[pleme-codegen] ValidatedEntity pattern applied to {} - saving ~40 lines", struct_name);
     
+    // Fields annotated #[brazilian(...)] are validated through BrazilianEntity's field validators
+    let brazilian_checks = match crate::brazilian_patterns::brazilian_fields(&input) {
+        Ok(fields) if fields.is_empty() => quote! {},
+        Ok(_) => quote! { errors.extend(self.validate_brazilian_fields()); },
+        Err(err) => return err.to_compile_error().into(),
+    };
+    
+    // Cross-field rules run after the field-level checks
+    let config = match ValidateConfig::from_attrs(&input.attrs) {
+        Ok(config) => config,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let rule_checks = config.rules.iter().map(|CrossFieldRule { expr, message, .. }| {
+        quote! {
+            if !(#expr) {
+                errors.push(#message.to_string());
+            }
+        }
+    });
+    
+    let errors_binding = if brazilian_checks.is_empty() && config.rules.is_empty() {
+        quote! { errors }
+    } else {
+        quote! { mut errors }
+    };
+    
+    // validate_all reports the same checks per field instead of as "field: message" strings
+    let brazilian_fields = match crate::brazilian_patterns::brazilian_fields(&input) {
+        Ok(fields) => fields,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let field_checks_all = brazilian_fields.iter().map(|(field, _)| {
+        let ident = field.ident.as_ref().unwrap();
+        let field_name = ident.to_string();
+        let validator = quote::format_ident!("validate_{}_field", ident);
+        let prefix = format!("{}: ", field_name);
+        quote! {
+            if let Err(message) = self.#validator() {
+                let message = message.strip_prefix(#prefix).unwrap_or(&message).to_string();
+                errors.push(#validation_error { field: #field_name.to_string(), message });
+            }
+        }
+    });
+    let rule_checks_all = config.rules.iter().map(|CrossFieldRule { expr, message, field }| {
+        let field = field.as_ref().map(|field| field.value()).unwrap_or_else(|| "_entity".to_string());
+        quote! {
+            if !(#expr) {
+                errors.push(#validation_error { field: #field.to_string(), message: #message.to_string() });
+            }
+        }
+    });
+    let all_errors_binding = if brazilian_fields.is_empty() && config.rules.is_empty() {
+        quote! { errors }
+    } else {
+        quote! { mut errors }
+    };
+    
     // For now, generate a simplified version that doesn't use reflection
     let expanded = quote! {
         impl #struct_name {
             /// Comprehensive validation with detailed error reporting
             pub fn validate(&self) -> Result<(), Vec<String>> {
-                let errors: Vec<String> = Vec::new();
+                let #errors_binding: Vec<String> = Vec::new();
+                #brazilian_checks
+                #(#rule_checks)*
                 
                 tracing::debug!(
                     entity = %stringify!(#struct_name),
@@ -38,6 +150,24 @@ pub fn derive_validated_entity(input: TokenStream) -> TokenStream {
                 }
             }
             
+            /// Run every field and cross-field check, reporting each failure with its field
+            pub fn validate_all(&self) -> Result<(), Vec<#validation_error>> {
+                let #all_errors_binding: Vec<#validation_error> = Vec::new();
+                #(#field_checks_all)*
+                #(#rule_checks_all)*
+                
+                if errors.is_empty() {
+                    Ok(())
+                } else {
+                    tracing::warn!(
+                        entity = %stringify!(#struct_name),
+                        error_count = %errors.len(),
+                        "Validation failed"
+                    );
+                    Err(errors)
+                }
+            }
+            
             /// Basic email validation
             pub fn is_valid_email(email: &str) -> bool {
                 email.contains('@') && 
@@ -128,6 +258,22 @@ pub fn derive_validated_entity(input: TokenStream) -> TokenStream {
                 digits[13] == digit2
             }
         }
+        
+        /// A failed validation check, attributed to a field
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct #validation_error {
+            /// Field name as declared on the struct (`_entity` for unattributed rules)
+            pub field: String,
+            pub message: String,
+        }
+        
+        impl std::fmt::Display for #validation_error {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}: {}", self.field, self.message)
+            }
+        }
+        
+        impl std::error::Error for #validation_error {}
     };
     
     TokenStream::from(expanded)
@@ -11,6 +11,53 @@ pub fn derive_wallet_entity(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let struct_name = &input.ident;
     
+    let config = match WalletConfig::from_attrs(&input.attrs) {
+        Ok(config) => config,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let table_name = &config.table_name;
+    let currency = &config.currency;
+    let ledger_entry_name = quote::format_ident!("{}LedgerEntry", struct_name);
+    
+    if config.ledger && !has_named_field(&input, "ledger") {
+        return syn::Error::new_spanned(
+            struct_name,
+            format!("#[wallet(ledger)] requires a `ledger: Vec<{}>` field", ledger_entry_name),
+        )
+        .to_compile_error()
+        .into();
+    }
+    
+    let ledger_record = if config.ledger {
+        quote! {
+            self.ledger.push(#ledger_entry_name {
+                reference: reference.to_string(),
+                amount,
+                balance_after: self.balance,
+                pending_balance_after: self.pending_balance,
+                recorded_at: self.updated_at,
+            });
+        }
+    } else {
+        quote! {}
+    };
+    
+    let ledger_entry_type = if config.ledger {
+        quote! {
+            /// Settlement recorded by `settle_pending` when `#[wallet(ledger)]` is enabled
+            #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+            pub struct #ledger_entry_name {
+                pub reference: String,
+                pub amount: rust_decimal::Decimal,
+                pub balance_after: rust_decimal::Decimal,
+                pub pending_balance_after: rust_decimal::Decimal,
+                pub recorded_at: chrono::DateTime<chrono::Utc>,
+            }
+        }
+    } else {
+        quote! {}
+    };
+    
     eprintln!("[pleme-codegen] WalletEntity pattern applied to {} - saving ~200 lines", struct_name);
     
     let expanded = quote! {
@@ -79,6 +126,65 @@ pub fn derive_wallet_entity(input: TokenStream) -> TokenStream {
                 Ok(())
             }
             
+            /// Add balance after checking `currency` matches the wallet's
+            pub fn add_balance_checked(&mut self, amount: rust_decimal::Decimal, currency: &str, description: &str) -> Result<(), PaymentError> {
+                self.assert_currency(currency)?;
+                self.add_balance(amount, description)
+            }
+            
+            /// Subtract balance after checking `currency` matches the wallet's
+            pub fn subtract_balance_checked(&mut self, amount: rust_decimal::Decimal, currency: &str, description: &str) -> Result<(), PaymentError> {
+                self.assert_currency(currency)?;
+                self.subtract_balance(amount, description)
+            }
+            
+            /// Move `amount` from this wallet to `other` in one call: both balances
+            /// change or, if crediting `other` fails, this wallet is restored
+            pub fn transfer_to(&mut self, other: &mut Self, amount: rust_decimal::Decimal, description: &str) -> Result<(), PaymentError> {
+                if self.id == other.id {
+                    return Err(PaymentError::SelfTransfer);
+                }
+                
+                if amount <= rust_decimal::Decimal::ZERO {
+                    return Err(PaymentError::InvalidAmount);
+                }
+                
+                if self.balance < amount {
+                    return Err(PaymentError::InsufficientFunds);
+                }
+                
+                let debited = (self.balance, self.lifetime_spending, self.updated_at);
+                let now = chrono::Utc::now();
+                self.balance -= amount;
+                self.lifetime_spending += amount;
+                self.updated_at = now;
+                
+                let credited = other.balance.checked_add(amount)
+                    .zip(other.lifetime_earnings.checked_add(amount));
+                let Some((balance, lifetime_earnings)) = credited else {
+                    (self.balance, self.lifetime_spending, self.updated_at) = debited;
+                    return Err(PaymentError::TransactionFailed(format!(
+                        "crediting wallet {} overflowed; transfer rolled back",
+                        other.id
+                    )));
+                };
+                other.balance = balance;
+                other.lifetime_earnings = lifetime_earnings;
+                other.updated_at = now;
+                
+                tracing::info!(
+                    from_wallet_id = %self.id,
+                    to_wallet_id = %other.id,
+                    amount = %amount,
+                    from_balance_after = %self.balance,
+                    to_balance_after = %other.balance,
+                    description = %description,
+                    "Balance transferred between wallets"
+                );
+                
+                Ok(())
+            }
+            
             /// Add tokens to wallet
             pub fn add_tokens(&mut self, tokens: i64, description: &str) -> Result<(), PaymentError> {
                 if tokens < 0i64 {
@@ -177,6 +283,14 @@ pub fn derive_wallet_entity(input: TokenStream) -> TokenStream {
                 Ok(())
             }
             
+            /// Settle part or all of the pending balance into available funds,
+            /// recording the settlement `reference` in the ledger when enabled
+            pub fn settle_pending(&mut self, amount: rust_decimal::Decimal, reference: &str) -> Result<(), PaymentError> {
+                self.clear_pending(amount, reference)?;
+                #ledger_record
+                Ok(())
+            }
+            
             /// Cancel pending balance
             pub fn cancel_pending(&mut self, amount: rust_decimal::Decimal, description: &str) -> Result<(), PaymentError> {
                 if amount <= rust_decimal::Decimal::ZERO {
@@ -300,6 +414,101 @@ pub fn derive_wallet_entity(input: TokenStream) -> TokenStream {
             pub fn is_active(&self) -> bool {
                 !self.locked
             }
+            
+            /// ISO 4217 currency of every balance in this wallet
+            pub const CURRENCY: &'static str = #currency;
+            
+            /// Reject amounts denominated in any currency other than `CURRENCY`
+            pub fn assert_currency(&self, code: &str) -> Result<(), PaymentError> {
+                if code.eq_ignore_ascii_case(Self::CURRENCY) {
+                    return Ok(());
+                }
+                
+                tracing::warn!(
+                    wallet_id = %self.id,
+                    expected = %Self::CURRENCY,
+                    actual = %code,
+                    "Currency mismatch on wallet operation"
+                );
+                Err(PaymentError::CurrencyMismatch {
+                    expected: Self::CURRENCY.to_string(),
+                    actual: code.to_string(),
+                })
+            }
+            
+            /// Database table holding the wallet balances
+            pub const WALLET_TABLE: &'static str = #table_name;
+            
+            /// Atomic balance update: `$1` is the signed delta, `$2` the wallet id.
+            /// The guard lets the database reject overdrafts without a row lock.
+            pub fn atomic_balance_update_query() -> String {
+                format!(
+                    "UPDATE {} SET balance = balance + $1 WHERE id = $2 AND balance + $1 >= 0 RETURNING balance",
+                    Self::WALLET_TABLE
+                )
+            }
+            
+            /// Map the `RETURNING balance` row of an atomic update to the new balance
+            pub fn resolve_atomic_balance_update(
+                returned: Option<rust_decimal::Decimal>,
+            ) -> Result<rust_decimal::Decimal, PaymentError> {
+                returned.ok_or(PaymentError::InsufficientFunds)
+            }
+            
+            /// Apply a signed balance delta in a single statement, returning the new balance
+            pub async fn apply_balance_delta(
+                pool: &sqlx::PgPool,
+                wallet_id: uuid::Uuid,
+                delta: rust_decimal::Decimal,
+            ) -> Result<rust_decimal::Decimal, PaymentError> {
+                let returned: Option<rust_decimal::Decimal> = sqlx::query_scalar(&Self::atomic_balance_update_query())
+                    .bind(delta)
+                    .bind(wallet_id)
+                    .fetch_optional(pool)
+                    .await
+                    .map_err(|e| PaymentError::TransactionFailed(e.to_string()))?;
+                
+                let balance = Self::resolve_atomic_balance_update(returned);
+                match &balance {
+                    Ok(balance_after) => tracing::info!(
+                        wallet_id = %wallet_id,
+                        delta = %delta,
+                        balance_after = %balance_after,
+                        "Wallet balance updated atomically"
+                    ),
+                    Err(_) => tracing::warn!(
+                        wallet_id = %wallet_id,
+                        delta = %delta,
+                        "Atomic balance update rejected - insufficient funds or unknown wallet"
+                    ),
+                }
+                
+                balance
+            }
+            
+            /// Atomically credit the wallet
+            pub async fn credit_atomic(
+                pool: &sqlx::PgPool,
+                wallet_id: uuid::Uuid,
+                amount: rust_decimal::Decimal,
+            ) -> Result<rust_decimal::Decimal, PaymentError> {
+                if amount <= rust_decimal::Decimal::ZERO {
+                    return Err(PaymentError::InvalidAmount);
+                }
+                Self::apply_balance_delta(pool, wallet_id, amount).await
+            }
+            
+            /// Atomically debit the wallet, failing with `InsufficientFunds` on overdraft
+            pub async fn debit_atomic(
+                pool: &sqlx::PgPool,
+                wallet_id: uuid::Uuid,
+                amount: rust_decimal::Decimal,
+            ) -> Result<rust_decimal::Decimal, PaymentError> {
+                if amount <= rust_decimal::Decimal::ZERO {
+                    return Err(PaymentError::InvalidAmount);
+                }
+                Self::apply_balance_delta(pool, wallet_id, -amount).await
+            }
         }
         
         /// Payout calculation result
@@ -324,7 +533,65 @@ pub fn derive_wallet_entity(input: TokenStream) -> TokenStream {
             pub pending_ratio: f64,
             pub last_activity: chrono::DateTime<chrono::Utc>,
         }
+        
+        #ledger_entry_type
     };
     
     TokenStream::from(expanded)
-}
\ No newline at end of file
+}
+
+/// Options parsed from `#[wallet(...)]`
+struct WalletConfig {
+    /// `table = "..."`, defaulting to `wallets`
+    table_name: String,
+    /// `ledger`: record settlements in the struct's `ledger` field
+    ledger: bool,
+    /// `currency = "..."`: ISO 4217 code of the balances, defaulting to `BRL`
+    currency: String,
+}
+
+impl WalletConfig {
+    fn from_attrs(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut config = WalletConfig {
+            table_name: "wallets".to_string(),
+            ledger: false,
+            currency: "BRL".to_string(),
+        };
+        
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("wallet")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("table") {
+                    let value: syn::LitStr = meta.value()?.parse()?;
+                    config.table_name = value.value();
+                } else if meta.path.is_ident("ledger") {
+                    config.ledger = true;
+                } else if meta.path.is_ident("currency") {
+                    let value: syn::LitStr = meta.value()?.parse()?;
+                    let code = value.value();
+                    if code.len() != 3 || !code.bytes().all(|b| b.is_ascii_uppercase()) {
+                        return Err(syn::Error::new_spanned(
+                            &value,
+                            format!("currency must be an ISO 4217 code like \"BRL\", got `{}`", code),
+                        ));
+                    }
+                    config.currency = code;
+                } else if meta.input.peek(syn::Token![=]) {
+                    let _: syn::Expr = meta.value()?.parse()?;
+                }
+                Ok(())
+            })?;
+        }
+        
+        Ok(config)
+    }
+}
+
+/// Whether the struct declares a named field called `name`
+fn has_named_field(input: &DeriveInput, name: &str) -> bool {
+    match &input.data {
+        syn::Data::Struct(syn::DataStruct { fields: syn::Fields::Named(fields), .. }) => {
+            fields.named.iter().any(|field| field.ident.as_ref().is_some_and(|ident| ident == name))
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,92 @@
+// Tests for the ArchitecturalMonitor derive
+use pleme_codegen::ArchitecturalMonitor;
+use std::sync::{Arc, Mutex};
+
+#[derive(ArchitecturalMonitor)]
+pub struct SettlementService;
+
+/// Subscriber recording the `operation` and `duration_ms` fields of every event
+#[derive(Clone, Default)]
+struct DurationRecorder {
+    events: Arc<Mutex<Vec<(String, u64)>>>,
+}
+
+/// Captures the operation name and duration of one event
+#[derive(Default)]
+struct DurationVisitor {
+    operation: Option<String>,
+    duration_ms: Option<u64>,
+}
+
+impl tracing::field::Visit for DurationVisitor {
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        match field.name() {
+            "operation" => self.operation = Some(format!("{:?}", value)),
+            "duration_ms" => self.duration_ms = format!("{:?}", value).parse().ok(),
+            _ => {}
+        }
+    }
+}
+
+impl tracing::Subscriber for DurationRecorder {
+    fn enabled(&self, _metadata: &tracing::Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+        tracing::span::Id::from_u64(1)
+    }
+
+    fn record(&self, _span: &tracing::span::Id, _values: &tracing::span::Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &tracing::span::Id, _follows: &tracing::span::Id) {}
+
+    fn event(&self, event: &tracing::Event<'_>) {
+        let mut visitor = DurationVisitor::default();
+        event.record(&mut visitor);
+        if let (Some(operation), Some(duration_ms)) = (visitor.operation, visitor.duration_ms) {
+            self.events.lock().unwrap().push((operation, duration_ms));
+        }
+    }
+
+    fn enter(&self, _span: &tracing::span::Id) {}
+
+    fn exit(&self, _span: &tracing::span::Id) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_monitor_async_times_the_whole_await() {
+        let recorder = DurationRecorder::default();
+        let _guard = tracing::subscriber::set_default(recorder.clone());
+
+        let settled = SettlementService
+            .monitor_async("settle_batch", || async {
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                42
+            })
+            .await;
+
+        assert_eq!(settled, 42);
+        let events = recorder.events.lock().unwrap().clone();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].0, "settle_batch");
+        assert!(events[0].1 >= 50, "recorded {}ms for a 50ms sleep", events[0].1);
+    }
+
+    #[test]
+    fn test_monitor_operation_records_sync_operations() {
+        let recorder = DurationRecorder::default();
+        let result = tracing::subscriber::with_default(recorder.clone(), || {
+            SettlementService.monitor_operation("reconcile", || "done")
+        });
+
+        assert_eq!(result, "done");
+        let events = recorder.events.lock().unwrap().clone();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].0, "reconcile");
+    }
+}
@@ -0,0 +1,121 @@
+// Smoke tests for the `bench` feature's generated bench_inputs()
+#![cfg(feature = "bench")]
+
+use pleme_codegen::{BrazilianTaxEntity, PaymentEntity, PaymentMethods, PixPayment};
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaymentStatus {
+    Pending,
+    Processing,
+    Completed,
+    Failed,
+    Refunded,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PaymentMethods)]
+pub enum PaymentMethod {
+    Pix,
+    Boleto,
+    CreditCard,
+    DebitCard,
+    TransferenciaTed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixKeyType {
+    Cpf,
+    Cnpj,
+    Email,
+    Phone,
+    Random,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PaymentError {
+    #[error("Invalid amount")]
+    InvalidAmount,
+    #[error("Invalid state transition from {from:?} to {to:?}")]
+    InvalidStateTransition { from: PaymentStatus, to: PaymentStatus },
+    #[error("Amount too low: minimum {min}, got {actual}")]
+    AmountTooLow { min: Decimal, actual: Decimal },
+    #[error("Amount too high: maximum {max}, got {actual}")]
+    AmountTooHigh { max: Decimal, actual: Decimal },
+    #[error("Amount has {actual} decimal places, currency allows {max}")]
+    InvalidScale { max: u32, actual: u32 },
+    #[error("Transaction failed: {0}")]
+    TransactionFailed(String),
+    #[error("Invalid PIX key: {reason}")]
+    InvalidPixKey { reason: String },
+    #[error("QR code generation failed: {reason}")]
+    QrCodeGenerationFailed { reason: String },
+    #[error("Invalid refund: {reason}")]
+    InvalidRefund { reason: String },
+}
+
+#[derive(Debug, Clone, PaymentEntity)]
+pub struct Payment {
+    pub id: uuid::Uuid,
+    pub amount: Decimal,
+    pub tax: Decimal,
+    pub status: PaymentStatus,
+    pub method: PaymentMethod,
+    pub failure_reason: Option<String>,
+    pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub failed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, PixPayment)]
+pub struct PixCheckout {
+    pub amount: Decimal,
+    pub pix_key: String,
+    pub pix_key_type: PixKeyType,
+    pub merchant_name: String,
+    pub end_to_end_id: Option<String>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, BrazilianTaxEntity)]
+pub struct Invoice {
+    pub subtotal: Decimal,
+    pub state: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payment_bench_inputs_are_valid() {
+        let inputs = Payment::bench_inputs();
+        assert!(!inputs.is_empty());
+        for payment in &inputs {
+            assert!(payment.validate_amount().is_ok());
+            assert_eq!(payment.status, PaymentStatus::Pending);
+        }
+    }
+
+    #[test]
+    fn test_pix_bench_inputs_produce_payloads() {
+        let inputs = PixCheckout::bench_inputs();
+        assert!(!inputs.is_empty());
+        for checkout in &inputs {
+            assert!(checkout.validate_pix_key().is_ok());
+            assert!(checkout.generate_qr_payload().starts_with("000201"));
+        }
+    }
+
+    #[test]
+    fn test_tax_bench_inputs_are_taxable() {
+        let inputs = Invoice::bench_inputs();
+        assert!(!inputs.is_empty());
+        for invoice in &inputs {
+            assert_eq!(invoice.state, "SP");
+            assert!(invoice.calculate_total_tax(invoice.subtotal, &invoice.state, false) > Decimal::ZERO);
+        }
+    }
+}
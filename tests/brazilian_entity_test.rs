@@ -0,0 +1,297 @@
+// Tests for the BrazilianEntity derive
+use pleme_codegen::{BrazilianEntity, ValidatedEntity};
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, BrazilianEntity)]
+struct Receipt;
+
+#[derive(Debug, Clone, BrazilianEntity)]
+#[brazilian(cnpj_alphanumeric)]
+struct Supplier;
+
+#[derive(Debug, Clone, BrazilianEntity)]
+#[brazilian(rg_checkdigit)]
+struct KycDocument;
+
+#[derive(Debug, Clone, BrazilianEntity, ValidatedEntity)]
+pub struct Customer {
+    pub name: String,
+    #[brazilian(cpf)]
+    pub cpf: String,
+    #[brazilian(cep)]
+    pub cep: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(amount: &str) -> String {
+        Receipt::amount_in_words(Decimal::from_str(amount).unwrap())
+    }
+
+    #[test]
+    fn test_amount_in_words_singular() {
+        assert_eq!(words("1.00"), "um real");
+        assert_eq!(words("0.01"), "um centavo");
+        assert_eq!(words("1.01"), "um real e um centavo");
+    }
+
+    #[test]
+    fn test_amount_in_words_centavos_only() {
+        assert_eq!(words("0.50"), "cinquenta centavos");
+        assert_eq!(words("0.00"), "zero reais");
+    }
+
+    #[test]
+    fn test_amount_in_words_thousands() {
+        assert_eq!(
+            words("1234.56"),
+            "mil duzentos e trinta e quatro reais e cinquenta e seis centavos"
+        );
+        assert_eq!(words("1100.00"), "mil e cem reais");
+        assert_eq!(words("2015.00"), "dois mil e quinze reais");
+        assert_eq!(words("100.00"), "cem reais");
+        assert_eq!(words("101.00"), "cento e um reais");
+    }
+
+    #[test]
+    fn test_amount_in_words_millions() {
+        assert_eq!(words("1000000.00"), "um milhão de reais");
+        assert_eq!(words("2500000.00"), "dois milhões e quinhentos mil reais");
+        assert_eq!(words("1000050.10"), "um milhão e cinquenta reais e dez centavos");
+    }
+
+    #[test]
+    fn test_amount_in_words_beyond_trilhoes() {
+        assert_eq!(words("1000000000000000"), "um quatrilhão de reais");
+        assert_eq!(words("1500000000000000"), "um quatrilhão e quinhentos trilhões de reais");
+        assert_eq!(words("2000000000000000000000"), "dois sextilhões de reais");
+        // Past u64, which used to collapse to "zero reais"
+        assert_eq!(words("20000000000000000000.50"), "vinte quintilhões de reais e cinquenta centavos");
+        assert!(Receipt::amount_in_words(Decimal::MAX).starts_with("setenta e nove octilhões duzentos e vinte e oito septilhões"));
+    }
+
+    #[test]
+    fn test_amount_from_words_round_trips() {
+        for amount in [
+            "0.00", "0.01", "1.00", "1.01", "0.50", "100.00", "101.00", "1100.00", "1234.56", "2015.00",
+            "1000000.00", "1000050.10", "2500000.00", "1001000.00", "987654321.99", "1500000000000000", "-42.42",
+        ] {
+            let amount = Decimal::from_str(amount).unwrap();
+            assert_eq!(Receipt::amount_from_words(&Receipt::amount_in_words(amount)), Some(amount), "{}", amount);
+        }
+        assert_eq!(Receipt::amount_from_words(&Receipt::amount_in_words(Decimal::MAX)), Some(Decimal::MAX));
+        assert_eq!(Receipt::amount_from_words("  Mil e Cem Reais "), Some(Decimal::from(1100)));
+    }
+
+    #[test]
+    fn test_amount_from_words_rejects_malformed_text() {
+        for text in [
+            "",
+            "cinquenta",
+            "vinte trinta reais",
+            "cem mil mil reais",
+            "mil milhões de reais",
+            "cento e duzentos centavos",
+            "dez reais e quarenta dólares",
+            "reais",
+            "dez reais e dez reais",
+        ] {
+            assert_eq!(Receipt::amount_from_words(text), None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_cnpj_root_groups_matriz_and_filial() {
+        assert_eq!(Receipt::cnpj_root("11.222.333/0001-81").as_deref(), Some("11222333"));
+        assert!(Receipt::same_corporate_group("11.222.333/0001-81", "11222333000262"));
+        assert!(!Receipt::same_corporate_group("11.222.333/0001-81", "33.445.566/0001-86"));
+
+        // Invalid CNPJs never group, even with a matching root
+        assert_eq!(Receipt::cnpj_root("11.222.333/0002-00"), None);
+        assert!(!Receipt::same_corporate_group("11.222.333/0001-81", "11.222.333/0002-00"));
+        assert_eq!(Supplier::cnpj_root("12.abc.345/01de-35").as_deref(), Some("12ABC345"));
+    }
+
+    #[test]
+    fn test_cnpj_alphanumeric_valid() {
+        assert!(Supplier::validate_cnpj("12.ABC.345/01DE-35"));
+        assert!(Supplier::validate_cnpj("12abc34501de35"));
+        assert_eq!(Supplier::format_cnpj("12abc34501de35"), "12.ABC.345/01DE-35");
+
+        // Legacy-mode entities keep rejecting letters
+        assert!(!Receipt::validate_cnpj("12.ABC.345/01DE-35"));
+    }
+
+    #[test]
+    fn test_cnpj_alphanumeric_accepts_legacy_numeric() {
+        assert!(Supplier::validate_cnpj("11.222.333/0001-81"));
+        assert!(Receipt::validate_cnpj("11.222.333/0001-81"));
+    }
+
+    #[test]
+    fn test_cnpj_alphanumeric_invalid_check_digit() {
+        assert!(!Supplier::validate_cnpj("12.ABC.345/01DE-36"));
+        assert!(!Supplier::validate_cnpj("12.ABC.345/01DE-3A"));
+    }
+
+    #[test]
+    fn test_strict_cpf_accepts_mask_or_bare_digits() {
+        assert!(Receipt::validate_cpf_strict("123.456.789-09"));
+        assert!(Receipt::validate_cpf_strict("12345678909"));
+        // Still checksum-validated
+        assert!(!Receipt::validate_cpf_strict("123.456.789-00"));
+    }
+
+    #[test]
+    fn test_strict_cpf_rejects_malformed_separators() {
+        for malformed in ["123/456#789!09", "123.456.789.09", "123-456-789-09", "123.456.78909", " 12345678909", "12.345.678/9-09"] {
+            assert!(Receipt::validate_cpf(malformed), "lenient check accepts {}", malformed);
+            assert!(!Receipt::validate_cpf_strict(malformed), "strict check rejects {}", malformed);
+        }
+    }
+
+    #[test]
+    fn test_strict_cnpj_accepts_mask_or_bare_digits() {
+        assert!(Receipt::validate_cnpj_strict("11.222.333/0001-81"));
+        assert!(Receipt::validate_cnpj_strict("11222333000181"));
+        assert!(Supplier::validate_cnpj_strict("12.ABC.345/01DE-35"));
+        assert!(Supplier::validate_cnpj_strict("12ABC34501DE35"));
+    }
+
+    #[test]
+    fn test_strict_cnpj_rejects_malformed_separators() {
+        for malformed in ["11.222.333.0001-81", "11/222/333/0001/81", "11.222.333/0001.81", "112.223.330-00181"] {
+            assert!(Receipt::validate_cnpj(malformed), "lenient check accepts {}", malformed);
+            assert!(!Receipt::validate_cnpj_strict(malformed), "strict check rejects {}", malformed);
+        }
+        assert!(!Supplier::validate_cnpj_strict("12.ABC.345.01DE-35"));
+    }
+
+    #[test]
+    fn test_cpf_batch_reports_each_document_in_order() {
+        let docs: Vec<String> = ["123.456.789-09", "123.456.789-00", "1234567890", "52998224725", "111.111.111-11"]
+            .iter()
+            .map(|doc| doc.to_string())
+            .collect();
+
+        let results = Receipt::validate_cpf_batch(&docs);
+        assert_eq!(
+            results,
+            vec![
+                ("123.456.789-09".to_string(), true),
+                ("123.456.789-00".to_string(), false),
+                ("1234567890".to_string(), false),
+                ("52998224725".to_string(), true),
+                ("111.111.111-11".to_string(), false),
+            ]
+        );
+        for (doc, valid) in &results {
+            assert_eq!(Receipt::validate_cpf(doc), *valid);
+        }
+    }
+
+    #[test]
+    fn test_cnpj_batch_reports_each_document_in_order() {
+        let docs: Vec<String> = ["11.222.333/0001-81", "11.222.333/0001-82", "11.222.333/0001", "12.ABC.345/01DE-35"]
+            .iter()
+            .map(|doc| doc.to_string())
+            .collect();
+
+        let flags = |results: Vec<(String, bool)>| results.into_iter().map(|(_, valid)| valid).collect::<Vec<_>>();
+        assert_eq!(flags(Receipt::validate_cnpj_batch(&docs)), vec![true, false, false, false]);
+        assert_eq!(flags(Supplier::validate_cnpj_batch(&docs)), vec![true, false, false, true]);
+        assert!(Receipt::validate_cnpj_batch(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_rg_with_x_check_digit() {
+        assert!(KycDocument::validate_rg("12.345.606-X"));
+        assert!(KycDocument::validate_rg("12345606x"));
+        assert_eq!(KycDocument::format_rg("12345606x"), "12.345.606-X");
+    }
+
+    #[test]
+    fn test_rg_all_digits() {
+        assert!(Receipt::validate_rg("123456782"));
+        assert!(KycDocument::validate_rg("12.345.678-2"));
+        assert_eq!(Receipt::format_rg("123456782"), "12.345.678-2");
+
+        // Only the opt-in checks the digit
+        assert!(Receipt::validate_rg("12.345.678-3"));
+        assert!(!KycDocument::validate_rg("12.345.678-3"));
+    }
+
+    #[test]
+    fn test_rg_too_short() {
+        assert!(!Receipt::validate_rg("1234567-8"));
+        assert!(!KycDocument::validate_rg("1234567-8"));
+        assert_eq!(Receipt::format_rg("1234567-8"), "1234567-8");
+        assert!(!Receipt::validate_rg("12.34A.678-2"));
+    }
+
+    #[test]
+    fn test_pis_valid() {
+        assert!(Receipt::validate_pis("170.04425.81-7"));
+        assert!(Receipt::validate_pis("17004425817"));
+        assert_eq!(Receipt::format_pis("17004425817"), "170.04425.81-7");
+    }
+
+    #[test]
+    fn test_pis_wrong_checksum() {
+        assert!(!Receipt::validate_pis("170.04425.81-8"));
+        assert!(!Receipt::validate_pis("111.11111.11-1"));
+    }
+
+    #[test]
+    fn test_pis_short_input() {
+        assert!(!Receipt::validate_pis("170.04425.81"));
+        assert_eq!(Receipt::format_pis("1700442581"), "1700442581");
+    }
+
+    #[test]
+    fn test_to_e164_mobile_without_country_code() {
+        assert_eq!(Receipt::to_e164("(11) 98765-4321").as_deref(), Some("+5511987654321"));
+        assert_eq!(Receipt::to_e164("011 98765-4321").as_deref(), Some("+5511987654321"));
+        // DDD 55 (RS) is not mistaken for the country code
+        assert_eq!(Receipt::to_e164("(55) 99123-4567").as_deref(), Some("+5555991234567"));
+    }
+
+    #[test]
+    fn test_to_e164_mobile_with_country_code() {
+        assert_eq!(Receipt::to_e164("+55 (21) 99876-5432").as_deref(), Some("+5521998765432"));
+        assert_eq!(Receipt::to_e164("5521998765432").as_deref(), Some("+5521998765432"));
+        assert_eq!(Receipt::to_e164("(21) 3456-7890").as_deref(), Some("+552134567890"));
+    }
+
+    #[test]
+    fn test_to_e164_rejects_invalid_numbers() {
+        assert_eq!(Receipt::to_e164("12345"), None);
+        assert_eq!(Receipt::to_e164("+1 415 555 2671"), None);
+        assert_eq!(Receipt::to_e164("(11) 88765-4321"), None);
+        assert_eq!(Receipt::to_e164("(01) 98765-4321"), None);
+    }
+
+    #[test]
+    fn test_validate_runs_brazilian_field_validators() {
+        let mut customer = Customer {
+            name: "Ana Souza".to_string(),
+            cpf: "123.456.789-09".to_string(),
+            cep: None,
+        };
+        assert!(customer.validate().is_ok());
+
+        customer.cpf = "123.456.789-00".to_string();
+        assert_eq!(customer.validate_cpf_field(), Err("cpf: invalid CPF".to_string()));
+        assert_eq!(customer.validate(), Err(vec!["cpf: invalid CPF".to_string()]));
+
+        // Errors from every annotated field are aggregated
+        customer.cep = Some("1234".to_string());
+        assert_eq!(
+            customer.validate(),
+            Err(vec!["cpf: invalid CPF".to_string(), "cep: invalid CEP".to_string()])
+        );
+    }
+}
@@ -0,0 +1,225 @@
+// Tests for the BrazilianPaymentEntity derive
+use pleme_codegen::{BrazilianEntity, BrazilianPaymentEntity};
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaymentStatus {
+    Pending,
+    Processing,
+    Completed,
+    Failed,
+    Refunded,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, BrazilianEntity, BrazilianPaymentEntity)]
+#[brazilian_payment(icms_rate = 0.18, pis_rate = 0.0165, cofins_rate = 0.076)]
+pub struct Sale {
+    pub id: uuid::Uuid,
+    pub amount: Decimal,
+    pub status: PaymentStatus,
+    pub customer_document: Option<String>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+// Several derives in one module each get their own supporting types
+#[derive(Debug, Clone, BrazilianEntity, BrazilianPaymentEntity)]
+#[brazilian_payment(regime = "simples")]
+pub struct ShopSale {
+    pub id: uuid::Uuid,
+    pub amount: Decimal,
+    pub status: PaymentStatus,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, BrazilianEntity, BrazilianPaymentEntity)]
+#[brazilian_payment(regime = "simples", anexo = "III")]
+pub struct ServiceSale {
+    pub id: uuid::Uuid,
+    pub amount: Decimal,
+    pub status: PaymentStatus,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sale(amount: &str) -> Sale {
+        Sale {
+            id: uuid::Uuid::new_v4(),
+            amount: Decimal::from_str(amount).unwrap(),
+            status: PaymentStatus::Pending,
+            customer_document: Some("123.456.789-09".to_string()),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_extract_taxes_from_inclusive_round_trips() {
+        for price in ["127.35", "9.99", "1000.00", "0.01"] {
+            let breakdown = sale(price).extract_taxes_from_inclusive();
+            let total = Decimal::from_str(price).unwrap();
+
+            assert_eq!(breakdown.gross_amount, total);
+            assert_eq!(breakdown.net_amount + breakdown.total_taxes, total);
+            assert_eq!(breakdown.total_taxes, breakdown.icms_amount + breakdown.pis_amount + breakdown.cofins_amount);
+        }
+    }
+
+    #[test]
+    fn test_extract_taxes_from_inclusive_recovers_net_base() {
+        // 100.00 net plus 27.25% of taxes is sold for 127.25
+        let breakdown = sale("127.25").extract_taxes_from_inclusive();
+
+        assert_eq!(breakdown.net_amount, Decimal::from_str("100.00").unwrap());
+        assert_eq!(breakdown.icms_amount, Decimal::from_str("18.00").unwrap());
+        assert_eq!(breakdown.pis_amount, Decimal::from_str("1.65").unwrap());
+        assert_eq!(breakdown.cofins_amount, Decimal::from_str("7.60").unwrap());
+
+        // Re-adding the embedded taxes on top of the base yields the shelf price
+        let re_added = breakdown.net_amount * (Decimal::ONE + breakdown.icms_rate + breakdown.pis_rate + breakdown.cofins_rate);
+        assert!((re_added - Decimal::from_str("127.25").unwrap()).abs() <= Decimal::from_str("0.01").unwrap());
+        assert_eq!(breakdown.currency, "BRL");
+    }
+
+    /// Banco do Brasil example: 1.00 due 2007-12-31
+    fn boleto(due_date: chrono::NaiveDate, amount: &str) -> SaleBoletoData {
+        SaleBoletoData {
+            bank_code: "001",
+            currency_code: "9",
+            agency: "4481",
+            account: "606068-0",
+            wallet: "18",
+            free_field: "0500940144816060680935031".to_string(),
+            our_number: "50094014481606068".to_string(),
+            document_number: "1".to_string(),
+            due_date: due_date.and_hms_opt(15, 0, 0).unwrap().and_utc(),
+            amount: Decimal::from_str(amount).unwrap(),
+            payer_name: "Cliente".to_string(),
+            payer_document: "123.456.789-09".to_string(),
+            instructions: Vec::new(),
+        }
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> chrono::NaiveDate {
+        chrono::NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_boleto_barcode_and_linha_digitavel_match_known_example() {
+        let boleto = boleto(date(2007, 12, 31), "1.00");
+
+        assert_eq!(boleto.boleto_barcode().unwrap(), "00193373700000001000500940144816060680935031");
+        // 00190.50095 40144.816069 06809.350314 3 37370000000100
+        assert_eq!(
+            boleto.boleto_linha_digitavel().unwrap(),
+            "00190500954014481606906809350314337370000000100"
+        );
+    }
+
+    #[test]
+    fn test_boleto_due_date_factor_restarts_after_9999() {
+        // 2025-02-21 is factor 9999, the next day starts over at 1000
+        let last = boleto(date(2025, 2, 21), "1.00").boleto_barcode().unwrap();
+        let first = boleto(date(2025, 2, 22), "1.00").boleto_barcode().unwrap();
+
+        assert_eq!(&last[5..9], "9999");
+        assert_eq!(&first[5..9], "1000");
+        assert_eq!(&first[9..19], "0000000100");
+    }
+
+    #[test]
+    fn test_boleto_rejects_malformed_input() {
+        let mut short_free_field = boleto(date(2007, 12, 31), "1.00");
+        short_free_field.free_field.pop();
+        assert!(matches!(short_free_field.boleto_barcode(), Err(SalePaymentError::InvalidBoleto(_))));
+
+        let fractional_cents = boleto(date(2007, 12, 31), "1.005");
+        assert!(matches!(fractional_cents.boleto_linha_digitavel(), Err(SalePaymentError::InvalidAmount(_))));
+
+        let before_factors = boleto(date(1999, 1, 1), "1.00");
+        assert!(matches!(before_factors.boleto_barcode(), Err(SalePaymentError::InvalidBoleto(_))));
+    }
+
+    #[test]
+    fn test_generated_boleto_produces_valid_barcode() {
+        let boleto = sale("150.00").generate_boleto().unwrap();
+        let barcode = boleto.boleto_barcode().unwrap();
+        let line = boleto.boleto_linha_digitavel().unwrap();
+
+        assert_eq!(barcode.len(), 44);
+        assert_eq!(line.len(), 47);
+        assert!(barcode.starts_with("3419"));
+        assert!(barcode.ends_with(&boleto.free_field));
+        assert_eq!(&barcode[9..19], "0000015000");
+    }
+
+    #[test]
+    fn test_pix_confirmation_uses_generated_accessors() {
+        let mut payment = sale("50.00");
+        payment.process_pix_confirmation(&"E".repeat(32), "psp-1").unwrap();
+
+        assert_eq!(payment.status, PaymentStatus::Completed);
+        assert!(payment.generate_pix_qr_code().unwrap().contains("amount=50.00"));
+
+        let receipt = payment.generate_brazilian_receipt().unwrap();
+        assert_eq!(receipt.status, "Concluído");
+        assert_eq!(receipt.customer_document, "123.456.789-09");
+    }
+
+    fn brl(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn test_simples_nacional_selects_anexo_bracket() {
+        let sale = ShopSale {
+            id: uuid::Uuid::new_v4(),
+            amount: brl("1000.00"),
+            status: PaymentStatus::Pending,
+            updated_at: chrono::Utc::now(),
+        };
+
+        // First bracket: the nominal 4% with no deduction
+        let first = sale.calculate_simples_nacional(brl("150000")).unwrap();
+        assert_eq!(first.das_rate, brl("0.04"));
+        assert_eq!(first.das_amount, brl("40.00"));
+        assert_eq!(first.total_taxes, first.das_amount);
+        assert_eq!(first.net_amount, brl("960.00"));
+        assert_eq!(first.icms_amount + first.pis_amount + first.cofins_amount, Decimal::ZERO);
+
+        // Third bracket: (500000 * 9.5% - 13860) / 500000
+        let third = sale.calculate_simples_nacional(brl("500000")).unwrap();
+        assert_eq!(third.das_rate, brl("0.06728"));
+        assert_eq!(third.das_amount, brl("67.28"));
+    }
+
+    #[test]
+    fn test_simples_nacional_uses_configured_anexo() {
+        let sale = ServiceSale {
+            id: uuid::Uuid::new_v4(),
+            amount: brl("1000.00"),
+            status: PaymentStatus::Pending,
+            updated_at: chrono::Utc::now(),
+        };
+
+        // Anexo III second bracket: (300000 * 11.2% - 9360) / 300000
+        let breakdown = sale.calculate_simples_nacional(brl("300000")).unwrap();
+        assert_eq!(breakdown.das_rate, brl("0.0808"));
+        assert_eq!(breakdown.das_amount, brl("80.80"));
+
+        assert!(matches!(
+            sale.calculate_simples_nacional(brl("5000000")),
+            Err(ServiceSalePaymentError::TaxCalculationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_lucro_presumido_breakdown_has_no_das() {
+        let breakdown = sale("100.00").calculate_brazilian_taxes().unwrap();
+        assert_eq!(breakdown.das_amount, Decimal::ZERO);
+        assert_eq!(breakdown.icms_amount, brl("18.00"));
+    }
+}
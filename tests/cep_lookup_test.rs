@@ -0,0 +1,94 @@
+// Tests for the generated lookup_cep() of #[brazilian(cep_lookup)]
+use pleme_codegen::BrazilianEntity;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+#[derive(Debug, Clone, BrazilianEntity)]
+#[brazilian(cep_lookup)]
+pub struct CheckoutAddress;
+
+/// Serve canned ViaCEP responses on a local port; returns the base URL
+async fn start_viacep_stub() -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        loop {
+            let Ok((mut socket, _)) = listener.accept().await else { return };
+            tokio::spawn(async move {
+                let mut buf = vec![0u8; 4096];
+                let n = socket.read(&mut buf).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]);
+                let path = request.split_whitespace().nth(1).unwrap_or_default().to_string();
+
+                let (status, body) = match path.as_str() {
+                    "/ws/01001000/json/" => ("200 OK", r#"{"cep":"01001-000","logradouro":"Praça da Sé","bairro":"Sé","localidade":"São Paulo","uf":"SP"}"#),
+                    "/ws/99999999/json/" => ("200 OK", r#"{"erro":true}"#),
+                    "/ws/22222222/json/" => {
+                        // Never answer, so only the client timeout ends the request
+                        tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+                        return;
+                    }
+                    _ => ("404 Not Found", ""),
+                };
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+
+    format!("http://{}/ws", addr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_lookup_cep_against_configured_endpoint() {
+        std::env::set_var("CEP_LOOKUP_URL", start_viacep_stub().await);
+
+        let address = CheckoutAddress::lookup_cep("01001-000").await.unwrap();
+        assert_eq!(
+            address,
+            CheckoutAddressCepAddress {
+                street: "Praça da Sé".to_string(),
+                neighborhood: "Sé".to_string(),
+                city: "São Paulo".to_string(),
+                state: "SP".to_string(),
+                cep: "01001-000".to_string(),
+            }
+        );
+
+        // ViaCEP's `{"erro": true}` and plain 404s are both "not found"
+        assert_eq!(
+            CheckoutAddress::lookup_cep("99999-999").await,
+            Err(CheckoutAddressCepLookupError::CepNotFound("99999999".to_string()))
+        );
+        assert_eq!(
+            CheckoutAddress::lookup_cep("12345678").await,
+            Err(CheckoutAddressCepLookupError::CepNotFound("12345678".to_string()))
+        );
+
+        // Malformed CEPs never reach the network
+        assert_eq!(
+            CheckoutAddress::lookup_cep("0100").await,
+            Err(CheckoutAddressCepLookupError::InvalidCep("0100".to_string()))
+        );
+
+        // A stalled endpoint fails once the client's timeout elapses
+        assert_eq!(CheckoutAddress::CEP_LOOKUP_TIMEOUT, std::time::Duration::from_secs(5));
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_millis(200))
+            .build()
+            .unwrap();
+        let started = std::time::Instant::now();
+        let stalled = CheckoutAddress::lookup_cep_with(&client, "22222-222").await;
+        assert!(matches!(stalled, Err(CheckoutAddressCepLookupError::LookupFailed(_))));
+        assert!(started.elapsed() < CheckoutAddress::CEP_LOOKUP_TIMEOUT);
+    }
+}
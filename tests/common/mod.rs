@@ -0,0 +1,140 @@
+// Test doubles shared by the integration tests
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+/// In-memory Redis answering the commands the generated code sends
+///
+/// Supports `GET`, `SET key value [NX] [EX ttl]`, `DEL`/`UNLINK` and paged
+/// `SCAN cursor [MATCH pattern]`, and records every command name it receives.
+pub struct MockRedis {
+    pub values: HashMap<String, String>,
+    /// TTL passed with `EX` on the last `SET` of each key
+    pub ttls: HashMap<String, u64>,
+    pub commands: Vec<String>,
+    scan_page_size: usize,
+    supports_unlink: bool,
+    /// Last key returned by each open SCAN cursor (cursor `n` is entry `n - 1`)
+    scan_cursors: Vec<String>,
+}
+
+impl Default for MockRedis {
+    fn default() -> Self {
+        Self {
+            values: HashMap::new(),
+            ttls: HashMap::new(),
+            commands: Vec::new(),
+            scan_page_size: 10,
+            supports_unlink: true,
+            scan_cursors: Vec::new(),
+        }
+    }
+}
+
+impl MockRedis {
+    /// Redis already holding `keys`, each with an empty value
+    pub fn with_keys<I, K>(keys: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<String>,
+    {
+        Self {
+            values: keys.into_iter().map(|key| (key.into(), String::new())).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Return at most `page_size` keys per SCAN reply
+    pub fn scan_page_size(mut self, page_size: usize) -> Self {
+        self.scan_page_size = page_size;
+        self
+    }
+
+    /// Reject `UNLINK` the way Redis servers older than 4.0 do
+    pub fn without_unlink(mut self) -> Self {
+        self.supports_unlink = false;
+        self
+    }
+
+    fn scan(&mut self, args: &[String]) -> redis::Value {
+        let after = match args[1].parse::<usize>().unwrap() {
+            0 => None,
+            cursor => Some(self.scan_cursors[cursor - 1].clone()),
+        };
+        let pattern = args.iter().position(|arg| arg == "MATCH")
+            .and_then(|i| args.get(i + 1))
+            .map_or("*", String::as_str);
+
+        let mut matching: Vec<&String> = self.values.keys()
+            .filter(|key| match pattern.strip_suffix('*') {
+                Some(prefix) => key.starts_with(prefix),
+                None => *key == pattern,
+            })
+            .filter(|key| after.as_ref().is_none_or(|after| *key > after))
+            .collect();
+        matching.sort();
+
+        let page: Vec<String> = matching.iter().take(self.scan_page_size).map(|key| key.to_string()).collect();
+        let next = if matching.len() > self.scan_page_size {
+            self.scan_cursors.push(page.last().unwrap().clone());
+            self.scan_cursors.len()
+        } else {
+            0
+        };
+
+        redis::Value::Bulk(vec![
+            redis::Value::Data(next.to_string().into_bytes()),
+            redis::Value::Bulk(page.into_iter().map(|key| redis::Value::Data(key.into_bytes())).collect()),
+        ])
+    }
+}
+
+impl redis::aio::ConnectionLike for MockRedis {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a redis::Cmd) -> redis::RedisFuture<'a, redis::Value> {
+        let args: Vec<String> = cmd.args_iter()
+            .filter_map(|arg| match arg {
+                redis::Arg::Simple(bytes) => Some(String::from_utf8_lossy(bytes).into_owned()),
+                redis::Arg::Cursor => None,
+            })
+            .collect();
+        self.commands.push(args[0].clone());
+
+        let reply = match args[0].as_str() {
+            "GET" => Ok(match self.values.get(&args[1]) {
+                Some(value) => redis::Value::Data(value.clone().into_bytes()),
+                None => redis::Value::Nil,
+            }),
+            "SET" if args.iter().any(|arg| arg == "NX") && self.values.contains_key(&args[1]) => Ok(redis::Value::Nil),
+            "SET" => {
+                self.values.insert(args[1].clone(), args[2].clone());
+                if let Some(ttl) = args.iter().position(|arg| arg == "EX").and_then(|i| args.get(i + 1)) {
+                    self.ttls.insert(args[1].clone(), ttl.parse().unwrap());
+                }
+                Ok(redis::Value::Okay)
+            }
+            "UNLINK" if !self.supports_unlink => {
+                Err((redis::ErrorKind::ResponseError, "ERR unknown command 'UNLINK'").into())
+            }
+            "UNLINK" | "DEL" => {
+                let removed = args[1..].iter().filter(|key| self.values.remove(*key).is_some()).count();
+                Ok(redis::Value::Int(removed as i64))
+            }
+            "SCAN" => Ok(self.scan(&args)),
+            _ => Ok(redis::Value::Okay),
+        };
+        Box::pin(async move { reply })
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        _cmd: &'a redis::Pipeline,
+        _offset: usize,
+        _count: usize,
+    ) -> redis::RedisFuture<'a, Vec<redis::Value>> {
+        Box::pin(async { Ok(Vec::new()) })
+    }
+
+    fn get_db(&self) -> i64 {
+        0
+    }
+}
@@ -0,0 +1,20 @@
+// Compile-time checks for macro expansion errors
+#[test]
+fn compile_tests() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/domain_no_auto_fields_pass.rs");
+    t.pass("tests/ui/domain_serde_roundtrip_pass.rs");
+    t.pass("tests/ui/brazilian_payment_entity_pass.rs");
+    t.pass("tests/ui/database_mapper_pass.rs");
+    t.compile_fail("tests/ui/domain_no_auto_fields_missing_field.rs");
+    t.compile_fail("tests/ui/domain_typestate_builder_incomplete.rs");
+    t.compile_fail("tests/ui/domain_zero_cache_ttl.rs");
+    t.compile_fail("tests/ui/brazilian_payment_negative_rate.rs");
+    t.compile_fail("tests/ui/payment_min_above_max.rs");
+    t.compile_fail("tests/ui/payment_risk_weight_too_large.rs");
+    t.compile_fail("tests/ui/status_unknown_trigger.rs");
+    t.compile_fail("tests/ui/domain_model_not_named_struct.rs");
+    t.compile_fail("tests/ui/row_mapper_not_named_struct.rs");
+    t.compile_fail("tests/ui/brazilian_entity_not_named_struct.rs");
+    t.compile_fail("tests/ui/database_mapper_not_named_struct.rs");
+}
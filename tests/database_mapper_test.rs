@@ -0,0 +1,154 @@
+// Tests for the DatabaseMapper derive
+use pleme_codegen::DatabaseMapper;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum OrderStatus {
+    Open,
+    Shipped,
+}
+
+impl std::fmt::Display for OrderStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            OrderStatus::Open => "open",
+            OrderStatus::Shipped => "shipped",
+        })
+    }
+}
+
+impl std::str::FromStr for OrderStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(OrderStatus::Open),
+            "shipped" => Ok(OrderStatus::Shipped),
+            other => Err(format!("unknown order status `{}`", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, DatabaseMapper)]
+#[database(table = "orders", primary_key = "id")]
+pub struct Order {
+    pub id: uuid::Uuid,
+    #[db(column = "customer_ref")]
+    pub customer_id: String,
+    #[db(enum)]
+    pub status: OrderStatus,
+    #[db(json)]
+    pub shipping_address: Option<serde_json::Value>,
+    #[db(type = "i64")]
+    pub quantity: u32,
+}
+
+/// Multi-tenant entity keyed on `(product, id)`, mapped in the same module as `Order`
+#[derive(Debug, Clone, Serialize, Deserialize, DatabaseMapper)]
+#[database(table = "tenant_orders", primary_key = "product, id")]
+pub struct TenantOrder {
+    pub id: uuid::Uuid,
+    pub product: String,
+    #[db(column = "total_cents")]
+    pub total: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_sql_uses_column_overrides() {
+        assert_eq!(Order::table_name(), "orders");
+        assert_eq!(Order::primary_key(), &["id"]);
+        assert_eq!(Order::columns(), &["id", "customer_ref", "status", "shipping_address", "quantity"]);
+        assert_eq!(
+            Order::insert_sql(),
+            "INSERT INTO orders (id, customer_ref, status, shipping_address, quantity) VALUES ($1, $2, $3, $4, $5) RETURNING *"
+        );
+        assert_eq!(
+            Order::find_by_id_sql(),
+            "SELECT id, customer_ref, status, shipping_address, quantity FROM orders WHERE id = $1"
+        );
+        assert_eq!(
+            Order::update_sql(),
+            "UPDATE orders SET customer_ref = $2, status = $3, shipping_address = $4, quantity = $5 WHERE id = $1 RETURNING *"
+        );
+        assert_eq!(Order::delete_sql(), "DELETE FROM orders WHERE id = $1");
+    }
+
+    #[test]
+    fn test_composite_key_binds_all_key_columns_first() {
+        assert_eq!(TenantOrder::primary_key(), &["product", "id"]);
+        assert_eq!(
+            TenantOrder::find_by_id_sql(),
+            "SELECT id, product, total_cents FROM tenant_orders WHERE product = $1 AND id = $2"
+        );
+        assert_eq!(
+            TenantOrder::update_sql(),
+            "UPDATE tenant_orders SET total_cents = $3 WHERE product = $1 AND id = $2 RETURNING *"
+        );
+        assert_eq!(TenantOrder::delete_sql(), "DELETE FROM tenant_orders WHERE product = $1 AND id = $2");
+        // Inserts still bind in column order
+        assert_eq!(
+            TenantOrder::insert_sql(),
+            "INSERT INTO tenant_orders (id, product, total_cents) VALUES ($1, $2, $3) RETURNING *"
+        );
+        assert_eq!(TenantOrder::entity_metadata().primary_key, ["product", "id"]);
+        assert_eq!(
+            TenantOrder::entity_metadata().to_string(),
+            "Entity TenantOrder -> Table tenant_orders (PK: product, id)"
+        );
+    }
+
+    #[test]
+    fn test_insert_query_with_binds_targets_insert_sql() {
+        let order = Order {
+            id: uuid::Uuid::nil(),
+            customer_id: "cus_1".to_string(),
+            status: OrderStatus::Shipped,
+            shipping_address: Some(serde_json::json!({ "cep": "01310-100" })),
+            quantity: 3,
+        };
+
+        let query = order.insert_query_with_binds().unwrap();
+        assert_eq!(sqlx::Execute::sql(&query), Order::insert_sql());
+    }
+
+    #[test]
+    fn test_bulk_insert_chunks_at_parameter_limit() {
+        assert_eq!(TenantOrder::FIELD_COUNT, 3);
+        assert_eq!(TenantOrder::MAX_BULK_ROWS, 21845);
+        assert_eq!(Order::MAX_BULK_ROWS, 65535 / 5);
+
+        assert_eq!(
+            TenantOrder::bulk_insert_query(2),
+            ["INSERT INTO tenant_orders (id, product, total_cents) VALUES ($1, $2, $3), ($4, $5, $6) RETURNING *"]
+        );
+        assert!(TenantOrder::bulk_insert_query(0).is_empty());
+
+        let statements = TenantOrder::bulk_insert_query(TenantOrder::MAX_BULK_ROWS + 1);
+        assert_eq!(statements.len(), 2);
+        assert!(statements[0].ends_with("($65533, $65534, $65535) RETURNING *"));
+        assert_eq!(
+            statements[1],
+            "INSERT INTO tenant_orders (id, product, total_cents) VALUES ($1, $2, $3) RETURNING *"
+        );
+    }
+
+    #[test]
+    fn test_query_builder_and_metadata() {
+        let sql = Order::query_builder()
+            .where_clause("status = 'open'")
+            .order_by("id", "DESC")
+            .limit(10)
+            .build_select();
+        assert_eq!(sql, "SELECT * FROM orders WHERE status = 'open' ORDER BY id DESC LIMIT 10");
+        assert_eq!(Order::entity_metadata().to_string(), "Entity Order -> Table orders (PK: id)");
+
+        // Each mapped entity gets its own builder and metadata types
+        let _: TenantOrderQueryBuilder = TenantOrder::query_builder();
+        let metadata: OrderMetadata = Order::entity_metadata();
+        assert_eq!(metadata.table, "orders");
+    }
+}
@@ -0,0 +1,1103 @@
+// Tests for DomainModel #[domain(...)] options
+use chrono::TimeZone;
+use pleme_codegen::{DomainModel, GraphQLBridge};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+#[derive(Debug, Clone, Serialize, Deserialize, DomainModel)]
+#[domain(table = "stores", timezone = "America/Manaus")]
+struct ManausStore {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, DomainModel)]
+struct DefaultStore {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, DomainModel)]
+#[domain(tenant_field = "tenant_id")]
+struct TenantScopedStore {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, DomainModel)]
+#[domain(created_by, updated_by)]
+struct AuditedDocument {
+    pub id: uuid::Uuid,
+    pub product: String,
+    pub title: String,
+    pub created_by: Option<uuid::Uuid>,
+    pub updated_by: Option<uuid::Uuid>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, DomainModel)]
+#[domain(shard_count = 16)]
+struct ShardedEvent {
+    pub id: uuid::Uuid,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CasError {
+    Conflict { current: i64 },
+    NotFound,
+    Database(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, DomainModel)]
+#[domain(versioned)]
+struct VersionedAccount {
+    pub id: uuid::Uuid,
+    pub product: String,
+    pub balance: i64,
+    pub version: i64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, DomainModel)]
+#[domain(soft_delete, schema_version = 3)]
+struct ArchivableCustomer {
+    pub id: uuid::Uuid,
+    pub product: String,
+    #[field(unique_in_tenant)]
+    pub email: String,
+    pub deleted_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, DomainModel, GraphQLBridge)]
+#[domain(api_casing = "camelCase")]
+struct ApiOrder {
+    pub id: uuid::Uuid,
+    #[graphql(decimal)]
+    pub total_amount: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, DomainModel)]
+#[domain(events, ndjson)]
+struct CustomerRegistered {
+    pub id: uuid::Uuid,
+    pub product: String,
+    pub email: String,
+    #[field(sensitive)]
+    pub cpf: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, DomainModel)]
+#[domain(sort_by = "created_at,id")]
+struct TimelineEntry {
+    pub id: uuid::Uuid,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, DomainModel)]
+#[domain(sort_by = "created_at")]
+struct AuditEntry {
+    pub id: uuid::Uuid,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub action: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, DomainModel)]
+#[domain(aggregate_columns = "amount,fee")]
+pub struct Sale {
+    pub id: uuid::Uuid,
+    pub product: String,
+    pub amount: rust_decimal::Decimal,
+    pub fee: rust_decimal::Decimal,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Fixed-size secret reporting its bytes to a shared witness once zeroized
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WitnessedSecret {
+    pub bytes: [u8; 8],
+    #[serde(skip)]
+    pub witness: std::sync::Arc<std::sync::Mutex<Option<[u8; 8]>>>,
+}
+
+impl zeroize::Zeroize for WitnessedSecret {
+    fn zeroize(&mut self) {
+        self.bytes.zeroize();
+        *self.witness.lock().unwrap() = Some(self.bytes);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, DomainModel)]
+pub struct PixCredential {
+    pub id: uuid::Uuid,
+    pub label: String,
+    #[field(zeroize)]
+    pub access_token: String,
+    #[field(zeroize)]
+    pub signing_key: WitnessedSecret,
+}
+
+/// Strict by default, so only the lenient path tolerates newer producers
+#[derive(Debug, Clone, Serialize, Deserialize, DomainModel)]
+#[serde(deny_unknown_fields)]
+#[domain(lenient_json)]
+pub struct OrderPlaced {
+    pub id: uuid::Uuid,
+    #[serde(rename = "totalAmount")]
+    pub total_amount: String,
+    #[serde(alias = "customer")]
+    pub customer_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, DomainModel)]
+pub struct Charge {
+    pub id: uuid::Uuid,
+    #[field(webhook)]
+    pub status: String,
+    #[field(webhook)]
+    #[serde(rename = "amountCents")]
+    pub amount_cents: i64,
+    #[field(webhook, sensitive)]
+    pub payer_document: String,
+    pub internal_notes: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, DomainModel)]
+struct SavedCard {
+    pub id: uuid::Uuid,
+    pub holder_name: String,
+    #[field(mask = "last4")]
+    pub card_number: String,
+    #[field(mask = "last4")]
+    pub bank_account: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, DomainModel)]
+#[domain(codegen_stats)]
+struct MeasuredStore {
+    pub id: uuid::Uuid,
+    pub product: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, DomainModel)]
+#[domain(codegen_stats)]
+struct MeasuredLedger {
+    pub id: uuid::Uuid,
+    pub product: String,
+    pub balance_cents: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, DomainModel)]
+#[domain(tenant_guard)]
+struct GuardedOrder {
+    pub id: uuid::Uuid,
+    pub product: String,
+    pub total_cents: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, DomainModel)]
+#[domain(typestate_builder)]
+pub struct Shipment {
+    pub id: uuid::Uuid,
+    pub product: String,
+    pub tracking_code: String,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, DomainModel)]
+#[csv]
+pub struct LedgerLine {
+    pub id: uuid::Uuid,
+    pub description: String,
+    pub amount: rust_decimal::Decimal,
+    pub fee: Option<rust_decimal::Decimal>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, DomainModel)]
+#[csv(locale = "pt-BR")]
+pub struct LancamentoContabil {
+    pub id: uuid::Uuid,
+    pub description: String,
+    pub amount: rust_decimal::Decimal,
+    pub fee: Option<rust_decimal::Decimal>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, DomainModel)]
+#[domain(rate_limit = "2/s")]
+pub struct QuoteRequest {
+    pub id: uuid::Uuid,
+}
+
+/// None of the standard fields declared: `#[domain_model]` adds all four
+#[pleme_codegen::domain_model]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Coupon {
+    pub code: String,
+    pub percent_off: u8,
+}
+
+/// Every standard field declared: nothing is added
+#[pleme_codegen::domain_model]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Supplier {
+    pub id: uuid::Uuid,
+    pub product: String,
+    pub legal_name: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Actor auditing without declaring the actor columns: `#[domain_model]` adds them too
+#[pleme_codegen::domain_model]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[domain(created_by, updated_by)]
+pub struct PriceList {
+    pub name: String,
+}
+
+/// Custom tenant column
+#[pleme_codegen::domain_model]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[domain(tenant_field = "tenant_id")]
+pub struct Warehouse {
+    pub city: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum CardPaymentStatus {
+    Authorized,
+    Captured,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, DomainModel)]
+#[domain(otel)]
+pub struct CardPayment {
+    pub id: uuid::Uuid,
+    pub product: String,
+    pub amount: rust_decimal::Decimal,
+    pub status: CardPaymentStatus,
+    #[field(sensitive)]
+    pub card_token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, DomainModel)]
+#[domain(schema_fingerprint = "68ce53332d6e9ec9")]
+pub struct Invoice {
+    pub id: uuid::Uuid,
+    pub product: String,
+    pub number: String,
+    pub total: rust_decimal::Decimal,
+}
+
+/// `Invoice` after a field was added
+pub mod invoice_v2 {
+    use pleme_codegen::DomainModel;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, Serialize, Deserialize, DomainModel)]
+    pub struct Invoice {
+        pub id: uuid::Uuid,
+        pub product: String,
+        pub number: String,
+        pub total: rust_decimal::Decimal,
+        pub due_date: chrono::NaiveDate,
+    }
+}
+
+/// Same fields as `Invoice`, declared separately
+pub mod invoice_copy {
+    use pleme_codegen::DomainModel;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, Serialize, Deserialize, DomainModel)]
+    pub struct Invoice {
+        pub id: uuid::Uuid,
+        pub product: String,
+        pub number: String,
+        pub total: rust_decimal::Decimal,
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum ServiceError {
+    NotFound(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(hour: u32) -> chrono::DateTime<chrono::Utc> {
+        chrono::Utc.with_ymd_and_hms(2024, 1, 15, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_business_hours_uses_configured_timezone() {
+        assert_eq!(ManausStore::TIMEZONE, "America/Manaus");
+
+        // 13:00 UTC is 09:00 in Manaus (UTC-4)
+        assert!(ManausStore::is_within_business_hours_at(utc(13), 9, 18));
+        // 12:00 UTC is 08:00 in Manaus, before opening
+        assert!(!ManausStore::is_within_business_hours_at(utc(12), 9, 18));
+        // 22:00 UTC is 18:00 in Manaus, closing hour is exclusive
+        assert!(!ManausStore::is_within_business_hours_at(utc(22), 9, 18));
+    }
+
+    #[test]
+    fn test_business_hours_defaults_to_sao_paulo() {
+        assert_eq!(DefaultStore::TIMEZONE, "America/Sao_Paulo");
+
+        // 12:00 UTC is 09:00 in Sao Paulo (UTC-3) but still 08:00 in Manaus
+        assert!(DefaultStore::is_within_business_hours_at(utc(12), 9, 18));
+        assert!(!ManausStore::is_within_business_hours_at(utc(12), 9, 18));
+    }
+
+    #[test]
+    fn test_business_hours_overnight_window() {
+        // 02:00 UTC is 22:00 in Manaus
+        assert!(ManausStore::is_within_business_hours_at(utc(2), 20, 6));
+        assert!(!ManausStore::is_within_business_hours_at(utc(16), 20, 6));
+    }
+
+    #[test]
+    fn test_business_hours_instance_method() {
+        let store = ManausStore { name: "Loja Centro".to_string() };
+        // Covers the whole day, so the result does not depend on the wall clock
+        assert!(store.is_within_business_hours(0, 24));
+    }
+
+    #[test]
+    fn test_key_bind_order_matches_select_by_id_placeholders() {
+        let query = ManausStore::select_by_id_query();
+        assert_eq!(query, "SELECT * FROM ManausStores WHERE id = $1 AND product = $2");
+
+        for (i, column) in ManausStore::KEY_BIND_ORDER.iter().enumerate() {
+            assert!(query.contains(&format!("{} = ${}", column, i + 1)));
+        }
+        assert_eq!(ManausStore::KEY_BIND_ORDER, ["id", "product"]);
+    }
+
+    #[test]
+    fn test_select_for_update_queries() {
+        assert_eq!(
+            ManausStore::select_by_id_for_update_query(),
+            "SELECT * FROM ManausStores WHERE id = $1 AND product = $2 FOR UPDATE"
+        );
+        assert_eq!(
+            ManausStore::select_by_id_for_update_skip_locked_query(),
+            "SELECT * FROM ManausStores WHERE id = $1 AND product = $2 FOR UPDATE SKIP LOCKED"
+        );
+        assert_eq!(
+            TenantScopedStore::select_by_id_for_update_query(),
+            "SELECT * FROM TenantScopedStores WHERE id = $1 AND tenant_id = $2 FOR UPDATE"
+        );
+    }
+
+    #[test]
+    fn test_columns_follow_field_declaration_order() {
+        assert_eq!(
+            AuditedDocument::columns(),
+            ["id", "product", "title", "created_by", "updated_by", "created_at", "updated_at"]
+        );
+        assert_eq!(
+            AuditedDocument::insert_query(),
+            "INSERT INTO AuditedDocuments (id, product, title, created_by, updated_by, created_at, updated_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7)"
+        );
+        assert_eq!(ManausStore::insert_query(), "INSERT INTO ManausStores (name) VALUES ($1)");
+    }
+
+    #[test]
+    fn test_key_placeholders_follow_update_fields() {
+        let query = ManausStore::update_query(&["name", "updated_at"]);
+        assert_eq!(
+            query,
+            "UPDATE ManausStores SET name = $1, updated_at = $2 WHERE id = $3 AND product = $4"
+        );
+    }
+
+    #[test]
+    fn test_key_bind_order_uses_tenant_field() {
+        assert_eq!(TenantScopedStore::KEY_BIND_ORDER, ["id", "tenant_id"]);
+        assert_eq!(
+            TenantScopedStore::delete_query(),
+            "DELETE FROM TenantScopedStores WHERE id = $1 AND tenant_id = $2"
+        );
+        assert_eq!(
+            TenantScopedStore::count_by_product_query(),
+            "SELECT COUNT(*) FROM TenantScopedStores WHERE tenant_id = $1"
+        );
+    }
+
+    #[test]
+    fn test_bind_key_binds_arguments() {
+        use sqlx::Execute;
+
+        let sql = ManausStore::select_by_id_query();
+        let mut query = ManausStore::bind_key(sqlx::query(&sql), uuid::Uuid::new_v4(), "nexus");
+        assert!(query.take_arguments().is_some());
+
+        let _typed = ManausStore::bind_key_as(
+            sqlx::query_as::<_, (uuid::Uuid,)>(&sql),
+            uuid::Uuid::new_v4(),
+            "nexus",
+        );
+    }
+
+    #[test]
+    fn test_new_with_actor_populates_actor_fields() {
+        let actor = uuid::Uuid::new_v4();
+        let doc = AuditedDocument::new_with_actor(actor, "nexus".to_string(), "Contrato".to_string());
+
+        assert_eq!(doc.created_by, Some(actor));
+        assert_eq!(doc.updated_by, Some(actor));
+        assert_eq!(doc.product, "nexus");
+        assert_eq!(doc.title, "Contrato");
+        assert!(!doc.id.is_nil());
+        assert_eq!(doc.created_at, doc.updated_at);
+        assert_eq!(AuditedDocument::ACTOR_COLUMNS, &["created_by", "updated_by"]);
+    }
+
+    #[test]
+    fn test_touch_by_updates_modifier_only() {
+        let creator = uuid::Uuid::new_v4();
+        let editor = uuid::Uuid::new_v4();
+        let mut doc = AuditedDocument::new_with_actor(creator, "nexus".to_string(), "Contrato".to_string());
+        let created_at = doc.created_at;
+
+        doc.touch_by(editor);
+
+        assert_eq!(doc.created_by, Some(creator));
+        assert_eq!(doc.updated_by, Some(editor));
+        assert!(doc.updated_at >= created_at);
+    }
+
+    #[test]
+    fn test_update_with_actor_query_sets_updated_by() {
+        assert_eq!(
+            AuditedDocument::update_with_actor_query(&["title"]),
+            "UPDATE AuditedDocuments SET title = $1, updated_by = $2 WHERE id = $3 AND product = $4"
+        );
+    }
+
+    #[test]
+    fn test_shard_index_is_stable() {
+        let id = uuid::Uuid::parse_str("6f1c2a8e-3b4d-4e5f-8a9b-0c1d2e3f4a5b").unwrap();
+        let event = ShardedEvent { id };
+
+        let shard = event.shard_index();
+        assert!(shard < ShardedEvent::SHARD_COUNT);
+        assert_eq!(shard, ShardedEvent::shard_index_for(id));
+        assert_eq!(shard, ShardedEvent { id }.shard_index());
+        assert_eq!(event.shard_table_name(), format!("ShardedEvents_{}", shard));
+    }
+
+    #[test]
+    fn test_shard_distribution_is_roughly_uniform() {
+        let mut counts = [0usize; 16];
+        for _ in 0..16_000 {
+            counts[ShardedEvent::shard_index_for(uuid::Uuid::new_v4()) as usize] += 1;
+        }
+
+        // Expect ~1000 per shard; allow generous slack for randomness
+        for count in counts {
+            assert!((700..=1300).contains(&count), "unbalanced shard count: {}", count);
+        }
+    }
+
+    #[test]
+    fn test_update_cas_query_guards_on_version() {
+        assert_eq!(VersionedAccount::CAS_UPDATE_COLUMNS, &["balance", "updated_at"]);
+        assert_eq!(
+            VersionedAccount::update_cas_query(),
+            "UPDATE VersionedAccounts SET balance = $1, updated_at = $2, version = version + 1 \
+             WHERE id = $3 AND product = $4 AND version = $5"
+        );
+        assert_eq!(
+            VersionedAccount::select_version_query(),
+            "SELECT version FROM VersionedAccounts WHERE id = $1 AND product = $2"
+        );
+    }
+
+    #[test]
+    fn test_stale_update_yields_conflict_with_current_version() {
+        // Zero rows affected while the row exists at version 7
+        assert_eq!(
+            VersionedAccount::resolve_cas_outcome(0, Some(7)),
+            Err(CasError::Conflict { current: 7 })
+        );
+        assert_eq!(VersionedAccount::resolve_cas_outcome(0, None), Err(CasError::NotFound));
+        assert_eq!(VersionedAccount::resolve_cas_outcome(1, None), Ok(()));
+    }
+
+    fn customer(email: &str) -> ArchivableCustomer {
+        ArchivableCustomer {
+            id: uuid::Uuid::new_v4(),
+            product: "nexus".to_string(),
+            email: email.to_string(),
+            deleted_at: None,
+        }
+    }
+
+    #[test]
+    fn test_unique_in_tenant_index_is_partial_under_soft_delete() {
+        assert_eq!(
+            ArchivableCustomer::unique_index_ddl(),
+            vec![
+                "CREATE UNIQUE INDEX IF NOT EXISTS archivablecustomers_product_email_key \
+                 ON ArchivableCustomers (product, email) WHERE deleted_at IS NULL"
+                    .to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_migration_record_references_version_and_table() {
+        assert_eq!(ArchivableCustomer::SCHEMA_VERSION, 3);
+        assert_eq!(
+            ArchivableCustomer::migration_record_sql(),
+            "INSERT INTO schema_migrations (table_name, version) VALUES ('ArchivableCustomers', 3) ON CONFLICT DO NOTHING"
+        );
+        assert_eq!(
+            ArchivableCustomer::migration_applied_sql(),
+            "SELECT EXISTS (SELECT 1 FROM schema_migrations WHERE table_name = 'ArchivableCustomers' AND version = 3)"
+        );
+        assert!(ArchivableCustomer::schema_migrations_ddl().contains("PRIMARY KEY (table_name, version)"));
+    }
+
+    #[test]
+    fn test_soft_delete_queries() {
+        assert_eq!(
+            ArchivableCustomer::soft_delete_query(),
+            "UPDATE ArchivableCustomers SET deleted_at = NOW() WHERE id = $1 AND product = $2 AND deleted_at IS NULL"
+        );
+        assert_eq!(
+            ArchivableCustomer::restore_query(),
+            "UPDATE ArchivableCustomers SET deleted_at = NULL WHERE id = $1 AND product = $2 AND deleted_at IS NOT NULL"
+        );
+        assert!(ArchivableCustomer::list_by_product_query(false).contains("AND deleted_at IS NULL"));
+        assert!(!ArchivableCustomer::list_by_product_query(true).contains("deleted_at"));
+    }
+
+    #[test]
+    fn test_soft_delete_sets_and_restore_clears_deleted_at() {
+        let mut entity = customer("ana@example.com");
+        assert!(!entity.is_deleted());
+
+        entity.mark_deleted();
+        assert!(entity.deleted_at.is_some());
+
+        entity.mark_restored();
+        assert!(entity.deleted_at.is_none());
+    }
+
+    #[test]
+    fn test_list_without_deleted_omits_deleted_rows() {
+        let mut deleted = customer("old@example.com");
+        deleted.mark_deleted();
+        let rows = vec![customer("ana@example.com"), deleted, customer("bia@example.com")];
+
+        let visible = ArchivableCustomer::filter_deleted(rows.clone(), false);
+        assert_eq!(visible.len(), 2);
+        assert!(visible.iter().all(|c| !c.is_deleted()));
+
+        assert_eq!(ArchivableCustomer::filter_deleted(rows, true).len(), 3);
+    }
+
+    fn api_order() -> ApiOrder {
+        ApiOrder {
+            id: uuid::Uuid::new_v4(),
+            total_amount: "10.50".to_string(),
+            created_at: utc(12),
+        }
+    }
+
+    #[test]
+    fn test_api_casing_renames_only_api_representation() {
+        let order = api_order();
+
+        let api = order.to_api_json().unwrap();
+        assert!(api.get("createdAt").is_some());
+        assert!(api.get("created_at").is_none());
+        assert!(api.get("id").is_some());
+
+        // DB-facing serialization and column names stay snake_case
+        let db = serde_json::to_value(&order).unwrap();
+        assert!(db.get("created_at").is_some());
+        assert_eq!(
+            ApiOrder::API_FIELD_NAMES,
+            &[("total_amount", "totalAmount"), ("created_at", "createdAt")]
+        );
+    }
+
+    #[test]
+    fn test_api_casing_applies_to_graphql_output() {
+        let graphql: serde_json::Value = serde_json::from_str(&api_order().to_graphql()).unwrap();
+
+        assert!(graphql.get("createdAt").is_some());
+        // Decimal coercion still runs on the snake_case name before renaming
+        assert_eq!(graphql["totalAmount"], serde_json::json!(10.5));
+    }
+
+    #[test]
+    fn test_event_envelope_carries_metadata_and_redacted_entity() {
+        let customer = CustomerRegistered {
+            id: uuid::Uuid::new_v4(),
+            product: "novaskyn".to_string(),
+            email: "cliente@example.com.br".to_string(),
+            cpf: "123.456.789-09".to_string(),
+        };
+
+        let envelope = customer.to_event_envelope("customer.registered");
+
+        for key in ["event_id", "event_type", "aggregate_type", "aggregate_id", "tenant", "occurred_at", "payload"] {
+            assert!(envelope.get(key).is_some(), "missing envelope key `{}`", key);
+        }
+        assert_eq!(envelope["event_type"], "customer.registered");
+        assert_eq!(envelope["aggregate_type"], "CustomerRegistered");
+        assert_eq!(envelope["aggregate_id"], customer.id.to_string());
+        assert_eq!(envelope["tenant"], "novaskyn");
+        assert!(envelope["event_id"].as_str().unwrap().parse::<uuid::Uuid>().is_ok());
+
+        assert_eq!(envelope["payload"]["email"], "cliente@example.com.br");
+        assert_eq!(envelope["payload"]["cpf"], "[REDACTED]");
+        assert_eq!(CustomerRegistered::SENSITIVE_FIELDS, &["cpf"]);
+    }
+
+    #[test]
+    fn test_sort_by_orders_by_created_at_then_id() {
+        let low_id = uuid::Uuid::from_u128(1);
+        let high_id = uuid::Uuid::from_u128(2);
+        let entry = |hour, id, title: &str| TimelineEntry { id, created_at: utc(hour), title: title.to_string() };
+
+        let mut entries = [
+            entry(12, low_id, "third"),
+            entry(14, low_id, "fourth"),
+            entry(9, high_id, "second"),
+            entry(9, low_id, "first"),
+        ];
+        entries.sort();
+
+        let titles: Vec<&str> = entries.iter().map(|e| e.title.as_str()).collect();
+        assert_eq!(titles, ["first", "second", "third", "fourth"]);
+        assert_eq!(entries[0].sort_key(), (utc(9), low_id));
+    }
+
+    #[test]
+    fn test_sort_by_breaks_ties_on_id() {
+        let entry = |id, action: &str| AuditEntry { id: uuid::Uuid::from_u128(id), created_at: utc(9), action: action.to_string() };
+
+        let mut entries = [entry(3, "third"), entry(1, "first"), entry(2, "second")];
+        entries.sort();
+
+        let actions: Vec<&str> = entries.iter().map(|e| e.action.as_str()).collect();
+        assert_eq!(actions, ["first", "second", "third"]);
+
+        // Distinct entities with the same key never compare Equal, matching the derived Eq
+        assert_ne!(entries[0].cmp(&entries[1]), std::cmp::Ordering::Equal);
+        assert_eq!(entries[0].cmp(&entries[0].clone()), std::cmp::Ordering::Equal);
+    }
+
+    #[test]
+    fn test_sum_by_day_query_groups_by_day_bucket() {
+        let sql = Sale::sum_by_day_query("amount").unwrap();
+        assert_eq!(
+            sql,
+            format!(
+                "SELECT date_trunc('day', created_at) AS bucket, SUM(amount) FROM {} WHERE product = $1 AND created_at BETWEEN $2 AND $3 GROUP BY bucket ORDER BY bucket",
+                Sale::TABLE_NAME
+            )
+        );
+
+        let hourly = Sale::sum_by_bucket_query("fee", "hour").unwrap();
+        assert!(hourly.starts_with("SELECT date_trunc('hour', created_at) AS bucket, SUM(fee)"));
+    }
+
+    #[test]
+    fn test_sum_by_bucket_query_rejects_unlisted_column_and_bucket() {
+        assert!(Sale::sum_by_day_query("id; DROP TABLE sales").is_err());
+        assert!(Sale::sum_by_day_query("product").is_err());
+        assert!(Sale::sum_by_bucket_query("amount", "week").is_err());
+    }
+
+    fn pix_credential(witness: &std::sync::Arc<std::sync::Mutex<Option<[u8; 8]>>>) -> PixCredential {
+        PixCredential {
+            id: uuid::Uuid::nil(),
+            label: "checkout".to_string(),
+            access_token: "tok_live_abc123".to_string(),
+            signing_key: WitnessedSecret { bytes: *b"s3cr3tk3", witness: witness.clone() },
+        }
+    }
+
+    #[test]
+    fn test_zeroize_wipes_only_marked_fields() {
+        let witness = Default::default();
+        let mut credential = pix_credential(&witness);
+        credential.zeroize();
+
+        assert!(credential.access_token.is_empty());
+        assert_eq!(credential.signing_key.bytes, [0; 8]);
+        assert_eq!(credential.label, "checkout");
+    }
+
+    #[test]
+    fn test_zeroize_field_is_zeroed_on_drop() {
+        let witness = std::sync::Arc::new(std::sync::Mutex::new(None));
+        let credential = pix_credential(&witness);
+        assert!(witness.lock().unwrap().is_none());
+
+        drop(credential);
+
+        assert_eq!(*witness.lock().unwrap(), Some([0; 8]));
+    }
+
+    #[test]
+    fn test_lenient_json_ignores_unknown_fields() {
+        let json = r#"{
+            "id": "00000000-0000-0000-0000-000000000007",
+            "totalAmount": "149.90",
+            "customer": "cus_42",
+            "loyaltyTier": "gold"
+        }"#;
+
+        assert!(serde_json::from_str::<OrderPlaced>(json).is_err());
+
+        let order = OrderPlaced::try_from_json_lenient(json).unwrap();
+        assert_eq!(order.id, uuid::Uuid::from_u128(7));
+        assert_eq!(order.total_amount, "149.90");
+        assert_eq!(order.customer_id, "cus_42");
+        assert_eq!(OrderPlaced::KNOWN_JSON_KEYS, &["id", "totalAmount", "customer_id", "customer"]);
+    }
+
+    #[test]
+    fn test_lenient_json_still_requires_known_fields() {
+        let missing_total = r#"{"id": "00000000-0000-0000-0000-000000000007", "customer_id": "cus_42"}"#;
+        assert!(OrderPlaced::try_from_json_lenient(missing_total).is_err());
+    }
+
+    #[test]
+    fn test_webhook_payload_contains_only_marked_fields() {
+        let charge = Charge {
+            id: uuid::Uuid::from_u128(9),
+            status: "paid".to_string(),
+            amount_cents: 4990,
+            payer_document: "123.456.789-09".to_string(),
+            internal_notes: "manual review".to_string(),
+        };
+
+        let payload = charge.to_webhook_payload("charge.paid");
+
+        assert_eq!(payload["event_type"], "charge.paid");
+        assert_eq!(payload["entity_type"], "Charge");
+        assert_eq!(payload["entity_id"], uuid::Uuid::from_u128(9).to_string());
+        assert!(payload["webhook_id"].as_str().unwrap().parse::<uuid::Uuid>().is_ok());
+        assert!(payload["created_at"].as_str().is_some());
+
+        assert_eq!(
+            payload["data"],
+            serde_json::json!({ "status": "paid", "amountCents": 4990, "payer_document": "[REDACTED]" })
+        );
+        assert_eq!(Charge::WEBHOOK_FIELDS, &["status", "amountCents", "payer_document"]);
+    }
+
+    #[test]
+    fn test_masked_display_hides_all_but_last4() {
+        let card = SavedCard {
+            id: uuid::Uuid::nil(),
+            holder_name: "Maria Silva".to_string(),
+            card_number: "4111111111111234".to_string(),
+            bank_account: None,
+        };
+
+        let shown = card.to_string();
+        assert!(shown.contains("card_number: ****1234"));
+        assert!(shown.contains("holder_name: \"Maria Silva\""));
+        assert!(shown.contains("bank_account: None"));
+        assert!(!shown.contains("4111111111111234"));
+        assert_eq!(shown, card.display_safe());
+
+        assert_eq!(SavedCard::mask_last4("123"), "****");
+    }
+
+    #[test]
+    fn test_codegen_stats_counts_generated_public_methods() {
+        // Every public method DomainModel emits for a struct without options
+        let generated_methods: [&str; 17] = {
+            let _ = MeasuredStore::cache_key;
+            let _ = MeasuredStore::create_audit_log;
+            let _ = MeasuredStore::cache_key_with_ttl;
+            let _ = MeasuredStore::track_repository_operation;
+            let _ = MeasuredStore::is_within_business_hours;
+            let _ = MeasuredStore::is_within_business_hours_at;
+            let _ = MeasuredStore::select_by_id_query;
+            let _ = MeasuredStore::select_by_id_for_update_query;
+            let _ = MeasuredStore::select_by_id_for_update_skip_locked_query;
+            let _ = MeasuredStore::columns;
+            let _ = MeasuredStore::insert_query;
+            let _ = MeasuredStore::update_query;
+            let _ = MeasuredStore::delete_query;
+            let _ = MeasuredStore::count_by_product_query;
+            let _ = MeasuredStore::bind_key;
+            let _ = MeasuredStore::bind_key_as::<(i64,)>;
+            let _ = MeasuredStore::codegen_stats;
+            [
+                "cache_key", "create_audit_log", "cache_key_with_ttl", "track_repository_operation",
+                "is_within_business_hours", "is_within_business_hours_at", "select_by_id_query",
+                "select_by_id_for_update_query", "select_by_id_for_update_skip_locked_query",
+                "columns", "insert_query", "update_query", "delete_query", "count_by_product_query", "bind_key", "bind_key_as",
+                "codegen_stats",
+            ]
+        };
+
+        let stats: MeasuredStoreCodegenStats = MeasuredStore::codegen_stats();
+        assert_eq!(stats.methods_generated, generated_methods.len());
+        assert!(stats.lines_generated > stats.methods_generated);
+
+        // A second measured entity in the same module gets its own stats type
+        let ledger_stats: MeasuredLedgerCodegenStats = MeasuredLedger::codegen_stats();
+        assert_eq!(ledger_stats.methods_generated, generated_methods.len());
+    }
+
+    #[test]
+    fn test_ndjson_export_writes_valid_redacted_lines() {
+        let customers: Vec<CustomerRegistered> = ["ana@example.com.br", "bruno@example.com.br"]
+            .iter()
+            .map(|email| CustomerRegistered {
+                id: uuid::Uuid::new_v4(),
+                product: "novaskyn".to_string(),
+                email: email.to_string(),
+                cpf: "123.456.789-09".to_string(),
+            })
+            .collect();
+
+        let mut out = Vec::new();
+        let written = CustomerRegistered::write_ndjson(&customers, &mut out).unwrap();
+        assert_eq!(written, 2);
+
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.ends_with('\n'));
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        for (line, customer) in lines.iter().zip(&customers) {
+            let value: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(value["email"], customer.email.as_str());
+            assert_eq!(value["cpf"], "[REDACTED]");
+        }
+
+        assert_eq!(customers[0].to_ndjson_line().unwrap(), lines[0]);
+    }
+
+    #[test]
+    fn test_tenant_guard_hides_entities_owned_by_another_tenant() {
+        let order = GuardedOrder {
+            id: uuid::Uuid::new_v4(),
+            product: "novaskyn".to_string(),
+            total_cents: 12_990,
+        };
+        let store: std::collections::HashMap<uuid::Uuid, GuardedOrder> =
+            [(order.id, order.clone())].into_iter().collect();
+        let fetch = |id: uuid::Uuid, product: &str| {
+            GuardedOrder::tenant_guard(store.get(&id).cloned(), id, product, ServiceError::NotFound)
+        };
+
+        let own = fetch(order.id, "novaskyn").unwrap();
+        assert_eq!(own.total_cents, 12_990);
+
+        // The id exists, but under another tenant: indistinguishable from a missing id
+        let cross = fetch(order.id, "lilitu");
+        let missing_id = uuid::Uuid::new_v4();
+        let missing = fetch(missing_id, "lilitu");
+        assert_eq!(cross.unwrap_err(), ServiceError::NotFound(format!("Entity not found: {}", order.id)));
+        assert_eq!(missing.unwrap_err(), ServiceError::NotFound(format!("Entity not found: {}", missing_id)));
+    }
+
+    #[test]
+    fn test_typestate_builder_builds_once_required_fields_are_set() {
+        let id = uuid::Uuid::new_v4();
+        // Required setters may come in any order; optional ones at any point
+        let shipment = Shipment::builder()
+            .notes(Some("fragile".to_string()))
+            .tracking_code("BR123456789".to_string())
+            .id(id)
+            .product("novaskyn".to_string())
+            .build();
+
+        assert_eq!(shipment.id, id);
+        assert_eq!(shipment.product, "novaskyn");
+        assert_eq!(shipment.tracking_code, "BR123456789");
+        assert_eq!(shipment.notes.as_deref(), Some("fragile"));
+
+        let without_notes = Shipment::builder()
+            .id(id)
+            .product("novaskyn".to_string())
+            .tracking_code("BR123456789".to_string())
+            .build();
+        assert_eq!(without_notes.notes, None);
+    }
+
+    #[test]
+    fn test_csv_round_trips_amounts_in_default_locale() {
+        let line = LedgerLine {
+            id: uuid::Uuid::new_v4(),
+            description: "Mensalidade, plano anual".to_string(),
+            amount: "1234.56".parse().unwrap(),
+            fee: None,
+        };
+
+        let csv = LedgerLine::to_csv([&line]);
+        assert_eq!(LedgerLine::CSV_HEADER, "id,description,amount,fee");
+        assert_eq!(
+            csv.lines().nth(1).unwrap(),
+            format!("{},\"Mensalidade, plano anual\",1234.56,", line.id)
+        );
+        assert_eq!(LedgerLine::from_csv(&csv).unwrap(), vec![line]);
+    }
+
+    #[test]
+    fn test_csv_round_trips_cells_with_line_breaks() {
+        let lines = vec![
+            LedgerLine {
+                id: uuid::Uuid::new_v4(),
+                description: "Parcela 1/3\nvence \"amanhã\"".to_string(),
+                amount: "10.00".parse().unwrap(),
+                fee: None,
+            },
+            LedgerLine {
+                id: uuid::Uuid::new_v4(),
+                description: "Linha Windows\r\nsegunda linha".to_string(),
+                amount: "20.00".parse().unwrap(),
+                fee: Some("0.50".parse().unwrap()),
+            },
+        ];
+
+        let csv = LedgerLine::to_csv(&lines);
+
+        assert_eq!(LedgerLine::from_csv(&csv).unwrap(), lines);
+        assert!(LedgerLine::from_csv(&format!("{}\n{},\"aberto,1.00,", LedgerLine::CSV_HEADER, lines[0].id)).is_err());
+    }
+
+    #[test]
+    fn test_csv_pt_br_uses_decimal_comma_and_semicolons() {
+        let line = LancamentoContabil {
+            id: uuid::Uuid::new_v4(),
+            description: "Taxa; boleto".to_string(),
+            amount: "1234.56".parse().unwrap(),
+            fee: Some("3.49".parse().unwrap()),
+        };
+
+        let row = line.to_csv_row();
+        assert_eq!(row, format!("{};\"Taxa; boleto\";1234,56;3,49", line.id));
+        assert_eq!(LancamentoContabil::CSV_HEADER, "id;description;amount;fee");
+        assert_eq!(LancamentoContabil::from_csv_row(&row).unwrap(), line);
+
+        let csv = LancamentoContabil::to_csv([&line]);
+        assert_eq!(LancamentoContabil::from_csv(&csv).unwrap(), vec![line]);
+        assert!(LancamentoContabil::from_csv_row("x;y;1,00;").is_err());
+    }
+
+    #[test]
+    fn test_rate_limited_call_reports_retry_after() {
+        assert_eq!(QuoteRequest::RATE_LIMIT_CAPACITY, 2);
+        assert_eq!(QuoteRequest::RATE_LIMIT_WINDOW, std::time::Duration::from_secs(1));
+
+        let limiter = QuoteRequest::rate_limiter();
+        let now = std::time::Instant::now();
+        assert!(limiter.try_acquire_at(now).is_ok());
+        assert!(limiter.try_acquire_at(now).is_ok());
+
+        // One token refills every 500ms; almost none has accrued yet
+        let rejected: QuoteRequestRateLimited = limiter.try_acquire_at(now).unwrap_err();
+        assert!(rejected.retry_after > std::time::Duration::from_millis(450));
+        assert!(rejected.retry_after <= std::time::Duration::from_millis(500));
+        assert_eq!(rejected.retry_after_header(), "1");
+
+        let later = limiter.try_acquire_at(now + std::time::Duration::from_millis(250)).unwrap_err();
+        assert!(later.retry_after < rejected.retry_after);
+
+        let retry_at = now + rejected.retry_after + std::time::Duration::from_millis(1);
+        assert!(limiter.try_acquire_at(retry_at).is_ok());
+    }
+
+    #[test]
+    fn test_domain_model_attribute_adds_missing_standard_fields() {
+        let coupon = Coupon::new("nexus".to_string(), "BF10".to_string(), 10);
+        let _: uuid::Uuid = coupon.id;
+        assert_eq!(coupon.product, "nexus");
+        assert_eq!(coupon.code, "BF10");
+        assert_eq!(coupon.percent_off, 10);
+        assert_eq!(coupon.created_at, coupon.updated_at);
+        assert!(coupon.belongs_to_product("nexus"));
+    }
+
+    #[test]
+    fn test_domain_model_attribute_keeps_declared_standard_fields() {
+        let mut supplier = Supplier::new("nexus".to_string(), "Distribuidora Sul".to_string());
+        let created_at = supplier.created_at;
+        supplier.touch();
+
+        assert_eq!(supplier.legal_name, "Distribuidora Sul");
+        assert!(supplier.updated_at >= created_at);
+        assert_eq!(serde_json::to_value(&supplier).unwrap().as_object().unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_domain_model_attribute_adds_actor_fields() {
+        let creator = uuid::Uuid::new_v4();
+        let mut list = PriceList::new_with_actor(creator, "nexus".to_string(), "Atacado".to_string());
+        assert_eq!((list.created_by, list.updated_by), (Some(creator), Some(creator)));
+        assert_eq!(PriceList::ACTOR_COLUMNS, &["created_by", "updated_by"]);
+
+        let editor = uuid::Uuid::new_v4();
+        list.touch_by(editor);
+        assert_eq!((list.created_by, list.updated_by), (Some(creator), Some(editor)));
+
+        // The plain constructor leaves the actors unset
+        let anonymous = PriceList::new("nexus".to_string(), "Varejo".to_string());
+        assert_eq!((anonymous.created_by, anonymous.updated_by), (None, None));
+        let json = serde_json::to_value(&anonymous).unwrap();
+        assert!(json.get("created_by").is_some() && json.get("updated_by").is_some());
+    }
+
+    #[test]
+    fn test_domain_model_attribute_uses_custom_tenant_field() {
+        let warehouse = Warehouse::new("tenant-42".to_string(), "Manaus".to_string());
+
+        assert_eq!(warehouse.tenant_id, "tenant-42");
+        assert_eq!(warehouse.city, "Manaus");
+        assert!(warehouse.belongs_to_product("tenant-42"));
+        let json = serde_json::to_value(&warehouse).unwrap();
+        assert!(json.get("tenant_id").is_some());
+        assert!(json.get("product").is_none());
+    }
+
+    #[test]
+    fn test_otel_attributes_carry_entity_identity_and_omit_sensitive_fields() {
+        let payment = CardPayment {
+            id: uuid::Uuid::new_v4(),
+            product: "nexus".to_string(),
+            amount: "129.90".parse().unwrap(),
+            status: CardPaymentStatus::Captured,
+            card_token: "tok_4111111111111111".to_string(),
+        };
+
+        let attributes: std::collections::HashMap<String, String> = payment.otel_attributes()
+            .into_iter()
+            .map(|kv| (kv.key.as_str().to_string(), kv.value.as_str().into_owned()))
+            .collect();
+
+        assert_eq!(attributes["entity.type"], "CardPayment");
+        assert_eq!(attributes["entity.id"], payment.id.to_string());
+        assert_eq!(attributes["tenant"], "nexus");
+        assert_eq!(attributes["payment.amount"], "129.90");
+        assert_eq!(attributes["payment.status"], "Captured");
+        assert_eq!(attributes.len(), 5);
+        assert!(attributes.values().all(|value| !value.contains("tok_")));
+    }
+
+    #[test]
+    fn test_schema_fingerprint_is_stable_and_tracks_field_changes() {
+        assert_eq!(Invoice::SCHEMA_FINGERPRINT, "68ce53332d6e9ec9");
+        assert_eq!(Invoice::SCHEMA_FINGERPRINT, invoice_copy::Invoice::SCHEMA_FINGERPRINT);
+        assert_ne!(Invoice::SCHEMA_FINGERPRINT, invoice_v2::Invoice::SCHEMA_FINGERPRINT);
+    }
+}
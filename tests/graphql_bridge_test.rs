@@ -0,0 +1,182 @@
+// Tests for the GraphQLBridge derive
+use async_graphql::ScalarType;
+use pleme_codegen::{BrazilianEntity, GraphQLBridge, ValidatedEntity};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+pleme_codegen::decimal_scalar!();
+
+#[derive(Debug, Clone, Serialize, Deserialize, GraphQLBridge)]
+#[graphql(decimal_scalar)]
+pub struct Invoice {
+    pub id: uuid::Uuid,
+    pub total_amount: Decimal,
+    pub discount: Option<Decimal>,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, GraphQLBridge)]
+#[graphql(decimal_scalar = crate::DecimalScalar)]
+pub struct CreditNote {
+    pub id: uuid::Uuid,
+    pub amount: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, GraphQLBridge, BrazilianEntity, ValidatedEntity)]
+#[graphql(validate)]
+pub struct CustomerInput {
+    pub name: String,
+    #[brazilian(cpf)]
+    pub cpf: String,
+    #[brazilian(phone)]
+    pub phone: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, GraphQLBridge)]
+pub struct LedgerEntry {
+    pub id: uuid::Uuid,
+    #[graphql(decimal)]
+    pub amount: Decimal,
+    #[graphql(rename = "postedAt")]
+    pub posted_at: String,
+    #[graphql(skip)]
+    pub internal_notes: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, GraphQLBridge)]
+pub struct Coupon {
+    pub discount_code: String,
+    pub total_amount: Decimal,
+    #[graphql(decimal)]
+    pub value: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, GraphQLBridge)]
+#[graphql(auto_coerce)]
+pub struct Quote {
+    pub unit_price: Decimal,
+    #[graphql(decimal_as_string)]
+    pub tax_rate: Decimal,
+    pub reference: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decimal_scalar_parses_string_input() {
+        let parsed = DecimalScalar::parse(async_graphql::Value::String("1234.5678".to_string())).unwrap();
+        assert_eq!(parsed.0, Decimal::from_str("1234.5678").unwrap());
+
+        let integer = DecimalScalar::parse(async_graphql::Value::from(42)).unwrap();
+        assert_eq!(integer.0, Decimal::from(42));
+
+        assert_eq!(parsed.to_value(), async_graphql::Value::String("1234.5678".to_string()));
+    }
+
+    #[test]
+    fn test_decimal_scalar_rejects_lossy_float_input() {
+        let float = async_graphql::Value::from(0.1_f64);
+        assert!(DecimalScalar::parse(float).is_err());
+        assert!(DecimalScalar::parse(async_graphql::Value::String("R$ 10".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_monetary_fields_use_decimal_scalar() {
+        let invoice = Invoice {
+            id: uuid::Uuid::new_v4(),
+            total_amount: Decimal::from_str("1999.99").unwrap(),
+            discount: None,
+            description: "Assinatura anual".to_string(),
+        };
+
+        assert_eq!(Invoice::GRAPHQL_DECIMAL_FIELDS, &["total_amount", "discount"]);
+        assert_eq!(invoice.total_amount_scalar(), DecimalScalar(invoice.total_amount));
+        assert_eq!(invoice.discount_scalar(), None);
+
+        // Monetary values keep their exact string form instead of becoming floats
+        let graphql: serde_json::Value = serde_json::from_str(&invoice.to_graphql()).unwrap();
+        assert_eq!(graphql["total_amount"], "1999.99");
+    }
+
+    #[test]
+    fn test_decimal_scalar_is_shared_across_entities() {
+        use async_graphql::InputType;
+
+        assert_eq!(DecimalScalar::type_name(), "Decimal");
+
+        // Both entities expose their amounts through the one schema-wide scalar
+        let note = CreditNote { id: uuid::Uuid::new_v4(), amount: Decimal::from_str("10.00").unwrap() };
+        let amount: DecimalScalar = note.amount_scalar();
+        assert_eq!(Decimal::from(amount), note.amount);
+    }
+
+    #[test]
+    fn test_validate_for_graphql_names_invalid_cpf_field() {
+        let mut input = CustomerInput {
+            name: "Ana Souza".to_string(),
+            cpf: "123.456.789-09".to_string(),
+            phone: Some("(11) 98765-4321".to_string()),
+        };
+        assert!(input.validate_for_graphql().is_ok());
+
+        input.cpf = "111.111.111-11".to_string();
+        let errors = input.validate_for_graphql().unwrap_err();
+        // Reported once even though validate() checks the same field
+        assert_eq!(errors, vec![CustomerInputFieldError { field: "cpf", message: "invalid CPF".to_string() }]);
+
+        let graphql_error = errors[0].to_graphql_error();
+        assert_eq!(graphql_error.message, "invalid CPF");
+        let extensions = serde_json::to_value(graphql_error.extensions.unwrap()).unwrap();
+        assert_eq!(extensions["field"], "cpf");
+    }
+
+    #[test]
+    fn test_field_rename_and_skip() {
+        let entry = LedgerEntry {
+            id: uuid::Uuid::new_v4(),
+            amount: Decimal::from_str("42.50").unwrap(),
+            posted_at: "2024-04-01T12:00:00Z".to_string(),
+            internal_notes: "manual adjustment".to_string(),
+        };
+
+        let graphql: serde_json::Value = serde_json::from_str(&entry.to_graphql()).unwrap();
+        let object = graphql.as_object().unwrap();
+        assert_eq!(object["postedAt"], "2024-04-01T12:00:00Z");
+        assert!(!object.contains_key("posted_at"));
+        assert!(!object.contains_key("internal_notes"));
+        // Renamed and skipped fields don't affect the decimal coercion
+        assert_eq!(object["amount"], 42.5);
+    }
+
+    #[test]
+    fn test_only_annotated_fields_are_coerced_by_default() {
+        let coupon = Coupon {
+            discount_code: "12345".to_string(),
+            total_amount: Decimal::from_str("99.90").unwrap(),
+            value: Decimal::from_str("15.5").unwrap(),
+        };
+
+        let graphql: serde_json::Value = serde_json::from_str(&coupon.to_graphql()).unwrap();
+        // Numeric-looking strings and money-like names are left alone without the attribute
+        assert_eq!(graphql["discount_code"], "12345");
+        assert_eq!(graphql["total_amount"], "99.90");
+        assert_eq!(graphql["value"], 15.5);
+    }
+
+    #[test]
+    fn test_auto_coerce_falls_back_to_field_names() {
+        let quote = Quote {
+            unit_price: Decimal::from_str("10.25").unwrap(),
+            tax_rate: Decimal::from_str("0.0925").unwrap(),
+            reference: "42".to_string(),
+        };
+
+        let graphql: serde_json::Value = serde_json::from_str(&quote.to_graphql()).unwrap();
+        assert_eq!(graphql["unit_price"], 10.25);
+        assert_eq!(graphql["tax_rate"], "0.0925");
+        assert_eq!(graphql["reference"], "42");
+    }
+}
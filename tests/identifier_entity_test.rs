@@ -0,0 +1,65 @@
+// Tests for the IdentifierEntity derive
+use pleme_codegen::IdentifierEntity;
+
+#[derive(Debug, Clone, IdentifierEntity)]
+#[identifier(prefix = "pay")]
+pub struct Payment;
+
+#[derive(Debug, Clone, IdentifierEntity)]
+#[identifier(prefix = "sub")]
+pub struct Subscription;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_id_round_trips() {
+        let id = Payment::generate_id();
+        assert!(id.starts_with("pay_"));
+        assert_eq!(id.len(), "pay_".len() + 22);
+
+        let uuid = Payment::parse_id(&id).unwrap();
+        assert_eq!(Payment::format_id(uuid), id);
+    }
+
+    #[test]
+    fn test_uuid_extremes_round_trip() {
+        for uuid in [uuid::Uuid::nil(), uuid::Uuid::max(), uuid::Uuid::new_v4()] {
+            assert_eq!(Payment::parse_id(&Payment::format_id(uuid)).unwrap(), uuid);
+        }
+        assert_eq!(Payment::format_id(uuid::Uuid::nil()), "pay_0000000000000000000000");
+    }
+
+    #[test]
+    fn test_parse_rejects_wrong_prefix() {
+        let subscription_id = Subscription::generate_id();
+
+        assert_eq!(
+            Payment::parse_id(&subscription_id),
+            Err(PaymentIdentifierError::WrongPrefix { expected: "pay", actual: "sub".to_string() })
+        );
+        assert_eq!(
+            Subscription::parse_id(&Payment::generate_id()),
+            Err(SubscriptionIdentifierError::WrongPrefix { expected: "sub", actual: "pay".to_string() })
+        );
+        assert!(matches!(
+            Payment::parse_id("7n42DGM5Tflk9n8mt7Fhc7"),
+            Err(PaymentIdentifierError::WrongPrefix { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_encoding() {
+        assert!(matches!(Payment::parse_id("pay_short"), Err(PaymentIdentifierError::InvalidEncoding(_))));
+        assert!(matches!(
+            Payment::parse_id("pay_00000000000000000000-0"),
+            Err(PaymentIdentifierError::InvalidEncoding(_))
+        ));
+        // Larger than u128::MAX
+        assert!(matches!(
+            Payment::parse_id("pay_zzzzzzzzzzzzzzzzzzzzzz"),
+            Err(PaymentIdentifierError::InvalidEncoding(_))
+        ));
+    }
+}
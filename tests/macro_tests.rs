@@ -32,6 +32,37 @@ pub enum PaymentStatus {
     Cancelled,
 }
 
+// `#[db(enum)]` columns are stored as text through Display / FromStr
+impl std::fmt::Display for PaymentStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            PaymentStatus::Pending => "pending",
+            PaymentStatus::Processing => "processing",
+            PaymentStatus::Completed => "completed",
+            PaymentStatus::Failed => "failed",
+            PaymentStatus::Refunded => "refunded",
+            PaymentStatus::Cancelled => "cancelled",
+        };
+        f.write_str(s)
+    }
+}
+
+impl std::str::FromStr for PaymentStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(PaymentStatus::Pending),
+            "processing" => Ok(PaymentStatus::Processing),
+            "completed" => Ok(PaymentStatus::Completed),
+            "failed" => Ok(PaymentStatus::Failed),
+            "refunded" => Ok(PaymentStatus::Refunded),
+            "cancelled" => Ok(PaymentStatus::Cancelled),
+            other => Err(format!("unknown payment status: {}", other)),
+        }
+    }
+}
+
 impl TestPayment {
     pub fn new() -> Self {
         let now = Utc::now();
@@ -58,6 +89,11 @@ pub enum TestError {
     ValidationFailed(String),
 }
 
+// TransactionalRepository resolves its error type under `crate::models`
+pub mod models {
+    pub use super::TestError;
+}
+
 // Mock Redis pool type for testing
 pub struct MockRedisPool;
 
@@ -115,7 +151,7 @@ fn test_cached_repository_compilation() {
 // DatabaseMapper Tests  
 // =============================================================================
 
-#[derive(DatabaseMapper)]
+#[derive(Debug, Clone, Serialize, Deserialize, DatabaseMapper)]
 #[database(table = "test_payments", primary_key = "id")]
 pub struct TestMappedEntity {
     pub id: Uuid,
@@ -137,7 +173,7 @@ fn test_database_mapper_sql_generation() {
     
     // Test metadata
     assert_eq!(TestMappedEntity::table_name(), "test_payments");
-    assert_eq!(TestMappedEntity::primary_key(), "id");
+    assert_eq!(TestMappedEntity::primary_key(), ["id"]);
     assert!(!TestMappedEntity::columns().is_empty());
 }
 
@@ -160,7 +196,7 @@ fn test_entity_metadata() {
     let metadata = TestMappedEntity::entity_metadata();
     assert_eq!(metadata.name, "TestMappedEntity");
     assert_eq!(metadata.table, "test_payments");
-    assert_eq!(metadata.primary_key, "id");
+    assert_eq!(metadata.primary_key, vec!["id"]);
     assert!(!metadata.columns.is_empty());
     
     // Test Display implementation
@@ -181,18 +217,18 @@ fn test_entity_metadata() {
     isolation_level = "ReadCommitted"
 )]
 pub struct TestTransactionalRepository {
-    pub pool: MockPgPool,
+    pub pool: sqlx::PgPool,
 }
 
 #[test]
 fn test_transactional_repository_compilation() {
-    let repo = TestTransactionalRepository {
-        pool: MockPgPool,
-    };
-    
     // Test that the struct compiles and methods are generated
     // Note: We can't actually test database transactions in unit tests,
     // but we can verify the methods exist and have correct signatures
+    assert_eq!(
+        TestTransactionalRepository::TRANSACTION_ISOLATION_SQL,
+        "SET TRANSACTION ISOLATION LEVEL READ COMMITTED"
+    );
     
     println!("TransactionalRepository macro generated methods successfully");
 }
@@ -201,7 +237,8 @@ fn test_transactional_repository_compilation() {
 // BrazilianPaymentEntity Tests
 // =============================================================================
 
-#[derive(BrazilianPaymentEntity)]
+// get_id/get_amount/... are generated from the fields; the PIX key checks come from BrazilianEntity
+#[derive(BrazilianEntity, BrazilianPaymentEntity)]
 #[brazilian_payment(
     tax_type = "icms",
     currency = "BRL",
@@ -215,37 +252,11 @@ pub struct TestBrazilianPayment {
     pub status: PaymentStatus,
     pub user_id: Uuid,
     pub product: String,
+    pub customer_document: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
-impl TestBrazilianPayment {
-    // Implement required methods for BrazilianPaymentEntity
-    fn get_id(&self) -> Uuid {
-        self.id
-    }
-    
-    fn get_amount(&self) -> Option<Decimal> {
-        Some(self.amount)
-    }
-    
-    fn get_status(&self) -> PaymentStatus {
-        self.status
-    }
-    
-    fn set_status(&mut self, status: PaymentStatus) {
-        self.status = status;
-    }
-    
-    fn set_updated_at(&mut self, timestamp: DateTime<Utc>) {
-        self.updated_at = timestamp;
-    }
-    
-    fn get_customer_document(&self) -> Option<String> {
-        Some("123.456.789-00".to_string())
-    }
-}
-
 #[test]
 fn test_brazilian_amount_formatting() {
     let amount = Decimal::new(123456, 2); // R$ 1,234.56
@@ -272,10 +283,10 @@ fn test_brazilian_amount_parsing() {
 
 #[test]
 fn test_pix_key_validation() {
-    use crate::PixKeyType;
+    use crate::TestBrazilianPaymentPixKeyType as PixKeyType;
     
     // Test CPF validation
-    assert!(TestBrazilianPayment::validate_pix_key("123.456.789-00", PixKeyType::Cpf).is_ok());
+    assert!(TestBrazilianPayment::validate_pix_key("123.456.789-09", PixKeyType::Cpf).is_ok());
     assert!(TestBrazilianPayment::validate_pix_key("invalid-cpf", PixKeyType::Cpf).is_err());
     
     // Test email validation
@@ -295,6 +306,7 @@ fn test_brazilian_tax_calculation() {
         status: PaymentStatus::Pending,
         user_id: Uuid::new_v4(),
         product: "test".to_string(),
+        customer_document: Some("123.456.789-09".to_string()),
         created_at: Utc::now(),
         updated_at: Utc::now(),
     };
@@ -328,6 +340,7 @@ fn test_brazilian_receipt_generation() {
         status: PaymentStatus::Completed,
         user_id: Uuid::new_v4(),
         product: "test".to_string(),
+        customer_document: Some("123.456.789-09".to_string()),
         created_at: Utc::now(),
         updated_at: Utc::now(),
     };
@@ -338,7 +351,7 @@ fn test_brazilian_receipt_generation() {
     assert_eq!(receipt.formatted_amount, "R$ 500,00");
     assert_eq!(receipt.status, "Concluído");
     assert_eq!(receipt.merchant_name, "Pleme Tecnologia Ltda");
-    assert_eq!(receipt.customer_document, "123.456.789-00");
+    assert_eq!(receipt.customer_document, "123.456.789-09");
 }
 
 // =============================================================================
@@ -348,12 +361,13 @@ fn test_brazilian_receipt_generation() {
 #[test]
 fn test_macro_composition() {
     // Test that multiple macros can be applied to the same struct
-    #[derive(DatabaseMapper, BrazilianPaymentEntity)]
+    #[derive(Debug, Clone, Serialize, Deserialize, DatabaseMapper, BrazilianEntity, BrazilianPaymentEntity)]
     #[database(table = "payments")]
     #[brazilian_payment(currency = "BRL")]
     pub struct ComposedEntity {
         pub id: Uuid,
         pub amount: Decimal,
+        #[db(enum)]
         pub status: PaymentStatus,
         pub user_id: Uuid,
         pub product: String,
@@ -361,15 +375,6 @@ fn test_macro_composition() {
         pub updated_at: DateTime<Utc>,
     }
     
-    impl ComposedEntity {
-        fn get_id(&self) -> Uuid { self.id }
-        fn get_amount(&self) -> Option<Decimal> { Some(self.amount) }
-        fn get_status(&self) -> PaymentStatus { self.status }
-        fn set_status(&mut self, status: PaymentStatus) { self.status = status; }
-        fn set_updated_at(&mut self, timestamp: DateTime<Utc>) { self.updated_at = timestamp; }
-        fn get_customer_document(&self) -> Option<String> { None }
-    }
-    
     // Test that both macros work together
     assert_eq!(ComposedEntity::table_name(), "payments");
     
@@ -384,6 +389,6 @@ fn test_error_handling() {
     let invalid_amount = TestBrazilianPayment::parse_brl_amount("invalid");
     assert!(invalid_amount.is_err());
     
-    let invalid_pix = TestBrazilianPayment::validate_pix_key("", crate::PixKeyType::Cpf);
+    let invalid_pix = TestBrazilianPayment::validate_pix_key("", crate::TestBrazilianPaymentPixKeyType::Cpf);
     assert!(invalid_pix.is_err());
 }
\ No newline at end of file
@@ -0,0 +1,647 @@
+// Tests for the PaymentEntity derive
+mod common;
+
+use common::MockRedis;
+use pleme_codegen::{PaymentEntity, PaymentMethods};
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaymentStatus {
+    Pending,
+    Processing,
+    Completed,
+    Failed,
+    Refunded,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PaymentMethods)]
+pub enum PaymentMethod {
+    Pix,
+    Boleto,
+    CreditCard,
+    DebitCard,
+    TransferenciaTed,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PaymentError {
+    #[error("Invalid amount")]
+    InvalidAmount,
+    #[error("Invalid state transition from {from:?} to {to:?}")]
+    InvalidStateTransition { from: PaymentStatus, to: PaymentStatus },
+    #[error("Amount too low: minimum {min}, got {actual}")]
+    AmountTooLow { min: Decimal, actual: Decimal },
+    #[error("Amount too high: maximum {max}, got {actual}")]
+    AmountTooHigh { max: Decimal, actual: Decimal },
+    #[error("Amount has {actual} decimal places, currency allows {max}")]
+    InvalidScale { max: u32, actual: u32 },
+    #[error("Transaction failed: {0}")]
+    TransactionFailed(String),
+    #[error("Refund of {requested} exceeds the remaining {remaining}")]
+    RefundExceedsRemaining { requested: Decimal, remaining: Decimal },
+}
+
+#[derive(Debug, Clone, PaymentEntity)]
+#[payment(reconciliation, tolerance = "0.05", risk_signals = "velocity:30,amount:50,new_account:20", comparable_by = "amount")]
+pub struct Payment {
+    pub id: uuid::Uuid,
+    pub amount: Decimal,
+    pub tax: Decimal,
+    pub status: PaymentStatus,
+    pub method: PaymentMethod,
+    pub failure_reason: Option<String>,
+    pub provider_reference: Option<String>,
+    pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub failed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Yen has no minor unit, so any fractional amount is invalid
+#[derive(Debug, Clone, PaymentEntity)]
+#[payment(currency = "JPY")]
+pub struct YenPayment {
+    pub id: uuid::Uuid,
+    pub amount: Decimal,
+    pub tax: Decimal,
+    pub status: PaymentStatus,
+    pub method: PaymentMethod,
+    pub failure_reason: Option<String>,
+    pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub failed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Merchant with a contractual floor and a lower PSP ceiling than the PIX defaults
+#[derive(Debug, Clone, PaymentEntity)]
+#[payment(min_amount = "1.00", max_amount = "50000.00")]
+pub struct BoundedPayment {
+    pub id: uuid::Uuid,
+    pub amount: Decimal,
+    pub tax: Decimal,
+    pub status: PaymentStatus,
+    pub method: PaymentMethod,
+    pub failure_reason: Option<String>,
+    pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub failed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Bank transfer exported to the debtor's bank as pain.001
+#[derive(Debug, Clone, PaymentEntity)]
+#[payment(iso20022)]
+pub struct BankTransfer {
+    pub id: uuid::Uuid,
+    pub amount: Decimal,
+    pub tax: Decimal,
+    pub status: PaymentStatus,
+    pub method: PaymentMethod,
+    pub failure_reason: Option<String>,
+    pub debtor_name: String,
+    pub debtor_account: String,
+    pub creditor_name: String,
+    pub creditor_account: String,
+    pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub failed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Card payment that can be refunded in parts
+#[derive(Debug, Clone, PaymentEntity)]
+#[payment(risk_signals = "velocity:100")]
+pub struct CardPayment {
+    pub id: uuid::Uuid,
+    pub amount: Decimal,
+    pub tax: Decimal,
+    pub refunded_amount: Decimal,
+    pub status: PaymentStatus,
+    pub method: PaymentMethod,
+    pub failure_reason: Option<String>,
+    pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub failed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending_payment(amount: &str) -> Payment {
+        let now = chrono::Utc::now();
+        Payment {
+            id: uuid::Uuid::new_v4(),
+            amount: Decimal::from_str(amount).unwrap(),
+            tax: Decimal::ZERO,
+            status: PaymentStatus::Pending,
+            method: PaymentMethod::Pix,
+            failure_reason: None,
+            provider_reference: Some("E1234567820261016ABC".to_string()),
+            completed_at: None,
+            failed_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_claim_idempotency_rejects_replay() {
+        let payment = pending_payment("150.00");
+        let key = payment.idempotency_key();
+        let mut cache = MockRedis::default();
+
+        assert!(payment.claim_idempotency(&mut cache, &key, 86_400).await.unwrap());
+        assert!(!payment.claim_idempotency(&mut cache, &key, 86_400).await.unwrap());
+
+        // A different payment claims its own key independently
+        let other = pending_payment("150.00");
+        assert!(other.claim_idempotency(&mut cache, &other.idempotency_key(), 86_400).await.unwrap());
+    }
+
+    #[test]
+    fn test_payment_method_round_trip() {
+        for method in PaymentMethod::ALL {
+            assert_eq!(PaymentMethod::from_str(method.as_str()).unwrap(), *method);
+            assert_eq!(method.to_string(), method.as_str());
+        }
+        assert_eq!(PaymentMethod::TransferenciaTed.as_str(), "transferencia_ted");
+        assert_eq!("CREDIT_CARD".parse::<PaymentMethod>().unwrap(), PaymentMethod::CreditCard);
+        assert!("cheque".parse::<PaymentMethod>().is_err());
+    }
+
+    #[test]
+    fn test_payment_method_is_instant() {
+        assert!(PaymentMethod::Pix.is_instant());
+        assert!(!PaymentMethod::Boleto.is_instant());
+        assert!(!PaymentMethod::CreditCard.is_instant());
+        assert!(!PaymentMethod::DebitCard.is_instant());
+        assert!(!PaymentMethod::TransferenciaTed.is_instant());
+    }
+
+    #[test]
+    fn test_reconcile_exact_match() {
+        let payment = pending_payment("150.00");
+        assert_eq!(
+            payment.reconcile_against(Decimal::from_str("150.00").unwrap(), "E1234567820261016ABC"),
+            PaymentReconciliationResult::Matched
+        );
+    }
+
+    #[test]
+    fn test_reconcile_within_tolerance() {
+        let payment = pending_payment("150.00");
+        assert_eq!(Payment::reconciliation_tolerance(), Decimal::from_str("0.05").unwrap());
+        assert_eq!(
+            payment.reconcile_against(Decimal::from_str("149.96").unwrap(), "E1234567820261016ABC"),
+            PaymentReconciliationResult::Matched
+        );
+    }
+
+    #[test]
+    fn test_reconcile_amount_mismatch() {
+        let payment = pending_payment("150.00");
+        assert_eq!(
+            payment.reconcile_against(Decimal::from_str("149.50").unwrap(), "E1234567820261016ABC"),
+            PaymentReconciliationResult::AmountMismatch { diff: Decimal::from_str("-0.50").unwrap() }
+        );
+    }
+
+    #[test]
+    fn test_reconcile_reference_mismatch() {
+        let payment = pending_payment("150.00");
+        assert_eq!(
+            payment.reconcile_against(Decimal::from_str("150.00").unwrap(), "E9999999920261016XYZ"),
+            PaymentReconciliationResult::ReferenceMismatch
+        );
+
+        let mut unsettled = pending_payment("150.00");
+        unsettled.provider_reference = None;
+        assert_eq!(
+            unsettled.reconcile_against(Decimal::from_str("150.00").unwrap(), "E1234567820261016ABC"),
+            PaymentReconciliationResult::ReferenceMismatch
+        );
+    }
+
+    fn risk_context(average_amount: &str, recent_payment_count: u32, account_age_days: i64) -> PaymentRiskContext {
+        PaymentRiskContext {
+            average_amount: Decimal::from_str(average_amount).unwrap(),
+            recent_payment_count,
+            account_age: chrono::Duration::days(account_age_days),
+        }
+    }
+
+    #[test]
+    fn test_risk_score_without_signals_is_low() {
+        let payment = pending_payment("150.00");
+        let ctx = risk_context("120.00", 1, 400);
+
+        assert_eq!(payment.risk_score(&ctx), 0);
+        assert!(!payment.is_high_risk(&ctx, 50));
+    }
+
+    #[test]
+    fn test_risk_score_unusual_amount_alone() {
+        let payment = pending_payment("5000.00");
+        let ctx = risk_context("120.00", 1, 400);
+
+        assert_eq!(payment.risk_score(&ctx), 50);
+        assert!(payment.is_high_risk(&ctx, 50));
+        assert!(!payment.is_high_risk(&ctx, 70));
+    }
+
+    #[test]
+    fn test_risk_score_combines_velocity_and_new_account() {
+        let payment = pending_payment("150.00");
+
+        let velocity_only = risk_context("120.00", 8, 400);
+        assert_eq!(payment.risk_score(&velocity_only), 30);
+        assert!(!payment.is_high_risk(&velocity_only, 50));
+
+        let velocity_and_new = risk_context("120.00", 8, 2);
+        assert_eq!(payment.risk_score(&velocity_and_new), 50);
+        assert!(payment.is_high_risk(&velocity_and_new, 50));
+
+        let all_signals = pending_payment("5000.00");
+        assert_eq!(all_signals.risk_score(&velocity_and_new), 100);
+    }
+
+    #[test]
+    fn test_risk_context_is_per_entity() {
+        // CardPayment scores risk in the same module with its own context type
+        let ctx = CardPaymentRiskContext {
+            average_amount: Decimal::from_str("120.00").unwrap(),
+            recent_payment_count: 8,
+            account_age: chrono::Duration::days(400),
+        };
+        assert_eq!(completed_card_payment().risk_score(&ctx), 100);
+    }
+
+    #[test]
+    fn test_validate_amount_accepts_two_decimal_brl() {
+        assert_eq!(Payment::CURRENCY, "BRL");
+        assert!(pending_payment("10.55").validate_amount().is_ok());
+        // Trailing zeros beyond the minor unit don't change the value
+        assert!(pending_payment("10.5500").validate_amount().is_ok());
+    }
+
+    #[test]
+    fn test_validate_amount_rejects_sub_centavo_brl() {
+        let result = pending_payment("10.555").validate_amount();
+        assert!(matches!(result, Err(PaymentError::InvalidScale { max: 2, actual: 3 })));
+    }
+
+    #[test]
+    fn test_validate_amount_rejects_fraction_for_zero_decimal_currency() {
+        let now = chrono::Utc::now();
+        let mut payment = YenPayment {
+            id: uuid::Uuid::new_v4(),
+            amount: Decimal::from_str("1500").unwrap(),
+            tax: Decimal::ZERO,
+            status: PaymentStatus::Pending,
+            method: PaymentMethod::Pix,
+            failure_reason: None,
+            completed_at: None,
+            failed_at: None,
+            created_at: now,
+            updated_at: now,
+        };
+        assert_eq!(YenPayment::CURRENCY_MINOR_UNITS, 0);
+        assert!(payment.validate_amount().is_ok());
+
+        payment.amount = Decimal::from_str("1500.5").unwrap();
+        assert!(matches!(payment.validate_amount(), Err(PaymentError::InvalidScale { max: 0, actual: 1 })));
+    }
+
+    #[test]
+    fn test_transition_matrix() {
+        use PaymentStatus::*;
+        const STATUSES: [PaymentStatus; 6] = [Pending, Processing, Completed, Failed, Refunded, Cancelled];
+        let allowed = |from: PaymentStatus, to: PaymentStatus| {
+            matches!(
+                (from, to),
+                (Pending, Processing | Completed | Failed) | (Processing, Completed | Failed) | (Completed, Refunded) | (Failed, Failed)
+            )
+        };
+
+        for from in STATUSES {
+            for to in STATUSES {
+                let mut payment = pending_payment("10.00");
+                payment.status = from;
+                assert_eq!(payment.can_transition_to(to), allowed(from, to), "{:?} -> {:?}", from, to);
+
+                let result = match to {
+                    Processing => payment.mark_processing(),
+                    Completed => payment.mark_completed(),
+                    Failed => payment.mark_failed("timeout".to_string()),
+                    Refunded => payment.mark_refunded(),
+                    Pending | Cancelled => continue,
+                };
+                assert_eq!(result.is_ok(), allowed(from, to), "mark {:?} -> {:?}", from, to);
+            }
+        }
+
+        let mut payment = pending_payment("10.00");
+        payment.status = Refunded;
+        assert!(payment.allowed_transitions().is_empty());
+        payment.status = Cancelled;
+        assert!(payment.allowed_transitions().is_empty());
+    }
+
+    #[test]
+    fn test_terminal_statuses() {
+        use PaymentStatus::*;
+        let mut payment = pending_payment("10.00");
+        for (status, terminal) in [
+            (Pending, false),
+            (Processing, false),
+            (Completed, true),
+            (Failed, true),
+            (Refunded, true),
+            (Cancelled, true),
+        ] {
+            payment.status = status;
+            assert_eq!(payment.is_terminal(), terminal, "{:?}", status);
+        }
+    }
+
+    #[test]
+    fn test_find_expired_pending_query() {
+        assert_eq!(Payment::PAYMENT_TABLE, "payments");
+        assert_eq!(
+            Payment::FIND_EXPIRED_PENDING_SQL,
+            "SELECT * FROM payments WHERE product = $1 AND status = 'pending' AND created_at < $2 ORDER BY created_at"
+        );
+
+        let query = Payment::find_expired_pending(chrono::Duration::minutes(30), "nexus");
+        let sql = sqlx::Execute::sql(&query);
+        assert!(sql.contains("created_at < $2"));
+        assert!(sql.contains("status = 'pending'"));
+    }
+
+    #[test]
+    fn test_configured_amount_bounds() {
+        assert_eq!(Payment::MIN_AMOUNT, "0.01");
+        assert_eq!(Payment::MAX_AMOUNT, "1000000.00");
+        assert_eq!(BoundedPayment::MIN_AMOUNT, "1.00");
+        assert_eq!(BoundedPayment::MAX_AMOUNT, "50000.00");
+
+        let now = chrono::Utc::now();
+        let mut payment = BoundedPayment {
+            id: uuid::Uuid::new_v4(),
+            amount: Decimal::from_str("0.50").unwrap(),
+            tax: Decimal::ZERO,
+            status: PaymentStatus::Pending,
+            method: PaymentMethod::Pix,
+            failure_reason: None,
+            completed_at: None,
+            failed_at: None,
+            created_at: now,
+            updated_at: now,
+        };
+        match payment.validate_amount() {
+            Err(PaymentError::AmountTooLow { min, .. }) => assert_eq!(min, Decimal::from_str("1.00").unwrap()),
+            other => panic!("expected AmountTooLow, got {:?}", other),
+        }
+
+        payment.amount = Decimal::from_str("50000.01").unwrap();
+        match payment.validate_amount() {
+            Err(PaymentError::AmountTooHigh { max, .. }) => assert_eq!(max, Decimal::from_str("50000.00").unwrap()),
+            other => panic!("expected AmountTooHigh, got {:?}", other),
+        }
+
+        payment.amount = Decimal::from_str("50000.00").unwrap();
+        assert!(payment.validate_amount().is_ok());
+    }
+
+    /// Check every element closes in order; enough to catch broken nesting or unescaped `<`/`&`
+    fn assert_well_formed_xml(xml: &str) {
+        let body = xml.strip_prefix(r#"<?xml version="1.0" encoding="UTF-8"?>"#).expect("XML declaration");
+        assert!(!body.contains('&') || body.split('&').skip(1).all(|rest| {
+            ["amp;", "lt;", "gt;", "quot;", "apos;"].iter().any(|entity| rest.starts_with(entity))
+        }));
+
+        let mut open = Vec::new();
+        let mut rest = body;
+        while let Some(start) = rest.find('<') {
+            let end = rest[start..].find('>').expect("unterminated tag") + start;
+            let tag = &rest[start + 1..end];
+            if let Some(name) = tag.strip_prefix('/') {
+                assert_eq!(open.pop(), Some(name.to_string()), "mismatched </{}>", name);
+            } else {
+                open.push(tag.split_whitespace().next().unwrap().to_string());
+            }
+            assert!(!rest[..start].contains('>'), "stray `>` in text");
+            rest = &rest[end + 1..];
+        }
+        assert!(open.is_empty(), "unclosed elements: {:?}", open);
+    }
+
+    #[test]
+    fn test_pain001_export_is_well_formed() {
+        let now = chrono::Utc::now();
+        let transfer = BankTransfer {
+            id: uuid::Uuid::new_v4(),
+            amount: Decimal::from_str("1234.5").unwrap(),
+            tax: Decimal::ZERO,
+            status: PaymentStatus::Pending,
+            method: PaymentMethod::TransferenciaTed,
+            failure_reason: None,
+            debtor_name: "Souza & Filhos <Ltda>".to_string(),
+            debtor_account: "0001-123456".to_string(),
+            creditor_name: "Distribuidora \"Sul\"".to_string(),
+            creditor_account: "0341-987654".to_string(),
+            completed_at: None,
+            failed_at: None,
+            created_at: now,
+            updated_at: now,
+        };
+
+        let xml = transfer.to_pain001().unwrap();
+        assert_well_formed_xml(&xml);
+        assert!(xml.contains(r#"xmlns="urn:iso:std:iso:20022:tech:xsd:pain.001.001.03""#));
+        assert!(xml.contains(r#"<InstdAmt Ccy="BRL">1234.50</InstdAmt>"#));
+        assert!(xml.contains(&format!("<EndToEndId>{}</EndToEndId>", transfer.id.simple())));
+        assert!(xml.contains("<Nm>Souza &amp; Filhos &lt;Ltda&gt;</Nm>"));
+        assert!(xml.contains("<Nm>Distribuidora &quot;Sul&quot;</Nm>"));
+
+        let mut invalid = transfer.clone();
+        invalid.amount = Decimal::ZERO;
+        assert!(invalid.to_pain001().is_err());
+    }
+
+    #[test]
+    fn test_sort_payments_by_amount() {
+        let mut payments = [pending_payment("80.00"), pending_payment("15.50"), pending_payment("230.00")];
+        payments.sort_by(Payment::cmp_by_amount);
+
+        let amounts: Vec<String> = payments.iter().map(|p| p.amount.to_string()).collect();
+        assert_eq!(amounts, ["15.50", "80.00", "230.00"]);
+    }
+
+    #[test]
+    fn test_sum_amounts_includes_tax_and_zero_payments() {
+        let mut taxed = pending_payment("100.00");
+        taxed.tax = Decimal::from_str("9.25").unwrap();
+        let payments = [taxed, pending_payment("0.00"), pending_payment("40.75")];
+
+        assert_eq!(Payment::sum_amounts(&payments), Decimal::from_str("150.00").unwrap());
+        assert_eq!(Payment::sum_amounts(&[]), Decimal::ZERO);
+    }
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct ProviderReceipt {
+        end_to_end_id: String,
+    }
+
+    #[tokio::test]
+    async fn test_idempotent_retry_runs_side_effect_once() {
+        let payment = pending_payment("150.00");
+        let key = payment.idempotency_key();
+        let mut cache = MockRedis::default();
+        let calls = std::cell::Cell::new(0);
+
+        let provider_call = || {
+            calls.set(calls.get() + 1);
+            let attempt = calls.get();
+            async move {
+                if attempt == 1 {
+                    Err(PaymentError::TransactionFailed("PSP timeout".to_string()))
+                } else {
+                    Ok(ProviderReceipt { end_to_end_id: format!("E2E-{}", attempt) })
+                }
+            }
+        };
+
+        let first = payment.with_idempotent_retry(&mut cache, &key, 3, provider_call).await.unwrap();
+        assert_eq!(first.end_to_end_id, "E2E-2");
+        assert_eq!(calls.get(), 2);
+
+        // A retried request gets the cached outcome without calling the provider again
+        let replay: ProviderReceipt = payment
+            .with_idempotent_retry(&mut cache, &key, 3, || async { panic!("provider must not be called") })
+            .await
+            .unwrap();
+        assert_eq!(replay, first);
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_idempotent_retry_gives_up_after_max_attempts() {
+        let payment = pending_payment("150.00");
+        let mut cache = MockRedis::default();
+        let calls = std::cell::Cell::new(0);
+        let started = std::time::Instant::now();
+
+        let result: Result<ProviderReceipt, _> = payment
+            .with_idempotent_retry(&mut cache, "pix:retry", 3, || {
+                calls.set(calls.get() + 1);
+                async { Err(PaymentError::TransactionFailed("PSP unavailable".to_string())) }
+            })
+            .await;
+
+        assert!(matches!(result, Err(PaymentError::TransactionFailed(_))));
+        assert_eq!(calls.get(), 3);
+        // Two backoff sleeps: 100ms, then 200ms
+        assert!(started.elapsed() >= std::time::Duration::from_millis(300));
+
+        // Giving up releases the claim, so a later request may run the call again
+        let receipt = payment
+            .with_idempotent_retry(&mut cache, "pix:retry", 3, || async {
+                Ok(ProviderReceipt { end_to_end_id: "E2E-late".to_string() })
+            })
+            .await
+            .unwrap();
+        assert_eq!(receipt.end_to_end_id, "E2E-late");
+    }
+
+    #[tokio::test]
+    async fn test_idempotent_retry_rejects_concurrent_caller() {
+        let payment = pending_payment("150.00");
+        let key = payment.idempotency_key();
+        let mut cache = MockRedis::default();
+
+        // Another caller holds the claim and has not stored an outcome yet
+        assert!(payment.claim_idempotency(&mut cache, &format!("{}:claim", key), 300).await.unwrap());
+
+        let result: Result<ProviderReceipt, _> = payment
+            .with_idempotent_retry(&mut cache, &key, 3, || async { panic!("provider must not be called") })
+            .await;
+
+        assert!(matches!(result, Err(PaymentError::TransactionFailed(reason)) if reason.contains("already being processed")));
+    }
+
+    fn completed_card_payment() -> CardPayment {
+        let now = chrono::Utc::now();
+        CardPayment {
+            id: uuid::Uuid::new_v4(),
+            amount: Decimal::from_str("90.00").unwrap(),
+            tax: Decimal::from_str("10.00").unwrap(),
+            refunded_amount: Decimal::ZERO,
+            status: PaymentStatus::Completed,
+            method: PaymentMethod::CreditCard,
+            failure_reason: None,
+            completed_at: Some(now),
+            failed_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_partial_refunds_summing_to_total_end_refunded() {
+        let mut payment = completed_card_payment();
+
+        payment.refund_partial(Decimal::from_str("40.00").unwrap()).unwrap();
+        assert_eq!(payment.status, PaymentStatus::Completed);
+        assert_eq!(payment.refundable_amount(), Decimal::from_str("60.00").unwrap());
+
+        payment.refund_partial(Decimal::from_str("60.00").unwrap()).unwrap();
+        assert_eq!(payment.status, PaymentStatus::Refunded);
+        assert_eq!(payment.refunded_amount, payment.total_amount());
+    }
+
+    #[test]
+    fn test_partial_refund_rejects_over_refund() {
+        let mut payment = completed_card_payment();
+        payment.refund_partial(Decimal::from_str("70.00").unwrap()).unwrap();
+
+        let result = payment.refund_partial(Decimal::from_str("30.01").unwrap());
+        assert!(matches!(
+            result,
+            Err(PaymentError::RefundExceedsRemaining { requested, remaining })
+                if requested == Decimal::from_str("30.01").unwrap() && remaining == Decimal::from_str("30.00").unwrap()
+        ));
+        assert_eq!(payment.refunded_amount, Decimal::from_str("70.00").unwrap());
+        assert_eq!(payment.status, PaymentStatus::Completed);
+    }
+
+    #[test]
+    fn test_partial_refund_requires_completed_payment() {
+        let mut payment = completed_card_payment();
+        payment.status = PaymentStatus::Pending;
+        assert!(matches!(
+            payment.refund_partial(Decimal::ONE),
+            Err(PaymentError::InvalidStateTransition { from: PaymentStatus::Pending, to: PaymentStatus::Refunded })
+        ));
+    }
+
+    #[test]
+    fn test_default_is_deterministic_and_valid() {
+        let payment = Payment::test_default();
+        assert_eq!(payment.amount, Decimal::from(100));
+        assert_eq!(payment.status, PaymentStatus::Pending);
+        assert_eq!(payment.id, Payment::test_default().id);
+        assert!(payment.completed_at.is_none());
+        assert!(payment.validate_amount().is_ok());
+
+        // Fields outside the payment standard fall back to Default
+        let transfer = BankTransfer::test_default();
+        assert!(transfer.debtor_name.is_empty());
+        assert!(transfer.validate_amount().is_ok());
+    }
+}
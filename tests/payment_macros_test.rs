@@ -59,8 +59,12 @@ pub enum PaymentError {
     AmountTooLow { min: rust_decimal::Decimal, actual: rust_decimal::Decimal },
     #[error("Amount too high: maximum {max}, got {actual}")]
     AmountTooHigh { max: rust_decimal::Decimal, actual: rust_decimal::Decimal },
+    #[error("Amount has {actual} decimal places, currency allows {max}")]
+    InvalidScale { max: u32, actual: u32 },
     #[error("QR code generation failed: {reason}")]
     QrCodeGenerationFailed { reason: String },
+    #[error("Invalid refund: {reason}")]
+    InvalidRefund { reason: String },
     #[error("Transaction failed: {0}")]
     TransactionFailed(String),
     #[error("Validation failed: {0}")]
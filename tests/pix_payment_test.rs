@@ -0,0 +1,301 @@
+// Tests for the PixPayment derive
+use pleme_codegen::PixPayment;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+#[derive(Debug, thiserror::Error)]
+pub enum PaymentError {
+    #[error("Invalid amount")]
+    InvalidAmount,
+    #[error("Invalid PIX key: {reason}")]
+    InvalidPixKey { reason: String },
+    #[error("QR code generation failed: {reason}")]
+    QrCodeGenerationFailed { reason: String },
+    #[error("Invalid refund: {reason}")]
+    InvalidRefund { reason: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixKeyType {
+    Cpf,
+    Cnpj,
+    Email,
+    Phone,
+    Random,
+}
+
+#[derive(Debug, Clone, PixPayment)]
+pub struct PixCheckout {
+    pub amount: Decimal,
+    pub pix_key: String,
+    pub pix_key_type: PixKeyType,
+    pub merchant_name: String,
+    pub end_to_end_id: Option<String>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, PixPayment)]
+#[pix(dynamic = false)]
+pub struct StaticPixCheckout {
+    pub amount: Decimal,
+    pub pix_key: String,
+    pub pix_key_type: PixKeyType,
+    pub merchant_name: String,
+    pub end_to_end_id: Option<String>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Checkout that tracks cumulative devoluções
+#[derive(Debug, Clone, PixPayment)]
+pub struct RefundablePixCheckout {
+    pub amount: Decimal,
+    pub refunded_amount: Decimal,
+    pub pix_key: String,
+    pub pix_key_type: PixKeyType,
+    pub merchant_name: String,
+    pub end_to_end_id: Option<String>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const E2E_ID: &str = "E1234567820261016120000000000001";
+
+    /// Split an EMV payload into its top-level `(tag, value)` pairs
+    fn decode_tlv(payload: &str) -> Vec<(String, String)> {
+        let mut fields = Vec::new();
+        let mut rest = payload;
+        while !rest.is_empty() {
+            let tag = &rest[..2];
+            let len: usize = rest[2..4].parse().unwrap();
+            fields.push((tag.to_string(), rest[4..4 + len].to_string()));
+            rest = &rest[4 + len..];
+        }
+        fields
+    }
+
+    fn tag<'a>(fields: &'a [(String, String)], tag: &str) -> Option<&'a str> {
+        fields.iter().find(|(t, _)| t == tag).map(|(_, value)| value.as_str())
+    }
+
+    fn checkout() -> PixCheckout {
+        PixCheckout {
+            amount: Decimal::from_str("150.00").unwrap(),
+            pix_key: "pagamentos@loja.com.br".to_string(),
+            pix_key_type: PixKeyType::Email,
+            merchant_name: "Loja Exemplo".to_string(),
+            end_to_end_id: Some("TX0001".to_string()),
+            expires_at: chrono::Utc::now() + chrono::Duration::minutes(30),
+        }
+    }
+
+    fn crc_is_valid(payload: &str) -> bool {
+        let (body, crc) = payload.split_at(payload.len() - 4);
+        let mut expected: u16 = 0xFFFF;
+        for byte in body.bytes() {
+            expected ^= (byte as u16) << 8;
+            for _ in 0..8 {
+                expected = if expected & 0x8000 != 0 { (expected << 1) ^ 0x1021 } else { expected << 1 };
+            }
+        }
+        format!("{:04X}", expected) == crc
+    }
+
+    #[test]
+    fn test_dynamic_payload_embeds_amount() {
+        let payload = checkout().generate_qr_payload();
+        let fields = decode_tlv(&payload);
+
+        assert_eq!(tag(&fields, "01"), Some("12"));
+        assert_eq!(tag(&fields, "54"), Some("150.00"));
+        assert_eq!(fields.last().unwrap().0, "63");
+        assert!(crc_is_valid(&payload));
+    }
+
+    #[test]
+    fn test_static_payload_omits_amount() {
+        let payment = StaticPixCheckout {
+            amount: Decimal::from_str("150.00").unwrap(),
+            pix_key: "pagamentos@loja.com.br".to_string(),
+            pix_key_type: PixKeyType::Email,
+            merchant_name: "Loja Exemplo".to_string(),
+            end_to_end_id: Some("TX0001".to_string()),
+            expires_at: chrono::Utc::now() + chrono::Duration::minutes(30),
+        };
+        let payload = payment.generate_qr_payload();
+        let fields = decode_tlv(&payload);
+
+        assert!(payload.starts_with("000201010211"));
+        assert_eq!(tag(&fields, "01"), Some("11"));
+        assert_eq!(tag(&fields, "54"), None);
+        assert_eq!(tag(&fields, "26"), Some("0014BR.GOV.BCB.PIX0122pagamentos@loja.com.br"));
+        assert_eq!(tag(&fields, "62"), Some("0506TX0001"));
+        assert!(crc_is_valid(&payload));
+    }
+
+    #[test]
+    fn test_full_pix_refund() {
+        let mut payment = checkout();
+        payment.end_to_end_id = Some(E2E_ID.to_string());
+
+        let refund = payment.generate_pix_refund(E2E_ID, payment.amount).unwrap();
+
+        assert_eq!(refund.status, PixCheckoutPixRefundStatus::Refunded);
+        assert_eq!(refund.nature, "ORIGINAL");
+        assert!(refund.refund_id.starts_with('D') && refund.refund_id.len() <= 35);
+        assert_eq!(refund.request_path(), format!("/pix/{}/devolucao/{}", E2E_ID, refund.refund_id));
+        assert_eq!(refund.to_request_body(), serde_json::json!({ "valor": "150.00", "natureza": "ORIGINAL" }));
+    }
+
+    #[test]
+    fn test_partial_pix_refunds_accumulate() {
+        let mut payment = RefundablePixCheckout {
+            amount: Decimal::from_str("150.00").unwrap(),
+            refunded_amount: Decimal::ZERO,
+            pix_key: "pagamentos@loja.com.br".to_string(),
+            pix_key_type: PixKeyType::Email,
+            merchant_name: "Loja Exemplo".to_string(),
+            end_to_end_id: Some(E2E_ID.to_string()),
+            expires_at: chrono::Utc::now() + chrono::Duration::minutes(30),
+        };
+        assert_eq!(payment.pix_refund_status(), None);
+
+        let first = payment.generate_pix_refund(E2E_ID, Decimal::from_str("50.00").unwrap()).unwrap();
+        assert_eq!(first.status, RefundablePixCheckoutPixRefundStatus::PartiallyRefunded);
+        assert_eq!(payment.apply_pix_refund(&first).unwrap(), RefundablePixCheckoutPixRefundStatus::PartiallyRefunded);
+        assert_eq!(payment.pix_refund_status(), Some(RefundablePixCheckoutPixRefundStatus::PartiallyRefunded));
+
+        // Only the remaining 100.00 is refundable now
+        assert!(matches!(
+            payment.generate_pix_refund(E2E_ID, Decimal::from_str("100.01").unwrap()),
+            Err(PaymentError::InvalidRefund { .. })
+        ));
+        let rest = payment.generate_pix_refund(E2E_ID, Decimal::from_str("100.00").unwrap()).unwrap();
+        assert_eq!(rest.status, RefundablePixCheckoutPixRefundStatus::Refunded);
+        payment.apply_pix_refund(&rest).unwrap();
+        assert_eq!(payment.pix_refund_status(), Some(RefundablePixCheckoutPixRefundStatus::Refunded));
+    }
+
+    #[test]
+    fn test_pix_refund_rejects_excess_and_foreign_ids() {
+        let mut payment = checkout();
+        payment.end_to_end_id = Some(E2E_ID.to_string());
+
+        assert!(matches!(
+            payment.generate_pix_refund(E2E_ID, Decimal::from_str("150.01").unwrap()),
+            Err(PaymentError::InvalidRefund { .. })
+        ));
+        assert!(matches!(payment.generate_pix_refund(E2E_ID, Decimal::ZERO), Err(PaymentError::InvalidAmount)));
+        assert!(matches!(
+            payment.generate_pix_refund("E9999999920261016120000000000001", Decimal::ONE),
+            Err(PaymentError::InvalidRefund { .. })
+        ));
+        assert!(matches!(
+            payment.generate_pix_refund("TX0001", Decimal::ONE),
+            Err(PaymentError::InvalidRefund { .. })
+        ));
+    }
+
+    #[test]
+    fn test_accented_merchant_name_truncates_on_char_boundary() {
+        let mut payment = checkout();
+        // 27 bytes; the 25-byte limit falls inside the two-byte "ç"
+        payment.merchant_name = "José Comércio Alimentação".to_string();
+
+        let payload = payment.generate_qr_payload();
+        let fields = decode_tlv(&payload);
+
+        assert_eq!(tag(&fields, "59"), Some("José Comércio Alimenta"));
+        // Length prefix counts bytes: 22 chars, 24 bytes
+        assert!(payload.contains("5924José Comércio Alimenta62"));
+        assert!(crc_is_valid(&payload));
+    }
+
+    #[test]
+    fn test_single_payload_matches_batch_payload() {
+        let payment = checkout();
+        let ctx = payment.merchant_context();
+        let charge = PixCheckoutPixCharge { amount: payment.amount, txid: payment.end_to_end_id.clone() };
+
+        let batch = PixCheckout::generate_qr_payloads(&[charge], &ctx);
+        assert_eq!(batch[0].as_ref().unwrap(), &payment.generate_qr_payload());
+    }
+
+    #[test]
+    fn test_batch_payloads_share_merchant_tlv() {
+        let ctx = checkout().merchant_context();
+        let charges = vec![
+            PixCheckoutPixCharge { amount: Decimal::from_str("10.00").unwrap(), txid: Some("FAT001".to_string()) },
+            PixCheckoutPixCharge { amount: Decimal::from_str("25.50").unwrap(), txid: Some("FAT002".to_string()) },
+            PixCheckoutPixCharge { amount: Decimal::from_str("99.99").unwrap(), txid: None },
+        ];
+
+        let payloads = PixCheckout::generate_qr_payloads(&charges, &ctx);
+        assert_eq!(payloads.len(), 3);
+
+        for (payload, charge) in payloads.iter().zip(&charges) {
+            let payload = payload.as_ref().unwrap();
+            assert!(payload.starts_with(&format!("000201010212{}", ctx.merchant_account_tlv)));
+            assert!(payload.contains(&format!("5802BR{}", ctx.merchant_name_tlv)));
+            assert!(payload.contains(&format!("{:.2}", charge.amount)));
+            assert!(crc_is_valid(payload));
+        }
+        assert!(payloads[0].as_ref().unwrap().contains("0506FAT001"));
+        assert!(payloads[1].as_ref().unwrap().contains("0506FAT002"));
+    }
+
+    #[test]
+    fn test_batch_reports_invalid_charges_individually() {
+        let ctx = checkout().merchant_context();
+        let charges = vec![
+            PixCheckoutPixCharge { amount: Decimal::ZERO, txid: None },
+            PixCheckoutPixCharge { amount: Decimal::ONE, txid: Some("has-dashes".to_string()) },
+            PixCheckoutPixCharge { amount: Decimal::ONE, txid: None },
+        ];
+
+        let payloads = PixCheckout::generate_qr_payloads(&charges, &ctx);
+        assert!(matches!(payloads[0], Err(PaymentError::InvalidAmount)));
+        assert!(matches!(payloads[1], Err(PaymentError::QrCodeGenerationFailed { .. })));
+        assert!(payloads[2].is_ok());
+    }
+
+    fn with_key(pix_key_type: PixKeyType, pix_key: &str) -> PixCheckout {
+        PixCheckout { pix_key_type, pix_key: pix_key.to_string(), ..checkout() }
+    }
+
+    #[test]
+    fn test_evp_key_accepts_canonical_uuid() {
+        let canonical = "123e4567-e89b-12d3-a456-426614174000";
+        assert_eq!(canonical.len(), 36);
+        assert!(with_key(PixKeyType::Random, canonical).validate_pix_key().is_ok());
+        assert!(with_key(PixKeyType::Random, &canonical.replace('-', "")).validate_pix_key().is_ok());
+
+        assert!(with_key(PixKeyType::Random, "123e4567-e89b-12d3-a456-42661417400").validate_pix_key().is_err());
+        assert!(with_key(PixKeyType::Random, "zz3e4567-e89b-12d3-a456-426614174000").validate_pix_key().is_err());
+    }
+
+    #[test]
+    fn test_email_key_format_and_length_boundary() {
+        let domain = "@pagamentos.lojaexemplo.com.br";
+        let at_limit = format!("{}{}", "a".repeat(77 - domain.len()), domain);
+        assert_eq!(at_limit.len(), 77);
+        assert!(with_key(PixKeyType::Email, &at_limit).validate_pix_key().is_ok());
+
+        let over_limit = format!("a{}", at_limit);
+        assert!(with_key(PixKeyType::Email, &over_limit).validate_pix_key().is_err());
+        for invalid in ["@loja.com.br", "ana@", "ana@loja", "ana@@loja.com", "ana souza@loja.com", "ana@loja..com"] {
+            assert!(with_key(PixKeyType::Email, invalid).validate_pix_key().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_phone_key_requires_e164_brazil() {
+        assert!(with_key(PixKeyType::Phone, "+5511987654321").validate_pix_key().is_ok());
+
+        for invalid in ["11987654321", "+1 4155552671", "+55 11 98765-4321", "+551198765432", "+5501987654321"] {
+            assert!(with_key(PixKeyType::Phone, invalid).validate_pix_key().is_err(), "{}", invalid);
+        }
+    }
+}
@@ -0,0 +1,242 @@
+// Tests for the RepositoryCrud derive
+mod common;
+
+use common::MockRedis;
+use pleme_codegen::{DomainModel, RepositoryCrud};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug)]
+pub enum PaymentError {
+    TransactionFailed(String),
+}
+
+#[derive(RepositoryCrud)]
+#[cached(invalidate_on = "status,amount")]
+#[repository(slow_query_ms = 250, idempotency_ttl_secs = 3600)]
+pub struct PaymentRepository {
+    pub redis: Option<deadpool_redis::Pool>,
+}
+
+#[derive(RepositoryCrud)]
+pub struct LedgerRepository {
+    pub redis: Option<deadpool_redis::Pool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedPayment {
+    pub id: uuid::Uuid,
+    pub status: String,
+    pub amount: String,
+    pub view_count: u64,
+}
+
+/// Audit rows are read rarely, so caching them at creation only evicts hot entries
+#[derive(Debug, Clone, Serialize, Deserialize, DomainModel)]
+#[domain(cache_warm_on_create = false, repository = PaymentRepository)]
+pub struct AuditEntry {
+    pub id: uuid::Uuid,
+    pub action: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, DomainModel)]
+#[domain(repository = PaymentRepository)]
+pub struct HotAccount {
+    pub id: uuid::Uuid,
+    pub balance: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Redis holding `keys` payment entries, served two per SCAN page
+    fn nexus_redis(keys: usize) -> MockRedis {
+        MockRedis::with_keys((0..keys).map(|i| format!("payment:nexus:{}", i))).scan_page_size(2)
+    }
+
+    fn cached_payment() -> CachedPayment {
+        CachedPayment {
+            id: uuid::Uuid::new_v4(),
+            status: "pending".to_string(),
+            amount: "99.90".to_string(),
+            view_count: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unlisted_field_change_skips_cache_write() {
+        let previous = cached_payment();
+        let mut updated = previous.clone();
+        updated.view_count += 1;
+        let mut cache = MockRedis::default();
+
+        let refreshed = PaymentRepository::refresh_cache_if_changed(&mut cache, &previous, &updated, "payment:1", 300)
+            .await
+            .unwrap();
+
+        assert!(!refreshed);
+        assert!(cache.commands.is_empty());
+        assert!(PaymentRepository::cache_relevant_changes(&previous, &updated).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_listed_field_change_refreshes_cache() {
+        let previous = cached_payment();
+        let mut updated = previous.clone();
+        updated.status = "completed".to_string();
+        updated.view_count += 1;
+        let mut cache = MockRedis::default();
+
+        let refreshed = PaymentRepository::refresh_cache_if_changed(&mut cache, &previous, &updated, "payment:1", 300)
+            .await
+            .unwrap();
+
+        assert!(refreshed);
+        assert_eq!(cache.commands, ["DEL", "SET"]);
+        assert_eq!(PaymentRepository::cache_relevant_changes(&previous, &updated), ["status"]);
+    }
+
+    #[tokio::test]
+    async fn test_missing_invalidate_on_field_refreshes_cache() {
+        /// `amount` is renamed on the wire, so `invalidate_on = "status,amount"` cannot find it
+        #[derive(Clone, Serialize)]
+        struct RenamedPayment {
+            status: String,
+            #[serde(rename = "totalAmount")]
+            amount: String,
+        }
+
+        let previous = RenamedPayment { status: "pending".to_string(), amount: "99.90".to_string() };
+        let mut cache = MockRedis::default();
+
+        let refreshed = PaymentRepository::refresh_cache_if_changed(&mut cache, &previous, &previous.clone(), "payment:1", 300)
+            .await
+            .unwrap();
+
+        assert!(refreshed);
+        assert_eq!(cache.commands, ["DEL", "SET"]);
+        assert_eq!(PaymentRepository::cache_relevant_changes(&previous, &previous), ["amount"]);
+    }
+
+    #[test]
+    fn test_slow_query_threshold_is_configurable() {
+        assert_eq!(PaymentRepository::SLOW_QUERY_MS, 250);
+        assert!(PaymentRepository::is_slow_query(300));
+        assert!(!PaymentRepository::is_slow_query(250));
+
+        // Unconfigured repositories keep the 1s default
+        assert_eq!(LedgerRepository::SLOW_QUERY_MS, 1000);
+        assert!(!LedgerRepository::is_slow_query(300));
+    }
+
+    #[tokio::test]
+    async fn test_cache_warm_on_create_false_skips_cache_write() {
+        let entry = AuditEntry { id: uuid::Uuid::new_v4(), action: "login".to_string() };
+        let mut cache = MockRedis::default();
+
+        let cached = PaymentRepository::cache_on_create(&mut cache, &entry, "audit:1")
+            .await
+            .unwrap();
+
+        assert!(!cached);
+        assert!(cache.commands.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cache_warm_on_create_defaults_to_caching() {
+        let account = HotAccount { id: uuid::Uuid::new_v4(), balance: "10.00".to_string() };
+        let mut cache = MockRedis::default();
+
+        let cached = PaymentRepository::cache_on_create(&mut cache, &account, "account:1")
+            .await
+            .unwrap();
+
+        assert!(cached);
+        assert_eq!(cache.commands, ["SET"]);
+    }
+
+    #[tokio::test]
+    async fn test_retried_create_returns_stored_entity_without_second_write() {
+        let mut cache = MockRedis::default();
+        let writes = std::sync::atomic::AtomicUsize::new(0);
+        let create = |payment: CachedPayment| {
+            let writes = &writes;
+            move || async move {
+                writes.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                Ok(payment)
+            }
+        };
+
+        let first = cached_payment();
+        let created = PaymentRepository::create_once(&mut cache, "checkout-42", 3600, create(first.clone()))
+            .await
+            .unwrap();
+
+        // The retry carries a fresh payload but must replay the original result
+        let mut retry = cached_payment();
+        retry.amount = "100.00".to_string();
+        let replayed: CachedPayment = PaymentRepository::create_once(&mut cache, "checkout-42", 3600, create(retry))
+            .await
+            .unwrap();
+
+        assert_eq!(writes.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(replayed.id, first.id);
+        assert_eq!(replayed.amount, "99.90");
+        assert_eq!(serde_json::to_string(&replayed).unwrap(), serde_json::to_string(&created).unwrap());
+        assert_eq!(cache.ttls["idem:checkout-42"], 3600);
+    }
+
+    #[tokio::test]
+    async fn test_distinct_idempotency_keys_create_separately() {
+        let mut cache = MockRedis::default();
+        let writes = std::sync::atomic::AtomicUsize::new(0);
+        let count = |payment: CachedPayment| {
+            let writes = &writes;
+            move || async move {
+                writes.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                Ok(payment)
+            }
+        };
+
+        PaymentRepository::create_once(&mut cache, "checkout-1", 60, count(cached_payment())).await.unwrap();
+        PaymentRepository::create_once(&mut cache, "checkout-2", 60, count(cached_payment())).await.unwrap();
+
+        assert_eq!(writes.load(std::sync::atomic::Ordering::SeqCst), 2);
+        assert_eq!(PaymentRepository::idempotency_cache_key("checkout-1"), "idem:checkout-1");
+    }
+
+    #[test]
+    fn test_idempotency_ttl_defaults_to_a_day() {
+        assert_eq!(PaymentRepository::IDEMPOTENCY_TTL_SECS, 3600);
+        assert_eq!(LedgerRepository::IDEMPOTENCY_TTL_SECS, 86_400);
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_pattern_scans_and_unlinks_in_batches() {
+        let mut redis = nexus_redis(5);
+
+        let deleted = PaymentRepository::unlink_matching(&mut redis, "payment:nexus:*").await.unwrap();
+
+        assert_eq!(deleted, 5);
+        assert_eq!(redis.commands, ["SCAN", "UNLINK", "SCAN", "UNLINK", "SCAN", "UNLINK"]);
+        assert!(!redis.commands.iter().any(|command| command == "KEYS"));
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_pattern_falls_back_to_del_without_unlink() {
+        let mut redis = nexus_redis(3).without_unlink();
+
+        let deleted = PaymentRepository::unlink_matching(&mut redis, "payment:nexus:*").await.unwrap();
+
+        assert_eq!(deleted, 3);
+        assert_eq!(redis.commands, ["SCAN", "UNLINK", "DEL", "SCAN", "DEL"]);
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_pattern_with_no_matches_deletes_nothing() {
+        let mut redis = nexus_redis(0);
+
+        assert_eq!(PaymentRepository::unlink_matching(&mut redis, "payment:*").await.unwrap(), 0);
+        assert_eq!(redis.commands, ["SCAN"]);
+    }
+}
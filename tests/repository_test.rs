@@ -0,0 +1,243 @@
+// Tests for the Repository derive
+use pleme_codegen::{DomainModel, Repository};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow, DomainModel, Repository)]
+#[repository(cache_ttl = 60)]
+pub struct Note {
+    pub id: uuid::Uuid,
+    pub product: String,
+    pub title: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow, DomainModel, Repository)]
+#[domain(soft_delete)]
+pub struct ArchivedNote {
+    pub id: uuid::Uuid,
+    pub product: String,
+    pub title: String,
+    pub deleted_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Cache store keeping entries in memory, ignoring TTLs
+#[derive(Default)]
+pub struct MemoryCache {
+    entries: Mutex<HashMap<String, String>>,
+}
+
+macro_rules! impl_memory_cache_store {
+    ($store:ident) => {
+        #[async_trait::async_trait]
+        impl $store for MemoryCache {
+            async fn get(&self, key: &str) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+                Ok(self.entries.lock().unwrap().get(key).cloned())
+            }
+
+            async fn set(&self, key: &str, value: String, _ttl: u64) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+                self.entries.lock().unwrap().insert(key.to_string(), value);
+                Ok(())
+            }
+
+            async fn delete(&self, key: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+                self.entries.lock().unwrap().remove(key);
+                Ok(())
+            }
+
+            async fn delete_pattern(&self, pattern: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+                let prefix = pattern.trim_end_matches('*');
+                self.entries.lock().unwrap().retain(|key, _| !key.starts_with(prefix));
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_memory_cache_store!(NoteCacheStore);
+impl_memory_cache_store!(ArchivedNoteCacheStore);
+
+fn archived_note(product: &str, title: &str) -> ArchivedNote {
+    let now = chrono::Utc::now();
+    ArchivedNote {
+        id: uuid::Uuid::new_v4(),
+        product: product.to_string(),
+        title: title.to_string(),
+        deleted_at: None,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+fn note(product: &str, title: &str) -> Note {
+    let now = chrono::Utc::now();
+    Note {
+        id: uuid::Uuid::new_v4(),
+        product: product.to_string(),
+        title: title.to_string(),
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+/// Pool for the database-backed tests; they are skipped when DATABASE_URL is unset
+async fn database() -> Option<sqlx::PgPool> {
+    let url = std::env::var("DATABASE_URL").ok()?;
+    Some(sqlx::PgPool::connect(&url).await.expect("DATABASE_URL should be reachable"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lazy_repository(cache: Arc<MemoryCache>) -> NoteRepository {
+        let pool = sqlx::PgPool::connect_lazy("postgres://localhost/unreachable").unwrap();
+        NoteRepository::new(pool, cache)
+    }
+
+    #[test]
+    fn test_cache_keys_are_scoped_to_product() {
+        let id = uuid::Uuid::nil();
+        assert_eq!(
+            NoteRepository::cache_key("novaskyn", id),
+            "novaskyn:note:00000000-0000-0000-0000-000000000000"
+        );
+        assert_eq!(NoteRepository::cache_pattern("novaskyn"), "novaskyn:note:*");
+        assert_eq!(NoteRepository::CACHE_TTL_SECS, 60);
+    }
+
+    #[tokio::test]
+    async fn test_find_by_id_is_served_from_cache() {
+        let cache = Arc::new(MemoryCache::default());
+        let cached = note("novaskyn", "cached");
+        cache.entries.lock().unwrap().insert(
+            NoteRepository::cache_key("novaskyn", cached.id),
+            serde_json::to_string(&cached).unwrap(),
+        );
+
+        // The pool points nowhere, so a cache miss would fail
+        let repository = lazy_repository(cache);
+        let found = repository.find_by_id(cached.id, "novaskyn").await.unwrap();
+
+        assert_eq!(found, Some(cached));
+    }
+
+    #[tokio::test]
+    async fn test_find_by_field_rejects_unknown_columns() {
+        let repository = lazy_repository(Arc::new(MemoryCache::default()));
+
+        let result = repository.find_by_field("title; DROP TABLE notes", "x", "novaskyn").await;
+
+        assert!(matches!(result, Err(NoteRepositoryError::InvalidField(field)) if field == "title; DROP TABLE notes"));
+    }
+
+    #[tokio::test]
+    async fn test_clear_cache_drops_only_the_product() {
+        let cache = Arc::new(MemoryCache::default());
+        let kept = note("other", "kept");
+        for entity in [note("novaskyn", "a"), kept.clone()] {
+            cache.entries.lock().unwrap().insert(
+                NoteRepository::cache_key(&entity.product, entity.id),
+                serde_json::to_string(&entity).unwrap(),
+            );
+        }
+
+        let repository = lazy_repository(cache.clone());
+        repository.clear_cache("novaskyn").await.unwrap();
+
+        let remaining: Vec<String> = cache.entries.lock().unwrap().keys().cloned().collect();
+        assert_eq!(remaining, vec![NoteRepository::cache_key("other", kept.id)]);
+    }
+
+    #[tokio::test]
+    async fn test_crud_round_trip_against_postgres() {
+        let Some(pool) = database().await else { return };
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS notes (
+                id UUID PRIMARY KEY,
+                product TEXT NOT NULL,
+                title TEXT NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL,
+                updated_at TIMESTAMPTZ NOT NULL
+            )",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let cache = Arc::new(MemoryCache::default());
+        let repository = NoteRepository::new(pool, cache.clone());
+        let product = format!("crud-{}", uuid::Uuid::new_v4());
+
+        let created = repository.create(&note(&product, "first")).await.unwrap();
+        assert_eq!(created.title, "first");
+        assert!(repository.exists(created.id, &product).await.unwrap());
+        assert!(!repository.exists(created.id, "another-product").await.unwrap());
+
+        let mut edited = created.clone();
+        edited.title = "edited".to_string();
+        let updated = repository.update(&edited).await.unwrap();
+        assert_eq!(updated.title, "edited");
+        assert!(updated.updated_at >= created.updated_at);
+
+        repository.bulk_create(&[note(&product, "second"), note(&product, "third")]).await.unwrap();
+        assert_eq!(repository.count_by_product(&product).await.unwrap(), 3);
+        assert_eq!(repository.list_by_product(&product, 2, 0, false).await.unwrap().len(), 2);
+        assert_eq!(repository.find_by_field("title", "edited", &product).await.unwrap(), vec![updated.clone()]);
+
+        cache.entries.lock().unwrap().clear();
+        assert_eq!(repository.find_by_id(created.id, &product).await.unwrap(), Some(updated));
+
+        assert!(repository.delete(created.id, &product).await.unwrap());
+        assert!(!repository.delete(created.id, &product).await.unwrap());
+        assert_eq!(repository.find_by_id(created.id, &product).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_soft_delete_and_restore_against_postgres() {
+        let Some(pool) = database().await else { return };
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS archivednotes (
+                id UUID PRIMARY KEY,
+                product TEXT NOT NULL,
+                title TEXT NOT NULL,
+                deleted_at TIMESTAMPTZ,
+                created_at TIMESTAMPTZ NOT NULL,
+                updated_at TIMESTAMPTZ NOT NULL
+            )",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let repository = ArchivedNoteRepository::new(pool, Arc::new(MemoryCache::default()));
+        let product = format!("soft-delete-{}", uuid::Uuid::new_v4());
+        let archived = repository.create(&archived_note(&product, "archived")).await.unwrap();
+        let live = repository.create(&archived_note(&product, "live")).await.unwrap();
+
+        // find_by_id caches the live copy, which soft_delete must evict
+        repository.find_by_id(archived.id, &product).await.unwrap();
+        assert!(repository.soft_delete(archived.id, &product).await.unwrap());
+        assert!(!repository.soft_delete(archived.id, &product).await.unwrap());
+        let deleted = repository.find_by_id(archived.id, &product).await.unwrap().unwrap();
+        assert!(deleted.deleted_at.is_some());
+
+        let visible = repository.list_by_product(&product, 10, 0, false).await.unwrap();
+        assert_eq!(visible.iter().map(|n| n.id).collect::<Vec<_>>(), vec![live.id]);
+        assert_eq!(repository.list_by_product(&product, 10, 0, true).await.unwrap().len(), 2);
+
+        assert!(repository.restore(archived.id, &product).await.unwrap());
+        assert!(!repository.restore(archived.id, &product).await.unwrap());
+        let restored = repository.find_by_id(archived.id, &product).await.unwrap().unwrap();
+        assert_eq!(restored.deleted_at, None);
+        assert_eq!(repository.list_by_product(&product, 10, 0, false).await.unwrap().len(), 2);
+
+        // delete stays a hard delete
+        assert!(repository.delete(archived.id, &product).await.unwrap());
+        assert_eq!(repository.list_by_product(&product, 10, 0, true).await.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,263 @@
+// Tests for the RowMapper derive
+use pleme_codegen::RowMapper;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+#[derive(Debug)]
+pub enum PaymentError {
+    TransactionFailed(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaymentStatus {
+    Pending,
+    Completed,
+}
+
+impl std::fmt::Display for PaymentStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            PaymentStatus::Pending => "pending",
+            PaymentStatus::Completed => "completed",
+        })
+    }
+}
+
+impl FromStr for PaymentStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(PaymentStatus::Pending),
+            "completed" => Ok(PaymentStatus::Completed),
+            other => Err(format!("unknown status `{}`", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaymentMetadata {
+    pub order_ref: String,
+    pub installments: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, RowMapper)]
+#[database(row_struct = "PaymentRow")]
+pub struct Payment {
+    pub id: uuid::Uuid,
+    pub amount: Decimal,
+    pub discount: Option<Decimal>,
+    #[row(enum)]
+    pub status: PaymentStatus,
+    #[row(json)]
+    pub metadata: PaymentMetadata,
+    pub description: Option<String>,
+}
+
+/// Thin DB-mapped representation kept apart from the domain struct
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaymentRow {
+    pub id: uuid::Uuid,
+    pub amount: Decimal,
+    pub discount: Option<Decimal>,
+    pub status: String,
+    pub metadata: serde_json::Value,
+    pub description: Option<String>,
+}
+
+/// Stand-in for the `tokio_postgres` crate: same `Row::try_get::<I, T>` shape,
+/// backed by a column map, so the generated mapper compiles and runs here
+pub mod tokio_postgres {
+    use std::any::Any;
+    use std::collections::HashMap;
+
+    #[derive(Debug)]
+    pub struct Error(pub String);
+
+    impl std::fmt::Display for Error {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(&self.0)
+        }
+    }
+
+    impl std::error::Error for Error {}
+
+    #[derive(Default)]
+    pub struct Row {
+        columns: HashMap<String, Box<dyn Any>>,
+    }
+
+    impl Row {
+        pub fn with<T: Any>(mut self, column: &str, value: T) -> Self {
+            self.columns.insert(column.to_string(), Box::new(value));
+            self
+        }
+
+        pub fn try_get<I: AsRef<str>, T: Any + Clone>(&self, idx: I) -> Result<T, Error> {
+            let column = idx.as_ref();
+            self.columns.get(column)
+                .ok_or_else(|| Error(format!("column `{}` not found", column)))?
+                .downcast_ref::<T>()
+                .cloned()
+                .ok_or_else(|| Error(format!("column `{}` has an unexpected type", column)))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, RowMapper)]
+#[row(backend = "tokio_postgres")]
+pub struct Refund {
+    pub id: uuid::Uuid,
+    pub amount: Decimal,
+    #[row(enum)]
+    pub status: PaymentStatus,
+    #[row(json)]
+    pub metadata: PaymentMetadata,
+    pub reason: Option<String>,
+}
+
+/// Enum outside the type-name allowlist: mapped only through `#[row(enum)]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisputeStage {
+    Inquiry,
+    Arbitration,
+}
+
+impl FromStr for DisputeStage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "inquiry" => Ok(DisputeStage::Inquiry),
+            "arbitration" => Ok(DisputeStage::Arbitration),
+            other => Err(format!("unknown dispute stage `{}`", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DisputeEvidence {
+    pub documents: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, RowMapper)]
+#[row(backend = "tokio_postgres")]
+pub struct Chargeback {
+    pub id: uuid::Uuid,
+    #[row(rename = "disputed_amount")]
+    pub amount: Decimal,
+    #[row(enum)]
+    pub stage: DisputeStage,
+    #[row(json, rename = "evidence_json")]
+    pub evidence: DisputeEvidence,
+    #[row(skip)]
+    pub display_label: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payment_row() -> PaymentRow {
+        PaymentRow {
+            id: uuid::Uuid::new_v4(),
+            amount: Decimal::from_str("1250.90").unwrap(),
+            discount: Some(Decimal::from_str("25.00").unwrap()),
+            status: "completed".to_string(),
+            metadata: serde_json::json!({ "order_ref": "PED-2026-0042", "installments": 3 }),
+            description: None,
+        }
+    }
+
+    #[test]
+    fn test_row_struct_round_trip() {
+        let row = payment_row();
+
+        let payment = Payment::try_from(row.clone()).unwrap();
+        assert_eq!(payment.amount, Decimal::from_str("1250.90").unwrap());
+        assert_eq!(payment.discount, Some(Decimal::from_str("25.00").unwrap()));
+        assert_eq!(payment.status, PaymentStatus::Completed);
+        assert_eq!(payment.metadata.order_ref, "PED-2026-0042");
+
+        assert_eq!(PaymentRow::from(&payment), row);
+    }
+
+    #[test]
+    fn test_row_struct_rejects_invalid_column_values() {
+        let mut row = payment_row();
+        row.status = "chargeback".to_string();
+        assert!(Payment::try_from(row).unwrap_err().contains("status"));
+
+        let mut row = payment_row();
+        row.metadata = serde_json::json!({ "order_ref": "PED-2026-0042" });
+        assert!(Payment::try_from(row).unwrap_err().contains("metadata"));
+    }
+
+    #[test]
+    fn test_tokio_postgres_backend_maps_rows() {
+        let id = uuid::Uuid::new_v4();
+        let row = tokio_postgres::Row::default()
+            .with("id", id)
+            .with("amount", Decimal::from_str("99.90").unwrap())
+            .with("status", "pending".to_string())
+            .with("metadata", serde_json::json!({ "order_ref": "PED-2026-0043", "installments": 1 }))
+            .with("reason", Some("duplicate charge".to_string()));
+
+        let refund = Refund::from_tokio_row(&row).unwrap();
+        assert_eq!(refund.id, id);
+        assert_eq!(refund.amount, Decimal::from_str("99.90").unwrap());
+        assert_eq!(refund.status, PaymentStatus::Pending);
+        assert_eq!(refund.metadata.order_ref, "PED-2026-0043");
+        assert_eq!(refund.reason.as_deref(), Some("duplicate charge"));
+
+        let bad_status = tokio_postgres::Row::default()
+            .with("id", id)
+            .with("amount", Decimal::ONE)
+            .with("status", "chargeback".to_string());
+        let PaymentError::TransactionFailed(message) = Refund::from_tokio_row(&bad_status).unwrap_err();
+        assert!(message.contains("status"));
+    }
+
+    #[test]
+    fn test_renamed_and_skipped_fields() {
+        let id = uuid::Uuid::new_v4();
+        let row = tokio_postgres::Row::default()
+            .with("id", id)
+            .with("disputed_amount", Decimal::from_str("310.00").unwrap())
+            .with("stage", "inquiry".to_string())
+            .with("evidence_json", serde_json::json!({ "documents": [] }));
+
+        let chargeback = Chargeback::from_tokio_row(&row).unwrap();
+        assert_eq!(chargeback.id, id);
+        assert_eq!(chargeback.amount, Decimal::from_str("310.00").unwrap());
+        assert_eq!(chargeback.display_label, String::default());
+
+        // The field name is no longer read
+        let by_field_name = tokio_postgres::Row::default()
+            .with("id", id)
+            .with("amount", Decimal::ONE);
+        let PaymentError::TransactionFailed(message) = Chargeback::from_tokio_row(&by_field_name).unwrap_err();
+        assert!(message.contains("disputed_amount"));
+    }
+
+    #[test]
+    fn test_enum_and_json_attributes_map_types_outside_the_allowlist() {
+        let row = tokio_postgres::Row::default()
+            .with("id", uuid::Uuid::new_v4())
+            .with("disputed_amount", Decimal::ONE)
+            .with("stage", "arbitration".to_string())
+            .with("evidence_json", serde_json::json!({ "documents": ["receipt.pdf"] }));
+
+        let chargeback = Chargeback::from_tokio_row(&row).unwrap();
+        assert_eq!(chargeback.stage, DisputeStage::Arbitration);
+        assert_eq!(chargeback.evidence.documents, vec!["receipt.pdf".to_string()]);
+
+        let bad_stage = tokio_postgres::Row::default()
+            .with("id", uuid::Uuid::new_v4())
+            .with("disputed_amount", Decimal::ONE)
+            .with("stage", "escalated".to_string());
+        let PaymentError::TransactionFailed(message) = Chargeback::from_tokio_row(&bad_stage).unwrap_err();
+        assert!(message.contains("stage"));
+    }
+}
@@ -0,0 +1,161 @@
+// Tests for the Service derive
+use pleme_codegen::{DomainModel, Repository, Service};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow, DomainModel, Repository)]
+#[domain(tenant_guard)]
+pub struct Ticket {
+    pub id: uuid::Uuid,
+    pub product: String,
+    pub subject: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Service)]
+pub struct TicketService {
+    repository: Arc<dyn TicketRepositoryTrait>,
+}
+
+/// Repository whose lookups ignore the product, reproducing a tenant-scoping bug
+#[derive(Default)]
+pub struct LeakyTicketRepository {
+    rows: Mutex<HashMap<uuid::Uuid, Ticket>>,
+}
+
+#[async_trait::async_trait]
+impl TicketRepositoryTrait for LeakyTicketRepository {
+    async fn create(&self, entity: &Ticket) -> TicketRepositoryResult<Ticket> {
+        self.rows.lock().unwrap().insert(entity.id, entity.clone());
+        Ok(entity.clone())
+    }
+
+    async fn find_by_id(&self, id: uuid::Uuid, _product: &str) -> TicketRepositoryResult<Option<Ticket>> {
+        Ok(self.rows.lock().unwrap().get(&id).cloned())
+    }
+
+    async fn update(&self, entity: &Ticket) -> TicketRepositoryResult<Ticket> {
+        self.rows.lock().unwrap().insert(entity.id, entity.clone());
+        Ok(entity.clone())
+    }
+
+    async fn delete(&self, id: uuid::Uuid, _product: &str) -> TicketRepositoryResult<bool> {
+        Ok(self.rows.lock().unwrap().remove(&id).is_some())
+    }
+
+    async fn list_by_product(&self, product: &str, limit: i64, offset: i64, _include_deleted: bool) -> TicketRepositoryResult<Vec<Ticket>> {
+        Ok(self.rows.lock().unwrap().values()
+            .filter(|ticket| ticket.product == product)
+            .skip(offset as usize)
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn count_by_product(&self, product: &str) -> TicketRepositoryResult<i64> {
+        Ok(self.rows.lock().unwrap().values().filter(|ticket| ticket.product == product).count() as i64)
+    }
+
+    async fn find_by_field(&self, _field: &str, _value: &str, _product: &str) -> TicketRepositoryResult<Vec<Ticket>> {
+        Ok(Vec::new())
+    }
+
+    async fn exists(&self, id: uuid::Uuid, _product: &str) -> TicketRepositoryResult<bool> {
+        Ok(self.rows.lock().unwrap().contains_key(&id))
+    }
+
+    async fn bulk_create(&self, entities: &[Ticket]) -> TicketRepositoryResult<Vec<Ticket>> {
+        for entity in entities {
+            self.create(entity).await?;
+        }
+        Ok(entities.to_vec())
+    }
+
+    async fn clear_cache(&self, _product: &str) -> TicketRepositoryResult<()> {
+        Ok(())
+    }
+}
+
+fn ticket(product: &str, subject: &str) -> Ticket {
+    let now = chrono::Utc::now();
+    Ticket {
+        id: uuid::Uuid::new_v4(),
+        product: product.to_string(),
+        subject: subject.to_string(),
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn service_with(tickets: &[Ticket]) -> (TicketService, Arc<LeakyTicketRepository>) {
+        let repository = Arc::new(LeakyTicketRepository::default());
+        repository.bulk_create(tickets).await.unwrap();
+        (TicketService { repository: repository.clone() }, repository)
+    }
+
+    #[tokio::test]
+    async fn test_get_by_id_returns_entity_of_the_same_product() {
+        let owned = ticket("novaskyn", "refund");
+        let (service, _) = service_with(std::slice::from_ref(&owned)).await;
+
+        assert_eq!(service.get_by_id(owned.id, "novaskyn").await.unwrap(), owned);
+    }
+
+    #[tokio::test]
+    async fn test_get_by_id_with_mismatched_product_is_not_found() {
+        // The id exists, but under another tenant
+        let foreign = ticket("thai", "refund");
+        let (service, _) = service_with(std::slice::from_ref(&foreign)).await;
+
+        let result = service.get_by_id(foreign.id, "novaskyn").await;
+
+        assert!(matches!(result, Err(TicketServiceError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_update_with_mismatched_product_is_not_found() {
+        let foreign = ticket("thai", "refund");
+        let (service, repository) = service_with(std::slice::from_ref(&foreign)).await;
+
+        let mut edited = foreign.clone();
+        edited.subject = "hijacked".to_string();
+        let result = service.update("novaskyn", edited.clone()).await;
+        assert!(matches!(result, Err(TicketServiceError::NotFound(_))));
+
+        // Relabelling the entity does not get past the guard either
+        edited.product = "novaskyn".to_string();
+        let result = service.update("novaskyn", edited).await;
+        assert!(matches!(result, Err(TicketServiceError::NotFound(_))));
+
+        assert_eq!(repository.rows.lock().unwrap()[&foreign.id], foreign);
+    }
+
+    #[tokio::test]
+    async fn test_delete_with_mismatched_product_is_not_found() {
+        let foreign = ticket("thai", "refund");
+        let (service, repository) = service_with(std::slice::from_ref(&foreign)).await;
+
+        let result = service.delete(foreign.id, "novaskyn").await;
+
+        assert!(matches!(result, Err(TicketServiceError::NotFound(_))));
+        assert!(repository.rows.lock().unwrap().contains_key(&foreign.id));
+        service.delete(foreign.id, "thai").await.unwrap();
+        assert!(repository.rows.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_create_rejects_entity_of_another_product() {
+        let (service, _) = service_with(&[]).await;
+
+        let result = service.create("novaskyn", ticket("thai", "refund")).await;
+
+        assert!(matches!(result, Err(TicketServiceError::Validation(_))));
+        assert_eq!(service.count("novaskyn").await.unwrap(), 0);
+    }
+}
@@ -0,0 +1,69 @@
+// Tests for the ShippingEntity derive
+use pleme_codegen::ShippingEntity;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, ShippingEntity)]
+pub struct Shipment;
+
+#[derive(Debug, Clone, ShippingEntity)]
+#[shipping(zone_rates = "local:10, national:40.00")]
+pub struct RegionalCarrierShipment;
+
+fn kg(weight: &str) -> Decimal {
+    Decimal::from_str(weight).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_region_freight() {
+        // São Paulo capital to São Paulo capital
+        assert_eq!(Shipment::freight_zone("01310-100", "04538-132"), Ok("local"));
+        assert_eq!(Shipment.freight_for_cep("01310-100", "04538-132", kg("0.8")), Ok(kg("12.50")));
+        // 2.3 kg: first kg plus two started kg at half the base rate
+        assert_eq!(Shipment.freight_for_cep("01310-100", "04538-132", kg("2.3")), Ok(kg("25.00")));
+
+        // São Paulo to Rio de Janeiro stays within the Sudeste
+        assert_eq!(Shipment::freight_zone("01310-100", "20040-020"), Ok("regional"));
+        assert_eq!(Shipment.freight_for_cep("01310-100", "20040-020", kg("1")), Ok(kg("18.90")));
+    }
+
+    #[test]
+    fn test_cross_region_freight() {
+        // São Paulo to Porto Alegre
+        assert_eq!(Shipment::freight_zone("01310-100", "90010-150"), Ok("national"));
+        assert_eq!(Shipment.freight_for_cep("01310-100", "90010-150", kg("3")), Ok(kg("59.80")));
+    }
+
+    #[test]
+    fn test_configured_zone_rates() {
+        let shipment = RegionalCarrierShipment;
+        assert_eq!(shipment.freight_for_cep("01310-100", "04538-132", kg("1")), Ok(kg("10.00")));
+        // Unlisted zones keep the default rate
+        assert_eq!(shipment.freight_for_cep("01310-100", "20040-020", kg("1")), Ok(kg("18.90")));
+        assert_eq!(shipment.freight_for_cep("01310-100", "90010-150", kg("1")), Ok(kg("40.00")));
+        assert_eq!(
+            shipment.freight_for_cep("01310-100", "90010-150", Decimal::ZERO),
+            Err(RegionalCarrierShipmentShippingError::InvalidWeight(Decimal::ZERO))
+        );
+    }
+
+    #[test]
+    fn test_invalid_cep_is_rejected() {
+        assert_eq!(
+            Shipment.freight_for_cep("0131", "90010-150", kg("1")),
+            Err(ShipmentShippingError::InvalidCep("0131".to_string()))
+        );
+        assert_eq!(
+            Shipment.freight_for_cep("01310-100", "00000-000", kg("1")),
+            Err(ShipmentShippingError::InvalidCep("00000-000".to_string()))
+        );
+        assert_eq!(
+            Shipment.freight_for_cep("01310-100", "90010-150", Decimal::ZERO),
+            Err(ShipmentShippingError::InvalidWeight(Decimal::ZERO))
+        );
+    }
+}
@@ -0,0 +1,271 @@
+// Tests for the SmartRepository derive
+use pleme_codegen::SmartRepository;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pleme_codegen::correlation_context!();
+
+#[derive(SmartRepository, Default)]
+#[repository(metrics_prefix = "payments", correlation_id)]
+pub struct LedgerRepository {
+    smart_cache: LedgerRepositorySmartCache,
+}
+
+#[derive(SmartRepository, Default)]
+#[repository(correlation_id)]
+pub struct AuditRepository {
+    smart_cache: AuditRepositorySmartCache,
+}
+
+/// `(target, metric)` of a recorded event
+type RecordedEvent = (String, Option<String>);
+
+/// Subscriber that records the target, `metric` and `correlation_id` fields of every event
+#[derive(Clone, Default)]
+struct TargetRecorder {
+    events: Arc<Mutex<Vec<RecordedEvent>>>,
+    correlation_ids: Arc<Mutex<Vec<Option<String>>>>,
+}
+
+/// Captures the debug rendering of one named field
+struct FieldVisitor(&'static str, Option<String>);
+
+impl tracing::field::Visit for FieldVisitor {
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        if field.name() == self.0 {
+            self.1 = Some(format!("{:?}", value));
+        }
+    }
+}
+
+impl tracing::Subscriber for TargetRecorder {
+    fn enabled(&self, _metadata: &tracing::Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+        tracing::span::Id::from_u64(1)
+    }
+
+    fn record(&self, _span: &tracing::span::Id, _values: &tracing::span::Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &tracing::span::Id, _follows: &tracing::span::Id) {}
+
+    fn event(&self, event: &tracing::Event<'_>) {
+        let mut visitor = FieldVisitor("metric", None);
+        event.record(&mut visitor);
+        self.events.lock().unwrap().push((event.metadata().target().to_string(), visitor.1));
+
+        // Events outside a correlation scope carry an empty id
+        let mut visitor = FieldVisitor("correlation_id", None);
+        event.record(&mut visitor);
+        self.correlation_ids.lock().unwrap().push(visitor.1.filter(|id| !id.is_empty()));
+    }
+
+    fn enter(&self, _span: &tracing::span::Id) {}
+
+    fn exit(&self, _span: &tracing::span::Id) {}
+}
+
+fn record_events<F: std::future::Future>(future: F) -> Vec<RecordedEvent> {
+    let recorder = TargetRecorder::default();
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    tracing::subscriber::with_default(recorder.clone(), || runtime.block_on(future));
+    let events = recorder.events.lock().unwrap().clone();
+    events
+}
+
+/// Correlation id seen by each event the future emits, in order
+fn record_correlation_ids<F: std::future::Future>(future: F) -> Vec<Option<String>> {
+    let recorder = TargetRecorder::default();
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    tracing::subscriber::with_default(recorder.clone(), || runtime.block_on(future));
+    let correlation_ids = recorder.correlation_ids.lock().unwrap().clone();
+    correlation_ids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_prefix_sets_tracing_target() {
+        let events = record_events(async {
+            LedgerRepository::default().create_with_observability(&"entry".to_string(), None).await.unwrap();
+            let _: Option<String> = LedgerRepository::default()
+                .find_with_smart_cache("42", || async { Ok::<_, std::io::Error>(Some("entry".to_string())) })
+                .await
+                .unwrap();
+        });
+
+        assert_eq!(events.len(), 4);
+        assert!(events.iter().all(|(target, _)| target == "payments::repository"));
+
+        let metrics: Vec<&str> = events.iter().filter_map(|(_, metric)| metric.as_deref()).collect();
+        assert_eq!(metrics, ["payments.repository.ledgerrepository.create", "payments.repository.ledgerrepository.find_with_cache"]);
+    }
+
+    #[test]
+    fn test_unprefixed_repository_keeps_module_target() {
+        assert_eq!(AuditRepository::METRICS_PREFIX, "");
+        assert_eq!(AuditRepository::metric_name("CREATE"), "repository.auditrepository.create");
+
+        let events = record_events(async {
+            AuditRepository::default().create_with_observability(&1u32, None).await.unwrap();
+        });
+        // Defaults to the module the derive expanded in
+        assert!(!events.is_empty());
+        assert!(events.iter().all(|(target, _)| target == "smart_repository_test"));
+    }
+
+    #[test]
+    fn test_borrowing_create_matches_cloning_create() {
+        let entry = vec!["debit".to_string(); 64];
+
+        let mut owned = None;
+        let owned_events = record_events(async {
+            owned = Some(LedgerRepository::default().create_with_observability(&entry, None).await.unwrap());
+        });
+        let borrowed_events = record_events(async {
+            let borrowed = LedgerRepository::default().create_with_observability_ref(&entry, None).await.unwrap();
+            assert!(std::ptr::eq(borrowed, &entry));
+        });
+
+        assert_eq!(owned.as_ref(), Some(&entry));
+        assert_eq!(owned_events, borrowed_events);
+    }
+
+    #[test]
+    fn test_correlation_id_tags_events_inside_scope() {
+        let correlation_ids = record_correlation_ids(async {
+            LedgerRepository::with_correlation_id("req-7f3a", async {
+                LedgerRepository::default().create_with_observability(&"entry".to_string(), None).await.unwrap();
+                // Other generated types pick up the same scope
+                AuditRepository::default().create_with_observability(&1u32, None).await.unwrap();
+            })
+            .await;
+            AuditRepository::default().create_with_observability(&2u32, None).await.unwrap();
+        });
+
+        let tagged = Some("req-7f3a".to_string());
+        assert_eq!(correlation_ids, [tagged.clone(), tagged.clone(), tagged.clone(), tagged, None, None]);
+    }
+
+    #[test]
+    fn test_correlation_id_follows_spawned_tasks() {
+        let correlation_ids = record_correlation_ids(async {
+            LedgerRepository::with_correlation_id("req-9c1d", async {
+                correlation::spawn(async {
+                    AuditRepository::default().create_with_observability(&1u32, None).await.unwrap();
+                })
+                .await
+                .unwrap();
+                // A plain spawn leaves the task-local behind
+                tokio::spawn(async {
+                    AuditRepository::default().create_with_observability(&2u32, None).await.unwrap();
+                })
+                .await
+                .unwrap();
+            })
+            .await;
+        });
+
+        let tagged = Some("req-9c1d".to_string());
+        assert_eq!(correlation_ids, [tagged.clone(), tagged, None, None]);
+    }
+
+    #[tokio::test]
+    async fn test_smart_cache_calls_fetcher_only_on_miss() {
+        let repository = AuditRepository::default();
+        let fetches = AtomicUsize::new(0);
+        let fetch = || async {
+            fetches.fetch_add(1, Ordering::SeqCst);
+            Ok::<_, std::io::Error>(Some(vec!["debit".to_string(), "credit".to_string()]))
+        };
+
+        let first = repository.find_with_smart_cache("entry-1", fetch).await.unwrap();
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+        let second = repository.find_with_smart_cache("entry-1", fetch).await.unwrap();
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        assert_eq!(first, second);
+
+        repository.evict_from_smart_cache::<Vec<String>>("entry-1");
+        repository.find_with_smart_cache("entry-1", fetch).await.unwrap();
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_smart_cache_does_not_cache_missing_or_failed_fetches() {
+        let repository = AuditRepository::default();
+        let fetches = AtomicUsize::new(0);
+
+        for _ in 0..2 {
+            let missing: Option<u64> = repository
+                .find_with_smart_cache("entry-missing", || async {
+                    fetches.fetch_add(1, Ordering::SeqCst);
+                    Ok::<_, std::io::Error>(None)
+                })
+                .await
+                .unwrap();
+            assert_eq!(missing, None);
+        }
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+
+        let failed = repository
+            .find_with_smart_cache("entry-failed", || async {
+                Err::<Option<u64>, _>(std::io::Error::other("database unavailable"))
+            })
+            .await;
+        assert_eq!(failed.unwrap_err().to_string(), "database unavailable");
+    }
+
+    #[tokio::test]
+    async fn test_smart_cache_keys_entries_by_type_and_instance() {
+        let repository = AuditRepository::default();
+        let fetches = AtomicUsize::new(0);
+
+        let entry: Option<String> = repository
+            .find_with_smart_cache("7", || async {
+                fetches.fetch_add(1, Ordering::SeqCst);
+                Ok::<_, std::io::Error>(Some("entry".to_string()))
+            })
+            .await
+            .unwrap();
+        // The same id for another entity type is a separate entry
+        let amount: Option<u64> = repository
+            .find_with_smart_cache("7", || async {
+                fetches.fetch_add(1, Ordering::SeqCst);
+                Ok::<_, std::io::Error>(Some(700))
+            })
+            .await
+            .unwrap();
+        assert_eq!((entry.as_deref(), amount), (Some("entry"), Some(700)));
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+        assert_eq!(repository.smart_cache.len(), 2);
+
+        // Another repository instance does not see this one's entries
+        assert!(AuditRepository::default().smart_cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_smart_cache_is_bounded_and_expires() {
+        let repository = AuditRepository { smart_cache: AuditRepositorySmartCache::new(2, Duration::from_secs(60)) };
+        let fetch = |value: u64| move || async move { Ok::<_, std::io::Error>(Some(value)) };
+
+        repository.find_with_smart_cache("1", fetch(1)).await.unwrap();
+        repository.find_with_smart_cache("2", fetch(2)).await.unwrap();
+        // Touch 1 so 2 is the least recently used when 3 arrives
+        repository.find_with_smart_cache("1", fetch(0)).await.unwrap();
+        repository.find_with_smart_cache("3", fetch(3)).await.unwrap();
+        assert_eq!(repository.smart_cache.len(), 2);
+
+        assert_eq!(repository.find_with_smart_cache("1", fetch(0)).await.unwrap(), Some(1));
+        assert_eq!(repository.find_with_smart_cache("2", fetch(20)).await.unwrap(), Some(20));
+
+        let expiring = AuditRepository { smart_cache: AuditRepositorySmartCache::new(8, Duration::ZERO) };
+        expiring.find_with_smart_cache("1", fetch(1)).await.unwrap();
+        assert_eq!(expiring.find_with_smart_cache("1", fetch(10)).await.unwrap(), Some(10));
+    }
+}
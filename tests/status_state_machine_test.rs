@@ -0,0 +1,118 @@
+// Tests for the StatusStateMachine derive
+use pleme_codegen::StatusStateMachine;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, StatusStateMachine)]
+#[status(db_mapping_test, triggers = "Pending->Paid: pay, Shipped->Delivered: confirm_delivery")]
+pub enum OrderStatus {
+    Pending,
+    Paid,
+    Shipped,
+    Delivered,
+    Cancelled,
+}
+
+/// `Chargeback` is not covered by `to_str`, so it silently maps to "unknown"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, StatusStateMachine)]
+pub enum DisputeStatus {
+    Disputed,
+    Chargeback,
+    Refunded,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_db_mapping_round_trips_every_variant() {
+        assert_eq!(OrderStatus::VARIANTS.len(), 5);
+        assert!(OrderStatus::verify_db_mapping().is_ok());
+
+        for status in OrderStatus::VARIANTS {
+            assert_eq!(OrderStatus::from_str(status.to_str()).unwrap(), *status);
+        }
+        assert!(OrderStatus::from_str("teleported").is_err());
+    }
+
+    #[test]
+    fn test_db_mapping_fails_when_variant_string_is_missing() {
+        let err = DisputeStatus::verify_db_mapping().unwrap_err();
+
+        assert!(err.contains("DisputeStatus::Chargeback"));
+        assert!(err.contains("\"unknown\""));
+        assert_eq!(DisputeStatus::from_str("chargeback").unwrap(), DisputeStatus::Chargeback);
+    }
+
+    #[test]
+    fn test_validate_transition_sequence_legal_history() {
+        let history = [
+            OrderStatus::Pending,
+            OrderStatus::Paid,
+            OrderStatus::Paid,
+            OrderStatus::Cancelled,
+        ];
+        assert_eq!(OrderStatus::validate_transition_sequence(&history), Ok(()));
+        assert_eq!(OrderStatus::validate_transition_sequence(&[]), Ok(()));
+        assert_eq!(OrderStatus::validate_transition_sequence(&[OrderStatus::Delivered]), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_transition_sequence_reports_first_illegal_hop() {
+        let history = [
+            OrderStatus::Pending,
+            OrderStatus::Paid,
+            OrderStatus::Delivered,
+            OrderStatus::Pending,
+        ];
+
+        let (index, reason) = OrderStatus::validate_transition_sequence(&history).unwrap_err();
+        assert_eq!(index, 2);
+        assert!(reason.contains("Paid -> Delivered"));
+    }
+
+    #[test]
+    fn test_status_graph_dot_matches_declared_transitions() {
+        let dot = OrderStatus::status_graph_dot();
+        let lines: Vec<&str> = dot.lines().map(str::trim).collect();
+
+        assert_eq!(lines.first(), Some(&"digraph OrderStatus {"));
+        assert_eq!(lines.last(), Some(&"}"));
+
+        let nodes: Vec<&str> = lines.iter()
+            .filter(|line| line.starts_with('"') && !line.contains("->"))
+            .map(|line| line.trim_end_matches(';').trim_matches('"'))
+            .collect();
+        let edges: Vec<(&str, &str)> = lines.iter()
+            .filter_map(|line| line.split_once(" -> "))
+            .map(|(from, to)| (from.trim_matches('"'), to.split('"').nth(1).unwrap()))
+            .collect();
+
+        assert_eq!(nodes, ["Pending", "Paid", "Shipped", "Delivered", "Cancelled"]);
+
+        // One edge per allowed transition between distinct declared variants
+        let mut expected = Vec::new();
+        for from in OrderStatus::VARIANTS {
+            for to in OrderStatus::VARIANTS {
+                if from != to && from.can_transition_to(to) {
+                    expected.push((format!("{:?}", from), format!("{:?}", to)));
+                }
+            }
+        }
+        assert_eq!(edges.len(), expected.len());
+        assert_eq!(edges.len(), 4);
+        for (from, to) in &expected {
+            assert!(edges.contains(&(from.as_str(), to.as_str())), "missing edge {} -> {}", from, to);
+        }
+    }
+
+    #[test]
+    fn test_status_graph_dot_labels_known_triggers() {
+        let dot = OrderStatus::status_graph_dot();
+
+        assert!(dot.contains("\"Pending\" -> \"Paid\" [label=\"pay\"];"));
+        assert!(dot.contains("\"Shipped\" -> \"Delivered\" [label=\"confirm_delivery\"];"));
+        assert!(dot.contains("\"Pending\" -> \"Cancelled\";"));
+        assert!(!DisputeStatus::status_graph_dot().contains("label"));
+    }
+}
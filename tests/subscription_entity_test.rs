@@ -0,0 +1,205 @@
+// Tests for the SubscriptionEntity derive
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use pleme_codegen::SubscriptionEntity;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubscriptionStatus {
+    Active,
+    Trialing,
+    PastDue,
+    Paused,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BillingInterval {
+    Monthly,
+    Quarterly,
+    Yearly,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PaymentError {
+    #[error("Invalid subscription transition from {from:?} to {to:?}")]
+    InvalidSubscriptionStateTransition { from: SubscriptionStatus, to: SubscriptionStatus },
+    #[error("Change date {change_date} is outside the period {period_start} - {period_end}")]
+    ProrationOutsidePeriod {
+        change_date: DateTime<Utc>,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    },
+}
+
+#[derive(Debug, Clone, SubscriptionEntity)]
+pub struct Subscription {
+    pub id: uuid::Uuid,
+    pub status: SubscriptionStatus,
+    pub interval: BillingInterval,
+    pub price: Decimal,
+    pub current_period_start: DateTime<Utc>,
+    pub current_period_end: DateTime<Utc>,
+    pub trial_start: Option<DateTime<Utc>>,
+    pub trial_end: Option<DateTime<Utc>>,
+    pub trial_converted_at: Option<DateTime<Utc>>,
+    pub pause_collection: Option<DateTime<Utc>>,
+    pub pause_reason: Option<String>,
+    pub cancelled_at: Option<DateTime<Utc>>,
+    pub cancellation_reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+pub mod per_second {
+    use super::{BillingInterval, PaymentError, SubscriptionStatus};
+    use chrono::{DateTime, Utc};
+    use pleme_codegen::SubscriptionEntity;
+    use rust_decimal::Decimal;
+
+    #[derive(Debug, Clone, SubscriptionEntity)]
+    #[subscription(proration = "second")]
+    pub struct MeteredSubscription {
+        pub id: uuid::Uuid,
+        pub status: SubscriptionStatus,
+        pub interval: BillingInterval,
+        pub price: Decimal,
+        pub current_period_start: DateTime<Utc>,
+        pub current_period_end: DateTime<Utc>,
+        pub trial_start: Option<DateTime<Utc>>,
+        pub trial_end: Option<DateTime<Utc>>,
+        pub trial_converted_at: Option<DateTime<Utc>>,
+        pub pause_collection: Option<DateTime<Utc>>,
+        pub pause_reason: Option<String>,
+        pub cancelled_at: Option<DateTime<Utc>>,
+        pub cancellation_reason: Option<String>,
+        pub created_at: DateTime<Utc>,
+        pub updated_at: DateTime<Utc>,
+    }
+}
+
+use per_second::MeteredSubscription;
+
+fn period_start() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 4, 1, 0, 0, 0).unwrap()
+}
+
+fn subscription(price: &str) -> Subscription {
+    let start = period_start();
+    Subscription {
+        id: uuid::Uuid::new_v4(),
+        status: SubscriptionStatus::Active,
+        interval: BillingInterval::Monthly,
+        price: Decimal::from_str(price).unwrap(),
+        current_period_start: start,
+        current_period_end: start + Duration::days(30),
+        trial_start: None,
+        trial_end: None,
+        trial_converted_at: None,
+        pause_collection: None,
+        pause_reason: None,
+        cancelled_at: None,
+        cancellation_reason: None,
+        created_at: start,
+        updated_at: start,
+    }
+}
+
+#[test]
+fn test_mid_cycle_upgrade_charges_half_the_difference() {
+    let subscription = subscription("100.00");
+    let change_date = period_start() + Duration::days(15);
+
+    let proration = subscription
+        .calculate_proration(Decimal::from_str("160.00").unwrap(), change_date)
+        .unwrap();
+
+    // 15 of 30 days left: 80.00 for the new plan minus 50.00 unused credit
+    assert_eq!(proration, Decimal::from_str("30.00").unwrap());
+}
+
+#[test]
+fn test_mid_cycle_downgrade_is_a_credit() {
+    let subscription = subscription("100.00");
+    let change_date = period_start() + Duration::days(20);
+
+    let proration = subscription
+        .calculate_proration(Decimal::from_str("40.00").unwrap(), change_date)
+        .unwrap();
+
+    assert_eq!(proration, Decimal::from_str("-20.00").unwrap());
+}
+
+#[test]
+fn test_day_granularity_ignores_partial_days() {
+    let subscription = subscription("100.00");
+    let change_date = period_start() + Duration::days(15) + Duration::hours(12);
+
+    let proration = subscription
+        .calculate_proration(Decimal::from_str("160.00").unwrap(), change_date)
+        .unwrap();
+
+    // 14 whole days remain
+    assert_eq!(proration, Decimal::from_str("28.00").unwrap());
+}
+
+#[test]
+fn test_change_on_period_boundaries() {
+    let subscription = subscription("100.00");
+    let new_amount = Decimal::from_str("160.00").unwrap();
+
+    let at_start = subscription
+        .calculate_proration(new_amount, subscription.current_period_start)
+        .unwrap();
+    assert_eq!(at_start, Decimal::from_str("60.00").unwrap());
+
+    let at_end = subscription
+        .calculate_proration(new_amount, subscription.current_period_end)
+        .unwrap();
+    assert_eq!(at_end, Decimal::ZERO);
+}
+
+#[test]
+fn test_change_outside_period_is_rejected() {
+    let subscription = subscription("100.00");
+    let new_amount = Decimal::from_str("160.00").unwrap();
+
+    let before = subscription.calculate_proration(new_amount, period_start() - Duration::seconds(1));
+    assert!(matches!(before, Err(PaymentError::ProrationOutsidePeriod { .. })));
+
+    let after = subscription.calculate_proration(
+        new_amount,
+        subscription.current_period_end + Duration::seconds(1),
+    );
+    assert!(matches!(after, Err(PaymentError::ProrationOutsidePeriod { .. })));
+}
+
+#[test]
+fn test_per_second_proration_counts_partial_days() {
+    let base = subscription("100.00");
+    let subscription = MeteredSubscription {
+        id: base.id,
+        status: base.status,
+        interval: base.interval,
+        price: base.price,
+        current_period_start: base.current_period_start,
+        current_period_end: base.current_period_end,
+        trial_start: None,
+        trial_end: None,
+        trial_converted_at: None,
+        pause_collection: None,
+        pause_reason: None,
+        cancelled_at: None,
+        cancellation_reason: None,
+        created_at: base.created_at,
+        updated_at: base.updated_at,
+    };
+    let change_date = period_start() + Duration::days(15) + Duration::hours(12);
+
+    let proration = subscription
+        .calculate_proration(Decimal::from_str("160.00").unwrap(), change_date)
+        .unwrap();
+
+    // 14.5 of 30 days remain: 60.00 * 14.5 / 30
+    assert_eq!(proration, Decimal::from_str("29.00").unwrap());
+}
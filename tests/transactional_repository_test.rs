@@ -0,0 +1,227 @@
+// Tests for the TransactionalRepository derive
+use pleme_codegen::TransactionalRepository;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+pub mod models {
+    #[derive(Debug, Clone, PartialEq, thiserror::Error)]
+    pub enum LedgerError {
+        #[error("Transaction failed: {0}")]
+        TransactionFailed(String),
+        #[error("Validation failed: {0}")]
+        ValidationFailed(String),
+    }
+}
+
+use models::LedgerError;
+
+#[derive(TransactionalRepository)]
+#[transactional(error_type = "LedgerError")]
+pub struct LedgerRepository {
+    pub pool: sqlx::PgPool,
+}
+
+#[derive(TransactionalRepository)]
+#[transactional(error_type = "LedgerError", isolation_level = "Serializable")]
+pub struct SettlementRepository {
+    pub pool: sqlx::PgPool,
+}
+
+/// In-memory checkpoint store standing in for a persistent one
+#[derive(Default)]
+pub struct MemoryCheckpoints {
+    checkpoints: Mutex<HashMap<String, usize>>,
+}
+
+#[async_trait::async_trait]
+impl LedgerRepositoryCheckpointStore for MemoryCheckpoints {
+    async fn load_checkpoint(&self, job_id: &str) -> Result<Option<usize>, LedgerError> {
+        Ok(self.checkpoints.lock().unwrap().get(job_id).copied())
+    }
+
+    async fn save_checkpoint(&self, job_id: &str, processed: usize) -> Result<(), LedgerError> {
+        self.checkpoints.lock().unwrap().insert(job_id.to_string(), processed);
+        Ok(())
+    }
+
+    async fn clear_checkpoint(&self, job_id: &str) -> Result<(), LedgerError> {
+        self.checkpoints.lock().unwrap().remove(job_id);
+        Ok(())
+    }
+}
+
+/// Wallet balances standing in for the locked rows `atomic_transfer` updates
+pub struct MemoryBalances(HashMap<uuid::Uuid, rust_decimal::Decimal>);
+
+/// Updater for `apply_transfer`: applies `delta` when `apply` is set and returns the balance
+fn memory_updater(
+    balances: &mut MemoryBalances,
+    id: uuid::Uuid,
+    delta: rust_decimal::Decimal,
+    apply: bool,
+) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<rust_decimal::Decimal, LedgerError>> + Send + '_>> {
+    Box::pin(async move {
+        let balance = balances.0.get_mut(&id).ok_or_else(|| LedgerError::ValidationFailed(format!("unknown wallet {}", id)))?;
+        if apply {
+            *balance += delta;
+        }
+        Ok(*balance)
+    })
+}
+
+/// Repository over a pool that never connects; the tests below return before using it
+fn offline_repository() -> LedgerRepository {
+    LedgerRepository {
+        pool: sqlx::PgPool::connect_lazy("postgres://localhost/ledger").unwrap(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_atomic_transfer_rejects_invalid_requests_before_locking() {
+        let repository = offline_repository();
+        let from_id = uuid::Uuid::new_v4();
+        let to_id = uuid::Uuid::new_v4();
+
+        let zero = repository
+            .atomic_transfer(from_id, to_id, rust_decimal::Decimal::ZERO, |_: &i64, _| Ok(()), |_, _, _, _| {
+                Box::pin(async { Ok(0) })
+            })
+            .await;
+        assert!(matches!(zero, Err(LedgerError::ValidationFailed(_))));
+
+        let same_account = repository
+            .atomic_transfer(from_id, from_id, rust_decimal::Decimal::ONE, |_: &i64, _| Ok(()), |_, _, _, _| {
+                Box::pin(async { Ok(0) })
+            })
+            .await;
+        assert!(matches!(same_account, Err(LedgerError::ValidationFailed(_))));
+    }
+
+    #[tokio::test]
+    async fn test_retry_transaction_retries_only_retryable_failures() {
+        let repository = offline_repository();
+
+        let attempts = Arc::new(AtomicU32::new(0));
+        let counter = attempts.clone();
+        let deadlocked: Result<(), _> = repository
+            .retry_transaction(3, 1, move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Box::pin(async { Err(LedgerError::TransactionFailed("deadlock detected".to_string())) })
+            })
+            .await;
+        assert!(deadlocked.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+        let attempts = Arc::new(AtomicU32::new(0));
+        let counter = attempts.clone();
+        let invalid: Result<(), _> = repository
+            .retry_transaction(3, 1, move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Box::pin(async { Err(LedgerError::ValidationFailed("negative balance".to_string())) })
+            })
+            .await;
+        assert!(invalid.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_resumable_batches_resume_after_last_checkpoint() {
+        let store = MemoryCheckpoints::default();
+        let items: Vec<u32> = (1..=10).collect();
+
+        // First run: batches 1 and 2 commit, then the process dies during batch 3
+        let mut first_run = Vec::new();
+        let crashed = LedgerRepository::run_checkpointed_batches("settlement", items.clone(), 3, &store, |batch| {
+            first_run.push(batch.clone());
+            let crash = first_run.len() == 3;
+            async move {
+                if crash {
+                    Err(LedgerError::TransactionFailed("connection reset".to_string()))
+                } else {
+                    Ok(batch)
+                }
+            }
+        })
+        .await;
+        assert!(crashed.is_err());
+        assert_eq!(store.load_checkpoint("settlement").await.unwrap(), Some(6));
+
+        // Second run starts at batch 3 instead of reprocessing batches 1 and 2
+        let mut second_run = Vec::new();
+        let resumed = LedgerRepository::run_checkpointed_batches("settlement", items, 3, &store, |batch| {
+            second_run.push(batch.clone());
+            async move { Ok(batch) }
+        })
+        .await
+        .unwrap();
+        assert_eq!(second_run, vec![vec![7, 8, 9], vec![10]]);
+        assert_eq!(resumed, vec![7, 8, 9, 10]);
+
+        // A finished job leaves no checkpoint behind
+        assert_eq!(store.load_checkpoint("settlement").await.unwrap(), None);
+    }
+
+    #[test]
+    fn test_advisory_lock_keys_differ_for_distinct_ids() {
+        let a = uuid::Uuid::from_u128(0x1111_2222_3333_4444_5555_6666_7777_8888);
+        let b = uuid::Uuid::from_u128(0x9999_aaaa_bbbb_cccc_dddd_eeee_ffff_0000);
+        assert_ne!(LedgerRepository::advisory_lock_keys(&a), LedgerRepository::advisory_lock_keys(&b));
+
+        // Ids differing only in their high half used to share a lock under `as_u128() as i64`
+        let c = uuid::Uuid::from_u128(0x2222_2222_3333_4444_5555_6666_7777_8888);
+        assert_ne!(LedgerRepository::advisory_lock_keys(&a), LedgerRepository::advisory_lock_keys(&c));
+
+        // The same id always maps to the same lock
+        assert_eq!(LedgerRepository::advisory_lock_keys(&a), LedgerRepository::advisory_lock_keys(&a));
+    }
+
+    #[test]
+    fn test_isolation_level_statement() {
+        assert_eq!(
+            SettlementRepository::TRANSACTION_ISOLATION_SQL,
+            "SET TRANSACTION ISOLATION LEVEL SERIALIZABLE"
+        );
+        assert_eq!(
+            LedgerRepository::TRANSACTION_ISOLATION_SQL,
+            "SET TRANSACTION ISOLATION LEVEL READ COMMITTED"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_transfer_keeps_direction_when_from_id_sorts_last() {
+        // from_id sorts after to_id, so with_ordered_locks locks to_id first
+        let from_id = uuid::Uuid::from_u128(2);
+        let to_id = uuid::Uuid::from_u128(1);
+        let mut balances = MemoryBalances(HashMap::from([
+            (from_id, rust_decimal::Decimal::new(100, 0)),
+            (to_id, rust_decimal::Decimal::ZERO),
+        ]));
+        let sufficient = |balance: &rust_decimal::Decimal, amount| {
+            if *balance >= amount {
+                Ok(())
+            } else {
+                Err(LedgerError::ValidationFailed("insufficient balance".to_string()))
+            }
+        };
+
+        let amount = rust_decimal::Decimal::new(30, 0);
+        let (from_balance, to_balance) =
+            LedgerRepository::apply_transfer(&mut balances, from_id, to_id, amount, &sufficient, &memory_updater)
+                .await
+                .unwrap();
+        assert_eq!(from_balance, rust_decimal::Decimal::new(70, 0));
+        assert_eq!(to_balance, rust_decimal::Decimal::new(30, 0));
+
+        // The balance check runs against the source wallet, not the lower id
+        let overdraw = rust_decimal::Decimal::new(80, 0);
+        let rejected =
+            LedgerRepository::apply_transfer(&mut balances, from_id, to_id, overdraw, &sufficient, &memory_updater).await;
+        assert!(matches!(rejected, Err(LedgerError::ValidationFailed(_))));
+        assert_eq!(balances.0[&from_id], rust_decimal::Decimal::new(70, 0));
+    }
+}
@@ -0,0 +1,12 @@
+use pleme_codegen::BrazilianEntity;
+
+#[derive(BrazilianEntity)]
+pub enum Channel {
+    Web,
+    Mobile,
+}
+
+#[derive(BrazilianEntity)]
+pub struct Reference(pub String);
+
+fn main() {}
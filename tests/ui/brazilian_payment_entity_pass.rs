@@ -0,0 +1,34 @@
+use pleme_codegen::BrazilianPaymentEntity;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaymentStatus {
+    Pending,
+    Processing,
+    Completed,
+    Failed,
+    Refunded,
+    Cancelled,
+}
+
+// Without PIX support neither BrazilianEntity nor `updated_at` is required
+#[derive(Debug, Clone, BrazilianPaymentEntity)]
+#[brazilian_payment(no_pix, tax_type = "simples_nacional", icms_rate = 0.12)]
+pub struct Invoice {
+    pub id: uuid::Uuid,
+    pub amount: Option<rust_decimal::Decimal>,
+    pub status: PaymentStatus,
+}
+
+fn main() {
+    let invoice = Invoice {
+        id: uuid::Uuid::nil(),
+        amount: Some(rust_decimal::Decimal::new(10000, 2)),
+        status: PaymentStatus::Pending,
+    };
+
+    let taxes = invoice.calculate_brazilian_taxes().unwrap();
+    assert_eq!(taxes.icms_amount, rust_decimal::Decimal::new(1200, 2));
+    assert!(invoice.generate_boleto().is_ok());
+    assert_eq!(Invoice::format_brl_amount(rust_decimal::Decimal::new(123456, 2)), "R$ 1.234,56");
+    assert_eq!(invoice.generate_brazilian_receipt().unwrap().customer_document, "");
+}
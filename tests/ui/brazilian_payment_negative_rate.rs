@@ -0,0 +1,21 @@
+use pleme_codegen::BrazilianPaymentEntity;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaymentStatus {
+    Pending,
+    Processing,
+    Completed,
+    Failed,
+    Refunded,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, BrazilianPaymentEntity)]
+#[brazilian_payment(no_pix, icms_rate = -0.18)]
+pub struct Invoice {
+    pub id: uuid::Uuid,
+    pub amount: rust_decimal::Decimal,
+    pub status: PaymentStatus,
+}
+
+fn main() {}
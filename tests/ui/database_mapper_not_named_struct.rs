@@ -0,0 +1,12 @@
+use pleme_codegen::DatabaseMapper;
+
+#[derive(DatabaseMapper)]
+pub enum Channel {
+    Web,
+    Mobile,
+}
+
+#[derive(DatabaseMapper)]
+pub struct Reference(pub String);
+
+fn main() {}
@@ -0,0 +1,20 @@
+use pleme_codegen::DatabaseMapper;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, DatabaseMapper)]
+#[database(table = "ledger_entries")]
+pub struct LedgerEntry {
+    pub id: uuid::Uuid,
+    #[db(column = "entry_description")]
+    pub description: String,
+    pub memo: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+fn main() {
+    let _: fn(&sqlx::postgres::PgRow) -> Result<LedgerEntry, sqlx::Error> = LedgerEntry::from_row;
+    assert_eq!(LedgerEntry::primary_key(), &["id"]);
+    assert_eq!(
+        LedgerEntry::find_by_id_sql(),
+        "SELECT id, entry_description, memo, created_at FROM ledger_entries WHERE id = $1"
+    );
+}
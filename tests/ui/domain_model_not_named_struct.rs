@@ -0,0 +1,12 @@
+use pleme_codegen::DomainModel;
+
+#[derive(DomainModel)]
+pub enum Channel {
+    Web,
+    Mobile,
+}
+
+#[derive(DomainModel)]
+pub struct Reference(pub String);
+
+fn main() {}
@@ -0,0 +1,12 @@
+use pleme_codegen::DomainModel;
+
+#[derive(Debug, Clone, DomainModel)]
+#[domain(no_auto_fields)]
+pub struct Invoice {
+    pub id: uuid::Uuid,
+    pub product: String,
+    pub number: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use pleme_codegen::DomainModel;
+
+#[derive(Debug, Clone, DomainModel)]
+#[domain(no_auto_fields)]
+pub struct Invoice {
+    pub id: uuid::Uuid,
+    pub product: String,
+    pub number: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+fn main() {
+    let mut invoice = Invoice::new("nexus".to_string(), "NF-0001".to_string());
+    invoice.touch();
+    assert!(invoice.belongs_to_product("nexus"));
+    assert_eq!(invoice.number, "NF-0001");
+}
@@ -0,0 +1,20 @@
+use pleme_codegen::DomainModel;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, DomainModel)]
+#[domain(no_auto_fields)]
+pub struct Invoice {
+    pub id: uuid::Uuid,
+    pub product: String,
+    pub number: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+fn main() {
+    let invoice = Invoice::new("nexus".to_string(), "NF-0001".to_string());
+
+    let json = serde_json::to_string(&invoice).unwrap();
+    let decoded: Invoice = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, invoice);
+}
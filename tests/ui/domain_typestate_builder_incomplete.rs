@@ -0,0 +1,18 @@
+use pleme_codegen::DomainModel;
+
+#[derive(Debug, Clone, serde::Serialize, DomainModel)]
+#[domain(typestate_builder)]
+pub struct Shipment {
+    pub id: uuid::Uuid,
+    pub product: String,
+    pub tracking_code: String,
+    pub notes: Option<String>,
+}
+
+fn main() {
+    // tracking_code is never set, so build() is not available
+    let _shipment = Shipment::builder()
+        .id(uuid::Uuid::new_v4())
+        .product("novaskyn".to_string())
+        .build();
+}
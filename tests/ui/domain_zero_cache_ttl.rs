@@ -0,0 +1,9 @@
+use pleme_codegen::DomainModel;
+
+#[derive(Debug, Clone, DomainModel)]
+#[domain(table = "sessions", cache_ttl = 0)]
+pub struct Session {
+    pub token: String,
+}
+
+fn main() {}
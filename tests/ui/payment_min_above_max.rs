@@ -0,0 +1,10 @@
+use pleme_codegen::PaymentEntity;
+
+#[derive(Debug, Clone, PaymentEntity)]
+#[payment(min_amount = "500.00", max_amount = "100.00")]
+pub struct Payment {
+    pub id: uuid::Uuid,
+    pub amount: rust_decimal::Decimal,
+}
+
+fn main() {}
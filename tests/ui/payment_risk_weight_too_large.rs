@@ -0,0 +1,10 @@
+use pleme_codegen::PaymentEntity;
+
+#[derive(Debug, Clone, PaymentEntity)]
+#[payment(risk_signals = "amount:4294967295,velocity")]
+pub struct Payment {
+    pub id: uuid::Uuid,
+    pub amount: rust_decimal::Decimal,
+}
+
+fn main() {}
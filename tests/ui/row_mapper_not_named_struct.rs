@@ -0,0 +1,12 @@
+use pleme_codegen::RowMapper;
+
+#[derive(RowMapper)]
+pub enum Channel {
+    Web,
+    Mobile,
+}
+
+#[derive(RowMapper)]
+pub struct Reference(pub String);
+
+fn main() {}
@@ -0,0 +1,11 @@
+use pleme_codegen::StatusStateMachine;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, StatusStateMachine)]
+#[status(triggers = "Delivered->Pending: reopen")]
+pub enum OrderStatus {
+    Pending,
+    Paid,
+    Delivered,
+}
+
+fn main() {}
@@ -0,0 +1,92 @@
+// Tests for the ValidatedEntity derive
+use chrono::NaiveDate;
+use pleme_codegen::{BrazilianEntity, ValidatedEntity};
+
+#[derive(Debug, Clone, ValidatedEntity)]
+#[validate(rule(expr = "self.end_date >= self.start_date", message = "end must be after start", field = "end_date"))]
+#[validate(rule(expr = "self.confirm_password == self.password", message = "passwords do not match"))]
+pub struct Enrollment {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub password: String,
+    pub confirm_password: String,
+}
+
+// Lives alongside Enrollment; each gets its own error type
+#[derive(Debug, Clone, BrazilianEntity, ValidatedEntity)]
+#[validate(rule(expr = "self.end_date >= self.start_date", message = "end must be after start", field = "end_date"))]
+pub struct StudentRegistration {
+    #[brazilian(cpf)]
+    pub cpf: String,
+    #[brazilian(cep)]
+    pub cep: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+}
+
+fn enrollment() -> Enrollment {
+    Enrollment {
+        start_date: NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+        end_date: NaiveDate::from_ymd_opt(2024, 6, 30).unwrap(),
+        password: "correct horse".to_string(),
+        confirm_password: "correct horse".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cross_field_rules_pass() {
+        assert!(enrollment().validate().is_ok());
+    }
+
+    #[test]
+    fn test_every_failing_cross_field_rule_is_reported() {
+        let mut enrollment = enrollment();
+        enrollment.end_date = NaiveDate::from_ymd_opt(2024, 2, 1).unwrap();
+        enrollment.confirm_password = "battery staple".to_string();
+
+        assert_eq!(
+            enrollment.validate(),
+            Err(vec![
+                "end must be after start".to_string(),
+                "passwords do not match".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_validate_all_reports_every_invalid_field() {
+        let registration = StudentRegistration {
+            cpf: "111.111.111-11".to_string(),
+            cep: "123".to_string(),
+            start_date: NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+            end_date: NaiveDate::from_ymd_opt(2024, 2, 1).unwrap(),
+        };
+
+        let errors = registration.validate_all().unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                StudentRegistrationValidationError { field: "cpf".to_string(), message: "invalid CPF".to_string() },
+                StudentRegistrationValidationError { field: "cep".to_string(), message: "invalid CEP".to_string() },
+                StudentRegistrationValidationError { field: "end_date".to_string(), message: "end must be after start".to_string() },
+            ]
+        );
+        // validate() keeps its original string format
+        assert_eq!(registration.validate().unwrap_err().len(), 3);
+    }
+
+    #[test]
+    fn test_validate_all_attributes_unnamed_rules_to_entity() {
+        let mut enrollment = enrollment();
+        enrollment.confirm_password = "battery staple".to_string();
+
+        let errors: Vec<EnrollmentValidationError> = enrollment.validate_all().unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "_entity");
+        assert_eq!(errors[0].to_string(), "_entity: passwords do not match");
+    }
+}
@@ -0,0 +1,271 @@
+//! Tests for the WalletEntity derive macro
+
+use pleme_codegen::WalletEntity;
+use rust_decimal::Decimal;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaymentError {
+    InvalidAmount,
+    InsufficientFunds,
+    SelfTransfer,
+    CurrencyMismatch { expected: String, actual: String },
+    TransactionFailed(String),
+}
+
+#[derive(Debug, Clone, WalletEntity)]
+pub struct Wallet {
+    pub id: uuid::Uuid,
+    pub user_id: uuid::Uuid,
+    pub balance: Decimal,
+    pub pending_balance: Decimal,
+    pub tokens: i64,
+    pub lifetime_earnings: Decimal,
+    pub lifetime_spending: Decimal,
+    pub locked: bool,
+    pub locked_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub lock_reason: Option<String>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+// PayoutCalculation and WalletHealthMetrics are emitted per derive, so a second
+// wallet lives in its own module.
+pub mod creator {
+    use super::PaymentError;
+    use pleme_codegen::WalletEntity;
+    use rust_decimal::Decimal;
+
+    #[derive(Debug, Clone, WalletEntity)]
+    #[wallet(table = "creator_wallets", currency = "USD")]
+    pub struct CreatorWallet {
+        pub id: uuid::Uuid,
+        pub user_id: uuid::Uuid,
+        pub balance: Decimal,
+        pub pending_balance: Decimal,
+        pub tokens: i64,
+        pub lifetime_earnings: Decimal,
+        pub lifetime_spending: Decimal,
+        pub locked: bool,
+        pub locked_at: Option<chrono::DateTime<chrono::Utc>>,
+        pub lock_reason: Option<String>,
+        pub updated_at: chrono::DateTime<chrono::Utc>,
+    }
+}
+
+pub mod ledgered {
+    use super::PaymentError;
+    use pleme_codegen::WalletEntity;
+    use rust_decimal::Decimal;
+
+    #[derive(Debug, Clone, WalletEntity)]
+    #[wallet(ledger)]
+    pub struct MerchantWallet {
+        pub id: uuid::Uuid,
+        pub user_id: uuid::Uuid,
+        pub balance: Decimal,
+        pub pending_balance: Decimal,
+        pub tokens: i64,
+        pub lifetime_earnings: Decimal,
+        pub lifetime_spending: Decimal,
+        pub locked: bool,
+        pub locked_at: Option<chrono::DateTime<chrono::Utc>>,
+        pub lock_reason: Option<String>,
+        pub updated_at: chrono::DateTime<chrono::Utc>,
+        pub ledger: Vec<MerchantWalletLedgerEntry>,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use creator::CreatorWallet;
+    use ledgered::MerchantWallet;
+
+    fn wallet_with_pending(pending: Decimal) -> Wallet {
+        Wallet {
+            id: uuid::Uuid::new_v4(),
+            user_id: uuid::Uuid::new_v4(),
+            balance: Decimal::new(1000, 2),
+            pending_balance: pending,
+            tokens: 0,
+            lifetime_earnings: Decimal::ZERO,
+            lifetime_spending: Decimal::ZERO,
+            locked: false,
+            locked_at: None,
+            lock_reason: None,
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    fn merchant_wallet_with_pending(pending: Decimal) -> MerchantWallet {
+        MerchantWallet {
+            id: uuid::Uuid::new_v4(),
+            user_id: uuid::Uuid::new_v4(),
+            balance: Decimal::ZERO,
+            pending_balance: pending,
+            tokens: 0,
+            lifetime_earnings: Decimal::ZERO,
+            lifetime_spending: Decimal::ZERO,
+            locked: false,
+            locked_at: None,
+            lock_reason: None,
+            updated_at: chrono::Utc::now(),
+            ledger: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_settle_pending_full_amount() {
+        let mut wallet = wallet_with_pending(Decimal::new(5000, 2));
+
+        wallet.settle_pending(Decimal::new(5000, 2), "pix-e2e-001").unwrap();
+
+        assert_eq!(wallet.pending_balance, Decimal::ZERO);
+        assert_eq!(wallet.balance, Decimal::new(6000, 2));
+        assert_eq!(wallet.lifetime_earnings, Decimal::new(5000, 2));
+    }
+
+    #[test]
+    fn test_settle_pending_partial_amount_records_ledger_entry() {
+        let mut wallet = merchant_wallet_with_pending(Decimal::new(10000, 2));
+
+        wallet.settle_pending(Decimal::new(2550, 2), "payout-batch-42").unwrap();
+
+        assert_eq!(wallet.pending_balance, Decimal::new(7450, 2));
+        assert_eq!(wallet.balance, Decimal::new(2550, 2));
+        assert_eq!(wallet.ledger.len(), 1);
+        let entry = &wallet.ledger[0];
+        assert_eq!(entry.reference, "payout-batch-42");
+        assert_eq!(entry.amount, Decimal::new(2550, 2));
+        assert_eq!(entry.balance_after, Decimal::new(2550, 2));
+        assert_eq!(entry.pending_balance_after, Decimal::new(7450, 2));
+    }
+
+    #[test]
+    fn test_settle_pending_rejects_more_than_pending() {
+        let mut wallet = merchant_wallet_with_pending(Decimal::new(1000, 2));
+
+        assert_eq!(
+            wallet.settle_pending(Decimal::new(1001, 2), "pix-e2e-002"),
+            Err(PaymentError::InvalidAmount)
+        );
+        assert_eq!(wallet.pending_balance, Decimal::new(1000, 2));
+        assert_eq!(wallet.balance, Decimal::ZERO);
+        assert!(wallet.ledger.is_empty());
+    }
+
+    #[test]
+    fn test_transfer_moves_balance_between_wallets() {
+        let mut payer = wallet_with_pending(Decimal::ZERO);
+        let mut payee = wallet_with_pending(Decimal::ZERO);
+
+        payer.transfer_to(&mut payee, Decimal::new(350, 2), "split-payment").unwrap();
+
+        assert_eq!(payer.balance, Decimal::new(650, 2));
+        assert_eq!(payer.lifetime_spending, Decimal::new(350, 2));
+        assert_eq!(payee.balance, Decimal::new(1350, 2));
+        assert_eq!(payee.lifetime_earnings, Decimal::new(350, 2));
+    }
+
+    #[test]
+    fn test_transfer_rejects_insufficient_funds() {
+        let mut payer = wallet_with_pending(Decimal::new(5000, 2));
+        let mut payee = wallet_with_pending(Decimal::ZERO);
+
+        // Pending funds are not spendable
+        assert_eq!(
+            payer.transfer_to(&mut payee, Decimal::new(1001, 2), "split-payment"),
+            Err(PaymentError::InsufficientFunds)
+        );
+        assert_eq!(payer.balance, Decimal::new(1000, 2));
+        assert_eq!(payee.balance, Decimal::new(1000, 2));
+    }
+
+    #[test]
+    fn test_transfer_rejects_same_wallet_and_non_positive_amounts() {
+        let mut wallet = wallet_with_pending(Decimal::ZERO);
+        let mut same_wallet = wallet.clone();
+        let mut other = wallet_with_pending(Decimal::ZERO);
+
+        assert_eq!(
+            wallet.transfer_to(&mut same_wallet, Decimal::ONE, "loop"),
+            Err(PaymentError::SelfTransfer)
+        );
+        assert_eq!(wallet.transfer_to(&mut other, Decimal::ZERO, "noop"), Err(PaymentError::InvalidAmount));
+        assert_eq!(wallet.transfer_to(&mut other, -Decimal::ONE, "reverse"), Err(PaymentError::InvalidAmount));
+        assert_eq!(wallet.balance, Decimal::new(1000, 2));
+        assert_eq!(other.balance, Decimal::new(1000, 2));
+    }
+
+    #[test]
+    fn test_transfer_rolls_back_debit_when_credit_fails() {
+        let mut payer = wallet_with_pending(Decimal::ZERO);
+        let mut payee = wallet_with_pending(Decimal::ZERO);
+        payee.balance = Decimal::MAX;
+        let payer_updated_at = payer.updated_at;
+
+        let result = payer.transfer_to(&mut payee, Decimal::ONE, "overflow");
+
+        assert!(matches!(result, Err(PaymentError::TransactionFailed(_))));
+        assert_eq!(payer.balance, Decimal::new(1000, 2));
+        assert_eq!(payer.lifetime_spending, Decimal::ZERO);
+        assert_eq!(payer.updated_at, payer_updated_at);
+        assert_eq!(payee.balance, Decimal::MAX);
+    }
+
+    #[test]
+    fn test_adding_usd_to_brl_wallet_is_rejected() {
+        let mut wallet = wallet_with_pending(Decimal::ZERO);
+        assert_eq!(Wallet::CURRENCY, "BRL");
+
+        assert_eq!(
+            wallet.add_balance_checked(Decimal::new(500, 2), "USD", "card-topup"),
+            Err(PaymentError::CurrencyMismatch { expected: "BRL".to_string(), actual: "USD".to_string() })
+        );
+        assert!(wallet.subtract_balance_checked(Decimal::new(500, 2), "USD", "payout").is_err());
+        assert_eq!(wallet.balance, Decimal::new(1000, 2));
+
+        wallet.add_balance_checked(Decimal::new(500, 2), "BRL", "pix-topup").unwrap();
+        assert_eq!(wallet.balance, Decimal::new(1500, 2));
+    }
+
+    #[test]
+    fn test_currency_option_sets_wallet_currency() {
+        assert_eq!(CreatorWallet::CURRENCY, "USD");
+
+        let wallet = wallet_with_pending(Decimal::ZERO);
+        assert!(wallet.assert_currency("BRL").is_ok());
+        assert!(wallet.assert_currency("brl").is_ok());
+        assert!(wallet.assert_currency("EUR").is_err());
+    }
+
+    #[test]
+    fn test_atomic_balance_update_uses_column_arithmetic_and_guard() {
+        let sql = Wallet::atomic_balance_update_query();
+
+        assert_eq!(
+            sql,
+            "UPDATE wallets SET balance = balance + $1 WHERE id = $2 AND balance + $1 >= 0 RETURNING balance"
+        );
+        assert!(sql.contains("SET balance = balance + $1"));
+        assert!(sql.contains("AND balance + $1 >= 0"));
+        assert!(!sql.contains("SET balance = $1"));
+    }
+
+    #[test]
+    fn test_atomic_balance_update_respects_table_option() {
+        assert_eq!(CreatorWallet::WALLET_TABLE, "creator_wallets");
+        assert!(CreatorWallet::atomic_balance_update_query().starts_with("UPDATE creator_wallets SET"));
+    }
+
+    #[test]
+    fn test_zero_rows_affected_is_insufficient_funds() {
+        assert_eq!(
+            Wallet::resolve_atomic_balance_update(None),
+            Err(PaymentError::InsufficientFunds)
+        );
+        assert_eq!(
+            Wallet::resolve_atomic_balance_update(Some(Decimal::new(4250, 2))),
+            Ok(Decimal::new(4250, 2))
+        );
+    }
+}